@@ -4,7 +4,7 @@ use file_transfer::FileData;
 use ockam::{
     errcode::{Kind, Origin},
     identity::{Identity, TrustEveryonePolicy},
-    remote::RemoteForwarder,
+    remote::{RemoteForwarder, RemoteForwarderOptions},
     vault::Vault,
     Context, Error, Result, Routed, TcpTransport, Worker, TCP,
 };
@@ -115,7 +115,7 @@ async fn main(ctx: Context) -> Result<()> {
     // All messages that arrive at that forwarding address will be sent to this program
     // using the TCP connection we created as a client.
     let node_in_hub = (TCP, "1.node.ockam.network:4000");
-    let forwarder = RemoteForwarder::create(&ctx, node_in_hub).await?;
+    let forwarder = RemoteForwarder::create(&ctx, node_in_hub, RemoteForwarderOptions::new()).await?;
     println!("\n[✓] RemoteForwarder was created on the node at: 1.node.ockam.network:4000");
     println!("Forwarding address for Receiver is:");
     println!("{}", forwarder.remote_address());
@@ -1,5 +1,8 @@
 use ockam::identity::{Identity, TrustEveryonePolicy};
-use ockam::{remote::RemoteForwarder, Routed, TcpTransport, Worker, TCP};
+use ockam::{
+    remote::{RemoteForwarder, RemoteForwarderOptions},
+    Routed, TcpTransport, Worker, TCP,
+};
 use ockam::{vault::Vault, Context, Result};
 
 struct Echoer;
@@ -45,7 +48,7 @@ async fn main(ctx: Context) -> Result<()> {
     // All messages that arrive at that forwarding address will be sent to this program
     // using the TCP connection we created as a client.
     let node_in_hub = (TCP, "1.node.ockam.network:4000");
-    let forwarder = RemoteForwarder::create(&ctx, node_in_hub).await?;
+    let forwarder = RemoteForwarder::create(&ctx, node_in_hub, RemoteForwarderOptions::new()).await?;
     println!("\n[✓] RemoteForwarder was created on the node at: 1.node.ockam.network:4000");
     println!("Forwarding address for Bob is:");
     println!("{}", forwarder.remote_address());
@@ -1,7 +1,10 @@
 use crate::{
     channel::{worker::ChannelWorker, CLUSTER_NAME},
     pipe::PipeBehavior,
-    protocols::channel::ChannelCreationHandshake,
+    protocols::channel::{
+        negotiate, ChannelCreationHandshake, ChannelCreationResponse, ChannelOffer,
+        CHANNEL_MIN_VERSION, CHANNEL_VERSION,
+    },
     Context,
 };
 use ockam_core::compat::boxed::Box;
@@ -52,7 +55,22 @@ impl Worker for ChannelListener {
             rx_addr,
             rx_int_addr,
             tx_int_addr,
+            offer,
         } = msg.as_body();
+
+        // Initiators which predate version negotiation don't expect
+        // an answer
+        let accepted = match offer {
+            Some(offer) => match self.negotiate(ctx, &msg, offer).await? {
+                Some(accepted) => Some(accepted),
+                None => return Ok(()),
+            },
+            None => {
+                debug!("Accepting channel creation request without version negotiation");
+                None
+            }
+        };
+
         let peer_channel_addr = msg.return_route().recipient();
 
         let peer_rx_base: Route = msg.return_route().modify().pop_back().into();
@@ -80,6 +98,66 @@ impl Worker for ChannelListener {
             self.rx_hooks.clone(),
         )
         .await?;
+
+        if let Some((reply_route, version, capabilities)) = accepted {
+            ctx.send(
+                reply_route,
+                ChannelCreationResponse::Accepted {
+                    version,
+                    capabilities,
+                },
+            )
+            .await?;
+        }
         Ok(())
     }
 }
+
+impl ChannelListener {
+    /// Negotiate the handshake version with an initiator
+    ///
+    /// Returns the route to send the acceptance to, along with the
+    /// negotiated version and capabilities.  Returns `None` if the
+    /// initiator's version was rejected.
+    async fn negotiate(
+        &self,
+        ctx: &Context,
+        msg: &Routed<ChannelCreationHandshake>,
+        offer: &ChannelOffer,
+    ) -> Result<Option<(Route, u8, u32)>> {
+        // The response goes to the initiator's node, next to its pipes
+        let reply_route: Route = msg
+            .return_route()
+            .modify()
+            .pop_back()
+            .append(offer.reply_addr.clone())
+            .into();
+
+        // Refuse peers speaking a handshake version we can't handle
+        match negotiate(offer.version, offer.capabilities) {
+            Ok((version, capabilities)) => {
+                debug!(
+                    "Negotiated channel version {} with capabilities {:#x}",
+                    version, capabilities
+                );
+                Ok(Some((reply_route, version, capabilities)))
+            }
+            Err(_) => {
+                warn!(
+                    "Rejecting channel creation request from {:?} with version {}",
+                    msg.return_route(),
+                    offer.version
+                );
+                ctx.send(
+                    reply_route,
+                    ChannelCreationResponse::Rejected {
+                        min_version: CHANNEL_MIN_VERSION,
+                        max_version: CHANNEL_VERSION,
+                    },
+                )
+                .await?;
+                Ok(None)
+            }
+        }
+    }
+}
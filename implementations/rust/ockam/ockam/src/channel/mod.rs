@@ -6,9 +6,10 @@ mod worker;
 use self::{listener::ChannelListener, worker::ChannelWorker};
 use crate::{
     pipe::{BehaviorHook, PipeBehavior},
-    Context,
+    protocols::channel::{ChannelCreationResponse, CHANNEL_LEGACY_VERSION, CHANNEL_VERSION},
+    Context, OckamError,
 };
-use ockam_core::{Address, Result, Route, RouteBuilder};
+use ockam_core::{errcode::Kind, Address, Result, Route, RouteBuilder};
 
 #[doc(inline)]
 pub use ockam_channel::SecureChannel;
@@ -18,11 +19,19 @@ mod tests;
 
 const CLUSTER_NAME: &str = "ockam.channel";
 
+/// Time in seconds [`ChannelBuilder::connect`] waits for a listener to
+/// answer the channel creation handshake
+pub const CHANNEL_NEGOTIATION_TIMEOUT: u64 = 5;
+
 /// Generalised ockam channel API
 pub struct ChannelBuilder {
     ctx: Context,
     tx_hooks: PipeBehavior,
     rx_hooks: PipeBehavior,
+    /// Handshake version offered to channel listeners
+    version: u8,
+    /// Seconds to wait for a listener to answer the handshake
+    negotiation_timeout: u64,
 }
 
 impl ChannelBuilder {
@@ -55,6 +64,8 @@ impl ChannelBuilder {
                 ctx,
                 tx_hooks: PipeBehavior::empty(),
                 rx_hooks: PipeBehavior::empty(),
+                version: CHANNEL_VERSION,
+                negotiation_timeout: CHANNEL_NEGOTIATION_TIMEOUT,
             })
     }
 
@@ -77,17 +88,84 @@ impl ChannelBuilder {
     }
 
     /// Connect to a channel listener
+    ///
+    /// Waits for the listener to accept the channel.  Fails with
+    /// [`OckamError::IncompatibleChannelVersion`] if the listener
+    /// doesn't support our handshake version.  Listeners which predate
+    /// version negotiation accept the channel without answering, so if
+    /// no answer arrives within [`CHANNEL_NEGOTIATION_TIMEOUT`] seconds
+    /// the channel is assumed to use the legacy handshake, and its
+    /// version is reported as [`CHANNEL_LEGACY_VERSION`].
     pub async fn connect<R: Into<Route>>(&self, listener: R) -> Result<ChannelHandle> {
         let tx = Address::random_local();
-        ChannelWorker::stage1(
+        let mut reply_ctx = self.ctx.new_context(Address::random_local()).await?;
+        let stage1 = ChannelWorker::stage1(
             &self.ctx,
             tx.clone(),
             listener.into(),
+            (self.version, reply_ctx.address()),
             PipeBehavior::empty(),
             PipeBehavior::empty(),
         )
-        .await?;
-        Ok(ChannelHandle { tx })
+        .await;
+        let (pipe_tx, pipe_rx) = match stage1 {
+            Ok(pipes) => pipes,
+            Err(e) => {
+                self.ctx.stop_worker(reply_ctx.address()).await?;
+                return Err(e);
+            }
+        };
+
+        let res = self.await_response(&mut reply_ctx).await;
+        self.ctx.stop_worker(reply_ctx.address()).await?;
+
+        match res {
+            Ok((version, capabilities)) => Ok(ChannelHandle {
+                tx,
+                version,
+                capabilities,
+            }),
+            Err(e) => {
+                for addr in [tx, pipe_tx, pipe_rx] {
+                    self.ctx.stop_worker(addr).await?;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Wait for the listener's answer to our channel creation handshake
+    ///
+    /// Returns the negotiated version and capabilities.
+    async fn await_response(&self, reply_ctx: &mut Context) -> Result<(u8, u32)> {
+        let response = match reply_ctx
+            .receive_timeout::<ChannelCreationResponse>(self.negotiation_timeout)
+            .await
+        {
+            Ok(response) => response.take().body(),
+            Err(e) if e.code().kind == Kind::Timeout => {
+                debug!("No channel negotiation response, assuming a legacy listener");
+                return Ok((CHANNEL_LEGACY_VERSION, 0));
+            }
+            Err(e) => return Err(e),
+        };
+
+        match response {
+            ChannelCreationResponse::Accepted {
+                version,
+                capabilities,
+            } => Ok((version, capabilities)),
+            ChannelCreationResponse::Rejected {
+                min_version,
+                max_version,
+            } => {
+                warn!(
+                    "Channel listener only supports versions {} to {}, not {}",
+                    min_version, max_version, self.version
+                );
+                Err(OckamError::IncompatibleChannelVersion.into())
+            }
+        }
     }
 
     /// Create a new channel listener
@@ -107,6 +185,8 @@ impl ChannelBuilder {
 /// This is implemented as a type-safe wrapper around an address.
 pub struct ChannelHandle {
     tx: Address,
+    version: u8,
+    capabilities: u32,
 }
 
 impl ChannelHandle {
@@ -119,6 +199,16 @@ impl ChannelHandle {
     pub fn address(&self) -> &Address {
         &self.tx
     }
+
+    /// Returns the handshake version negotiated with the listener
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the capability flags supported by both peers
+    pub fn capabilities(&self) -> u32 {
+        self.capabilities
+    }
 }
 
 impl From<ChannelHandle> for Address {
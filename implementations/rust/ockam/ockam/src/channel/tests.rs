@@ -2,9 +2,15 @@
 use crate::{
     channel::*,
     pipe::{ReceiverConfirm, ReceiverOrdering, SenderConfirm},
-    Context,
+    protocols::channel::{
+        negotiate, ChannelCreationHandshake, ChannelOffer, CHANNEL_CAPABILITIES,
+        CHANNEL_LEGACY_VERSION, CHANNEL_MIN_VERSION, CHANNEL_VERSION,
+    },
+    Context, Message, OckamError,
 };
-use ockam_core::Result;
+use ockam_core::compat::boxed::Box;
+use ockam_core::{Decodable, Encodable, LocalMessage, Result, Routed, Worker};
+use serde::{Deserialize, Serialize};
 
 #[crate::test]
 async fn simple_channel(ctx: &mut Context) -> Result<()> {
@@ -18,6 +24,8 @@ async fn simple_channel(ctx: &mut Context) -> Result<()> {
     // Create a channel via the listener.  We re-use the
     // ChannelBuilder here but could also use a new one
     let ch = builder.connect(vec!["my-channel-listener"]).await?;
+    assert_eq!(ch.version(), CHANNEL_VERSION);
+    assert_eq!(ch.capabilities(), CHANNEL_CAPABILITIES);
 
     // Send a message through the channel
     let msg = "Hello through the channel!".to_string();
@@ -59,3 +67,157 @@ async fn reliable_channel(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+#[crate::test]
+async fn channel_version_mismatch(ctx: &mut Context) -> Result<()> {
+    let builder = ChannelBuilder::new(ctx).await?;
+    builder
+        .create_channel_listener("my-channel-listener")
+        .await?;
+
+    // An initiator which only speaks a version the listener doesn't
+    // support anymore
+    let mut old_builder = ChannelBuilder::new(ctx).await?;
+    old_builder.version = CHANNEL_MIN_VERSION - 1;
+
+    let workers = ctx.list_workers().await?;
+    match old_builder.connect(vec!["my-channel-listener"]).await {
+        Err(e) => assert_eq!(
+            e.code(),
+            crate::Error::from(OckamError::IncompatibleChannelVersion).code()
+        ),
+        Ok(_) => panic!("listener accepted an incompatible version"),
+    }
+
+    // The channel worker, its pipes and the reply context are gone
+    let mut left = ctx.list_workers().await?;
+    left.sort();
+    let mut workers = workers;
+    workers.sort();
+    assert_eq!(left, workers);
+
+    ctx.stop().await
+}
+
+/// The channel creation handshake as it was before version negotiation
+#[derive(Serialize, Deserialize, Message)]
+struct LegacyHandshake {
+    channel_addr: Address,
+    tx_addr: Address,
+    tx_int_addr: Address,
+    rx_addr: Address,
+    rx_int_addr: Address,
+}
+
+/// Decodes handshakes like a listener which predates version
+/// negotiation, and passes them on to a current listener in the
+/// legacy format
+struct LegacyListener {
+    listener: Address,
+}
+
+#[crate::worker]
+impl Worker for LegacyListener {
+    type Message = LegacyHandshake;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Context,
+        msg: Routed<LegacyHandshake>,
+    ) -> Result<()> {
+        let legacy = msg.as_body().encode()?;
+        let mut trans = msg.into_transport_message();
+        trans.payload = legacy;
+        trans
+            .onward_route
+            .modify()
+            .pop_front()
+            .prepend(self.listener.clone());
+        ctx.forward(LocalMessage::new(trans, vec![])).await
+    }
+}
+
+#[crate::test]
+async fn channel_with_legacy_peers(ctx: &mut Context) -> Result<()> {
+    let mut builder = ChannelBuilder::new(ctx).await?;
+    builder.negotiation_timeout = 1;
+    builder
+        .create_channel_listener("my-channel-listener")
+        .await?;
+    ctx.start_worker(
+        "legacy-listener",
+        LegacyListener {
+            listener: "my-channel-listener".into(),
+        },
+    )
+    .await?;
+
+    // The listener accepts the legacy handshake without answering, and
+    // the initiator falls back to the legacy handshake
+    let ch = builder.connect(vec!["legacy-listener"]).await?;
+    assert_eq!(ch.version(), CHANNEL_LEGACY_VERSION);
+    assert_eq!(ch.capabilities(), 0);
+
+    let msg = "Hello through the legacy channel!".to_string();
+    ctx.send(ch.tx().append("app"), msg.clone()).await?;
+    let recv = ctx.receive().await?;
+    assert_eq!(recv, msg);
+
+    ctx.stop().await
+}
+
+#[test]
+fn handshake_decodes_with_and_without_offer() -> Result<()> {
+    let legacy = LegacyHandshake {
+        channel_addr: "a".into(),
+        tx_addr: "b".into(),
+        tx_int_addr: "c".into(),
+        rx_addr: "d".into(),
+        rx_int_addr: "e".into(),
+    };
+    let handshake = ChannelCreationHandshake::decode(&legacy.encode()?)?;
+    assert_eq!(handshake.rx_int_addr, "e".into());
+    assert_eq!(handshake.offer, None);
+
+    let offer = ChannelOffer {
+        version: CHANNEL_VERSION,
+        capabilities: CHANNEL_CAPABILITIES,
+        reply_addr: "f".into(),
+    };
+    let handshake = ChannelCreationHandshake {
+        offer: Some(offer.clone()),
+        ..handshake
+    };
+    let encoded = handshake.encode()?;
+    assert_eq!(
+        ChannelCreationHandshake::decode(&encoded)?.offer,
+        Some(offer)
+    );
+
+    // Listeners which predate version negotiation ignore the offer
+    let legacy = LegacyHandshake::decode(&encoded)?;
+    assert_eq!(legacy.rx_int_addr, "e".into());
+
+    // An offer cut short is an error, not a legacy handshake
+    assert!(ChannelCreationHandshake::decode(&encoded[..encoded.len() - 1]).is_err());
+    Ok(())
+}
+
+#[test]
+fn negotiate_same_version() {
+    let (version, capabilities) = negotiate(CHANNEL_VERSION, CHANNEL_CAPABILITIES).unwrap();
+    assert_eq!(version, CHANNEL_VERSION);
+    assert_eq!(capabilities, CHANNEL_CAPABILITIES);
+
+    // A newer peer is downgraded to our version, and unknown
+    // capability flags are masked out
+    let (version, capabilities) = negotiate(CHANNEL_VERSION + 1, u32::MAX).unwrap();
+    assert_eq!(version, CHANNEL_VERSION);
+    assert_eq!(capabilities, CHANNEL_CAPABILITIES);
+}
+
+#[test]
+fn negotiate_rejects_old_version() {
+    assert!(negotiate(CHANNEL_MIN_VERSION - 1, CHANNEL_CAPABILITIES).is_err());
+}
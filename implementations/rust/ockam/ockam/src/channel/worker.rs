@@ -2,7 +2,7 @@ use crate::{
     channel::CLUSTER_NAME,
     pipe::{HandshakeInit, PipeBehavior, PipeReceiver, PipeSender},
     protocols::{
-        channel::ChannelCreationHandshake,
+        channel::{ChannelCreationHandshake, ChannelOffer, CHANNEL_CAPABILITIES},
         pipe::internal::{Handshake, InternalCmd},
     },
     Context,
@@ -21,6 +21,9 @@ pub struct ChannelWorker {
     stage: WorkerStage,
     /// Route to the peer channel listener
     listener: Option<Route>,
+    /// Handshake version to offer the peer channel listener, and the
+    /// address which receives its response
+    offer: Option<(u8, Address)>,
     /// Address of the local pipe sender
     tx_addr: Address,
     /// Address of the local pipe receiver
@@ -54,6 +57,7 @@ impl ChannelWorker {
             ChannelWorker {
                 stage: WorkerStage::Stage2,
                 listener: None,
+                offer: None,
                 tx_addr: Address::random_local(),
                 rx_addr: Address::random_local(),
                 peer_routes: Some((peer_tx_route, peer_rx_route)),
@@ -69,34 +73,43 @@ impl ChannelWorker {
     /// Create a new stage-1 channel worker
     ///
     /// Stage-1 of the handshake consists of creating a PipeReceiver
-    /// and initiating the channel creation handshake
+    /// and initiating the channel creation handshake.  The listener's
+    /// [`ChannelCreationResponse`](crate::protocols::channel::ChannelCreationResponse)
+    /// is sent to `reply_addr`.
+    ///
+    /// Returns the addresses of the PipeSender and PipeReceiver.
     pub async fn stage1(
         ctx: &Context,
         pub_addr: Address,
         listener: Route,
+        (version, reply_addr): (u8, Address),
         tx_hooks: PipeBehavior,
         rx_hooks: PipeBehavior,
-    ) -> Result<()> {
+    ) -> Result<(Address, Address)> {
         let int_addr = Address::random_local();
+        let tx_addr = Address::random_local();
+        let rx_addr = Address::random_local();
         ctx.start_worker(
             vec![int_addr.clone(), pub_addr.clone()],
             ChannelWorker {
                 stage: WorkerStage::Stage1,
                 listener: Some(listener),
+                offer: Some((version, reply_addr)),
                 peer_routes: None,
                 // This is a bit of a hack so that we don't have to
                 // create an outgoing message cache in the channel
                 // endpoint and can instead use the PipeSender cache
                 // mechanism instead
                 peer_addr: Address::random_local(),
-                rx_addr: Address::random_local(),
-                tx_addr: Address::random_local(),
+                rx_addr: rx_addr.clone(),
+                tx_addr: tx_addr.clone(),
                 self_addrs: (pub_addr, int_addr),
                 tx_hooks,
                 rx_hooks,
             },
         )
-        .await
+        .await?;
+        Ok((tx_addr, rx_addr))
     }
 }
 
@@ -178,6 +191,11 @@ impl ChannelWorker {
                 rx_addr: self.rx_addr.clone(),
                 tx_int_addr: tx_int,
                 rx_int_addr: rx_int,
+                offer: self.offer.take().map(|(version, reply_addr)| ChannelOffer {
+                    version,
+                    capabilities: CHANNEL_CAPABILITIES,
+                    reply_addr,
+                }),
             },
             self.self_addrs.1.clone(),
         )
@@ -33,6 +33,7 @@ pub enum OckamError {
     NoSuchProtocol,
     SystemAddressNotBound,
     SystemInvalidConfiguration,
+    IncompatibleChannelVersion,
 }
 
 impl ockam_core::compat::error::Error for OckamError {}
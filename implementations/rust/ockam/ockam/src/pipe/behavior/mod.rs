@@ -2,7 +2,7 @@
 
 mod resend;
 use dyn_clone::DynClone;
-pub use resend::{ReceiverConfirm, SenderConfirm};
+pub use resend::{ReceiverConfirm, ResendMetrics, SenderConfirm};
 
 mod ordering;
 pub use ordering::ReceiverOrdering;
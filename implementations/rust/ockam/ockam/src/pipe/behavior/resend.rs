@@ -2,26 +2,77 @@ use crate::{
     delay::DelayedEvent,
     pipe::behavior::{BehaviorHook, PipeModifier},
     protocols::pipe::{
-        internal::{Ack, InternalCmd, Resend},
+        internal::{Ack, InternalCmd, Resend, Unavailable},
         PipeMessage,
     },
     Context,
 };
-use ockam_core::compat::boxed::Box;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use ockam_core::compat::{boxed::Box, collections::BTreeSet, sync::Arc};
 use ockam_core::{async_trait, compat::collections::BTreeMap, Address, Result, Route};
 
+/// Counters describing the resend history of a [`SenderConfirm`]
+///
+/// Clones share the same counters, so a handle obtained before the
+/// behavior is handed to a pipe sender keeps observing it.
+#[derive(Default, Clone)]
+pub struct ResendMetrics {
+    history_size: Arc<AtomicUsize>,
+    resends: Arc<AtomicUsize>,
+}
+
+impl ResendMetrics {
+    /// Number of sent messages currently retained for re-sending
+    pub fn history_size(&self) -> usize {
+        self.history_size.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages re-sent so far
+    pub fn resend_count(&self) -> usize {
+        self.resends.load(Ordering::Relaxed)
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct SenderConfirm {
     /// A set of message indices not confirmed yet
     on_route: BTreeMap<u64, PipeMessage>,
+    /// Maximum number of messages kept in `on_route`
+    max_history: Option<usize>,
+    /// Indices evicted from `on_route` before being confirmed
+    evicted: BTreeSet<u64>,
+    metrics: ResendMetrics,
 }
 
 impl SenderConfirm {
     pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `max` unconfirmed messages for re-sending
+    ///
+    /// When the history is full, the message with the lowest index is
+    /// evicted to make room for the next one.  A re-send requested for
+    /// an evicted message is answered with
+    /// [`InternalCmd::Unavailable`], sent to this pipe sender's
+    /// internal address so that all of its behavior hooks see it.
+    pub fn with_max_history(max: usize) -> Self {
         Self {
-            on_route: BTreeMap::new(),
+            max_history: Some(max),
+            ..Self::default()
         }
     }
+
+    /// Get a handle to the resend metrics of this behavior
+    pub fn metrics(&self) -> ResendMetrics {
+        self.metrics.clone()
+    }
+
+    fn update_history_size(&self) {
+        self.metrics
+            .history_size
+            .store(self.on_route.len(), Ordering::Relaxed);
+    }
 }
 
 #[async_trait]
@@ -34,6 +85,15 @@ impl BehaviorHook for SenderConfirm {
         msg: &PipeMessage,
     ) -> Result<PipeModifier> {
         self.on_route.insert(msg.index.u64(), msg.clone());
+        if let Some(max) = self.max_history {
+            while self.on_route.len() > max {
+                let oldest = *self.on_route.keys().next().expect("history is not empty");
+                debug!("Evicting message index '{}' from resend history", oldest);
+                self.on_route.remove(&oldest);
+                self.evicted.insert(oldest);
+            }
+        }
+        self.update_history_size();
 
         DelayedEvent::new(
             ctx,
@@ -69,13 +129,24 @@ impl BehaviorHook for SenderConfirm {
                         .await?;
 
                     // Then actually re-send the message
+                    self.metrics.resends.fetch_add(1, Ordering::Relaxed);
                     ctx.send(peer, msg).await?;
                 }
+                None if self.evicted.remove(idx) => {
+                    warn!(
+                        "Message index '{}' was evicted from the resend history",
+                        idx
+                    );
+                    ctx.send(this, InternalCmd::Unavailable(Unavailable { idx: *idx }))
+                        .await?;
+                }
                 None => trace!("Received timeout for message, but message was acknowleged"),
             },
             InternalCmd::Ack(Ack { idx }) => {
                 debug!("Received pipe delivery ACK for index {}", idx);
                 self.on_route.remove(idx);
+                self.evicted.remove(idx);
+                self.update_history_size();
             }
             cmd => trace!("SenderResend behavior ignoring {:?}", cmd),
         }
@@ -4,7 +4,7 @@
 mod behavior;
 pub use behavior::{
     BehaviorHook, HandshakeInit, PipeBehavior, PipeModifier, ReceiverConfirm, ReceiverOrdering,
-    SenderConfirm,
+    ResendMetrics, SenderConfirm,
 };
 
 mod listener;
@@ -1,9 +1,12 @@
 use crate::{
     pipe::*,
-    protocols::pipe::{internal::InternalCmd, PipeMessage},
+    protocols::pipe::{
+        internal::{InternalCmd, Resend, Unavailable},
+        PipeMessage,
+    },
     Context,
 };
-use ockam_core::{async_trait, Address, Result, Route};
+use ockam_core::{async_trait, route, Address, Result, Route};
 
 use super::behavior::ReceiverOrdering;
 
@@ -280,3 +283,102 @@ async fn layered_pipe(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+/// Report the index of every delivered message to the app, then drop it
+#[derive(Clone)]
+struct ReportDelivery;
+
+#[async_trait]
+impl BehaviorHook for ReportDelivery {
+    async fn on_internal(
+        &mut self,
+        _: Address,
+        _: Route,
+        _: &mut Context,
+        _: &InternalCmd,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_external(
+        &mut self,
+        _: Address,
+        _: Route,
+        ctx: &mut Context,
+        msg: &PipeMessage,
+    ) -> Result<PipeModifier> {
+        ctx.send("app", format!("delivered {}", msg.index.u64()))
+            .await?;
+        Ok(PipeModifier::Drop)
+    }
+}
+
+/// Report re-sends which are not possible anymore to the app
+#[derive(Clone)]
+struct ReportUnavailable;
+
+#[async_trait]
+impl BehaviorHook for ReportUnavailable {
+    async fn on_internal(
+        &mut self,
+        _: Address,
+        _: Route,
+        ctx: &mut Context,
+        msg: &InternalCmd,
+    ) -> Result<()> {
+        match msg {
+            InternalCmd::Unavailable(Unavailable { idx }) => {
+                ctx.send("app", format!("unavailable {}", idx)).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn on_external(
+        &mut self,
+        _: Address,
+        _: Route,
+        _: &mut Context,
+        _: &PipeMessage,
+    ) -> Result<PipeModifier> {
+        Ok(PipeModifier::None)
+    }
+}
+
+#[crate::test]
+async fn bounded_resend_history(ctx: &mut Context) -> Result<()> {
+    receiver_with_behavior(ctx, "pipe-receiver", ReportDelivery).await?;
+    let confirm = SenderConfirm::with_max_history(2);
+    let metrics = confirm.metrics();
+    PipeSender::create(
+        ctx,
+        route!["pipe-receiver"],
+        "pipe-sender".into(),
+        "pipe-sender-int".into(),
+        PipeBehavior::with(confirm).attach(ReportUnavailable),
+    )
+    .await?;
+
+    for i in 1..=4 {
+        ctx.send(route!["pipe-sender", "app"], format!("message {}", i))
+            .await?;
+        let msg = ctx.receive::<String>().await?.take().body();
+        assert_eq!(msg, format!("delivered {}", i));
+    }
+    assert_eq!(metrics.history_size(), 2);
+
+    // The first two messages were evicted from the history
+    ctx.send("pipe-sender-int", InternalCmd::Resend(Resend { idx: 1 }))
+        .await?;
+    let msg = ctx.receive::<String>().await?.take().body();
+    assert_eq!(msg, "unavailable 1");
+
+    ctx.send("pipe-sender-int", InternalCmd::Resend(Resend { idx: 4 }))
+        .await?;
+    let msg = ctx.receive::<String>().await?.take().body();
+    assert_eq!(msg, "delivered 4");
+    assert_eq!(metrics.resend_count(), 1);
+    assert_eq!(metrics.history_size(), 2);
+
+    ctx.stop().await
+}
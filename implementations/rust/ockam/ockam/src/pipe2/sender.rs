@@ -93,7 +93,7 @@ impl PipeSender {
 
         // Grab data from the Routed wrapper
         let msg_addr = msg.msg_addr().clone();
-        let onward_route = msg.onward_route();
+        let onward_route = msg.local_message().transport().onward_route.clone();
         let return_route = msg.return_route();
 
         // Grab the internal message and edit its route info
@@ -1,14 +1,161 @@
 //! Ockam channel protocol structures
-use crate::Message;
-use ockam_core::Address;
-use serde::{Deserialize, Serialize};
+use crate::{
+    protocols::{required, trailing_byte},
+    Message, OckamError,
+};
+use core::fmt;
+use ockam_core::{Address, Result};
+use serde::{
+    de::{SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Highest channel handshake version supported by this implementation
+pub const CHANNEL_VERSION: u8 = 1;
+
+/// Lowest channel handshake version accepted from a peer
+pub const CHANNEL_MIN_VERSION: u8 = 1;
+
+/// Channel capability flags supported by this implementation
+///
+/// No optional capabilities are defined yet.
+pub const CHANNEL_CAPABILITIES: u32 = 0;
+
+/// Handshake version reported for listeners which predate version
+/// negotiation
+pub const CHANNEL_LEGACY_VERSION: u8 = 0;
 
 /// A simple message type to create a bi-directional channel
-#[derive(Debug, Serialize, Deserialize, Message)]
+///
+/// The negotiation offer is encoded after the fields of the original
+/// handshake, and only if present.  Listeners which predate it ignore
+/// it, and handshakes of initiators which predate it decode without it.
+#[derive(Debug, Message)]
 pub struct ChannelCreationHandshake {
     pub(crate) channel_addr: Address,
     pub(crate) tx_addr: Address,
     pub(crate) tx_int_addr: Address,
     pub(crate) rx_addr: Address,
     pub(crate) rx_int_addr: Address,
+    /// Version negotiation offer, `None` for initiators which predate it
+    pub(crate) offer: Option<ChannelOffer>,
+}
+
+/// The version negotiation part of a [`ChannelCreationHandshake`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelOffer {
+    /// Highest handshake version supported by the initiator
+    pub(crate) version: u8,
+    /// Capability flags supported by the initiator
+    pub(crate) capabilities: u32,
+    /// Address on the initiator's node which receives the
+    /// [`ChannelCreationResponse`]
+    pub(crate) reply_addr: Address,
+}
+
+impl Serialize for ChannelCreationHandshake {
+    fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
+        let len = 5 + 3 * self.offer.is_some() as usize;
+        let mut st = serializer.serialize_struct("ChannelCreationHandshake", len)?;
+        st.serialize_field("channel_addr", &self.channel_addr)?;
+        st.serialize_field("tx_addr", &self.tx_addr)?;
+        st.serialize_field("tx_int_addr", &self.tx_int_addr)?;
+        st.serialize_field("rx_addr", &self.rx_addr)?;
+        st.serialize_field("rx_int_addr", &self.rx_int_addr)?;
+        if let Some(offer) = &self.offer {
+            st.serialize_field("version", &offer.version)?;
+            st.serialize_field("capabilities", &offer.capabilities)?;
+            st.serialize_field("reply_addr", &offer.reply_addr)?;
+        }
+        st.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ChannelCreationHandshake {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+        struct HandshakeVisitor;
+
+        impl<'de> Visitor<'de> for HandshakeVisitor {
+            type Value = ChannelCreationHandshake;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("struct ChannelCreationHandshake")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> core::result::Result<ChannelCreationHandshake, A::Error> {
+                let channel_addr = required(&mut seq, 0, &self)?;
+                let tx_addr = required(&mut seq, 1, &self)?;
+                let tx_int_addr = required(&mut seq, 2, &self)?;
+                let rx_addr = required(&mut seq, 3, &self)?;
+                let rx_int_addr = required(&mut seq, 4, &self)?;
+                let offer = match trailing_byte(&mut seq) {
+                    Some(version) => Some(ChannelOffer {
+                        version,
+                        capabilities: required(&mut seq, 6, &self)?,
+                        reply_addr: required(&mut seq, 7, &self)?,
+                    }),
+                    None => None,
+                };
+                Ok(ChannelCreationHandshake {
+                    channel_addr,
+                    tx_addr,
+                    tx_int_addr,
+                    rx_addr,
+                    rx_int_addr,
+                    offer,
+                })
+            }
+        }
+
+        deserializer.deserialize_struct(
+            "ChannelCreationHandshake",
+            &[
+                "channel_addr",
+                "tx_addr",
+                "tx_int_addr",
+                "rx_addr",
+                "rx_int_addr",
+                "version",
+                "capabilities",
+                "reply_addr",
+            ],
+            HandshakeVisitor,
+        )
+    }
+}
+
+/// The listener's answer to a [`ChannelCreationHandshake`]
+#[derive(Debug, Serialize, Deserialize, Message)]
+pub enum ChannelCreationResponse {
+    /// The channel was created with the given version and capabilities
+    Accepted {
+        /// Negotiated handshake version
+        version: u8,
+        /// Capability flags supported by both peers
+        capabilities: u32,
+    },
+    /// The initiator's handshake version is not supported
+    Rejected {
+        /// Lowest version supported by the listener
+        min_version: u8,
+        /// Highest version supported by the listener
+        max_version: u8,
+    },
+}
+
+/// Select the handshake version and capabilities to use with a peer
+///
+/// The highest version supported by both sides is picked.  Peers
+/// which only support versions older than [`CHANNEL_MIN_VERSION`]
+/// are rejected.
+pub(crate) fn negotiate(version: u8, capabilities: u32) -> Result<(u8, u32)> {
+    let selected = version.min(CHANNEL_VERSION);
+    if selected < CHANNEL_MIN_VERSION {
+        return Err(OckamError::IncompatibleChannelVersion.into());
+    }
+    Ok((selected, capabilities & CHANNEL_CAPABILITIES))
 }
@@ -5,6 +5,7 @@
 
 use crate::{Message, Result};
 use ockam_core::{compat::vec::Vec, ProtocolId};
+use serde::de::{self, Expected, SeqAccess};
 use serde::{Deserialize, Serialize};
 
 pub mod channel;
@@ -60,3 +61,25 @@ pub trait ProtocolParser: Sized {
     /// supported by this parser.
     fn parse(pp: ProtocolPayload) -> Result<Self>;
 }
+
+/// Read the next field of a struct that must be present
+fn required<'de, A, T>(seq: &mut A, idx: usize, exp: &dyn Expected) -> Result<T, A::Error>
+where
+    A: SeqAccess<'de>,
+    T: Deserialize<'de>,
+{
+    seq.next_element()?
+        .ok_or_else(|| de::Error::invalid_length(idx, exp))
+}
+
+/// Read the first byte of a trailing struct field that older peers
+/// don't encode
+///
+/// A compact binary encoding has no field delimiters, so a missing
+/// trailing field shows up as the end of the input.  Reading a single
+/// byte can only fail there, so `None` means that the field is absent.
+/// Callers must treat any failure to read the rest of the field as an
+/// error.
+fn trailing_byte<'de, A: SeqAccess<'de>>(seq: &mut A) -> Option<u8> {
+    seq.next_element().ok().flatten()
+}
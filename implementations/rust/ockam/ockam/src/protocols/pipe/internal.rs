@@ -18,6 +18,13 @@ pub struct Ack {
     pub idx: u64,
 }
 
+/// Report that a payload can no longer be re-sent
+#[derive(Debug, Serialize, Deserialize, Message)]
+pub struct Unavailable {
+    /// The index which was evicted from the resend history
+    pub idx: u64,
+}
+
 /// Payload sent from handshake listener to newly spawned receiver
 #[derive(Debug, Serialize, Deserialize, Message)]
 pub struct Handshake {
@@ -38,6 +45,8 @@ pub enum InternalCmd {
     Handshake(Handshake),
     /// Initialise a pipe sender with a route
     InitSender,
+    /// A requested re-send is not possible anymore
+    Unavailable(Unavailable),
 }
 
 impl InternalCmd {
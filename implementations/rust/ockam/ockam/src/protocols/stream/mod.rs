@@ -2,3 +2,12 @@
 
 pub mod requests;
 pub mod responses;
+
+/// Codec tag for stream messages holding an encoded
+/// [`TransportMessage`](crate::TransportMessage)
+///
+/// Messages without a codec tag are treated as opaque bytes, which the
+/// stream consumer still tries to decode as transport messages.  The
+/// stream producer doesn't tag its pushes, so that they remain readable
+/// by stream services that predate codec tags.
+pub const TRANSPORT_MESSAGE_CODEC: &str = "ockam/transport_message";
@@ -57,6 +57,42 @@ impl PushRequest {
     }
 }
 
+/// Push a message into the mailbox, tagged with the codec of its data
+///
+/// This is sent as `stream_push_tagged`, so that stream services
+/// which predate codec tags reject it instead of misreading it.  The
+/// expected response to this request is
+/// [`PushConfirm`](super::responses::PushConfirm).
+#[derive(Debug, PartialEq, Serialize, Deserialize, Message)]
+pub struct TaggedPushRequest {
+    /// The request ID
+    pub request_id: Uint,
+    /// The encoded message data.
+    pub data: Vec<u8>,
+    /// How `data` is encoded.
+    pub codec: String,
+}
+
+impl TaggedPushRequest {
+    /// Create a [`ProtocolPayload`] for a [`TaggedPushRequest`].
+    //noinspection ALL
+    #[allow(dead_code, clippy::new_ret_no_self)]
+    pub fn new<T, S>(request_id: u64, data: T, codec: S) -> ProtocolPayload
+    where
+        T: Into<Vec<u8>>,
+        S: Into<String>,
+    {
+        ProtocolPayload::new(
+            "stream_push_tagged",
+            Self {
+                request_id: request_id.into(),
+                data: data.into(),
+                codec: codec.into(),
+            },
+        )
+    }
+}
+
 /// Pull messages from the mailbox.
 ///
 /// The expected response to this request is [`super::responses::PullResponse`].
@@ -88,6 +124,30 @@ impl PullRequest {
     }
 }
 
+/// Query the head and tail indices of a stream
+///
+/// The expected response to this request is
+/// [`StreamStatus`](super::responses::StreamStatus).
+#[derive(Debug, PartialEq, Serialize, Deserialize, Message)]
+pub struct StreamStatusRequest {
+    /// The stream name
+    pub stream_name: String,
+}
+
+impl StreamStatusRequest {
+    /// Create a [`ProtocolPayload`] for a [`StreamStatusRequest`].
+    //noinspection ALL
+    #[allow(dead_code, clippy::new_ret_no_self)]
+    pub fn new<S: Into<String>>(stream_name: S) -> ProtocolPayload {
+        ProtocolPayload::new(
+            "stream_status",
+            Self {
+                stream_name: stream_name.into(),
+            },
+        )
+    }
+}
+
 /// Index request protocols to get and save indices.
 ///
 /// The expected response to this request is
@@ -85,6 +85,10 @@ impl From<Option<()>> for Status {
 }
 
 /// Response to a [`PullRequest`](super::requests::PullRequest)
+///
+/// A response with codec tags is sent as `stream_pull_tagged`.  A
+/// response without any tags is sent as `stream_pull`, in the layout
+/// of stream services that predate codec tags.
 #[derive(Debug, PartialEq, Serialize, Deserialize, Message)]
 pub struct PullResponse {
     /// The request id
@@ -99,16 +103,51 @@ impl PullResponse {
     //noinspection RsExternalLinter
     #[allow(dead_code, clippy::new_ret_no_self)]
     pub fn new<T: Into<Vec<StreamMessage>>>(request_id: u64, messages: T) -> ProtocolPayload {
+        let messages = messages.into();
+        if messages.iter().any(|m| m.codec.is_some()) {
+            return ProtocolPayload::new(
+                "stream_pull_tagged",
+                Self {
+                    request_id: request_id.into(),
+                    messages,
+                },
+            );
+        }
         ProtocolPayload::new(
             "stream_pull",
-            Self {
+            UntaggedPullResponse {
                 request_id: request_id.into(),
-                messages: messages.into(),
+                messages: messages.into_iter().map(|m| (m.index, m.data)).collect(),
             },
         )
     }
 }
 
+/// The layout of a [`PullResponse`] without codec tags
+#[derive(Serialize, Deserialize, Message)]
+struct UntaggedPullResponse {
+    request_id: Uint,
+    messages: Vec<(Uint, Vec<u8>)>,
+}
+
+impl From<UntaggedPullResponse> for PullResponse {
+    fn from(r: UntaggedPullResponse) -> Self {
+        let messages = r
+            .messages
+            .into_iter()
+            .map(|(index, data)| StreamMessage {
+                index,
+                data,
+                codec: None,
+            })
+            .collect();
+        Self {
+            request_id: r.request_id,
+            messages,
+        }
+    }
+}
+
 /// A stream message with a reference index
 #[derive(Debug, PartialEq, Serialize, Deserialize, Message)]
 pub struct StreamMessage {
@@ -116,6 +155,10 @@ pub struct StreamMessage {
     pub index: Uint,
     /// Encoded data of the message
     pub data: Vec<u8>,
+    /// How `data` is encoded, `None` for opaque bytes
+    ///
+    /// Stream services that predate codec tags never set this.
+    pub codec: Option<String>,
 }
 
 /// The index return payload, to an
@@ -130,6 +173,54 @@ pub struct IndexResponse {
     pub index: Option<Uint>,
 }
 
+/// Response to a [`StreamStatusRequest`](super::requests::StreamStatusRequest)
+///
+/// An empty stream has a `count` of zero, and its `head` and `tail`
+/// are meaningless.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Message)]
+pub struct StreamStatus {
+    /// The stream name
+    pub stream_name: String,
+    /// Index of the oldest message still held by the stream
+    pub head: Uint,
+    /// Index of the latest message pushed to the stream
+    pub tail: Uint,
+    /// Number of messages held by the stream
+    pub count: Uint,
+}
+
+impl StreamStatus {
+    /// Create a [`ProtocolPayload`] responding to a
+    /// [`StreamStatusRequest`](super::requests::StreamStatusRequest).
+    //noinspection RsExternalLinter
+    #[allow(dead_code, clippy::new_ret_no_self)]
+    pub fn new<S: Into<String>>(
+        stream_name: S,
+        head: u64,
+        tail: u64,
+        count: u64,
+    ) -> ProtocolPayload {
+        ProtocolPayload::new(
+            "stream_status",
+            Self {
+                stream_name: stream_name.into(),
+                head: head.into(),
+                tail: tail.into(),
+                count: count.into(),
+            },
+        )
+    }
+
+    /// Number of messages a consumer still has to pull, given the
+    /// index it saved, i.e. the index of the next message it will pull
+    pub fn lag(&self, saved_index: u64) -> u64 {
+        if self.count.u64() == 0 {
+            return 0;
+        }
+        (self.tail.u64() + 1).saturating_sub(saved_index)
+    }
+}
+
 /// A convenience enum to wrap all possible response types
 ///
 /// In your worker you will want to match this enum, given to you via
@@ -145,6 +236,8 @@ pub enum Response {
     PullResponse(PullResponse),
     /// Wraps a [`IndexResponse`], see its documentation for more info.
     Index(IndexResponse),
+    /// Wraps a [`StreamStatus`] response, see its documentation for more info.
+    Status(StreamStatus),
 }
 
 impl ProtocolParser for Response {
@@ -153,7 +246,9 @@ impl ProtocolParser for Response {
             "stream_create",
             "stream_push",
             "stream_pull",
+            "stream_pull_tagged",
             "stream_index",
+            "stream_status",
         ]
         .into_iter()
         .collect::<BTreeSet<_>>()
@@ -164,8 +259,10 @@ impl ProtocolParser for Response {
         Ok(match protocol.as_str() {
             "stream_create" => Response::Init(InitResponse::decode(&data)?),
             "stream_push" => Response::PushConfirm(PushConfirm::decode(&data)?),
-            "stream_pull" => Response::PullResponse(PullResponse::decode(&data)?),
+            "stream_pull" => Response::PullResponse(UntaggedPullResponse::decode(&data)?.into()),
+            "stream_pull_tagged" => Response::PullResponse(PullResponse::decode(&data)?),
             "stream_index" => Response::Index(IndexResponse::decode(&data)?),
+            "stream_status" => Response::Status(StreamStatus::decode(&data)?),
             _ => return Err(OckamError::NoSuchProtocol.into()),
         })
     }
@@ -1,20 +1,22 @@
 //! Registration with Ockam Hub, and forwarding to local workers.
 #![deny(missing_docs)]
 
+use crate::identity::{IdentityIdentifier, IdentitySecureChannelLocalInfo};
 use crate::{route, Context, Message, OckamError};
 use core::time::Duration;
 use ockam_core::compat::rand::random;
 use ockam_core::compat::{
     boxed::Box,
+    collections::{BTreeMap, VecDeque},
     string::{String, ToString},
     vec::Vec,
 };
-use ockam_core::{Address, AddressSet, Any, Decodable, Result, Route, Routed, Worker};
+use ockam_core::{Address, Any, Decodable, LocalMessage, Result, Route, Routed, Worker};
 use ockam_node::DelayedEvent;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Information about a remotely forwarded worker.
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Message)]
@@ -39,6 +41,62 @@ impl RemoteForwarderInfo {
     }
 }
 
+/// Outcome of a registration, as reported by a hub.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RegistrationStatus {
+    /// The forwarder was registered.
+    Registered,
+    /// The forwarder was registered before and keeps its remote address.
+    AlreadyRegistered,
+}
+
+/// Structured registration acknowledgement.
+///
+/// Older hubs acknowledge a registration by echoing the registration
+/// payload.  Newer hubs may reply with this type instead.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Message)]
+pub struct RegistrationAck {
+    /// The registration outcome.
+    pub status: RegistrationStatus,
+    /// The payload of the registration request being acknowledged.
+    pub payload: String,
+}
+
+impl RegistrationAck {
+    /// Parse a hub's response to the registration request with `payload`.
+    fn parse(data: &[u8], payload: &str) -> Result<RegistrationStatus> {
+        // Plain echo of the registration payload
+        if let Ok(echo) = Vec::<u8>::decode(data) {
+            if echo == payload.as_bytes() {
+                return Ok(RegistrationStatus::Registered);
+            }
+        }
+
+        match RegistrationAck::decode(data) {
+            Ok(ack) if ack.payload == payload => Ok(ack.status),
+            _ => Err(OckamError::InvalidHubResponse.into()),
+        }
+    }
+}
+
+/// Outcome of the first registration, reported to the creating context
+#[derive(Serialize, Deserialize, Message)]
+enum RegistrationOutcome {
+    Registered(RemoteForwarderInfo),
+    Unauthenticated,
+    InvalidResponse,
+}
+
+impl RegistrationOutcome {
+    fn into_result(self) -> Result<RemoteForwarderInfo> {
+        match self {
+            Self::Registered(info) => Ok(info),
+            Self::Unauthenticated => Err(OckamError::SecureChannelCannotBeAuthenticated.into()),
+            Self::InvalidResponse => Err(OckamError::InvalidHubResponse.into()),
+        }
+    }
+}
+
 /// All addresses `RemoteForwarder` is registered for
 #[derive(Clone)]
 struct Addresses {
@@ -46,6 +104,8 @@ struct Addresses {
     main_address: Address,
     /// Address used for heartbeat messages
     heartbeat_address: Address,
+    /// Address receiving replies when return routes are rewritten
+    reply_address: Address,
 }
 
 impl Distribution<Addresses> for Standard {
@@ -53,13 +113,174 @@ impl Distribution<Addresses> for Standard {
         Addresses {
             main_address: rng.gen(),
             heartbeat_address: rng.gen(),
+            reply_address: rng.gen(),
         }
     }
 }
 
-impl Addresses {
-    fn into_set(self) -> AddressSet {
-        vec![self.main_address, self.heartbeat_address].into()
+/// Upper bound on the number of return routes a forwarder remembers
+const MAX_REPLY_ROUTES: usize = 256;
+
+/// Return routes hidden behind a forwarder's reply address
+///
+/// Every distinct inbound return route is assigned a random token
+/// address.  Forwarded messages carry `[reply_address, token]` as
+/// their return route, and a reply sent along it is mapped back to
+/// the original route.  Messages from different clients arrive with
+/// different return routes, so each client's replies are routed back
+/// to that client only.  The least recently assigned route is
+/// forgotten once more than [`MAX_REPLY_ROUTES`] are in use.
+#[derive(Default)]
+struct ReplyRoutes {
+    tokens: BTreeMap<Route, Address>,
+    routes: BTreeMap<Address, Route>,
+    order: VecDeque<Address>,
+}
+
+impl ReplyRoutes {
+    /// Return the token for a return route, assigning one if needed
+    fn token(&mut self, route: &Route) -> Address {
+        if let Some(token) = self.tokens.get(route) {
+            return token.clone();
+        }
+
+        if self.order.len() == MAX_REPLY_ROUTES {
+            if let Some(old) = self.order.pop_front() {
+                if let Some(old_route) = self.routes.remove(&old) {
+                    self.tokens.remove(&old_route);
+                }
+            }
+        }
+
+        let token = Address::random_local();
+        self.tokens.insert(route.clone(), token.clone());
+        self.routes.insert(token.clone(), route.clone());
+        self.order.push_back(token.clone());
+        token
+    }
+
+    /// Look up the return route behind a token
+    fn route(&self, token: &Address) -> Option<&Route> {
+        self.routes.get(token)
+    }
+}
+
+/// Secure channel requirement for forwarded messages
+#[derive(Clone)]
+struct SecureChannelCheck {
+    /// Identities allowed to send, or `None` for any identity
+    allowed: Option<Vec<IdentityIdentifier>>,
+}
+
+impl SecureChannelCheck {
+    /// Whether a message arrived through an acceptable secure channel
+    fn allows(&self, msg: &LocalMessage) -> bool {
+        match IdentitySecureChannelLocalInfo::find_info(msg) {
+            Ok(info) => self
+                .allowed
+                .as_ref()
+                .map_or(true, |ids| ids.contains(info.their_identity_id())),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Options for a [`RemoteForwarder`]
+///
+/// ```
+/// # use ockam::remote::RemoteForwarderOptions;
+/// # use ockam::route;
+/// let options = RemoteForwarderOptions::new()
+///     .destination(route!["secure_channel"])
+///     .hide_return_route()
+///     .require_secure_channel(None)
+///     .require_reply_secure_channel(None)
+///     .require_hub_secure_channel(None);
+/// ```
+#[derive(Clone, Default)]
+pub struct RemoteForwarderOptions {
+    destination: Option<Route>,
+    hide_return_route: bool,
+    channel_check: Option<SecureChannelCheck>,
+    reply_check: Option<SecureChannelCheck>,
+    hub_check: Option<SecureChannelCheck>,
+}
+
+impl RemoteForwarderOptions {
+    /// Options of a forwarder which relays messages as is
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send messages received from the hub along `destination`
+    /// (e.g. into a secure channel) before continuing on their own
+    /// onward route.
+    pub fn destination(mut self, destination: impl Into<Route>) -> Self {
+        self.destination = Some(destination.into());
+        self
+    }
+
+    /// Hide the inbound return route of forwarded messages
+    ///
+    /// Replies are sent back to the forwarder, which routes them along
+    /// the original return route.  The local destination thus never
+    /// learns the hub-facing path.
+    pub fn hide_return_route(mut self) -> Self {
+        self.hide_return_route = true;
+        self
+    }
+
+    /// Only forward messages from the hub which arrived through an
+    /// identity secure channel, i.e. carry
+    /// [`IdentitySecureChannelLocalInfo`]
+    ///
+    /// If `allowed_identities` is given, the identity on the other end
+    /// of that channel must be one of them.  All other messages are
+    /// dropped.  This neither applies to replies to messages with a
+    /// hidden return route, see
+    /// [`require_reply_secure_channel`](Self::require_reply_secure_channel),
+    /// nor to the hub's registration response, see
+    /// [`require_hub_secure_channel`](Self::require_hub_secure_channel).
+    pub fn require_secure_channel(
+        mut self,
+        allowed_identities: Option<Vec<IdentityIdentifier>>,
+    ) -> Self {
+        self.channel_check = Some(SecureChannelCheck {
+            allowed: allowed_identities,
+        });
+        self
+    }
+
+    /// Only route back replies to messages with a hidden return route
+    /// which arrived through an identity secure channel
+    ///
+    /// If `allowed_identities` is given, the identity on the other end
+    /// of that channel must be one of them.  All other replies are
+    /// dropped.  See [`hide_return_route`](Self::hide_return_route).
+    pub fn require_reply_secure_channel(
+        mut self,
+        allowed_identities: Option<Vec<IdentityIdentifier>>,
+    ) -> Self {
+        self.reply_check = Some(SecureChannelCheck {
+            allowed: allowed_identities,
+        });
+        self
+    }
+
+    /// Only accept a registration response from the hub which arrived
+    /// through an identity secure channel
+    ///
+    /// If `hub_identities` is given, the identity on the other end of
+    /// that channel must be one of them.  Creating the forwarder fails
+    /// if the first registration response is rejected.
+    pub fn require_hub_secure_channel(
+        mut self,
+        hub_identities: Option<Vec<IdentityIdentifier>>,
+    ) -> Self {
+        self.hub_check = Some(SecureChannelCheck {
+            allowed: hub_identities,
+        });
+        self
     }
 }
 
@@ -69,9 +290,20 @@ pub struct RemoteForwarder {
     registration_route: Route,
     registration_payload: String,
     callback_address: Option<Address>,
-    // We only use Heartbeat for static RemoteForwarder
+    // We only use Heartbeat for static RemoteForwarder, it is created
+    // on initialization so that it belongs to the forwarder
     heartbeat: Option<DelayedEvent<Vec<u8>>>,
-    heartbeat_interval: Duration,
+    heartbeat_interval: Option<Duration>,
+    /// Route prepended to the onward route of forwarded messages
+    destination: Option<Route>,
+    /// Hidden return routes, if return routes are rewritten
+    replies: Option<ReplyRoutes>,
+    /// Requirement on the origin of forwarded messages
+    channel_check: Option<SecureChannelCheck>,
+    /// Requirement on the origin of replies
+    reply_check: Option<SecureChannelCheck>,
+    /// Requirement on the origin of registration responses
+    hub_check: Option<SecureChannelCheck>,
 }
 
 impl RemoteForwarder {
@@ -80,88 +312,252 @@ impl RemoteForwarder {
         registration_route: Route,
         registration_payload: String,
         callback_address: Address,
-        heartbeat: Option<DelayedEvent<Vec<u8>>>,
-        heartbeat_interval: Duration,
+        heartbeat_interval: Option<Duration>,
     ) -> Self {
         Self {
             addresses,
             registration_route,
             registration_payload,
             callback_address: Some(callback_address),
-            heartbeat,
+            heartbeat: None,
             heartbeat_interval,
+            destination: None,
+            replies: None,
+            channel_check: None,
+            reply_check: None,
+            hub_check: None,
         }
     }
 
+    /// Apply `options` to a new forwarder
+    ///
+    /// Returns the addresses the forwarder has to be started at.
+    fn apply_options(&mut self, options: RemoteForwarderOptions) -> Vec<Address> {
+        self.destination = options.destination;
+        self.channel_check = options.channel_check;
+        self.reply_check = options.reply_check;
+        self.hub_check = options.hub_check;
+
+        let mut addresses = vec![self.addresses.main_address.clone()];
+        if self.heartbeat_interval.is_some() {
+            addresses.push(self.addresses.heartbeat_address.clone());
+        }
+        if options.hide_return_route {
+            self.replies = Some(ReplyRoutes::default());
+            addresses.push(self.addresses.reply_address.clone());
+        }
+        addresses
+    }
+
     /// Create and start static RemoteForwarder at predefined address with given Ockam Hub address
     pub async fn create_static(
         ctx: &Context,
         hub_addr: impl Into<Address>,
         alias: impl Into<String>,
+    ) -> Result<RemoteForwarderInfo> {
+        Self::create_static_with_options(ctx, hub_addr, alias, RemoteForwarderOptions::new()).await
+    }
+
+    /// Create and start static RemoteForwarder at predefined address with given Ockam Hub address
+    ///
+    /// See [`RemoteForwarderOptions`] for the available options.
+    pub async fn create_static_with_options(
+        ctx: &Context,
+        hub_addr: impl Into<Address>,
+        alias: impl Into<String>,
+        options: RemoteForwarderOptions,
     ) -> Result<RemoteForwarderInfo> {
         let address: Address = random();
-        let mut child_ctx = ctx.new_context(address).await?;
+        let child_ctx = ctx.new_context(address).await?;
 
         let addresses: Addresses = random();
 
-        let heartbeat =
-            DelayedEvent::create(ctx, addresses.heartbeat_address.clone(), vec![]).await?;
-        let forwarder = Self::new(
+        let mut forwarder = Self::new(
             addresses.clone(),
             route![hub_addr.into(), "static_forwarding_service"],
             alias.into(),
             child_ctx.address(),
-            Some(heartbeat),
-            Duration::from_secs(5),
+            Some(Duration::from_secs(5)),
         );
+        let worker_addresses = forwarder.apply_options(options);
 
         debug!(
             "Starting static RemoteForwarder at {}",
             &addresses.heartbeat_address
         );
-        ctx.start_worker(addresses.into_set(), forwarder).await?;
+        ctx.start_worker(worker_addresses, forwarder).await?;
 
-        let resp = child_ctx
-            .receive::<RemoteForwarderInfo>()
-            .await?
-            .take()
-            .body();
-
-        Ok(resp)
+        Self::wait_for_registration(ctx, child_ctx, addresses.main_address).await
     }
 
     /// Create and start new ephemeral RemoteForwarder at random address with given Ockam Hub address
+    ///
+    /// See [`RemoteForwarderOptions`] for the available options.
     pub async fn create(
         ctx: &Context,
         hub_addr: impl Into<Address>,
+        options: RemoteForwarderOptions,
     ) -> Result<RemoteForwarderInfo> {
         let address: Address = random();
-        let mut child_ctx = ctx.new_context(address).await?;
+        let child_ctx = ctx.new_context(address).await?;
 
         let addresses: Addresses = random();
 
-        let forwarder = Self::new(
+        let mut forwarder = Self::new(
             addresses.clone(),
             route![hub_addr.into(), "forwarding_service"],
             "register".to_string(),
             child_ctx.address(),
             None,
-            Duration::from_secs(10),
         );
+        let worker_addresses = forwarder.apply_options(options);
 
         debug!(
             "Starting ephemeral RemoteForwarder at {}",
             &addresses.main_address
         );
-        ctx.start_worker(addresses.main_address, forwarder).await?;
+        ctx.start_worker(worker_addresses, forwarder).await?;
 
-        let resp = child_ctx
-            .receive::<RemoteForwarderInfo>()
-            .await?
-            .take()
-            .body();
+        Self::wait_for_registration(ctx, child_ctx, addresses.main_address).await
+    }
 
-        Ok(resp)
+    /// Create and start new ephemeral RemoteForwarder at random address with given Ockam Hub address
+    ///
+    /// Messages received from the hub are sent along `destination`
+    /// (e.g. into a secure channel) before continuing on their own
+    /// onward route.
+    pub async fn create_with_route(
+        ctx: &Context,
+        hub_addr: impl Into<Address>,
+        destination: impl Into<Route>,
+    ) -> Result<RemoteForwarderInfo> {
+        let options = RemoteForwarderOptions::new().destination(destination);
+        Self::create(ctx, hub_addr, options).await
+    }
+
+    /// Create and start new ephemeral RemoteForwarder which hides return routes
+    ///
+    /// Like [`create_with_route`](Self::create_with_route), but
+    /// forwarded messages don't carry their inbound return route.
+    /// Instead, replies are sent back to the forwarder, which routes
+    /// them along the original return route.  The local destination
+    /// thus never learns the hub-facing path.
+    pub async fn create_with_reply_route(
+        ctx: &Context,
+        hub_addr: impl Into<Address>,
+        destination: impl Into<Route>,
+    ) -> Result<RemoteForwarderInfo> {
+        let options = RemoteForwarderOptions::new()
+            .destination(destination)
+            .hide_return_route();
+        Self::create(ctx, hub_addr, options).await
+    }
+}
+
+impl RemoteForwarder {
+    /// Wait for the outcome of the first registration on `child_ctx`
+    ///
+    /// The forwarder is stopped if there is no outcome in time.
+    async fn wait_for_registration(
+        ctx: &Context,
+        mut child_ctx: Context,
+        forwarder: Address,
+    ) -> Result<RemoteForwarderInfo> {
+        let outcome = child_ctx
+            .receive::<RegistrationOutcome>()
+            .await
+            .map(|msg| msg.take().body());
+        ctx.stop_worker(child_ctx.address()).await?;
+        match outcome {
+            Ok(outcome) => outcome.into_result(),
+            Err(e) => {
+                if let Err(stop) = ctx.stop_worker(forwarder).await {
+                    warn!(
+                        "Failed to stop RemoteForwarder without registration: {}",
+                        stop
+                    );
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Handle the hub's response to a registration request
+    async fn handle_registration(&mut self, ctx: &Context, msg: Routed<Any>) -> Result<()> {
+        debug!("RemoteForwarder received service message");
+
+        if let Some(check) = &self.hub_check {
+            if !check.allows(msg.local_message()) {
+                warn!("RemoteForwarder dropping unauthenticated registration response");
+                return self
+                    .fail_registration(ctx, RegistrationOutcome::Unauthenticated)
+                    .await;
+            }
+        }
+
+        let status = match RegistrationAck::parse(msg.payload(), &self.registration_payload) {
+            Ok(status) => status,
+            Err(e) => {
+                warn!(
+                    "RemoteForwarder received invalid registration response: {}",
+                    e
+                );
+                return self
+                    .fail_registration(ctx, RegistrationOutcome::InvalidResponse)
+                    .await;
+            }
+        };
+        debug!("RemoteForwarder registration status: {:?}", status);
+
+        if let Some(callback_address) = self.callback_address.clone() {
+            let route = msg.return_route();
+
+            info!("RemoteForwarder registered with route: {}", route);
+            let address = match route.clone().recipient().to_string().strip_prefix("0#") {
+                Some(addr) => addr.to_string(),
+                None => {
+                    return self
+                        .fail_registration(ctx, RegistrationOutcome::InvalidResponse)
+                        .await
+                }
+            };
+
+            let info = RemoteForwarderInfo {
+                forwarding_route: route,
+                remote_address: address,
+                worker_address: ctx.address(),
+            };
+            self.callback_address = None;
+            ctx.send(callback_address, RegistrationOutcome::Registered(info))
+                .await?;
+        }
+
+        self.schedule_heartbeat().await?;
+
+        Ok(())
+    }
+
+    /// Schedule the next heartbeat, if this forwarder sends them
+    async fn schedule_heartbeat(&mut self) -> Result<()> {
+        match (&mut self.heartbeat, self.heartbeat_interval) {
+            (Some(heartbeat), Some(interval)) => heartbeat.schedule(interval).await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Report a failed first registration to the creator and stop
+    ///
+    /// Responses to later registrations are dropped.
+    async fn fail_registration(
+        &mut self,
+        ctx: &Context,
+        outcome: RegistrationOutcome,
+    ) -> Result<()> {
+        if let Some(callback_address) = self.callback_address.take() {
+            ctx.send(callback_address, outcome).await?;
+            ctx.stop_worker(ctx.address()).await?;
+        }
+        Ok(())
     }
 }
 
@@ -171,6 +567,12 @@ impl Worker for RemoteForwarder {
     type Message = Any;
 
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        if self.heartbeat_interval.is_some() {
+            let heartbeat =
+                DelayedEvent::create(ctx, self.addresses.heartbeat_address.clone(), vec![]).await?;
+            self.heartbeat = Some(heartbeat);
+        }
+
         debug!("RemoteForwarder registration...");
 
         ctx.send_from_address(
@@ -197,66 +599,95 @@ impl Worker for RemoteForwarder {
             )
             .await?;
 
-            if let Some(heartbeat) = &mut self.heartbeat {
-                heartbeat.schedule(self.heartbeat_interval).await?;
-            }
+            self.schedule_heartbeat().await?;
 
             return Ok(());
         }
 
         // We are the final recipient of the message because it's registration response for our Worker
-        if msg.onward_route().recipient() == self.addresses.main_address {
-            debug!("RemoteForwarder received service message");
-
-            let payload =
-                Vec::<u8>::decode(msg.payload()).map_err(|_| OckamError::InvalidHubResponse)?;
-            let payload = String::from_utf8(payload).map_err(|_| OckamError::InvalidHubResponse)?;
-            if payload != self.registration_payload {
-                return Err(OckamError::InvalidHubResponse.into());
-            }
+        if msg.local_message().transport().onward_route.recipient() == self.addresses.main_address {
+            return self.handle_registration(ctx, msg).await;
+        }
 
-            if let Some(callback_address) = self.callback_address.take() {
-                let route = msg.return_route();
-
-                info!("RemoteForwarder registered with route: {}", route);
-                let address = match route.clone().recipient().to_string().strip_prefix("0#") {
-                    Some(addr) => addr.to_string(),
-                    None => return Err(OckamError::InvalidHubResponse.into()),
-                };
-
-                ctx.send(
-                    callback_address,
-                    RemoteForwarderInfo {
-                        forwarding_route: route,
-                        remote_address: address,
-                        worker_address: ctx.address(),
-                    },
-                )
-                .await?;
-            }
+        // Reply to a message forwarded with a hidden return route
+        if msg.msg_addr() == self.addresses.reply_address {
+            let replies = match &self.replies {
+                Some(replies) => replies,
+                None => return Ok(()),
+            };
 
-            if let Some(heartbeat) = &mut self.heartbeat {
-                heartbeat.schedule(self.heartbeat_interval).await?;
+            if let Some(check) = &self.reply_check {
+                if !check.allows(msg.local_message()) {
+                    warn!("RemoteForwarder dropping unauthenticated reply");
+                    return Ok(());
+                }
             }
-        } else {
-            debug!("RemoteForwarder received payload message");
 
             let mut message = msg.into_local_message();
             let transport_message = message.transport_mut();
 
-            // Remove my address from the onward_route
+            // Remove my reply address and the token from the onward_route
             transport_message.onward_route.step()?;
+            let token = transport_message.onward_route.step()?;
+
+            let return_route = match replies.route(&token) {
+                Some(route) => route.clone(),
+                None => {
+                    warn!(
+                        "RemoteForwarder dropping reply with unknown token {}",
+                        token
+                    );
+                    return Ok(());
+                }
+            };
 
-            // Send the message on its onward_route
-            ctx.forward(message).await?;
+            debug!("RemoteForwarder received reply message");
+            transport_message
+                .onward_route
+                .modify()
+                .prepend_route(return_route);
+            transport_message
+                .return_route
+                .modify()
+                .prepend(self.addresses.main_address.clone());
+
+            return ctx.forward(message).await;
+        }
 
-            // We received message from the other node, our registration is still alive, let's reset
-            // heartbeat timer
-            if let Some(heartbeat) = &mut self.heartbeat {
-                heartbeat.schedule(self.heartbeat_interval).await?;
+        if let Some(check) = &self.channel_check {
+            if !check.allows(msg.local_message()) {
+                warn!("RemoteForwarder dropping unauthenticated message");
+                return Ok(());
             }
         }
 
+        debug!("RemoteForwarder received payload message");
+
+        let mut message = msg.into_local_message();
+        let transport_message = message.transport_mut();
+
+        // Remove my address from the onward_route
+        transport_message.onward_route.step()?;
+
+        if let Some(destination) = &self.destination {
+            transport_message
+                .onward_route
+                .modify()
+                .prepend_route(destination.clone());
+        }
+
+        if let Some(replies) = &mut self.replies {
+            let token = replies.token(&transport_message.return_route);
+            transport_message.return_route = route![self.addresses.reply_address.clone(), token];
+        }
+
+        // Send the message on its onward_route
+        ctx.forward(message).await?;
+
+        // We received message from the other node, our registration is still alive, let's reset
+        // heartbeat timer
+        self.schedule_heartbeat().await?;
+
         Ok(())
     }
 }
@@ -264,7 +695,8 @@ impl Worker for RemoteForwarder {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::workers::Echoer;
+    use crate::workers::{Echoer, RouteRecord, RouteTap};
+    use ockam_core::{errcode::Origin, Encodable, TransportMessage};
     use ockam_transport_tcp::{TcpTransport, TCP};
     use std::env;
 
@@ -280,6 +712,367 @@ mod test {
         None
     }
 
+    /// Stands in for the hub's forwarding service by confirming
+    /// every registration
+    ///
+    /// Without a status the registration payload is echoed, otherwise
+    /// a structured acknowledgement is sent.
+    struct ForwardingService(Option<RegistrationStatus>);
+
+    #[crate::worker]
+    impl Worker for ForwardingService {
+        type Context = Context;
+        type Message = String;
+
+        async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+            let return_route = msg.return_route();
+            match self.0 {
+                None => ctx.send(return_route, msg.body()).await,
+                Some(status) => {
+                    let ack = RegistrationAck {
+                        status,
+                        payload: msg.body(),
+                    };
+                    ctx.send(return_route, ack).await
+                }
+            }
+        }
+    }
+
+    async fn register_and_forward(
+        ctx: &mut Context,
+        ack: Option<RegistrationStatus>,
+    ) -> Result<()> {
+        ctx.start_worker("hub", RouteTap::new()).await?;
+        ctx.start_worker("forwarding_service", ForwardingService(ack))
+            .await?;
+
+        let mut final_ctx = ctx.new_context("final").await?;
+        let remote_info =
+            RemoteForwarder::create(ctx, "hub", RemoteForwarderOptions::new()).await?;
+
+        ctx.send(
+            route![remote_info.worker_address().clone(), "final"],
+            "Hello".to_string(),
+        )
+        .await?;
+
+        let resp = final_ctx.receive::<String>().await?.take().body();
+        assert_eq!(resp, "Hello");
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn registration__echo__should_succeed(ctx: &mut Context) -> Result<()> {
+        register_and_forward(ctx, None).await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn registration__structured_ack__should_succeed(ctx: &mut Context) -> Result<()> {
+        register_and_forward(ctx, Some(RegistrationStatus::Registered)).await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn registration__already_registered__should_succeed(ctx: &mut Context) -> Result<()> {
+        register_and_forward(ctx, Some(RegistrationStatus::AlreadyRegistered)).await
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn registration_ack__mismatch__should_fail() {
+        let echo = "other".to_string().encode().unwrap();
+        assert!(RegistrationAck::parse(&echo, "register").is_err());
+
+        let ack = RegistrationAck {
+            status: RegistrationStatus::Registered,
+            payload: "other".into(),
+        }
+        .encode()
+        .unwrap();
+        assert!(RegistrationAck::parse(&ack, "register").is_err());
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__destination_route__should_reach_final_worker(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        ctx.start_worker("hub", RouteTap::new()).await?;
+        ctx.start_worker("forwarding_service", ForwardingService(None))
+            .await?;
+        let mut collector = ctx.new_context("collector").await?;
+        ctx.start_worker("hop1", RouteTap::with_collector("collector"))
+            .await?;
+        ctx.start_worker("hop2", RouteTap::new()).await?;
+
+        let mut final_ctx = ctx.new_context("final").await?;
+        let mut default_ctx = ctx.new_context("default").await?;
+        let remote_info =
+            RemoteForwarder::create_with_route(ctx, "hub", route!["hop1", "hop2", "final"]).await?;
+
+        // Without the destination route the message would go to
+        // "default" directly
+        ctx.send(
+            route![remote_info.worker_address().clone(), "default"],
+            "Hello".to_string(),
+        )
+        .await?;
+
+        let record = collector.receive::<RouteRecord>().await?.take().body();
+        assert_eq!(
+            record.onward_route,
+            route!["hop1", "hop2", "final", "default"]
+        );
+        let resp = final_ctx.receive::<String>().await?.take();
+        assert!(resp.return_route().contains(&"hop1".into()));
+        assert!(resp.return_route().contains(&"hop2".into()));
+        assert_eq!(resp.body(), "Hello");
+        assert!(default_ctx.receive_timeout::<String>(1).await.is_err());
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__reply_route__should_hide_return_route(ctx: &mut Context) -> Result<()> {
+        ctx.start_worker("hub", RouteTap::new()).await?;
+        ctx.start_worker("forwarding_service", ForwardingService(None))
+            .await?;
+        ctx.start_worker("tap", RouteTap::with_collector(route!["collector"]))
+            .await?;
+        ctx.start_worker("echoer", Echoer).await?;
+
+        let mut collector = ctx.new_context("collector").await?;
+        let remote_info = RemoteForwarder::create_with_reply_route(ctx, "hub", "tap").await?;
+
+        let mut client = ctx.new_context(Address::random_local()).await?;
+        client
+            .send(
+                route!["hub", remote_info.worker_address().clone(), "echoer"],
+                "Hello".to_string(),
+            )
+            .await?;
+
+        let resp = client.receive::<String>().await?.take();
+        assert!(resp.return_route().contains(remote_info.worker_address()));
+        assert_eq!(resp.body(), "Hello");
+
+        let record = collector.receive::<RouteRecord>().await?.take().body();
+        assert!(!record.return_route.contains(&"hub".into()));
+        assert!(!record.return_route.contains(&client.address()));
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__reply_route__should_reply_to_each_client(ctx: &mut Context) -> Result<()> {
+        ctx.start_worker("hub", RouteTap::new()).await?;
+        ctx.start_worker("forwarding_service", ForwardingService(None))
+            .await?;
+        ctx.start_worker("echoer", Echoer).await?;
+
+        let remote_info = RemoteForwarder::create_with_reply_route(ctx, "hub", route![]).await?;
+        let route = route!["hub", remote_info.worker_address().clone(), "echoer"];
+
+        let mut alice = ctx.new_context(Address::random_local()).await?;
+        let mut bob = ctx.new_context(Address::random_local()).await?;
+        alice.send(route.clone(), "Hello Alice".to_string()).await?;
+        bob.send(route, "Hello Bob".to_string()).await?;
+
+        assert_eq!(
+            alice.receive::<String>().await?.take().body(),
+            "Hello Alice"
+        );
+        assert_eq!(bob.receive::<String>().await?.take().body(), "Hello Bob");
+
+        ctx.stop().await
+    }
+
+    /// Stands in for an identity secure channel by attaching the
+    /// local info of a fixed identity to every message passing through
+    struct SecureChannelStub(IdentityIdentifier);
+
+    #[crate::worker]
+    impl Worker for SecureChannelStub {
+        type Context = Context;
+        type Message = Any;
+
+        async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+            let mut msg = msg.into_transport_message();
+            msg.onward_route.step()?;
+            msg.return_route.modify().prepend(ctx.address());
+            let local_info = IdentitySecureChannelLocalInfo::new(self.0.clone()).to_local_info()?;
+            ctx.forward(LocalMessage::new(msg, vec![local_info])).await
+        }
+    }
+
+    fn identity(name: &str) -> IdentityIdentifier {
+        IdentityIdentifier::from_key_id(name.into())
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__authenticated__should_drop_unauthenticated(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        ctx.start_worker("hub", SecureChannelStub(identity("hub")))
+            .await?;
+        ctx.start_worker("forwarding_service", ForwardingService(None))
+            .await?;
+
+        let alice = identity("alice");
+        let mallory = identity("mallory");
+
+        let mut final_ctx = ctx.new_context("final").await?;
+        let options = RemoteForwarderOptions::new()
+            .require_secure_channel(Some(vec![alice.clone()]))
+            .require_hub_secure_channel(Some(vec![identity("hub")]));
+        let remote_info = RemoteForwarder::create(ctx, "hub", options).await?;
+        let route = route![remote_info.worker_address().clone(), "final"];
+
+        let send_from = |id: Option<IdentityIdentifier>, body: &str| -> Result<LocalMessage> {
+            let msg = TransportMessage::v1(route.clone(), route![], body.to_string().encode()?);
+            let local_info = match id {
+                Some(id) => vec![IdentitySecureChannelLocalInfo::new(id).to_local_info()?],
+                None => vec![],
+            };
+            Ok(LocalMessage::new(msg, local_info))
+        };
+
+        ctx.forward(send_from(None, "plain")?).await?;
+        ctx.forward(send_from(Some(mallory), "mallory")?).await?;
+        ctx.forward(send_from(Some(identity("hub")), "hub")?)
+            .await?;
+        ctx.forward(send_from(Some(alice), "alice")?).await?;
+
+        let resp = final_ctx.receive::<String>().await?.take().body();
+        assert_eq!(resp, "alice");
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__static_authenticated__should_drop_unauthenticated(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        ctx.start_worker("hub", SecureChannelStub(identity("hub")))
+            .await?;
+        ctx.start_worker("static_forwarding_service", ForwardingService(None))
+            .await?;
+
+        let mut final_ctx = ctx.new_context("final").await?;
+        let options = RemoteForwarderOptions::new()
+            .require_secure_channel(Some(vec![identity("alice")]))
+            .require_hub_secure_channel(Some(vec![identity("hub")]));
+        let remote_info =
+            RemoteForwarder::create_static_with_options(ctx, "hub", "alias", options).await?;
+        let route = route![remote_info.worker_address().clone(), "final"];
+
+        let plain = TransportMessage::v1(route.clone(), route![], "plain".to_string().encode()?);
+        ctx.forward(LocalMessage::new(plain, vec![])).await?;
+        let alice = TransportMessage::v1(route, route![], "alice".to_string().encode()?);
+        let local_info = IdentitySecureChannelLocalInfo::new(identity("alice")).to_local_info()?;
+        ctx.forward(LocalMessage::new(alice, vec![local_info]))
+            .await?;
+
+        let resp = final_ctx.receive::<String>().await?.take().body();
+        assert_eq!(resp, "alice");
+
+        // The hub's responses have to be authenticated as well
+        ctx.start_worker("plain_hub", RouteTap::new()).await?;
+        let options = RemoteForwarderOptions::new().require_hub_secure_channel(None);
+        assert!(
+            RemoteForwarder::create_static_with_options(ctx, "plain_hub", "alias", options)
+                .await
+                .is_err()
+        );
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__authenticated__should_drop_unauthenticated_registration(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        ctx.start_worker("hub", RouteTap::new()).await?;
+        ctx.start_worker("forwarding_service", ForwardingService(None))
+            .await?;
+
+        let workers = ctx.list_workers().await?.len();
+        let options = RemoteForwarderOptions::new().require_hub_secure_channel(None);
+        let res = ockam_node::tokio::time::timeout(
+            Duration::from_millis(500),
+            RemoteForwarder::create(ctx, "hub", options),
+        )
+        .await;
+        let err = res.expect("Should fail without waiting").unwrap_err();
+        assert_eq!(err.code().origin, Origin::Ockam);
+
+        // The forwarder was stopped
+        assert_eq!(ctx.list_workers().await?.len(), workers);
+
+        // The hub policy is separate from the one for forwarded messages
+        let options = RemoteForwarderOptions::new().require_secure_channel(None);
+        assert!(RemoteForwarder::create(ctx, "hub", options).await.is_ok());
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__authenticated_reply_route__should_check_replies(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        ctx.start_worker("hub", SecureChannelStub(identity("hub")))
+            .await?;
+        ctx.start_worker("forwarding_service", ForwardingService(None))
+            .await?;
+        ctx.start_worker("channel", SecureChannelStub(identity("service")))
+            .await?;
+        ctx.start_worker("echoer", Echoer).await?;
+
+        // Replies coming back through the channel are authenticated
+        let options = RemoteForwarderOptions::new()
+            .destination("channel")
+            .hide_return_route()
+            .require_reply_secure_channel(None);
+        let remote_info = RemoteForwarder::create(ctx, "hub", options).await?;
+
+        let mut client = ctx.new_context(Address::random_local()).await?;
+        let route = route!["hub", remote_info.worker_address().clone(), "echoer"];
+        client.send(route, "Hello".to_string()).await?;
+        assert_eq!(client.receive::<String>().await?.take().body(), "Hello");
+
+        // Replies sent directly to the forwarder are not
+        let options = RemoteForwarderOptions::new()
+            .hide_return_route()
+            .require_reply_secure_channel(None);
+        let remote_info = RemoteForwarder::create(ctx, "hub", options).await?;
+
+        let route = route!["hub", remote_info.worker_address().clone(), "echoer"];
+        client.send(route, "Hello".to_string()).await?;
+        assert!(client.receive_timeout::<String>(1).await.is_err());
+
+        // The check on forwarded messages leaves replies alone
+        let options = RemoteForwarderOptions::new()
+            .hide_return_route()
+            .require_secure_channel(None);
+        let remote_info = RemoteForwarder::create(ctx, "hub", options).await?;
+
+        let route = route!["hub", remote_info.worker_address().clone(), "echoer"];
+        client.send(route, "Hello".to_string()).await?;
+        assert_eq!(client.receive::<String>().await?.take().body(), "Hello");
+
+        ctx.stop().await
+    }
+
     #[allow(non_snake_case)]
     #[ockam_macros::test]
     async fn forwarding__ephemeral_address__should_respond(ctx: &mut Context) -> Result<()> {
@@ -296,7 +1089,9 @@ mod test {
         TcpTransport::create(&ctx).await?;
 
         let node_in_hub = (TCP, cloud_address);
-        let remote_info = RemoteForwarder::create(ctx, node_in_hub.clone()).await?;
+        let remote_info =
+            RemoteForwarder::create(ctx, node_in_hub.clone(), RemoteForwarderOptions::new())
+                .await?;
 
         let mut child_ctx = ctx.new_context(Address::random_local()).await?;
 
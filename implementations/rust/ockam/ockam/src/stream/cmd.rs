@@ -20,6 +20,23 @@ pub enum StreamWorkerCmd {
         /// Zero is used as a sentinal to indicate "all messages".
         num: usize,
     },
+    /// Wait for all pending pushes of a stream producer to be confirmed
+    ///
+    /// The producer answers with a plain `Flush` message once no
+    /// pushes are outstanding anymore.
+    Flush,
+    /// Acknowledge that a message has been processed
+    ///
+    /// Only used by stream consumers in
+    /// [acknowledgement mode](crate::stream::Stream::with_ack_mode).
+    Ack {
+        /// Stream index of the processed message
+        index: u64,
+    },
+    /// Stop waiting for a previously requested [`Flush`](StreamWorkerCmd::Flush)
+    ///
+    /// The producer drops the waiter with the same return route.
+    CancelFlush,
 }
 
 impl StreamWorkerCmd {
@@ -37,14 +54,41 @@ impl StreamWorkerCmd {
     pub fn pull(num: usize) -> ProtocolPayload {
         ProtocolPayload::new(ProtocolId::from("internal.stream.pull"), Self::Pull { num })
     }
+
+    /// Return a [`ProtocolPayload`] containing a
+    /// [`Flush`](StreamWorkerCmd::Flush) event.
+    pub fn flush() -> ProtocolPayload {
+        ProtocolPayload::new(ProtocolId::from("internal.stream.flush"), Self::Flush)
+    }
+
+    /// Return a [`ProtocolPayload`] containing a
+    /// [`CancelFlush`](StreamWorkerCmd::CancelFlush) event.
+    pub fn cancel_flush() -> ProtocolPayload {
+        ProtocolPayload::new(
+            ProtocolId::from("internal.stream.cancel_flush"),
+            Self::CancelFlush,
+        )
+    }
+
+    /// Return a [`ProtocolPayload`] containing an
+    /// [`Ack`](StreamWorkerCmd::Ack) for the message at `index`.
+    pub fn ack(index: u64) -> ProtocolPayload {
+        ProtocolPayload::new(ProtocolId::from("internal.stream.ack"), Self::Ack { index })
+    }
 }
 
 impl ProtocolParser for StreamWorkerCmd {
     fn check_id(id: &str) -> bool {
-        vec!["internal.stream.fetch", "internal.stream.pull"]
-            .into_iter()
-            .collect::<BTreeSet<_>>()
-            .contains(id)
+        vec![
+            "internal.stream.fetch",
+            "internal.stream.pull",
+            "internal.stream.flush",
+            "internal.stream.ack",
+            "internal.stream.cancel_flush",
+        ]
+        .into_iter()
+        .collect::<BTreeSet<_>>()
+        .contains(id)
     }
 
     fn parse(pp: ProtocolPayload) -> Result<Self> {
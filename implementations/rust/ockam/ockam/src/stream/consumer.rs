@@ -2,7 +2,7 @@ use crate::{
     delay::DelayedEvent,
     monotonic::Monotonic,
     protocols::{
-        stream::{requests::*, responses::*},
+        stream::{requests::*, responses::*, TRANSPORT_MESSAGE_CODEC},
         ProtocolParser, ProtocolPayload,
     },
     stream::StreamWorkerCmd,
@@ -10,7 +10,7 @@ use crate::{
 };
 use crate::{Address, Any, Context, Result, Route, Routed, Worker};
 use core::time::Duration;
-use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
+use ockam_core::compat::{boxed::Box, collections::BTreeSet, string::String, vec::Vec};
 use ockam_core::{Decodable, LocalMessage};
 
 /// A stream worker
@@ -23,6 +23,9 @@ pub struct StreamConsumer {
     index_route: Route,
     /// Sender address
     sender_address: Option<Address>,
+    /// Recipient of messages without onward route, instead of the
+    /// receiver
+    forwarding_address: Option<Address>,
     /// Receiving stream name
     receiver_name: String,
     /// Fetch interval
@@ -31,6 +34,13 @@ pub struct StreamConsumer {
     receiver_rx: Address,
     /// Last known index position
     idx: u64,
+    /// Index position last saved with the index service
+    saved_idx: u64,
+    /// Whether messages must be acknowledged before the saved index
+    /// moves past them
+    ack_mode: bool,
+    /// Indices of delivered messages which were not acknowledged yet
+    unacked: BTreeSet<u64>,
     ids: Monotonic,
 }
 
@@ -65,6 +75,8 @@ async fn handle_response(
             info!("Updating index '{}' to: {}", stream_name, index.u64());
             w.index_route = return_route;
             w.idx = index.u64();
+            w.saved_idx = w.idx;
+            w.unacked.clear();
 
             // Queue a near-immediate fetch event -- however future
             // events will be using the specified user interval
@@ -77,20 +89,45 @@ async fn handle_response(
         Response::PullResponse(PullResponse { messages, .. }) => {
             trace!("PullResponse, {} message(s) available", messages.len());
 
-            let last_idx = w.idx;
-
             // Update the index if we received messages
             if let Some(msg) = messages.last() {
                 w.idx = msg.index.u64() + 1;
             }
 
             for msg in messages {
-                let mut trans = match TransportMessage::decode(&msg.data) {
-                    Ok(t) => t,
-                    _ => {
+                let index = msg.index.u64();
+                let tagged = msg.codec.as_deref() == Some(TRANSPORT_MESSAGE_CODEC);
+                let trans = match msg.codec.as_deref() {
+                    None | Some(TRANSPORT_MESSAGE_CODEC) => {
+                        TransportMessage::decode(&msg.data).ok()
+                    }
+                    Some(_) => None,
+                };
+
+                let mut trans = match trans {
+                    Some(t) => t,
+                    None if tagged => {
                         error!("Failed to decode TransportMessage from StreamMessage payload; skipping!");
                         continue;
                     }
+                    // Opaque data, or data in some other codec
+                    None => {
+                        let res = match w.forwarding_address {
+                            Some(ref addr) => {
+                                info!("Forwarding {} message to addr: {}", w.receiver_name, addr);
+                                ctx.send(addr.clone(), msg).await
+                            }
+                            None => {
+                                info!("Forwarding {} message to receiver.next()", w.receiver_name);
+                                w.track_delivery(index);
+                                ctx.send(w.receiver_rx.clone(), msg).await
+                            }
+                        };
+                        if let Err(e) = res {
+                            error!("Failed forwarding stream message: {}", e);
+                        }
+                        continue;
+                    }
                 };
 
                 // If a producer exists, insert its address into the return_route
@@ -105,10 +142,21 @@ async fn handle_response(
                         let local_msg = LocalMessage::new(trans, Vec::new());
                         ctx.forward(local_msg).await
                     }
-                    Err(_) => {
-                        info!("Forwarding {} message to receiver.next()", w.receiver_name);
-                        ctx.send(w.receiver_rx.clone(), msg).await
-                    }
+                    Err(_) => match w.forwarding_address {
+                        // In forwarding mode the recipient receives the
+                        // original message, with a return route via the
+                        // producer
+                        Some(ref addr) => {
+                            info!("Forwarding {} message to addr: {}", w.receiver_name, addr);
+                            trans.onward_route = addr.clone().into();
+                            ctx.forward(LocalMessage::new(trans, Vec::new())).await
+                        }
+                        None => {
+                            info!("Forwarding {} message to receiver.next()", w.receiver_name);
+                            w.track_delivery(index);
+                            ctx.send(w.receiver_rx.clone(), msg).await
+                        }
+                    },
                 };
 
                 match res {
@@ -120,13 +168,7 @@ async fn handle_response(
             }
 
             // If the index was updated, save it
-            if last_idx != w.idx {
-                ctx.send(
-                    w.index_route.clone(),
-                    IndexRequest::save(w.receiver_name.clone(), w.client_id.clone(), w.idx),
-                )
-                .await?;
-            }
+            save_index(w, ctx).await?;
 
             // Queue a new fetch event and mark this event as handled
             if fetch_interval(ctx, w.interval).await.is_err() {
@@ -162,6 +204,22 @@ async fn handle_cmd(
 
             Ok(())
         }
+        StreamWorkerCmd::Ack { index } => {
+            trace!("Handling StreamWorkerCmd::Ack for index {}", index);
+            if !w.ack_mode {
+                warn!(
+                    "Ignoring ack for consumer {} not in ack mode",
+                    ctx.address()
+                );
+                return Ok(());
+            }
+
+            if !w.unacked.remove(&index) {
+                warn!("Ignoring ack for unknown stream index {}", index);
+                return Ok(());
+            }
+            save_index(w, ctx).await
+        }
         f => {
             warn!("Unhandled message type {:?}", f);
             Err(OckamError::NoSuchProtocol.into())
@@ -169,6 +227,29 @@ async fn handle_cmd(
     }
 }
 
+/// Save the consumer's index if it has advanced since the last save
+///
+/// In ack mode the saved index never moves past the lowest delivered
+/// message which has not been acknowledged, so that it is delivered
+/// again after a restart.  Acknowledged messages after such a gap are
+/// delivered again as well.
+async fn save_index(w: &mut StreamConsumer, ctx: &mut Context) -> Result<()> {
+    let idx = match w.unacked.iter().next() {
+        Some(lowest) => *lowest,
+        None => w.idx,
+    };
+    if idx == w.saved_idx {
+        return Ok(());
+    }
+
+    w.saved_idx = idx;
+    ctx.send(
+        w.index_route.clone(),
+        IndexRequest::save(w.receiver_name.clone(), w.client_id.clone(), idx),
+    )
+    .await
+}
+
 /// Dispatch a fetch event with an interval duration
 ///
 /// This function must be re-called whenever a fetch event is handled
@@ -233,21 +314,33 @@ impl StreamConsumer {
         sender_address: Option<Address>,
         receiver_name: String,
         interval: Duration,
-        _forwarding_address: Option<Address>, // TODO implement forwarding
+        forwarding_address: Option<Address>,
         receiver_rx: Address,
         stream_service: String,
         index_service: String,
+        ack_mode: bool,
     ) -> Self {
         Self {
             client_id,
             service_route: route.clone().modify().append(stream_service).into(),
             index_route: route.modify().append(index_service).into(),
             sender_address,
+            forwarding_address,
             receiver_name,
             interval,
             receiver_rx,
             idx: 0,
+            saved_idx: 0,
+            ack_mode,
+            unacked: BTreeSet::new(),
             ids: Monotonic::new(),
         }
     }
+
+    /// Remember a message handed to the receiver until it is acknowledged
+    fn track_delivery(&mut self, index: u64) {
+        if self.ack_mode {
+            self.unacked.insert(index);
+        }
+    }
 }
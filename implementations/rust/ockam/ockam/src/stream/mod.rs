@@ -9,14 +9,20 @@ mod producer;
 use producer::StreamProducer;
 
 use crate::{
-    protocols::stream::responses::*, Address, Context, Message, Result, Route, Routed,
-    TransportMessage,
+    protocols::{
+        stream::{requests::StreamStatusRequest, responses::*},
+        ProtocolParser, ProtocolPayload,
+    },
+    Address, Context, Message, OckamError, Result, Route, Routed, TransportMessage,
 };
 use core::{ops::Deref, time::Duration};
 use ockam_core::compat::rand::{self, Rng};
 use ockam_core::compat::string::String;
 use ockam_core::{Decodable, RouteBuilder, TransportType};
 
+#[cfg(test)]
+mod tests;
+
 /// Stream controller transport type.
 pub const STREAM: TransportType = TransportType::new(16);
 
@@ -32,6 +38,7 @@ pub struct Stream {
     stream_service: String,
     index_service: String,
     client_id: Option<String>,
+    ack_mode: bool,
 }
 
 /// A simple address wrapper for stream workers
@@ -65,18 +72,99 @@ impl From<SenderAddress> for Route {
 /// The reciever half of [`SenderAddress`].
 pub struct ReceiverAddress {
     ctx: Context,
-    _inner: Address,
+    inner: Address,
 }
 
 impl ReceiverAddress {
+    /// Wait for the next raw message received by the stream consumer
+    ///
+    /// Use this to dispatch on the [`codec`](StreamMessage::codec) of
+    /// messages from producers that don't push transport messages.
+    pub async fn next_message(&mut self) -> Result<Routed<StreamMessage>> {
+        Ok(self.ctx.receive_block::<StreamMessage>().await?.take())
+    }
+
     /// Wait for the next message received by the stream consumer
     pub async fn next<T: Message>(&mut self) -> Result<Routed<T>> {
         let routed = self.ctx.receive_block::<StreamMessage>().await?.take();
         let stream_msg = routed.as_body();
         let (addr, local_msg) = routed.dissolve();
 
-        let transport = TransportMessage::decode(&stream_msg.data).unwrap();
-        T::decode(&transport.payload).map(|t| Routed::new(t, addr, local_msg))
+        decode_message(stream_msg).map(|t| Routed::new(t, addr, local_msg))
+    }
+
+    /// Acknowledge that the message at stream `index` was processed
+    ///
+    /// Only has an effect if the stream was set up
+    /// [with ack mode](Stream::with_ack_mode).  The index is the
+    /// [`index`](StreamMessage::index) of the received message.
+    pub async fn ack(&self, index: u64) -> Result<()> {
+        self.ctx
+            .send(self.inner.clone(), StreamWorkerCmd::ack(index))
+            .await
+    }
+
+    /// Turn this receiver into an asynchronous stream of raw messages
+    ///
+    /// The stream yields the same messages as
+    /// [`next_message`](Self::next_message), in order, and ends after
+    /// the first error.  Messages are buffered in the receiver's
+    /// mailbox.  While the mailbox is full, the stream consumer waits
+    /// before fetching more messages, so a slow reader holds back
+    /// further pulls from the stream service.
+    pub fn into_stream(self) -> impl futures::Stream<Item = Result<StreamMessage>> {
+        futures::stream::unfold(Some(self), |rx| async move {
+            let mut rx = rx?;
+            match rx.next_message().await {
+                Ok(msg) => Some((Ok(msg.body()), Some(rx))),
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+}
+
+/// Decode the transport message carried by `msg` as a `T`
+fn decode_message<T: Message>(msg: &StreamMessage) -> Result<T> {
+    let transport = TransportMessage::decode(&msg.data)?;
+    T::check_version(transport.version)?;
+    T::decode(&transport.payload)
+}
+
+/// A stream opened for sending and receiving
+///
+/// Created by [`Stream::open`].
+pub struct StreamHandle {
+    ctx: Context,
+    sender: SenderAddress,
+    receiver: ReceiverAddress,
+}
+
+impl StreamHandle {
+    /// Push a message to the stream
+    pub async fn send<M: Message + Send + 'static>(&self, msg: M) -> Result<()> {
+        self.ctx.send(self.sender.to_route(), msg).await
+    }
+
+    /// Wait for the next message received from the stream
+    pub async fn next<T: Message>(&mut self) -> Result<Routed<T>> {
+        self.receiver.next().await
+    }
+
+    /// Turn this handle into an asynchronous stream of messages
+    ///
+    /// Built on [`ReceiverAddress::into_stream`].  Messages which can
+    /// not be decoded as a `T` yield an error, and the stream ends
+    /// after the first error receiving a message.
+    pub fn into_stream<T: Message>(self) -> impl futures::Stream<Item = Result<T>> {
+        use futures::StreamExt;
+        self.receiver
+            .into_stream()
+            .map(|msg| msg.and_then(|msg| decode_message(&msg)))
+    }
+
+    /// Split this handle into its sender and receiver addresses
+    pub fn split(self) -> (SenderAddress, ReceiverAddress) {
+        (self.sender, self.receiver)
     }
 }
 
@@ -95,6 +183,7 @@ impl Stream {
                 stream_service: "stream".into(),
                 index_service: "stream_index".into(),
                 client_id: None,
+                ack_mode: false,
             })
     }
 
@@ -133,11 +222,42 @@ impl Stream {
         }
     }
 
+    /// Require messages to be acknowledged by the receiver
+    ///
+    /// By default the consumer saves its index as soon as it has
+    /// pulled messages, so messages which were pulled but not yet
+    /// processed are lost when the node stops.  In ack mode, every
+    /// message handed to the [`ReceiverAddress`] must be acknowledged
+    /// with [`ReceiverAddress::ack`].  The saved index never moves past
+    /// the lowest unacknowledged message, which gives at-least-once
+    /// delivery: after a restart, delivery resumes from that message,
+    /// even if messages after it were already acknowledged.
+    ///
+    /// Messages forwarded along their own onward route, or to the
+    /// address given to [`with_recipient`](Self::with_recipient),
+    /// count as acknowledged once they have been forwarded.  Only
+    /// messages handed to the [`ReceiverAddress`] are tracked.
+    pub fn with_ack_mode(self) -> Self {
+        Self {
+            ack_mode: true,
+            ..self
+        }
+    }
+
     /// Specify an address to forward incoming messages to
     ///
+    /// Messages which have no onward route left are sent to `addr`
+    /// instead of the [`ReceiverAddress`].  Messages pushed by a
+    /// stream producer arrive as the original message, with a return
+    /// route via this stream's producer; other data arrives as a
+    /// [`StreamMessage`].
+    ///
+    /// The recipient can not acknowledge messages, so in
+    /// [ack mode](Self::with_ack_mode) messages sent to it count as
+    /// acknowledged once they have been forwarded.
+    ///
     /// When setting up a stream without calling this function
-    /// messages will be buffered by the StreamConsumer and must be
-    /// polled via the [`StreamWorkerCmd`].
+    /// messages are delivered to the [`ReceiverAddress`].
     pub fn with_recipient<A: Into<Address>>(self, addr: A) -> Self {
         Self {
             forwarding_address: Some(addr.into()),
@@ -201,6 +321,7 @@ impl Stream {
                     receiver_rx.clone(),
                     self.stream_service.clone(),
                     self.index_service.clone(),
+                    self.ack_mode,
                 ),
             )
             .await?;
@@ -219,9 +340,110 @@ impl Stream {
                 inner: sender_address,
             },
             ReceiverAddress {
-                _inner: receiver_address,
+                inner: receiver_address,
                 ctx: self.ctx.new_context(receiver_rx).await?,
             },
         ))
     }
+
+    /// Open the stream `stream_name` for both sending and receiving
+    ///
+    /// This starts a producer and a consumer for the same stream, as
+    /// [`connect`](Self::connect) does, and packages them into a
+    /// [`StreamHandle`].  Messages sent through the handle are
+    /// received by every consumer of the stream, including the
+    /// handle itself unless the stream was set up
+    /// [with a recipient](Self::with_recipient).
+    pub async fn open<R, S>(&self, route: R, stream_name: S) -> Result<StreamHandle>
+    where
+        R: Into<Route>,
+        S: Into<String>,
+    {
+        let stream_name = stream_name.into();
+        let (sender, receiver) = self
+            .connect(route, stream_name.clone(), stream_name)
+            .await?;
+        Ok(StreamHandle {
+            ctx: self.ctx.new_context(Address::random_local()).await?,
+            sender,
+            receiver,
+        })
+    }
+
+    /// Wait until all messages pushed via `sender` have been confirmed
+    ///
+    /// Pushes which were rejected by the stream service count as
+    /// confirmed.  Returns an error if the stream producer did not
+    /// drain its pending pushes within `timeout_secs` seconds.
+    pub async fn flush(&self, sender: &SenderAddress, timeout_secs: u64) -> Result<()> {
+        let mut ctx = self.ctx.new_context(Address::random_local()).await?;
+        let res = wait_for_flush(&mut ctx, &sender.inner, timeout_secs).await;
+        let stopped = self.ctx.stop_worker(ctx.address()).await;
+        res.and(stopped)
+    }
+
+    /// Query the head and tail indices of the stream `stream_name`
+    ///
+    /// The `route` parameter is the route to the remote which hosts
+    /// the stream service, as passed to [`connect`](Self::connect).
+    /// Use [`StreamStatus::lag`] to find out how far behind a
+    /// consumer is.  Returns an error if the stream service did not
+    /// answer within `timeout_secs` seconds.
+    pub async fn status<R, S>(
+        &self,
+        route: R,
+        stream_name: S,
+        timeout_secs: u64,
+    ) -> Result<StreamStatus>
+    where
+        R: Into<Route>,
+        S: Into<String>,
+    {
+        let mut ctx = self.ctx.new_context(Address::random_local()).await?;
+        let route: Route = route
+            .into()
+            .modify()
+            .append(self.stream_service.clone())
+            .into();
+        let res = query_status(&mut ctx, route, stream_name.into(), timeout_secs).await;
+        let stopped = self.ctx.stop_worker(ctx.address()).await;
+        stopped.and(res)
+    }
+}
+
+/// Ask the producer at `sender` to tell `ctx` once its pushes are confirmed
+async fn wait_for_flush(ctx: &mut Context, sender: &Address, timeout_secs: u64) -> Result<()> {
+    ctx.send(sender.clone(), StreamWorkerCmd::flush()).await?;
+    let res = ctx
+        .receive_timeout::<StreamWorkerCmd>(timeout_secs)
+        .await
+        .map(|_| ());
+
+    // The producer must not answer once `ctx` is stopped
+    if res.is_err() {
+        ctx.send(sender.clone(), StreamWorkerCmd::cancel_flush())
+            .await?;
+    }
+    res
+}
+
+/// Ask the stream service at `route` for the status of `stream_name`
+async fn query_status(
+    ctx: &mut Context,
+    route: Route,
+    stream_name: String,
+    timeout_secs: u64,
+) -> Result<StreamStatus> {
+    ctx.send(route, StreamStatusRequest::new(stream_name))
+        .await?;
+
+    let pp = ctx
+        .receive_timeout::<ProtocolPayload>(timeout_secs)
+        .await?
+        .take()
+        .body();
+    match Response::parse(pp)? {
+        Response::Status(status) => Ok(status),
+        _ => Err(OckamError::NoSuchProtocol.into()),
+    }
 }
@@ -1,3 +1,4 @@
+use super::StreamWorkerCmd;
 use crate::{
     monotonic::Monotonic,
     protocols::{
@@ -6,7 +7,12 @@ use crate::{
     },
     Any, Context, OckamError, Result, Route, Routed, Worker,
 };
-use ockam_core::compat::{boxed::Box, collections::VecDeque, string::String};
+use ockam_core::compat::{
+    boxed::Box,
+    collections::{BTreeSet, VecDeque},
+    string::String,
+    vec::Vec,
+};
 use ockam_core::{Decodable, Encodable};
 
 pub struct StreamProducer {
@@ -16,6 +22,10 @@ pub struct StreamProducer {
     // parser: ProtocolParser<Self>,
     outbox: VecDeque<ProtocolPayload>,
     ids: Monotonic,
+    /// Request ids of pushes which have not been confirmed yet
+    pending: BTreeSet<u64>,
+    /// Routes waiting for all pending pushes to be confirmed
+    flush_waiters: Vec<Route>,
     /// Keep track of whether this producer has been initialised
     ///
     /// The reason for this is that `route` first is the Route to the
@@ -65,6 +75,15 @@ async fn handle_response(
                 index.u64(),
                 status
             );
+
+            // Failed pushes are no longer pending either, so that
+            // flushing never waits on a confirm which won't come
+            w.pending.remove(&request_id.u64());
+            if w.pending.is_empty() {
+                for waiter in core::mem::take(&mut w.flush_waiters) {
+                    ctx.send(waiter, StreamWorkerCmd::Flush).await?;
+                }
+            }
             Ok(())
         }
         _ => Err(OckamError::NoSuchProtocol.into()),
@@ -97,13 +116,35 @@ impl Worker for StreamProducer {
             if Response::check_id(id) {
                 let response = Response::parse(pp)?;
                 return handle_response(self, ctx, msg, response).await;
+            } else if StreamWorkerCmd::check_id(id) {
+                return match StreamWorkerCmd::parse(pp)? {
+                    StreamWorkerCmd::Flush if self.pending.is_empty() => {
+                        ctx.send(msg.return_route(), StreamWorkerCmd::Flush).await
+                    }
+                    StreamWorkerCmd::Flush => {
+                        debug!("Flush requested with {} pending pushes", self.pending.len());
+                        self.flush_waiters.push(msg.return_route());
+                        Ok(())
+                    }
+                    StreamWorkerCmd::CancelFlush => {
+                        let waiter = msg.return_route();
+                        self.flush_waiters.retain(|w| *w != waiter);
+                        Ok(())
+                    }
+                    cmd => {
+                        warn!("Unhandled message type {:?}", cmd);
+                        Err(OckamError::NoSuchProtocol.into())
+                    }
+                };
             }
         }
 
         let mut trans = msg.into_transport_message();
         trans.onward_route.step()?; // Consume THIS address
 
-        let proto_msg = PushRequest::new(self.ids.next() as u64, trans.encode()?);
+        let request_id = self.ids.next() as u64;
+        let proto_msg = PushRequest::new(request_id, trans.encode()?);
+        self.pending.insert(request_id);
 
         if self.init {
             debug!(
@@ -131,6 +172,8 @@ impl StreamProducer {
             stream_service,
             outbox: VecDeque::new(),
             ids: Monotonic::new(),
+            pending: BTreeSet::new(),
+            flush_waiters: Vec::new(),
             init: false,
         }
     }
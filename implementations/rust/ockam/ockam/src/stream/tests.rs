@@ -0,0 +1,502 @@
+//! Ockam stream tests
+use crate::{
+    protocols::{
+        stream::{requests::*, responses::*, TRANSPORT_MESSAGE_CODEC},
+        ProtocolParser, ProtocolPayload,
+    },
+    stream::{Stream, StreamWorkerCmd},
+    Any, Context, Result, Route, Routed, Worker,
+};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+use futures::StreamExt;
+use ockam_core::compat::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::String,
+    sync::{Arc, Mutex},
+    vec::Vec,
+};
+use ockam_core::{route, DeadLetter, Decodable, Encodable, Uint};
+
+/// A stream service which confirms pushes only after a short delay
+struct SlowStreamService {
+    confirmed: Arc<AtomicUsize>,
+    delay: Duration,
+}
+
+#[crate::worker]
+impl Worker for SlowStreamService {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let pp = ProtocolPayload::decode(msg.payload())?;
+        match pp.protocol.as_str() {
+            "stream_create" => {
+                let req = CreateStreamRequest::decode(&pp.data)?;
+                let name = req.stream_name.unwrap_or_default();
+                ctx.send(msg.return_route(), InitResponse::new(name)).await
+            }
+            "stream_push" => {
+                let req = PushRequest::decode(&pp.data)?;
+                ockam_node::tokio::time::sleep(self.delay).await;
+                self.confirmed.fetch_add(1, Ordering::SeqCst);
+                let confirm = PushConfirm::new(req.request_id.u64(), true, req.request_id.u64());
+                ctx.send(msg.return_route(), confirm).await
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[crate::test]
+async fn flush_waits_for_push_confirms(ctx: &mut Context) -> Result<()> {
+    let confirmed = Arc::new(AtomicUsize::new(0));
+    ctx.start_worker(
+        "stream",
+        SlowStreamService {
+            confirmed: confirmed.clone(),
+            delay: Duration::from_millis(100),
+        },
+    )
+    .await?;
+
+    let stream = Stream::new(ctx).await?;
+    let (tx, _rx) = stream.connect(Route::new(), "flush-tx", "flush-rx").await?;
+
+    for i in 0..3 {
+        ctx.send(tx.to_route().append("app"), format!("message {}", i))
+            .await?;
+    }
+
+    stream.flush(&tx, 5).await?;
+    assert_eq!(confirmed.load(Ordering::SeqCst), 3);
+
+    ctx.stop().await
+}
+
+#[crate::test]
+async fn flush_timeout_stops_waiting(ctx: &mut Context) -> Result<()> {
+    let confirmed = Arc::new(AtomicUsize::new(0));
+    ctx.start_worker(
+        "stream",
+        SlowStreamService {
+            confirmed: confirmed.clone(),
+            delay: Duration::from_millis(1500),
+        },
+    )
+    .await?;
+
+    let stream = Stream::new(ctx).await?;
+    let (tx, _rx) = stream.connect(Route::new(), "flush-tx", "flush-rx").await?;
+    ctx.send(tx.to_route().append("app"), "message".to_string())
+        .await?;
+
+    // Replies to stopped contexts end up here
+    let mut dead_letters = ctx.new_context("dead_letters").await?;
+    ctx.set_dead_letter_address("dead_letters").await?;
+
+    let workers = ctx.list_workers().await?.len();
+    assert!(stream.flush(&tx, 1).await.is_err());
+    assert_eq!(ctx.list_workers().await?.len(), workers);
+
+    // Once the push is confirmed, the producer has nobody to tell
+    while confirmed.load(Ordering::SeqCst) == 0 {
+        ockam_node::tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    ockam_node::tokio::time::sleep(Duration::from_millis(100)).await;
+    let flush_replies = dead_letters
+        .receive_all::<DeadLetter>()
+        .await?
+        .into_iter()
+        .filter_map(|d| ProtocolPayload::decode(&d.message().payload).ok())
+        .filter(|pp| StreamWorkerCmd::check_id(pp.protocol.as_str()))
+        .count();
+    assert_eq!(flush_replies, 0);
+
+    ctx.stop().await
+}
+
+/// A stream service which keeps a single stream in memory
+#[derive(Default)]
+struct MemoryStreamService {
+    messages: Vec<StreamMessage>,
+    /// Saved consumer indices by client id
+    indices: Arc<Mutex<BTreeMap<String, u64>>>,
+}
+
+impl MemoryStreamService {
+    fn push(&mut self, data: Vec<u8>, codec: Option<String>) -> u64 {
+        let index = self.messages.len() as u64;
+        self.messages.push(StreamMessage {
+            index: index.into(),
+            data,
+            codec,
+        });
+        index
+    }
+}
+
+#[crate::worker]
+impl Worker for MemoryStreamService {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let pp = ProtocolPayload::decode(msg.payload())?;
+        match pp.protocol.as_str() {
+            "stream_create" => {
+                let req = CreateStreamRequest::decode(&pp.data)?;
+                let name = req.stream_name.unwrap_or_default();
+                ctx.send(msg.return_route(), InitResponse::new(name)).await
+            }
+            "stream_push" => {
+                let req = PushRequest::decode(&pp.data)?;
+                let index = self.push(req.data, None);
+                let confirm = PushConfirm::new(req.request_id.u64(), true, index);
+                ctx.send(msg.return_route(), confirm).await
+            }
+            "stream_push_tagged" => {
+                let req = TaggedPushRequest::decode(&pp.data)?;
+                let index = self.push(req.data, Some(req.codec));
+                let confirm = PushConfirm::new(req.request_id.u64(), true, index);
+                ctx.send(msg.return_route(), confirm).await
+            }
+            "stream_pull" => {
+                let req = PullRequest::decode(&pp.data)?;
+                let messages: Vec<_> = self
+                    .messages
+                    .iter()
+                    .skip(req.index.u64() as usize)
+                    .map(|m| StreamMessage {
+                        index: m.index.u64().into(),
+                        data: m.data.clone(),
+                        codec: m.codec.clone(),
+                    })
+                    .collect();
+                let response = PullResponse::new(req.request_id.u64(), messages);
+                ctx.send(msg.return_route(), response).await
+            }
+            "stream_status" => {
+                let req = StreamStatusRequest::decode(&pp.data)?;
+                let count = self.messages.len() as u64;
+                let response =
+                    StreamStatus::new(req.stream_name, 0, count.saturating_sub(1), count);
+                ctx.send(msg.return_route(), response).await
+            }
+            "stream_index" => match IndexRequest::decode(&pp.data)? {
+                IndexRequest::Get {
+                    client_id,
+                    stream_name,
+                } => {
+                    let index = self.indices.lock().unwrap().get(&client_id).copied();
+                    let response = IndexResponse {
+                        client_id,
+                        stream_name,
+                        index: index.map(Uint::from),
+                    };
+                    let pp = ProtocolPayload {
+                        protocol: "stream_index".into(),
+                        data: response.encode()?,
+                    };
+                    ctx.send(msg.return_route(), pp).await
+                }
+                IndexRequest::Save {
+                    client_id, index, ..
+                } => {
+                    self.indices.lock().unwrap().insert(client_id, index.u64());
+                    Ok(())
+                }
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn untagged_push_request_keeps_legacy_layout() -> Result<()> {
+    let pp = PushRequest::new(7, vec![1, 2, 3]);
+    assert_eq!(pp.protocol.as_str(), "stream_push");
+    assert_eq!(pp.data, (Uint::from(7), vec![1u8, 2, 3]).encode()?);
+
+    let pp = TaggedPushRequest::new(7, vec![1, 2, 3], "application/json");
+    assert_eq!(pp.protocol.as_str(), "stream_push_tagged");
+    let req = TaggedPushRequest::decode(&pp.data)?;
+    assert_eq!(req.data, vec![1, 2, 3]);
+    assert_eq!(req.codec, "application/json");
+    Ok(())
+}
+
+#[test]
+fn pull_response_carries_codec_per_message() -> Result<()> {
+    let codecs = [
+        Some(TRANSPORT_MESSAGE_CODEC),
+        None,
+        Some("application/json"),
+    ];
+    let messages: Vec<_> = codecs
+        .iter()
+        .enumerate()
+        .map(|(i, codec)| StreamMessage {
+            index: (i as u64).into(),
+            data: vec![i as u8],
+            codec: codec.map(String::from),
+        })
+        .collect();
+
+    let response = match Response::parse(PullResponse::new(1, messages))? {
+        Response::PullResponse(r) => r,
+        _ => panic!("expected a pull response"),
+    };
+    let decoded: Vec<_> = response
+        .messages
+        .iter()
+        .map(|m| m.codec.as_deref())
+        .collect();
+    assert_eq!(decoded, codecs);
+
+    // Stream services without codec support send no tags at all
+    let legacy = ProtocolPayload {
+        protocol: "stream_pull".into(),
+        data: (Uint::from(1), vec![(Uint::from(0), vec![1u8])]).encode()?,
+    };
+    let response = match Response::parse(legacy)? {
+        Response::PullResponse(r) => r,
+        _ => panic!("expected a pull response"),
+    };
+    assert_eq!(response.messages.len(), 1);
+    assert_eq!(response.messages[0].codec, None);
+
+    // Untagged responses keep that layout
+    let untagged = vec![StreamMessage {
+        index: 0.into(),
+        data: vec![1],
+        codec: None,
+    }];
+    let pp = PullResponse::new(1, untagged);
+    assert_eq!(pp.protocol.as_str(), "stream_pull");
+    assert_eq!(
+        pp.data,
+        (Uint::from(1), vec![(Uint::from(0), vec![1u8])]).encode()?
+    );
+    Ok(())
+}
+
+#[crate::test]
+async fn consumer_receives_codec_tags(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker(
+        vec!["stream", "stream_index"],
+        MemoryStreamService::default(),
+    )
+    .await?;
+
+    let stream = Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50));
+    let (_tx, mut rx) = stream.connect(Route::new(), "codec", "codec").await?;
+
+    // Other producers push messages with their own encodings
+    ctx.send(
+        route!["stream"],
+        TaggedPushRequest::new(1, "{}", "application/json"),
+    )
+    .await?;
+    ctx.send(route!["stream"], PushRequest::new(2, vec![0xff]))
+        .await?;
+
+    let json = rx.next_message().await?;
+    assert_eq!(json.codec.as_deref(), Some("application/json"));
+    assert_eq!(json.data, b"{}");
+
+    // Untagged data that isn't a transport message is passed on as is
+    let opaque = rx.next_message().await?;
+    assert_eq!(opaque.codec, None);
+    assert_eq!(opaque.data, vec![0xff]);
+
+    ctx.stop().await
+}
+
+#[crate::test]
+async fn receiver_stream_yields_messages_in_order(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker(
+        vec!["stream", "stream_index"],
+        MemoryStreamService::default(),
+    )
+    .await?;
+
+    let stream = Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50));
+    let (_tx, rx) = stream.connect(Route::new(), "async", "async").await?;
+
+    for i in 0..5 {
+        ctx.send(
+            route!["stream"],
+            TaggedPushRequest::new(i, vec![i as u8], "application/octet-stream"),
+        )
+        .await?;
+    }
+
+    let messages: Vec<StreamMessage> = rx
+        .into_stream()
+        .take(5)
+        .map(|msg| msg.unwrap())
+        .collect()
+        .await;
+    let data: Vec<_> = messages.iter().map(|m| m.data.clone()).collect();
+    assert_eq!(data, vec![vec![0], vec![1], vec![2], vec![3], vec![4]]);
+
+    ctx.stop().await
+}
+
+#[crate::test]
+async fn status_reports_last_pushed_index(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("stream", MemoryStreamService::default())
+        .await?;
+
+    let stream = Stream::new(ctx).await?;
+    let status = stream.status(Route::new(), "status", 5).await?;
+    assert_eq!(status.count.u64(), 0);
+    assert_eq!(status.lag(0), 0);
+
+    let mut last_index = 0;
+    for i in 0..5 {
+        ctx.send(route!["stream"], PushRequest::new(i, vec![i as u8]))
+            .await?;
+        let pp = ctx.receive::<ProtocolPayload>().await?.take().body();
+        match Response::parse(pp)? {
+            Response::PushConfirm(confirm) => last_index = confirm.index.u64(),
+            _ => panic!("expected a push confirm"),
+        }
+    }
+
+    let status = stream.status(Route::new(), "status", 5).await?;
+    assert_eq!(status.stream_name, "status");
+    assert_eq!(status.tail.u64(), last_index);
+    assert_eq!(status.count.u64(), 5);
+    assert_eq!(status.lag(2), 3);
+    assert_eq!(status.lag(last_index + 1), 0);
+
+    ctx.stop().await
+}
+
+#[crate::test]
+async fn ack_mode_redelivers_unacked_messages(ctx: &mut Context) -> Result<()> {
+    let service = MemoryStreamService::default();
+    let indices = service.indices.clone();
+    ctx.start_worker(vec!["stream", "stream_index"], service)
+        .await?;
+
+    let stream = Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50))
+        .client_id("acker")
+        .with_ack_mode();
+    let (_tx, mut rx) = stream.connect(Route::new(), "acks", "acks").await?;
+
+    for i in 0..3 {
+        ctx.send(route!["stream"], PushRequest::new(i, vec![i as u8]))
+            .await?;
+    }
+    for i in 0..3 {
+        assert_eq!(rx.next_message().await?.index.u64(), i);
+    }
+
+    // Leave a gap at the second message
+    rx.ack(0).await?;
+    rx.ack(2).await?;
+    while indices.lock().unwrap().get("acker") != Some(&1) {
+        ockam_node::tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Restart the consumer
+    ctx.stop_worker(rx.inner.clone()).await?;
+    let (_tx, mut rx) = stream.connect(Route::new(), "acks", "acks").await?;
+
+    let msg = rx.next_message().await?.body();
+    assert_eq!(msg.index.u64(), 1);
+    assert_eq!(msg.data, vec![1]);
+    assert_eq!(rx.next_message().await?.index.u64(), 2);
+
+    ctx.stop().await
+}
+
+#[crate::test]
+async fn stream_handle_round_trips_messages(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker(
+        vec!["stream", "stream_index"],
+        MemoryStreamService::default(),
+    )
+    .await?;
+
+    let stream = Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50));
+    let mut handle = stream.open(Route::new(), "handle").await?;
+
+    handle.send(String::from("Hello")).await?;
+    assert_eq!(handle.next::<String>().await?.body(), "Hello");
+
+    handle.send(String::from("Ockam")).await?;
+    let mut messages = Box::pin(handle.into_stream::<String>());
+    assert_eq!(messages.next().await.unwrap()?, "Ockam");
+
+    ctx.stop().await
+}
+
+#[crate::test]
+async fn stream_forwards_to_recipient(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker(
+        vec!["stream", "stream_index"],
+        MemoryStreamService::default(),
+    )
+    .await?;
+
+    let stream = Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50))
+        .with_recipient(ctx.address());
+    let handle = stream.open(Route::new(), "recipient").await?;
+
+    handle.send(String::from("Hello")).await?;
+    let msg = ctx.receive::<String>().await?.take();
+    let (sender, _) = handle.split();
+    assert_eq!(msg.return_route().next()?, &*sender);
+    assert_eq!(msg.body(), "Hello");
+
+    ctx.stop().await
+}
+
+#[crate::test]
+async fn ack_mode_counts_recipient_messages_as_acked(ctx: &mut Context) -> Result<()> {
+    let service = MemoryStreamService::default();
+    let indices = service.indices.clone();
+    ctx.start_worker(vec!["stream", "stream_index"], service)
+        .await?;
+
+    let stream = Stream::new(ctx)
+        .await?
+        .with_interval(Duration::from_millis(50))
+        .client_id("forwarder")
+        .with_ack_mode()
+        .with_recipient(ctx.address());
+    let handle = stream.open(Route::new(), "forwarded").await?;
+
+    for i in 0..3 {
+        handle.send(format!("message {}", i)).await?;
+    }
+    for i in 0..3 {
+        assert_eq!(
+            ctx.receive::<String>().await?.take().body(),
+            format!("message {}", i)
+        );
+    }
+
+    // Nothing was acknowledged, yet the index moves past all messages
+    while indices.lock().unwrap().get("forwarder") != Some(&3) {
+        ockam_node::tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    ctx.stop().await
+}
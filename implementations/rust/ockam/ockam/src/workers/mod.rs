@@ -1,7 +1,12 @@
 //! A collection of utility workers for various use cases.
 //!
-//! Currently, this contains an echoer worker which is used in many examples,
-//! and is useful for debugging.
+//! Currently, this contains an echoer worker which is used in many
+//! examples, a route tap which is useful for debugging, and a
+//! recorder and replayer for capturing message sequences in tests.
 mod echoer;
+mod recorder;
+mod route_tap;
 
 pub use echoer::*;
+pub use recorder::*;
+pub use route_tap::*;
@@ -0,0 +1,215 @@
+use crate::{Any, Context, Result, Route, Routed, Worker};
+use minicbor::{Decoder, Encoder};
+use ockam_core::compat::{
+    boxed::Box,
+    sync::{Arc, Mutex},
+    vec::Vec,
+};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Decodable, Encodable, Error, LocalMessage, TransportMessage};
+
+/// A sequence of messages captured by a [`Recorder`].
+///
+/// Clones share the same log, so a recording can be handed to a
+/// [`Recorder`] and inspected or replayed later.  A recording is
+/// serialised as a CBOR array, which holds the encoding of every
+/// [`LocalMessage`] as a byte string.
+#[derive(Clone)]
+pub struct Recording {
+    messages: Arc<Mutex<Vec<LocalMessage>>>,
+}
+
+impl Recording {
+    /// Create an empty recording.
+    pub fn new() -> Self {
+        Self::from_messages(Vec::new())
+    }
+
+    /// Return a copy of all recorded messages, in the order they were
+    /// recorded.
+    pub fn messages(&self) -> Vec<LocalMessage> {
+        self.messages.lock().unwrap().clone()
+    }
+
+    /// Return the number of recorded messages.
+    pub fn len(&self) -> usize {
+        self.messages.lock().unwrap().len()
+    }
+
+    /// Return `true` if nothing has been recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Serialise the recording, e.g. to store it in a file.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let messages = self.messages.lock().unwrap();
+        let mut enc = Encoder::new(Vec::new());
+        enc.array(messages.len() as u64).map_err(cbor_error)?;
+        for msg in messages.iter() {
+            enc.bytes(&msg.encode()?).map_err(cbor_error)?;
+        }
+        Ok(enc.into_writer())
+    }
+
+    /// Load a recording previously serialised with
+    /// [`Recording::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut dec = Decoder::new(bytes);
+        let len = dec
+            .array()
+            .map_err(cbor_error)?
+            .ok_or_else(|| cbor_error("recording has no length"))?;
+        // The length is untrusted, so don't allocate for it up front
+        let mut messages = Vec::new();
+        for _ in 0..len {
+            messages.push(LocalMessage::decode(dec.bytes().map_err(cbor_error)?)?);
+        }
+        if dec.position() != bytes.len() {
+            return Err(cbor_error("trailing bytes after recording"));
+        }
+        Ok(Self::from_messages(messages))
+    }
+
+    fn from_messages(messages: Vec<LocalMessage>) -> Self {
+        Self {
+            messages: Arc::new(Mutex::new(messages)),
+        }
+    }
+
+    fn push(&self, msg: LocalMessage) {
+        self.messages.lock().unwrap().push(msg);
+    }
+}
+
+#[cfg(feature = "std")]
+fn cbor_error<E>(e: E) -> Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    Error::new(Origin::Ockam, Kind::Serialization, e)
+}
+
+#[cfg(not(feature = "std"))]
+fn cbor_error<E: core::fmt::Display>(e: E) -> Error {
+    Error::new(Origin::Ockam, Kind::Serialization, e)
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A worker which appends every message passing through it to a
+/// [`Recording`], and then forwards the message unchanged.
+///
+/// Insert the recorder as a hop into any route.  Messages are
+/// recorded as they arrive at the recorder, i.e. with the recorder
+/// still at the head of their onward route.  Like the
+/// [`RouteTap`](crate::workers::RouteTap), the recorder adds itself
+/// to the return route.
+///
+/// Mostly intended for use in tests, together with a [`Replayer`].
+pub struct Recorder {
+    recording: Recording,
+}
+
+impl Recorder {
+    /// Create a recorder which appends to `recording`.
+    pub fn new(recording: Recording) -> Self {
+        Self { recording }
+    }
+}
+
+#[crate::worker]
+impl Worker for Recorder {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let mut local_msg = msg.into_local_message();
+        self.recording.push(local_msg.clone());
+
+        let transport_msg = local_msg.transport_mut();
+        transport_msg.onward_route.step()?;
+        transport_msg.return_route.modify().prepend(ctx.address());
+
+        ctx.forward(local_msg).await
+    }
+}
+
+/// Sends the messages of a [`Recording`] again.
+///
+/// Every message is sent to the target route with its original
+/// payload, transport message version and local info, in the order
+/// it was recorded.  The return route is replaced with the address of
+/// the replaying context, so that replies reach the caller instead of
+/// the original sender.
+pub struct Replayer {
+    recording: Recording,
+}
+
+impl Replayer {
+    /// Create a replayer for `recording`.
+    pub fn new(recording: Recording) -> Self {
+        Self { recording }
+    }
+
+    /// Replay all recorded messages to `target`, returning the number
+    /// of messages sent.
+    pub async fn replay(&self, ctx: &Context, target: impl Into<Route>) -> Result<usize> {
+        let target = target.into();
+        let messages = self.recording.messages();
+
+        for msg in &messages {
+            let transport_msg = TransportMessage {
+                version: msg.transport().version,
+                onward_route: target.clone(),
+                return_route: ctx.address().into(),
+                payload: msg.transport().payload.clone(),
+            };
+            ctx.forward(LocalMessage::new(transport_msg, msg.local_info().to_vec()))
+                .await?;
+        }
+
+        Ok(messages.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route;
+    use ockam_core::compat::string::{String, ToString};
+
+    #[crate::test]
+    async fn replay_recorded_exchange(ctx: &mut Context) -> Result<()> {
+        let recording = Recording::new();
+        ctx.start_worker("recorder", Recorder::new(recording.clone()))
+            .await?;
+
+        for body in ["a", "b", "c"] {
+            ctx.send(route!["recorder", ctx.address()], body.to_string())
+                .await?;
+            let msg = ctx.receive::<String>().await?.take();
+            assert_eq!(msg.return_route(), route!["recorder", ctx.address()]);
+            assert_eq!(msg.body(), body);
+        }
+        assert_eq!(recording.len(), 3);
+
+        // Round-trip through the serialised format before replaying
+        let recording = Recording::from_bytes(&recording.to_bytes()?)?;
+        let mut target = ctx.new_context("target").await?;
+        let sent = Replayer::new(recording).replay(ctx, "target").await?;
+        assert_eq!(sent, 3);
+
+        for body in ["a", "b", "c"] {
+            let msg = target.receive::<String>().await?.take();
+            assert_eq!(msg.return_route(), route![ctx.address()]);
+            assert_eq!(msg.body(), body);
+        }
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{Any, Context, Message, Result, Route, Routed, Worker};
+use ockam_core::compat::boxed::Box;
+use serde::{Deserialize, Serialize};
+
+/// A single observation made by a [`RouteTap`].
+#[derive(Serialize, Deserialize, Message, Clone, Debug, PartialEq, Eq)]
+pub struct RouteRecord {
+    /// The onward route of the message as it arrived at the tap.
+    pub onward_route: Route,
+    /// The return route of the message as it arrived at the tap.
+    pub return_route: Route,
+    /// Length of the message payload in bytes.
+    pub payload_len: u64,
+}
+
+/// A worker which records the routes of every message passing
+/// through it, and then forwards the message unchanged.
+///
+/// Insert the tap as a hop into any route to inspect the onward and
+/// return routes at that point.  Each observation is written to the
+/// `debug!` log and, if a collector route was given, also sent there
+/// as a [`RouteRecord`].  The tap adds itself to the return route, so
+/// replies travel back through it as well.
+///
+/// Mostly intended for use when debugging.
+pub struct RouteTap {
+    collector: Option<Route>,
+}
+
+impl RouteTap {
+    /// Create a tap which only logs the routes it observes.
+    pub fn new() -> Self {
+        Self { collector: None }
+    }
+
+    /// Create a tap which additionally sends a [`RouteRecord`] to
+    /// `collector` for every message it observes.
+    pub fn with_collector(collector: impl Into<Route>) -> Self {
+        Self {
+            collector: Some(collector.into()),
+        }
+    }
+}
+
+impl Default for RouteTap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[crate::worker]
+impl Worker for RouteTap {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let mut local_msg = msg.into_local_message();
+        let transport_msg = local_msg.transport_mut();
+
+        let record = RouteRecord {
+            onward_route: transport_msg.onward_route.clone(),
+            return_route: transport_msg.return_route.clone(),
+            payload_len: transport_msg.payload.len() as u64,
+        };
+        debug!(
+            "Tap {}: onward route: {}, return route: {}, payload length: {}",
+            ctx.address(),
+            record.onward_route,
+            record.return_route,
+            record.payload_len
+        );
+
+        transport_msg.onward_route.step()?;
+        transport_msg.return_route.modify().prepend(ctx.address());
+
+        if let Some(collector) = &self.collector {
+            ctx.send(collector.clone(), record).await?;
+        }
+
+        ctx.forward(local_msg).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route;
+    use ockam_core::compat::string::String;
+    use ockam_core::Encodable;
+
+    #[crate::test]
+    async fn tap_records_routes(ctx: &mut Context) -> Result<()> {
+        let mut collector = ctx.new_context("collector").await?;
+        ctx.start_worker("tap", RouteTap::with_collector("collector"))
+            .await?;
+
+        ctx.send(route!["tap", ctx.address()], String::from("Hello"))
+            .await?;
+
+        let record = collector.receive::<RouteRecord>().await?.take().body();
+        assert_eq!(record.onward_route, route!["tap", ctx.address()]);
+        assert_eq!(record.return_route, route![ctx.address()]);
+        assert_eq!(
+            record.payload_len,
+            String::from("Hello").encode()?.len() as u64
+        );
+
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!(msg.return_route(), route!["tap", ctx.address()]);
+        assert_eq!(msg.body(), "Hello");
+
+        ctx.stop().await
+    }
+}
@@ -40,7 +40,9 @@ pub use traits::*;
 mod tests {
     use crate::SecureChannel;
     use ockam_core::compat::string::{String, ToString};
-    use ockam_core::{AsyncTryClone, Result, Route};
+    use ockam_core::{
+        Any, AsyncTryClone, Encodable, LocalMessage, Result, Route, TransportMessage,
+    };
     use ockam_key_exchange_core::NewKeyExchanger;
     use ockam_key_exchange_xx::XXNewKeyExchanger;
     use ockam_node::Context;
@@ -75,4 +77,79 @@ mod tests {
         assert_eq!(ctx.receive::<String>().await?, test_msg);
         ctx.stop().await
     }
+
+    #[ockam_macros::test]
+    async fn channel_keeps_transport_message_version(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?);
+        SecureChannel::create_listener_extended(
+            ctx,
+            "secure_channel_listener".to_string(),
+            new_key_exchanger.async_try_clone().await?,
+            vault.async_try_clone().await?,
+        )
+        .await?;
+        let initiator = SecureChannel::create_extended(
+            ctx,
+            Route::new().append("secure_channel_listener"),
+            None,
+            new_key_exchanger.initiator().await?,
+            vault,
+        )
+        .await?;
+
+        // The version signals the codec of the payload to the receiver
+        let msg = TransportMessage {
+            version: 2,
+            onward_route: Route::new()
+                .append(initiator.address())
+                .append("app")
+                .into(),
+            return_route: Route::new().append(ctx.address()).into(),
+            payload: "Hello, channel".to_string().encode()?,
+        };
+        ctx.forward(LocalMessage::new(msg, vec![])).await?;
+
+        let msg = ctx.receive::<Any>().await?.take();
+        assert_eq!(msg.local_message().transport().version, 2);
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn channel_teardown_destroys_secrets(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?);
+        SecureChannel::create_listener_extended(
+            ctx,
+            "secure_channel_listener".to_string(),
+            new_key_exchanger.async_try_clone().await?,
+            vault.async_try_clone().await?,
+        )
+        .await?;
+        let baseline = vault.secret_count().await;
+
+        let initiator = SecureChannel::create_extended(
+            ctx,
+            Route::new().append("secure_channel_listener"),
+            None,
+            new_key_exchanger.initiator().await?,
+            vault.async_try_clone().await?,
+        )
+        .await?;
+        ctx.send(
+            Route::new().append(initiator.address()).append("app"),
+            "Hello, channel".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert!(vault.secret_count().await > baseline);
+
+        // Close both ends of the channel
+        let responder = msg.return_route().next()?.clone();
+        ctx.stop_worker_and_wait(responder).await?;
+        ctx.stop_worker_and_wait(initiator.address()).await?;
+
+        assert_eq!(vault.secret_count().await, baseline);
+        ctx.stop().await
+    }
 }
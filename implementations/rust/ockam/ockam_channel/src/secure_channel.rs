@@ -3,7 +3,7 @@ use crate::{
     SecureChannelNewKeyExchanger, SecureChannelVault, SecureChannelWorker,
 };
 use ockam_core::compat::rand::random;
-use ockam_core::{Address, Result, Route};
+use ockam_core::{Address, AllowSourceAddress, Result, Route};
 use ockam_node::Context;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
@@ -97,8 +97,57 @@ impl SecureChannel {
         key_exchanger: impl SecureChannelKeyExchanger,
         vault: impl SecureChannelVault,
     ) -> Result<SecureChannelInfo> {
-        let address_remote: Address = random();
+        let address: Address = random();
         let address_local: Address = random();
+        // The child context only waits for the channel worker's
+        // completion notice
+        let mut child_ctx = ctx
+            .new_context_with_access_control(address, AllowSourceAddress(address_local.clone()))
+            .await?;
+
+        Self::create_impl(
+            &mut child_ctx,
+            address_local,
+            route,
+            first_responder_address,
+            key_exchanger,
+            vault,
+        )
+        .await
+    }
+
+    /// Create initiator channel with given route to a remote channel listener,
+    /// waiting for the key exchange to complete on the given context.
+    ///
+    /// Stopping the context's address before the key exchange completes
+    /// aborts the channel and destroys its partial secrets.
+    pub async fn create_cancellable(
+        ctx: &mut Context,
+        route: impl Into<Route>,
+        first_responder_address: Option<Address>,
+        key_exchanger: impl SecureChannelKeyExchanger,
+        vault: impl SecureChannelVault,
+    ) -> Result<SecureChannelInfo> {
+        Self::create_impl(
+            ctx,
+            random(),
+            route,
+            first_responder_address,
+            key_exchanger,
+            vault,
+        )
+        .await
+    }
+
+    async fn create_impl(
+        ctx: &mut Context,
+        address_local: Address,
+        route: impl Into<Route>,
+        first_responder_address: Option<Address>,
+        key_exchanger: impl SecureChannelKeyExchanger,
+        vault: impl SecureChannelVault,
+    ) -> Result<SecureChannelInfo> {
+        let address_remote: Address = random();
 
         debug!(
             "Starting SecureChannel initiator at local: {}, remote: {}",
@@ -107,14 +156,12 @@ impl SecureChannel {
 
         let route = route.into();
 
-        let address: Address = random();
-        let mut child_ctx = ctx.new_context(address).await?;
         let channel = SecureChannelWorker::new(
             true,
             route,
             address_remote.clone(),
             address_local.clone(),
-            Some(child_ctx.address()),
+            Some(ctx.address()),
             first_responder_address,
             key_exchanger,
             vault,
@@ -124,11 +171,17 @@ impl SecureChannel {
         ctx.start_worker(vec![address_remote.clone(), address_local.clone()], channel)
             .await?;
 
-        let resp = child_ctx
-            .receive_timeout::<KeyExchangeCompleted>(120)
-            .await?
-            .take()
-            .body();
+        let resp = match ctx.receive_timeout::<KeyExchangeCompleted>(120).await {
+            Ok(resp) => resp.take().body(),
+            Err(e) => {
+                debug!(
+                    "Aborting SecureChannel initiator at local: {}",
+                    &address_local
+                );
+                ctx.stop_worker(address_local).await?;
+                return Err(e);
+            }
+        };
 
         let info = SecureChannelInfo {
             worker_address: address_local,
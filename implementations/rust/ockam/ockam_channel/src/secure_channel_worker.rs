@@ -4,7 +4,7 @@ use crate::{
 };
 use ockam_core::async_trait;
 use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
-use ockam_core::vault::Secret;
+use ockam_core::vault::{derive_nonce, Secret};
 use ockam_core::{
     Address, Any, Decodable, Encodable, LocalMessage, Message, Result, Route, Routed,
     TransportMessage, Worker,
@@ -64,24 +64,17 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelWorker<V,
         })
     }
 
+    // Each direction has its own key, so both can use direction 0. The
+    // nonce is capped at u16::MAX, after which the channel must be rekeyed.
     fn convert_nonce_u16(nonce: u16) -> ([u8; 2], [u8; 12]) {
-        let mut n: [u8; 12] = [0; 12];
-        let b: [u8; 2] = nonce.to_be_bytes();
-        n[10] = b[0];
-        n[11] = b[1];
-
-        (b, n)
+        (nonce.to_be_bytes(), derive_nonce(nonce as u64, 0))
     }
 
     fn convert_nonce_small(b: &[u8]) -> Result<[u8; 12]> {
         if b.len() != 2 {
             return Err(SecureChannelError::InvalidNonce.into());
         }
-        let mut n: [u8; 12] = [0; 12];
-        n[10] = b[0];
-        n[11] = b[1];
-
-        Ok(n)
+        Ok(derive_nonce(u16::from_be_bytes([b[0], b[1]]) as u64, 0))
     }
 
     fn get_keys(keys: &mut Option<ChannelKeys>) -> Result<&mut ChannelKeys> {
@@ -125,13 +118,15 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelWorker<V,
         debug!("SecureChannel received Encrypt");
 
         let reply = msg.return_route();
-        let mut onward_route = msg.onward_route();
+        let onward_route = msg.onward_route();
         let transport_message = msg.into_transport_message();
-        let payload = transport_message.payload;
 
-        let _ = onward_route.step();
-
-        let msg = TransportMessage::v1(onward_route, reply, payload.to_vec());
+        let msg = TransportMessage {
+            version: transport_message.version,
+            onward_route,
+            return_route: reply,
+            payload: transport_message.payload,
+        };
         let payload = msg.encode()?;
 
         let payload = {
@@ -318,6 +313,26 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> Worker for SecureChann
         Ok(())
     }
 
+    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        // Try to destroy every secret, even if destroying one fails,
+        // and report the first failure
+        let mut res = Ok(());
+
+        // Key exchange didn't complete, destroy its partial secrets
+        if let Some(mut key_exchanger) = self.key_exchanger.take() {
+            res = res.and(key_exchanger.abort().await);
+        }
+
+        // Channel keys are only used by this worker
+        if let Some(keys) = self.keys.take() {
+            let encrypt = self.vault.secret_destroy(keys.encrypt_key).await;
+            let decrypt = self.vault.secret_destroy(keys.decrypt_key).await;
+            res = res.and(encrypt).and(decrypt);
+        }
+
+        res
+    }
+
     async fn handle_message(
         &mut self,
         ctx: &mut Self::Context,
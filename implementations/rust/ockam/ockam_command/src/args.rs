@@ -1,4 +1,5 @@
 use clap::{Args, Parser, Subcommand};
+use std::net::SocketAddr;
 
 #[derive(Clone, Debug, Parser)]
 #[clap(name = "ockam", version)]
@@ -21,7 +22,7 @@ pub struct InletOpts {
     /// Alias that is used to identify Control Plane node
     pub alias: String,
     /// Bind address for the inlet to listen on.
-    pub inlet_address: String,
+    pub inlet_address: SocketAddr,
 }
 
 #[derive(Clone, Debug, Subcommand)]
@@ -52,6 +53,18 @@ pub enum Command {
     /// been modified, etc.
     #[clap(display_order = 1005)]
     PrintPath,
+    /// Rotate a key of the currently configured identity.
+    ///
+    /// The updated identity is written back to `<ockam_dir>/identity.json`
+    /// and `<ockam_dir>/vault.json`, and the new public key is printed.
+    #[clap(display_order = 1006)]
+    RotateIdentityKey(RotateKeyOpts),
+    /// Open a secure channel to a listener to check connectivity.
+    ///
+    /// The channel is authenticated with the currently configured identity
+    /// and closed again once the check is done.
+    #[clap(display_order = 1007)]
+    CreateSecureChannel(SecureChannelOpts),
 }
 
 #[derive(Clone, Debug, Args)]
@@ -62,6 +75,7 @@ pub struct OutletOpts {
     pub alias: String,
     /// Address of tcp service running on Control Plane node that will receive
     /// connections from the Outlet
+    #[clap(parse(try_from_str = parse_host_port))]
     pub outlet_target: String,
 }
 
@@ -79,6 +93,13 @@ pub struct IdentityOpts {
     pub overwrite: bool,
 }
 
+#[derive(Clone, Debug, Args)]
+pub struct RotateKeyOpts {
+    /// Label of the key to rotate. The root key is rotated if omitted.
+    #[clap(long)]
+    pub label: Option<String>,
+}
+
 #[derive(Clone, Debug, Args)]
 pub struct AddTrustedIdentityOpts {
     /// Discard any identities currently in `~/.config/ockam/trusted`, and
@@ -91,3 +112,58 @@ pub struct AddTrustedIdentityOpts {
     /// to avoid adding duplicates entries in the file. Note that
     pub to_trust: String,
 }
+
+#[derive(Clone, Debug, Args)]
+pub struct SecureChannelOpts {
+    /// TCP address of the node running the secure channel listener.
+    pub peer: String,
+    /// Address of the secure channel listener on that node.
+    #[clap(long, default_value = "secure_channel_listener")]
+    pub listener: String,
+    /// Accept the listener's identity without checking it against
+    /// `<ockam_dir>/trusted`.
+    #[clap(long)]
+    pub trust_any: bool,
+    /// Address of an echoer on the peer node. If given, a message is sent
+    /// through the channel and the round-trip time is reported.
+    #[clap(long)]
+    pub ping: Option<String>,
+}
+
+/// Check that `s` has the form `host:port`
+fn parse_host_port(s: &str) -> Result<String, String> {
+    match s.rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => Ok(s.to_string()),
+        _ => Err(format!("expected `host:port`, got {:?}", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, clap::Error> {
+        CliArgs::try_parse_from([&["ockam"], args].concat()).map(|a| a.command)
+    }
+
+    #[test]
+    fn inlet_address_is_validated() {
+        let cmd = parse(&["create-inlet", "cloud:4000", "alias", "127.0.0.1:5000"]).unwrap();
+        match cmd {
+            Command::CreateInlet(opts) => {
+                assert_eq!(opts.inlet_address, "127.0.0.1:5000".parse().unwrap())
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+
+        assert!(parse(&["create-inlet", "cloud:4000", "alias", "127.0.0.1"]).is_err());
+        assert!(parse(&["create-inlet", "cloud:4000", "alias", "127.0.0.1:port"]).is_err());
+    }
+
+    #[test]
+    fn outlet_target_is_validated() {
+        assert!(parse(&["create-outlet", "cloud:4000", "alias", "localhost:5000"]).is_ok());
+        assert!(parse(&["create-outlet", "cloud:4000", "alias", "localhost"]).is_err());
+        assert!(parse(&["create-outlet", "cloud:4000", "alias", ":5000"]).is_err());
+    }
+}
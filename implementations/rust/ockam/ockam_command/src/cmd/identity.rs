@@ -1,4 +1,8 @@
-use crate::{args::IdentityOpts, identity::save_identity, storage};
+use crate::{
+    args::{IdentityOpts, RotateKeyOpts},
+    identity::{load_identity_and_vault, save_identity},
+    storage,
+};
 use anyhow::Context as Ctx;
 use ockam::{identity::*, vault::*, Context};
 
@@ -36,3 +40,64 @@ pub async fn run(args: IdentityOpts, mut ctx: Context) -> anyhow::Result<()> {
     ctx.stop().await?;
     Ok(())
 }
+
+pub async fn rotate_key(args: RotateKeyOpts, mut ctx: Context) -> anyhow::Result<()> {
+    storage::ensure_identity_exists(false)?;
+    let ockam_dir = storage::get_ockam_dir()?;
+    let public_key = rotate_key_in(&ctx, &ockam_dir, args.label).await?;
+    println!("{}", hex::encode(public_key.data()));
+    ctx.stop().await?;
+    Ok(())
+}
+
+/// Rotate a key of the identity stored in `ockam_dir` and save the result
+async fn rotate_key_in(
+    ctx: &Context,
+    ockam_dir: &std::path::Path,
+    label: Option<String>,
+) -> anyhow::Result<PublicKey> {
+    let (exported, vault) = load_identity_and_vault(ockam_dir)?;
+    let identity = Identity::import(ctx, &vault, exported).await?;
+    let label = label.unwrap_or_else(|| IdentityStateConst::ROOT_LABEL.to_string());
+    identity
+        .rotate_key(label.clone())
+        .await
+        .with_context(|| format!("Failed to rotate key {:?}", label))?;
+    let public_key = identity.get_public_key(label).await?;
+    let exported = identity.export().await;
+    tracing::info!("Saving rotated identity: {:?}", exported.id.key_id());
+    save_identity(ockam_dir, &exported, &vault).await?;
+    Ok(public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::load_identity;
+
+    #[ockam::test(crate = "ockam")]
+    async fn rotate_default_key(ctx: &mut Context) -> ockam::Result<()> {
+        let ockam_dir = std::env::temp_dir().join(format!("ockam-{}", rand::random::<u64>()));
+        std::fs::create_dir(&ockam_dir).unwrap();
+
+        let vault = Vault::create();
+        let identity = Identity::create(ctx, &vault).await?;
+        let before = identity.get_root_public_key().await?;
+        save_identity(&ockam_dir, &identity.export().await, &vault)
+            .await
+            .unwrap();
+
+        let after = rotate_key_in(ctx, &ockam_dir, None).await.unwrap();
+        let changes = load_identity(&ockam_dir.join("identity.json"))
+            .unwrap()
+            .change_history;
+        std::fs::remove_dir_all(&ockam_dir).unwrap();
+
+        assert_ne!(before, after);
+        assert_eq!(
+            changes.as_ref().len(),
+            identity.get_changes().await?.len() + 1
+        );
+        ctx.stop().await
+    }
+}
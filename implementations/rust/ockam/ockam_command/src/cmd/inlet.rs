@@ -53,7 +53,10 @@ impl SessionManager for InletSessionManager {
 
         let inlet_address = self
             .tcp
-            .create_inlet(&self.args.inlet_address, route![channel.clone(), "outlet"])
+            .create_inlet(
+                self.args.inlet_address.to_string(),
+                route![channel.clone(), "outlet"],
+            )
             .await?;
 
         self.existing_session = Some(ExistingSession {
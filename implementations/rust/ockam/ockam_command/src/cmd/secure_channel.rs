@@ -0,0 +1,106 @@
+use crate::{args::SecureChannelOpts, identity, storage, OckamVault};
+use anyhow::Context as Ctx;
+use ockam::{identity::*, route, Context, Route, TcpTransport, TCP};
+use std::time::{Duration, Instant};
+
+/// How long to wait for the channel handshake and the ping reply
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn run(args: SecureChannelOpts, mut ctx: Context) -> anyhow::Result<()> {
+    // The trusted list is not consulted when trusting any identity
+    storage::ensure_identity_exists(!args.trust_any)?;
+    let ockam_dir = storage::get_ockam_dir()?;
+
+    let (exported_id, vault) = identity::load_identity_and_vault(&ockam_dir)?;
+    let mut identity = Identity::import(&ctx, &vault, exported_id).await?;
+    TcpTransport::create(&ctx).await?;
+
+    let route = route![(TCP, &args.peer), &args.listener];
+    let rtt = if args.trust_any {
+        check_channel(&ctx, &mut identity, route, TrustEveryonePolicy, args.ping).await?
+    } else {
+        let policy = storage::load_trust_policy(&ockam_dir)?;
+        check_channel(&ctx, &mut identity, route, policy, args.ping).await?
+    };
+
+    println!("Secure channel to {} established.", args.peer);
+    if let Some(rtt) = rtt {
+        println!("Round-trip time: {:?}", rtt);
+    }
+    ctx.stop().await?;
+    Ok(())
+}
+
+/// Open a secure channel along `route`, optionally ping an echoer
+/// through it, and close it again
+///
+/// Returns the round-trip time of the ping, if one was sent.
+async fn check_channel(
+    ctx: &Context,
+    identity: &mut Identity<OckamVault>,
+    route: Route,
+    policy: impl TrustPolicy,
+    ping: Option<String>,
+) -> anyhow::Result<Option<Duration>> {
+    let channel = identity
+        .create_secure_channel_extended(route.clone(), policy, TIMEOUT)
+        .await
+        .with_context(|| format!("Failed to establish a secure channel along {}", route))?;
+    tracing::info!("Secure channel established at {}", channel);
+
+    let rtt = match ping {
+        Some(echoer) => Some(ping_echoer(ctx, route![channel.clone(), echoer]).await),
+        None => None,
+    };
+
+    // Stopping the identity channel also stops the underlying channel
+    ctx.stop_worker(channel).await?;
+    rtt.transpose()
+}
+
+/// Send a message to an echoer and wait for it to come back
+async fn ping_echoer(ctx: &Context, route: Route) -> anyhow::Result<Duration> {
+    let mut child_ctx = ctx.new_context(ockam::Address::random_local()).await?;
+    let payload = format!("ping {}", rand::random::<u32>());
+
+    let start = Instant::now();
+    child_ctx.send(route.clone(), payload.clone()).await?;
+    let reply = child_ctx
+        .receive_timeout::<String>(TIMEOUT.as_secs())
+        .await
+        .with_context(|| format!("No reply from echoer at {}", route))?
+        .take()
+        .body();
+    let rtt = start.elapsed();
+
+    anyhow::ensure!(reply == payload, "Echoer replied with {:?}", reply);
+    Ok(rtt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::{vault::Vault, workers::Echoer};
+
+    #[ockam::test(crate = "ockam")]
+    async fn check_local_channel(ctx: &mut Context) -> ockam::Result<()> {
+        let bob = Identity::create(ctx, &Vault::create()).await?;
+        bob.create_secure_channel_listener("listener", TrustEveryonePolicy)
+            .await?;
+        ctx.start_worker("echoer", Echoer).await?;
+
+        let mut alice = Identity::create(ctx, &Vault::create()).await?;
+        let rtt = check_channel(
+            ctx,
+            &mut alice,
+            route!["listener"],
+            TrustEveryonePolicy,
+            Some("echoer".into()),
+        )
+        .await
+        .unwrap();
+        assert!(rtt.is_some());
+
+        ctx.stop().await
+    }
+}
@@ -22,6 +22,7 @@ pub(crate) mod cmd {
     pub(crate) mod identity;
     pub(crate) mod inlet;
     pub(crate) mod outlet;
+    pub(crate) mod secure_channel;
 }
 
 pub(crate) mod session {
@@ -45,6 +46,12 @@ pub fn run_main() {
         args::Command::AddTrustedIdentity(arg) => exit_with_result(verbose > 0, add_trusted(arg)),
         args::Command::PrintIdentity => exit_with_result(verbose > 0, print_identity()),
         args::Command::PrintPath => exit_with_result(verbose > 0, print_ockam_dir()),
+        args::Command::RotateIdentityKey(arg) => {
+            node_subcommand(verbose > 0, arg, cmd::identity::rotate_key)
+        }
+        args::Command::CreateSecureChannel(arg) => {
+            node_subcommand(verbose > 0, arg, cmd::secure_channel::run)
+        }
     }
 }
 
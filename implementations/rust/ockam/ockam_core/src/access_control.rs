@@ -1,5 +1,5 @@
 use crate::compat::boxed::Box;
-use crate::{LocalMessage, Result};
+use crate::{Address, LocalMessage, Result};
 
 /// Defines the interface for message flow authorization.
 ///
@@ -50,6 +50,134 @@ impl AccessControl for DenyAll {
     }
 }
 
+/// An Access Control type that only allows messages from a single
+/// address.
+///
+/// The source of a message is the first hop of its return route.
+pub struct AllowSourceAddress(pub Address);
+
+#[async_trait]
+impl AccessControl for AllowSourceAddress {
+    async fn is_authorized(&self, local_msg: &LocalMessage) -> Result<bool> {
+        match local_msg.transport().return_route.next() {
+            Ok(source) if source == &self.0 => crate::allow(),
+            _ => crate::deny(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use rate_limit::RateLimit;
+
+#[cfg(feature = "std")]
+mod rate_limit {
+    use super::AccessControl;
+    use crate::compat::{
+        boxed::Box,
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+    use crate::{Address, LocalMessage, Result};
+    use core::time::Duration;
+    use std::time::Instant;
+
+    /// An Access Control type that limits how many messages pass
+    /// within an interval.
+    ///
+    /// Messages are counted either globally or, with
+    /// [`per_source`](RateLimit::per_source), separately for the first
+    /// hop of each message's return route.  Once the budget of an
+    /// interval is used up, further messages are rejected until the
+    /// next interval starts.  Rejected messages are dropped by the
+    /// worker's mailbox and counted in [`dropped`](RateLimit::dropped).
+    ///
+    /// Clones share their budget and counters, so a clone kept outside
+    /// the worker can be used to inspect them.
+    #[derive(Clone)]
+    pub struct RateLimit {
+        per_source: bool,
+        max_per_interval: u32,
+        interval: Duration,
+        state: Arc<Mutex<State>>,
+    }
+
+    #[derive(Default)]
+    struct State {
+        windows: BTreeMap<Option<Address>, Window>,
+        dropped: u64,
+    }
+
+    struct Window {
+        start: Instant,
+        count: u32,
+    }
+
+    impl RateLimit {
+        /// Allow up to `max_per_interval` messages within each `interval`
+        pub fn new(max_per_interval: u32, interval: Duration) -> Self {
+            Self {
+                per_source: false,
+                max_per_interval,
+                interval,
+                state: Default::default(),
+            }
+        }
+
+        /// Count messages separately for each source instead of globally
+        ///
+        /// The source of a message is the first hop of its return route.
+        pub fn per_source(mut self, per_source: bool) -> Self {
+            self.per_source = per_source;
+            self
+        }
+
+        /// Return the number of messages rejected so far
+        pub fn dropped(&self) -> u64 {
+            self.state.lock().unwrap().dropped
+        }
+    }
+
+    #[async_trait]
+    impl AccessControl for RateLimit {
+        async fn is_authorized(&self, local_msg: &LocalMessage) -> Result<bool> {
+            let source = if self.per_source {
+                local_msg.transport().return_route.next().ok().cloned()
+            } else {
+                None
+            };
+
+            let now = Instant::now();
+            let interval = self.interval;
+            let mut state = self.state.lock().unwrap();
+
+            // Forget the sources whose interval is over before tracking
+            // a new one
+            if !state.windows.contains_key(&source) {
+                state
+                    .windows
+                    .retain(|_, w| now.duration_since(w.start) < interval);
+            }
+
+            let window = state.windows.entry(source).or_insert(Window {
+                start: now,
+                count: 0,
+            });
+            if now.duration_since(window.start) >= interval {
+                window.start = now;
+                window.count = 0;
+            }
+
+            if window.count < self.max_per_interval {
+                window.count += 1;
+                crate::allow()
+            } else {
+                state.dropped += 1;
+                crate::deny()
+            }
+        }
+    }
+}
+
 #[cfg(feature = "alloc")]
 #[cfg(test)]
 mod tests {
@@ -59,7 +187,7 @@ mod tests {
     };
     use futures_util::future::{Future, FutureExt};
 
-    use super::{AccessControl, AllowAll, DenyAll};
+    use super::{AccessControl, AllowAll, AllowSourceAddress, DenyAll};
 
     #[test]
     fn test_allow_all() {
@@ -93,6 +221,56 @@ mod tests {
         assert_ne!(is_authorized, crate::allow().ok());
     }
 
+    #[test]
+    fn test_allow_source_address() {
+        let policy = AllowSourceAddress("alice".into());
+        let authorized = |return_route| {
+            let msg =
+                LocalMessage::new(TransportMessage::v1(route![], return_route, vec![]), vec![]);
+            poll_once(async { policy.is_authorized(&msg).await }).unwrap()
+        };
+
+        assert!(authorized(route!["alice", "bob"]));
+        assert!(!authorized(route!["bob", "alice"]));
+        assert!(!authorized(route![]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_rate_limit_per_source() {
+        use super::RateLimit;
+        use core::time::Duration;
+
+        let msg_from = |source: &str| {
+            LocalMessage::new(
+                TransportMessage::v1(route![], route![source], vec![]),
+                vec![],
+            )
+        };
+        let limit = RateLimit::new(2, Duration::from_secs(60)).per_source(true);
+        let authorized = |source: &str| {
+            let msg = msg_from(source);
+            poll_once(async { limit.is_authorized(&msg).await }).unwrap()
+        };
+
+        assert!(authorized("alice"));
+        assert!(authorized("alice"));
+        assert!(!authorized("alice"));
+        assert!(authorized("bob"));
+        assert!(authorized("bob"));
+        assert!(!authorized("bob"));
+        assert_eq!(limit.dropped(), 2);
+
+        let limit = RateLimit::new(2, Duration::from_secs(60));
+        let msg = msg_from("alice");
+        let authorized = || poll_once(async { limit.is_authorized(&msg).await }).unwrap();
+        assert!(authorized());
+        assert!(authorized());
+        let msg = msg_from("bob");
+        assert!(!poll_once(async { limit.is_authorized(&msg).await }).unwrap());
+        assert_eq!(limit.dropped(), 1);
+    }
+
     /// TODO document
     /// TODO move somewhere sensible
     fn poll_once<'a, F, T>(future: F) -> Result<T>
@@ -324,7 +324,7 @@ macro_rules! from_prim {
             $($Enum::$Variant => ()),*
         };
         match $prim {
-            $(v if v == ($Enum::$Variant as _) => Some($Enum::$Variant),)*
+            $(v if v == ($Enum::$Variant as u8) => Some($Enum::$Variant),)*
             _ => None,
         }
     }}
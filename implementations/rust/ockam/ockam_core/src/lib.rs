@@ -53,6 +53,7 @@ pub mod compat;
 mod error;
 mod message;
 mod processor;
+mod retry;
 mod routing;
 mod uint;
 pub mod vault;
@@ -62,6 +63,7 @@ pub use access_control::*;
 pub use error::*;
 pub use message::*;
 pub use processor::*;
+pub use retry::RetryPolicy;
 pub use routing::*;
 pub use uint::*;
 pub use worker::*;
@@ -4,7 +4,7 @@ use crate::{
         vec::Vec,
     },
     errcode::{Kind, Origin},
-    Address, Error, LocalMessage, Result, Route, TransportMessage,
+    Address, Error, LocalInfo, LocalMessage, Result, Route, TransportMessage,
 };
 use core::{
     fmt::{self, Debug, Display, Formatter},
@@ -67,6 +67,22 @@ pub trait Decodable: Sized {
     /// Decode a slice.
     #[allow(clippy::ptr_arg)]
     fn decode(e: &[u8]) -> Result<Self>;
+
+    /// Check that a payload sent with the transport message `version`
+    /// can be decoded with [`decode`](Self::decode)
+    ///
+    /// Only payloads encoded with [`Codec::Bare`] are accepted.
+    fn check_version(version: u8) -> Result<()> {
+        if version == Codec::Bare.transport_version() {
+            Ok(())
+        } else {
+            Err(Error::new(
+                Origin::Core,
+                Kind::Serialization,
+                "payload is not encoded with BARE, use Routed::cast_with_codec",
+            ))
+        }
+    }
 }
 
 /// A user defined message that can be serialised and deserialized.
@@ -102,6 +118,63 @@ impl From<serde_bare::error::Error> for Error {
     }
 }
 
+/// A serialisation format for message payloads
+///
+/// Every message is encoded with [`Codec::Bare`] unless a different
+/// codec is chosen when sending it.  The codec is signalled to the
+/// receiver in the [`version`](TransportMessage::version) of the
+/// transport message, see [`Codec::transport_version`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Codec {
+    /// BARE, the encoding of [`Encodable`] for all serde types
+    Bare,
+    /// JSON, for interoperability with external systems
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl Codec {
+    /// Return the transport message version signalling this codec
+    pub fn transport_version(self) -> u8 {
+        match self {
+            Self::Bare => 1,
+            #[cfg(feature = "json")]
+            Self::Json => 2,
+        }
+    }
+
+    /// Return the codec signalled by a transport message version
+    pub fn from_transport_version(version: u8) -> Option<Self> {
+        match version {
+            1 => Some(Self::Bare),
+            #[cfg(feature = "json")]
+            2 => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Encode `msg` with this codec
+    pub fn encode<T: Serialize>(self, msg: &T) -> Result<Encoded> {
+        match self {
+            Self::Bare => Ok(serde_bare::to_vec(msg)?),
+            #[cfg(feature = "json")]
+            Self::Json => serde_json::to_vec(msg)
+                .map_err(|e| Error::new(Origin::Core, Kind::Serialization, e)),
+        }
+    }
+
+    /// Decode a `T` encoded with this codec
+    pub fn decode<T: DeserializeOwned>(self, data: &[u8]) -> Result<T> {
+        match self {
+            Self::Bare => Ok(serde_bare::from_slice(data)?),
+            #[cfg(feature = "json")]
+            Self::Json => serde_json::from_slice(data)
+                .map_err(|e| Error::new(Origin::Core, Kind::Serialization, e)),
+        }
+    }
+}
+
 /// A message wrapper that provides message route information.
 ///
 /// Workers can accept arbitrary message types, which may not contain
@@ -151,9 +224,15 @@ impl<M: Message> Routed<M> {
     }
 
     /// Return a copy of the onward route for the wrapped message.
+    ///
+    /// The address this message was received on has already been
+    /// stepped, so the route starts with the next hop and is empty if
+    /// this worker is the final recipient.
     #[inline]
     pub fn onward_route(&self) -> Route {
-        self.local_msg.transport().onward_route.clone()
+        let mut onward_route = self.local_msg.transport().onward_route.clone();
+        let _ = onward_route.step();
+        onward_route
     }
 
     /// Return a copy of the full return route for the wrapped message.
@@ -161,6 +240,16 @@ impl<M: Message> Routed<M> {
     pub fn return_route(&self) -> Route {
         self.local_msg.transport().return_route.clone()
     }
+
+    /// Return copies of the onward and return routes for the wrapped message.
+    ///
+    /// See [`onward_route`](Self::onward_route) and
+    /// [`return_route`](Self::return_route).
+    #[inline]
+    pub fn routes(&self) -> (Route, Route) {
+        (self.onward_route(), self.return_route())
+    }
+
     /// Return a copy of the sender address for the wrapped message.
     #[inline]
     pub fn sender(&self) -> Address {
@@ -197,6 +286,21 @@ impl<M: Message> Routed<M> {
         &self.local_msg
     }
 
+    /// Return the local info attached to the wrapped message.
+    #[inline]
+    pub fn local_info(&self) -> &[LocalInfo] {
+        self.local_msg.local_info()
+    }
+
+    /// Return the codec the payload was encoded with
+    ///
+    /// Returns `None` if the transport message version does not
+    /// signal a known codec.
+    #[inline]
+    pub fn codec(&self) -> Option<Codec> {
+        Codec::from_transport_version(self.local_msg.transport().version)
+    }
+
     /// Return a reference to the underlying transport message's binary payload.
     #[inline]
     pub fn payload(&self) -> &[u8] {
@@ -213,6 +317,7 @@ impl<M: Message> Routed<M> {
 impl Routed<Any> {
     /// Try to cast an `Any` message into another valid message type
     pub fn cast<M: Message>(self) -> Result<Routed<M>> {
+        M::check_version(self.local_msg.transport().version)?;
         let inner = M::decode(&self.local_msg.transport().payload)?;
         Ok(Routed {
             inner,
@@ -220,6 +325,28 @@ impl Routed<Any> {
             local_msg: self.local_msg,
         })
     }
+
+    /// Try to cast an `Any` message into another message type,
+    /// decoding it with the codec it was sent with
+    ///
+    /// Use this for messages which may have been sent with a codec
+    /// other than [`Codec::Bare`], which [`cast`](Self::cast) and
+    /// typed workers can not decode.
+    pub fn cast_with_codec<M: Message + DeserializeOwned>(self) -> Result<Routed<M>> {
+        let codec = self.codec().ok_or_else(|| {
+            Error::new(
+                Origin::Core,
+                Kind::Unsupported,
+                "unknown transport message version",
+            )
+        })?;
+        let inner = codec.decode(&self.local_msg.transport().payload)?;
+        Ok(Routed {
+            inner,
+            msg_addr: self.msg_addr,
+            local_msg: self.local_msg,
+        })
+    }
 }
 
 impl<M: Message> Deref for Routed<M> {
@@ -315,4 +442,8 @@ impl Decodable for Any {
     fn decode(_: &[u8]) -> Result<Self> {
         Ok(Self)
     }
+
+    fn check_version(_: u8) -> Result<()> {
+        Ok(())
+    }
 }
@@ -0,0 +1,223 @@
+use crate::compat::rand::{thread_rng, Rng};
+use core::time::Duration;
+
+/// An exponential backoff policy for retried operations, such as
+/// transport (re-)connection attempts
+///
+/// Each call to [`next_delay`](RetryPolicy::next_delay) yields the time
+/// to wait before the next attempt, starting at the initial delay and
+/// growing by the multiplier up to the maximum delay.  Once the
+/// maximum number of attempts has been handed out `None` is returned.
+///
+/// ```
+/// # use core::time::Duration;
+/// # use ockam_core::RetryPolicy;
+/// let mut policy = RetryPolicy::new(3)
+///     .initial_delay(Duration::from_millis(100))
+///     .multiplier(2.0);
+///
+/// assert_eq!(policy.next_delay(), Some(Duration::from_millis(100)));
+/// assert_eq!(policy.next_delay(), Some(Duration::from_millis(200)));
+/// assert_eq!(policy.next_delay(), Some(Duration::from_millis(400)));
+/// assert_eq!(policy.next_delay(), None);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_delay: Duration,
+    multiplier: f64,
+    max_delay: Duration,
+    jitter: f64,
+    attempt: u32,
+    current: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Five attempts, starting at 250 milliseconds and doubling up to
+    /// 10 seconds, without jitter
+    fn default() -> Self {
+        Self::new(5)
+    }
+}
+
+impl RetryPolicy {
+    /// Create a new policy allowing `max_attempts` retries
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            initial_delay: Duration::from_millis(250),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(10),
+            jitter: 0.0,
+            attempt: 0,
+            current: Duration::from_millis(250),
+        }
+    }
+
+    /// Set the delay before the first retry
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self.current = delay;
+        self
+    }
+
+    /// Set the factor applied to the delay after every attempt
+    ///
+    /// Values below `1.0` are treated as `1.0`.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Set the upper bound for a single delay
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Randomly shorten each delay by up to this fraction
+    ///
+    /// The value is clamped to `0.0..=1.0`.  Jitter spreads out
+    /// reconnection attempts of many clients after a shared outage.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Return the number of delays handed out so far
+    pub fn attempts(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Return the delay to wait before the next attempt
+    ///
+    /// Returns `None` once the maximum number of attempts is reached.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.attempt >= self.max_attempts {
+            return None;
+        }
+        self.attempt += 1;
+
+        let delay = self.current.min(self.max_delay);
+        self.current = scale(self.current, self.multiplier, self.max_delay);
+
+        if self.jitter > 0.0 {
+            let factor = 1.0 - self.jitter * thread_rng().gen::<f64>();
+            Some(scale(delay, factor, delay))
+        } else {
+            Some(delay)
+        }
+    }
+
+    /// Start over from the initial delay, e.g. after a successful connection
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+        self.current = self.initial_delay;
+    }
+}
+
+/// Multiply `delay` by `factor`, but return no more than `max`
+///
+/// The product is clamped in f64, since a large product overflows
+/// `Duration` and makes [`Duration::mul_f64`] panic.
+fn scale(delay: Duration, factor: f64, max: Duration) -> Duration {
+    let secs = delay.as_secs_f64() * factor;
+    if secs < max.as_secs_f64() {
+        Duration::from_secs_f64(secs)
+    } else {
+        max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_grow_by_multiplier() {
+        let mut policy = RetryPolicy::new(4)
+            .initial_delay(Duration::from_millis(10))
+            .multiplier(3.0);
+
+        let delays: Vec<_> = core::iter::from_fn(|| policy.next_delay()).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(10),
+                Duration::from_millis(30),
+                Duration::from_millis(90),
+                Duration::from_millis(270),
+            ]
+        );
+    }
+
+    #[test]
+    fn delays_are_capped() {
+        let mut policy = RetryPolicy::new(10)
+            .initial_delay(Duration::from_secs(1))
+            .multiplier(10.0)
+            .max_delay(Duration::from_secs(5));
+
+        assert_eq!(policy.next_delay(), Some(Duration::from_secs(1)));
+        for _ in 0..9 {
+            assert_eq!(policy.next_delay(), Some(Duration::from_secs(5)));
+        }
+    }
+
+    #[test]
+    fn huge_multiplier_does_not_overflow() {
+        let mut policy = RetryPolicy::new(4)
+            .initial_delay(Duration::from_secs(1))
+            .multiplier(f64::INFINITY)
+            .max_delay(Duration::MAX);
+
+        assert_eq!(policy.next_delay(), Some(Duration::from_secs(1)));
+        assert_eq!(policy.next_delay(), Some(Duration::MAX));
+
+        let mut policy = RetryPolicy::new(64)
+            .initial_delay(Duration::from_secs(1))
+            .multiplier(1e10)
+            .max_delay(Duration::MAX);
+        while let Some(delay) = policy.next_delay() {
+            assert!(delay >= Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn stops_after_max_attempts() {
+        let mut policy = RetryPolicy::new(2);
+        assert!(policy.next_delay().is_some());
+        assert!(policy.next_delay().is_some());
+        assert_eq!(policy.next_delay(), None);
+        assert_eq!(policy.attempts(), 2);
+
+        policy.reset();
+        assert_eq!(policy.next_delay(), Some(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn jitter_only_shortens_delays() {
+        let mut policy = RetryPolicy::new(100)
+            .initial_delay(Duration::from_secs(1))
+            .multiplier(1.0)
+            .jitter(0.5);
+
+        while let Some(delay) = policy.next_delay() {
+            assert!(delay <= Duration::from_secs(1));
+            assert!(delay >= Duration::from_millis(500));
+        }
+    }
+
+    #[test]
+    fn jitter_near_max_delay_does_not_overflow() {
+        let mut policy = RetryPolicy::new(100)
+            .initial_delay(Duration::MAX)
+            .max_delay(Duration::MAX)
+            // Rounds the jitter factor to 1.0
+            .jitter(1e-20);
+
+        while let Some(delay) = policy.next_delay() {
+            assert!(delay >= Duration::from_secs(1));
+        }
+    }
+}
@@ -32,6 +32,25 @@ impl AddressSet {
             .map(|_| true)
             .unwrap_or(false)
     }
+
+    /// Check if any address of `other` is contained in this set.
+    pub fn contains_any(&self, other: &AddressSet) -> bool {
+        other.iter().any(|a| self.contains(a))
+    }
+
+    /// Merge the addresses of `other` into this set.
+    ///
+    /// If an address would appear twice in the merged set, the first
+    /// such address is returned and this set is left unchanged.
+    pub fn merge(&mut self, other: AddressSet) -> Result<(), Address> {
+        for (i, a) in other.iter().enumerate() {
+            if self.contains(a) || other.0[..i].contains(a) {
+                return Err(a.clone());
+            }
+        }
+        self.0.extend(other.0);
+        Ok(())
+    }
 }
 
 impl IntoIterator for AddressSet {
@@ -120,6 +139,8 @@ pub struct AddressParseError {
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum AddressParseErrorKind {
+    /// Address string or its data part after the '#' separator is empty.
+    Empty,
     /// Unable to parse address num in the address string.
     InvalidType(core::num::ParseIntError),
     /// Address string has more than one '#' separator.
@@ -140,6 +161,9 @@ impl AddressParseError {
 impl Display for AddressParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.kind {
+            AddressParseErrorKind::Empty => {
+                write!(f, "Invalid address string: address is empty")
+            }
             AddressParseErrorKind::InvalidType(e) => {
                 write!(f, "Failed to parse address type: '{}'", e)
             }
@@ -155,6 +179,17 @@ impl Display for AddressParseError {
 
 impl crate::compat::error::Error for AddressParseError {}
 
+impl From<AddressParseError> for crate::Error {
+    #[track_caller]
+    fn from(err: AddressParseError) -> Self {
+        crate::Error::new(
+            crate::errcode::Origin::Core,
+            crate::errcode::Kind::Invalid,
+            err,
+        )
+    }
+}
+
 impl Address {
     /// Creates a new address from separate transport type and data parts.
     ///
@@ -178,6 +213,7 @@ impl Address {
     /// # Panics
     ///
     /// This function will panic if passed an invalid address string.
+    /// Use [`Address::parse`] to get an [`AddressParseError`] instead.
     ///
     /// # Examples
     ///
@@ -198,6 +234,27 @@ impl Address {
         }
     }
 
+    /// Parses an address from a string, rejecting empty addresses.
+    ///
+    /// Unlike [`Address::from_string`] and the `From` conversions,
+    /// this also fails if the string or its part after the `#`
+    /// separator is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ockam_core::Address;
+    /// assert!(Address::parse("1#carol").is_ok());
+    /// assert!(Address::parse("1#").is_err());
+    /// ```
+    pub fn parse(s: &str) -> Result<Self, AddressParseError> {
+        let addr: Address = s.parse()?;
+        if addr.inner.is_empty() {
+            return Err(AddressParseError::new(AddressParseErrorKind::Empty));
+        }
+        Ok(addr)
+    }
+
     /// Generate a random address with the given transport type.
     ///
     /// # Examples
@@ -432,3 +489,65 @@ fn parse_addr_invalid() {
 fn parse_addr_invalid_multiple_separators() {
     let _ = Address::from_string("1#invalid#");
 }
+
+#[test]
+fn parse_addr_errors() {
+    let kind = |s: &str| Address::parse(s).unwrap_err().kind().clone();
+
+    assert_eq!(kind(""), AddressParseErrorKind::Empty);
+    assert_eq!(kind("1#"), AddressParseErrorKind::Empty);
+    assert!(matches!(
+        kind("#alice"),
+        AddressParseErrorKind::InvalidType(_)
+    ));
+    assert!(matches!(
+        kind("x#alice"),
+        AddressParseErrorKind::InvalidType(_)
+    ));
+    assert!(matches!(
+        kind("256#alice"),
+        AddressParseErrorKind::InvalidType(_)
+    ));
+    assert_eq!(kind("1#a#b"), AddressParseErrorKind::MultipleSep);
+}
+
+#[test]
+fn parse_addr_empty_with_from() {
+    assert_eq!(Address::from(""), Address::new(LOCAL, ""));
+    assert_eq!(Address::from("1#"), Address::new(TransportType::new(1), ""));
+}
+
+#[test]
+fn parse_addr_error_into_error() {
+    fn parse(s: &str) -> crate::Result<Address> {
+        Ok(Address::parse(s)?)
+    }
+
+    let err = parse("x#alice").unwrap_err();
+    assert_eq!(err.code().kind, crate::errcode::Kind::Invalid);
+    assert!(parse("1#alice").is_ok());
+}
+
+#[test]
+fn address_set_merge_disjoint() {
+    let mut set = AddressSet::from(vec!["a", "b"]);
+    let other = AddressSet::from(vec!["c", "1#a"]);
+
+    assert!(!set.contains_any(&other));
+    assert_eq!(set.merge(other), Ok(()));
+    assert_eq!(set, AddressSet::from(vec!["a", "b", "c", "1#a"]));
+}
+
+#[test]
+fn address_set_merge_overlapping() {
+    let mut set = AddressSet::from(vec!["a", "b"]);
+    let other = AddressSet::from(vec!["c", "b", "a"]);
+
+    assert!(set.contains_any(&other));
+    assert_eq!(set.merge(other), Err(Address::from("b")));
+    assert_eq!(set, AddressSet::from(vec!["a", "b"]));
+
+    let dup = AddressSet::from(vec!["c", "c"]);
+    assert_eq!(set.merge(dup), Err(Address::from("c")));
+    assert_eq!(set, AddressSet::from(vec!["a", "b"]));
+}
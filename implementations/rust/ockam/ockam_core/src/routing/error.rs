@@ -8,6 +8,8 @@ use crate::{
 pub enum RouteError {
     /// Message had an incomplete route
     IncompleteRoute,
+    /// Route would exceed the configured maximum number of hops
+    HopLimitExceeded,
 }
 
 impl From<RouteError> for Error {
@@ -15,6 +17,7 @@ impl From<RouteError> for Error {
     fn from(err: RouteError) -> Self {
         let kind = match err {
             RouteError::IncompleteRoute => Kind::Misuse,
+            RouteError::HopLimitExceeded => Kind::Misuse,
         };
         Error::new(Origin::Core, kind, err)
     }
@@ -25,6 +28,7 @@ impl core::fmt::Display for RouteError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             RouteError::IncompleteRoute => "incomplete route".fmt(f),
+            RouteError::HopLimitExceeded => "route hop limit exceeded".fmt(f),
         }
     }
 }
@@ -5,3 +5,9 @@ pub use transport_message::*;
 
 mod local_message;
 pub use local_message::*;
+
+mod dead_letter;
+pub use dead_letter::*;
+
+mod delivery_report;
+pub use delivery_report::*;
@@ -0,0 +1,35 @@
+use crate::{compat::string::String, Message, TransportMessage};
+use serde::{Deserialize, Serialize};
+
+/// A message that could not be delivered to its destination
+///
+/// When a node has a dead-letter address configured, messages whose
+/// next hop can't be resolved are wrapped in a `DeadLetter` and sent
+/// there, instead of being dropped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Message)]
+pub struct DeadLetter {
+    reason: String,
+    message: TransportMessage,
+}
+
+impl DeadLetter {
+    /// Create a new `DeadLetter` for the given message and failure reason
+    pub fn new(reason: impl Into<String>, message: TransportMessage) -> Self {
+        DeadLetter {
+            reason: reason.into(),
+            message,
+        }
+    }
+    /// Why the message could not be delivered
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+    /// Return a reference to the undeliverable message
+    pub fn message(&self) -> &TransportMessage {
+        &self.message
+    }
+    /// Consumes the dead letter and returns the undeliverable message
+    pub fn into_message(self) -> TransportMessage {
+        self.message
+    }
+}
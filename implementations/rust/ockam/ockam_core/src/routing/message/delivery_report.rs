@@ -0,0 +1,92 @@
+use crate::{
+    compat::string::String, Address, Decodable, Encodable, LocalInfo, LocalMessage, Message, Result,
+};
+use serde::{Deserialize, Serialize};
+
+/// [`LocalInfo`] type identifier of a [`DeliveryReportRequest`]
+pub const DELIVERY_REPORT_IDENTIFIER: &str = "DELIVERY_REPORT";
+
+/// Asks the transport which sends a message to another node to
+/// report the outcome with a [`DeliveryReport`]
+///
+/// The request is attached to a [`LocalMessage`] as [`LocalInfo`].
+/// Transports which don't support delivery reports ignore it.
+///
+/// The attempt id is returned in the report, so that a sender can
+/// tell reports of a message it sent again apart.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryReportRequest {
+    report_to: Address,
+    attempt: u64,
+}
+
+impl DeliveryReportRequest {
+    /// Ask for the [`DeliveryReport`] of send `attempt` to be sent to `report_to`
+    pub fn new(report_to: Address, attempt: u64) -> Self {
+        Self { report_to, attempt }
+    }
+    /// Address which receives the [`DeliveryReport`]
+    pub fn report_to(&self) -> &Address {
+        &self.report_to
+    }
+    /// Id of the send attempt this request belongs to
+    pub fn attempt(&self) -> u64 {
+        self.attempt
+    }
+    /// Create the report answering this request
+    pub fn report(&self, status: DeliveryStatus) -> DeliveryReport {
+        DeliveryReport {
+            attempt: self.attempt,
+            status,
+        }
+    }
+    /// Encode the request as [`LocalInfo`]
+    pub fn to_local_info(&self) -> Result<LocalInfo> {
+        Ok(LocalInfo::new(
+            DELIVERY_REPORT_IDENTIFIER.into(),
+            self.encode()?,
+        ))
+    }
+    /// Find the delivery report request attached to `local_msg`, if any
+    pub fn find_info(local_msg: &LocalMessage) -> Option<Self> {
+        local_msg
+            .local_info()
+            .iter()
+            .find(|x| x.type_identifier() == DELIVERY_REPORT_IDENTIFIER)
+            .and_then(|x| Self::decode(x.data()).ok())
+    }
+}
+
+/// Report of a transport on sending a message to another node
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Message)]
+pub struct DeliveryReport {
+    attempt: u64,
+    status: DeliveryStatus,
+}
+
+impl DeliveryReport {
+    /// Id of the send attempt, see [`DeliveryReportRequest::attempt`]
+    pub fn attempt(&self) -> u64 {
+        self.attempt
+    }
+    /// Outcome of the send attempt
+    pub fn status(&self) -> &DeliveryStatus {
+        &self.status
+    }
+    /// Consume the report and return the outcome of the send attempt
+    pub fn into_status(self) -> DeliveryStatus {
+        self.status
+    }
+}
+
+/// Outcome of sending a message to another node
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// The message was written to the connection to the next hop
+    Sent,
+    /// The connection to the next hop failed, sending the message
+    /// again later may succeed
+    Unavailable(String),
+    /// The message can not be sent, e.g. because its route is invalid
+    Rejected(String),
+}
@@ -106,6 +106,7 @@ impl Route {
         RouteBuilder {
             inner: self.inner.clone(),
             write_back: Some(self),
+            max_hops: None,
         }
     }
 
@@ -190,6 +191,45 @@ impl Route {
             .cloned()
             .expect("Route::recipient failed on invalid Route!")
     }
+
+    /// Check whether this route begins with all addresses of `prefix`.
+    ///
+    /// An empty prefix matches every route.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ockam_core::{route, Route};
+    /// let route: Route = route!["1#alice", "bob", "carol"];
+    ///
+    /// assert!(route.starts_with(&route!["1#alice", "bob"]));
+    /// assert!(!route.starts_with(&route!["bob"]));
+    /// ```
+    ///
+    pub fn starts_with(&self, prefix: &Route) -> bool {
+        prefix.inner.len() <= self.inner.len()
+            && self
+                .inner
+                .iter()
+                .zip(prefix.inner.iter())
+                .all(|(a, b)| a == b)
+    }
+
+    /// Check whether the given `Address` appears anywhere in this route.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ockam_core::{route, Address, Route};
+    /// let route: Route = route!["1#alice", "bob"];
+    ///
+    /// assert!(route.contains(&Address::from_string("0#bob")));
+    /// assert!(!route.contains(&Address::from_string("1#bob")));
+    /// ```
+    ///
+    pub fn contains(&self, addr: &Address) -> bool {
+        self.inner.contains(addr)
+    }
 }
 
 impl Display for Route {
@@ -254,6 +294,7 @@ impl<T: Into<Address>> From<Vec<T>> for Route {
 pub struct RouteBuilder<'r> {
     inner: VecDeque<Address>,
     write_back: Option<&'r mut Route>,
+    max_hops: Option<usize>,
 }
 
 impl Default for RouteBuilder<'_> {
@@ -268,9 +309,74 @@ impl RouteBuilder<'_> {
         Self {
             inner: VecDeque::new(),
             write_back: None,
+            max_hops: None,
+        }
+    }
+
+    /// Limit the number of hops of the route.
+    ///
+    /// The `try_*` methods fail as soon as a hop would exceed the
+    /// limit.  Hops added with the plain methods are checked by
+    /// [`build`](Self::build), which fails if the finished route is
+    /// longer than the limit.  Converting the builder with `into()`
+    /// does not check the limit.  By default routes are unbounded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ockam_core::{Result, Route, RouteBuilder};
+    /// # fn main() -> Result<()> {
+    /// let builder: RouteBuilder = Route::new()
+    ///     .with_max_hops(2)
+    ///     .try_append("1#alice")?
+    ///     .try_append("bob")?;
+    ///
+    /// assert!(builder.try_append("carol").is_err());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    pub fn with_max_hops(mut self, max_hops: usize) -> Self {
+        self.max_hops = Some(max_hops);
+        self
+    }
+
+    /// Fail if adding `n` hops would exceed the maximum number of hops.
+    fn check_hops(&self, n: usize) -> Result<()> {
+        match self.max_hops {
+            Some(max) if self.inner.len() + n > max => Err(RouteError::HopLimitExceeded.into()),
+            _ => Ok(()),
         }
     }
 
+    /// Finish the route, failing if it exceeds the maximum number of
+    /// hops.
+    ///
+    /// If the builder was created with [`Route::modify`] the original
+    /// route is only updated when the limit holds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ockam_core::{Route, RouteBuilder};
+    /// let builder: RouteBuilder = Route::new()
+    ///     .with_max_hops(1)
+    ///     .append("1#alice")
+    ///     .append("bob");
+    ///
+    /// assert!(builder.build().is_err());
+    /// ```
+    ///
+    pub fn build(mut self) -> Result<Route> {
+        if let Err(e) = self.check_hops(0) {
+            self.write_back = None;
+            return Err(e);
+        }
+        Ok(Route {
+            inner: self.inner.clone(),
+        })
+    }
+
     /// Push a new item to the back of the route.
     ///
     /// # Examples
@@ -290,6 +396,13 @@ impl RouteBuilder<'_> {
         self
     }
 
+    /// Like [`append`](Self::append), but fail if the route would
+    /// exceed its maximum number of hops.
+    pub fn try_append<A: Into<Address>>(self, addr: A) -> Result<Self> {
+        self.check_hops(1)?;
+        Ok(self.append(addr))
+    }
+
     /// Push an item with an explicit type to the back of the route.
     ///
     /// # Examples
@@ -329,6 +442,13 @@ impl RouteBuilder<'_> {
         self
     }
 
+    /// Like [`prepend`](Self::prepend), but fail if the route would
+    /// exceed its maximum number of hops.
+    pub fn try_prepend<A: Into<Address>>(self, addr: A) -> Result<Self> {
+        self.check_hops(1)?;
+        Ok(self.prepend(addr))
+    }
+
     /// Prepend a full route to an existing route.
     ///
     /// # Examples
@@ -353,6 +473,13 @@ impl RouteBuilder<'_> {
         self
     }
 
+    /// Like [`prepend_route`](Self::prepend_route), but fail if the
+    /// route would exceed its maximum number of hops.
+    pub fn try_prepend_route(self, route: Route) -> Result<Self> {
+        self.check_hops(route.inner.len())?;
+        Ok(self.prepend_route(route))
+    }
+
     /// Replace the next item in the route with a new address.
     ///
     /// Similar to [`Self::prepend(...)`](RouteBuilder::prepend), but
@@ -494,4 +621,117 @@ mod tests {
         r1.modify().prepend_route(r2);
         assert_eq!(r1, vec!["1", "2", "3", "a", "b", "c"].into());
     }
+
+    #[test]
+    fn test_route_starts_with() {
+        let route: Route = vec!["a", "b", "c"].into();
+
+        assert!(route.starts_with(&vec!["a"].into()));
+        assert!(route.starts_with(&vec!["a", "b", "c"].into()));
+        assert!(route.starts_with(&Route::new().into()));
+        assert!(!route.starts_with(&vec!["b"].into()));
+        assert!(!route.starts_with(&vec!["a", "c"].into()));
+        assert!(!route.starts_with(&vec!["a", "b", "c", "d"].into()));
+    }
+
+    #[test]
+    fn test_route_max_hops() {
+        let builder = Route::new()
+            .with_max_hops(3)
+            .try_append("b")
+            .unwrap()
+            .try_prepend("a")
+            .unwrap()
+            .try_append("c")
+            .unwrap();
+
+        validate_error(builder.try_append("d").err().unwrap());
+
+        let builder = Route::new().with_max_hops(3).append("c");
+        validate_error(
+            builder
+                .try_prepend_route(vec!["a", "b", "x"].into())
+                .err()
+                .unwrap(),
+        );
+
+        let mut route: Route = vec!["c"].into();
+        route
+            .modify()
+            .with_max_hops(3)
+            .try_prepend_route(vec!["a", "b"].into())
+            .unwrap();
+        assert_eq!(route, vec!["a", "b", "c"].into());
+    }
+
+    #[test]
+    fn test_route_max_hops_checked_on_build() {
+        use crate::errcode::Kind;
+
+        let err = Route::new()
+            .with_max_hops(2)
+            .append("a")
+            .append_t(crate::LOCAL, "b")
+            .prepend("c")
+            .build()
+            .unwrap_err();
+        assert_eq!(err.code().kind, Kind::Misuse);
+
+        let err = Route::new()
+            .with_max_hops(2)
+            .prepend_route(vec!["a", "b", "c"].into())
+            .build()
+            .unwrap_err();
+        assert_eq!(err.code().kind, Kind::Misuse);
+
+        let route = Route::new()
+            .with_max_hops(2)
+            .append("a")
+            .append("b")
+            .build()
+            .unwrap();
+        assert_eq!(route, vec!["a", "b"].into());
+
+        let mut route: Route = vec!["c"].into();
+        assert!(route
+            .modify()
+            .with_max_hops(2)
+            .prepend_route(vec!["a", "b"].into())
+            .build()
+            .is_err());
+        assert_eq!(route, vec!["c"].into());
+
+        route
+            .modify()
+            .with_max_hops(2)
+            .prepend("b")
+            .build()
+            .unwrap();
+        assert_eq!(route, vec!["b", "c"].into());
+    }
+
+    #[test]
+    fn test_route_unbounded_by_default() {
+        let mut builder = Route::new();
+        for i in 0..1000 {
+            builder = builder
+                .try_append(Address::from_string(format!("{}", i)))
+                .unwrap();
+        }
+        let route: Route = builder.into();
+        assert_eq!(route.recipient(), Address::from_string("999"));
+    }
+
+    #[test]
+    fn test_route_contains() {
+        let route: Route = vec!["a", "b", "c"].into();
+
+        assert!(route.contains(&"a".into()));
+        assert!(route.contains(&"b".into()));
+        assert!(route.contains(&"c".into()));
+        assert!(!route.contains(&"d".into()));
+        assert!(!route.contains(&Address::from_string("1#a")));
+        let empty: Route = Route::new().into();
+        assert!(!empty.contains(&"a".into()));
+    }
 }
@@ -0,0 +1,45 @@
+use crate::vault::{Buffer, Secret};
+use crate::Result;
+use crate::{async_trait, compat::boxed::Box};
+
+/// Maximum plaintext length of a single chunk of a chunked AEAD payload.
+pub const AEAD_STREAM_CHUNK_LENGTH: usize = 64 * 1024;
+/// Length of the random salt which starts a chunked AEAD payload.
+pub const AEAD_STREAM_SALT_LENGTH: usize = 32;
+/// Length of the AES-GCM tag which ends every chunk.
+pub const AEAD_STREAM_TAG_LENGTH: usize = 16;
+
+/// Defines the Vault interface for encrypting payloads too large to be
+/// handled as one AES-GCM message.
+///
+/// A chunked payload is a random salt followed by the chunks. The chunks
+/// are not encrypted with the given key itself but with a subkey of the
+/// same length, derived from it by HKDF-SHA256 over the salt. Every
+/// payload thus has a key of its own, and the number of payloads a key
+/// may encrypt is not limited by nonce collisions.
+///
+/// Every chunk but the last one holds exactly
+/// [`AEAD_STREAM_CHUNK_LENGTH`] bytes of plaintext, and every chunk is
+/// its AES-GCM ciphertext followed by its tag. Chunk `i` is encrypted
+/// with the nonce `0 (7 bytes) || i (u32, big endian) || last`, where
+/// `last` is 1 for the final chunk and 0 otherwise. Reordered chunks
+/// thus fail to decrypt, and so does a payload truncated at a chunk
+/// boundary, since its final chunk was not encrypted as the last one.
+#[async_trait]
+pub trait AeadStreamVault {
+    /// Encrypt a payload chunk by chunk using AES-GCM.
+    async fn aead_encrypt_stream(
+        &self,
+        context: &Secret,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<Buffer<u8>>;
+
+    /// Decrypt a payload produced by [`AeadStreamVault::aead_encrypt_stream`].
+    async fn aead_decrypt_stream(
+        &self,
+        context: &Secret,
+        cipher_text: &[u8],
+        aad: &[u8],
+    ) -> Result<Buffer<u8>>;
+}
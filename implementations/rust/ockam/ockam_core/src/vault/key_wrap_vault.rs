@@ -0,0 +1,27 @@
+use crate::vault::{Buffer, Secret, SecretAttributes};
+use crate::Result;
+use crate::{async_trait, compat::boxed::Box};
+
+/// Defines the Vault interface for key wrapping, i.e. encrypting a
+/// secret under another secret.
+#[async_trait]
+pub trait KeyWrapVault {
+    /// Encrypt `target` under the AES key `wrapping_key`.
+    ///
+    /// The returned buffer contains the nonce followed by the
+    /// AES-GCM ciphertext of the exported target secret.  The
+    /// attributes of `target` are authenticated along with it.
+    async fn wrap_secret(&self, wrapping_key: &Secret, target: &Secret) -> Result<Buffer<u8>>;
+
+    /// Decrypt a secret produced by [`KeyWrapVault::wrap_secret`] and
+    /// import it into the vault with the given attributes.
+    ///
+    /// Fails unless `attributes` are the attributes the secret was
+    /// wrapped with.
+    async fn unwrap_secret(
+        &self,
+        wrapping_key: &Secret,
+        wrapped: &[u8],
+        attributes: SecretAttributes,
+    ) -> Result<Secret>;
+}
@@ -10,9 +10,11 @@
 //!
 //! [`ockam_vault`]: https://docs.rs/ockam_vault/latest
 
+mod aead_stream_vault;
 mod asymmetric_vault;
 mod hasher;
 mod key_id_vault;
+mod key_wrap_vault;
 mod secret;
 mod secret_vault;
 mod signer;
@@ -22,9 +24,11 @@ mod verifier;
 
 pub mod test_support;
 
+pub use aead_stream_vault::*;
 pub use asymmetric_vault::*;
 pub use hasher::*;
 pub use key_id_vault::*;
+pub use key_wrap_vault::*;
 pub use secret::*;
 pub use secret_vault::*;
 pub use signer::*;
@@ -0,0 +1,103 @@
+use crate::compat::vec::Vec;
+use crate::vault::{
+    AeadStreamVault, Secret, SecretAttributes, SecretPersistence, SecretType, SecretVault,
+    AEAD_STREAM_CHUNK_LENGTH, AEAD_STREAM_SALT_LENGTH, AEAD_STREAM_TAG_LENGTH,
+    AES256_SECRET_LENGTH,
+};
+
+async fn aes_key(vault: &mut impl SecretVault) -> Secret {
+    let attributes = SecretAttributes::new(
+        SecretType::Aes,
+        SecretPersistence::Ephemeral,
+        AES256_SECRET_LENGTH,
+    );
+    vault.secret_generate(attributes).await.unwrap()
+}
+
+pub async fn aead_stream(vault: &mut (impl AeadStreamVault + SecretVault)) {
+    let ctx = &aes_key(vault).await;
+    let aad = b"Extra payload data";
+
+    // Several full chunks and a partial one
+    let message: Vec<u8> = (0..3 * 1024 * 1024 + 123).map(|i| i as u8).collect();
+    let ciphertext = vault.aead_encrypt_stream(ctx, &message, aad).await.unwrap();
+    let chunks = message.len() / AEAD_STREAM_CHUNK_LENGTH + 1;
+    assert_eq!(
+        ciphertext.len(),
+        AEAD_STREAM_SALT_LENGTH + message.len() + chunks * AEAD_STREAM_TAG_LENGTH
+    );
+    let plaintext = vault
+        .aead_decrypt_stream(ctx, &ciphertext, aad)
+        .await
+        .unwrap();
+    assert_eq!(plaintext, message);
+
+    // Exactly one full chunk and an empty payload
+    for message in [vec![7u8; AEAD_STREAM_CHUNK_LENGTH], Vec::new()].iter() {
+        let ciphertext = vault.aead_encrypt_stream(ctx, message, aad).await.unwrap();
+        let plaintext = vault
+            .aead_decrypt_stream(ctx, &ciphertext, aad)
+            .await
+            .unwrap();
+        assert_eq!(&plaintext, message);
+    }
+
+    // Every payload is encrypted with a subkey of its own
+    let message = b"Same message";
+    let first = vault.aead_encrypt_stream(ctx, message, aad).await.unwrap();
+    let second = vault.aead_encrypt_stream(ctx, message, aad).await.unwrap();
+    assert_ne!(
+        first[..AEAD_STREAM_SALT_LENGTH],
+        second[..AEAD_STREAM_SALT_LENGTH]
+    );
+    assert_ne!(
+        first[AEAD_STREAM_SALT_LENGTH..],
+        second[AEAD_STREAM_SALT_LENGTH..]
+    );
+}
+
+pub async fn aead_stream_tampered(vault: &mut (impl AeadStreamVault + SecretVault)) {
+    let ctx = &aes_key(vault).await;
+    let aad = b"Extra payload data";
+    let chunk_length = AEAD_STREAM_CHUNK_LENGTH + AEAD_STREAM_TAG_LENGTH;
+
+    let message = vec![42u8; 2 * AEAD_STREAM_CHUNK_LENGTH + 10];
+    let ciphertext = vault.aead_encrypt_stream(ctx, &message, aad).await.unwrap();
+
+    // A modified byte in the second chunk
+    let mut tampered = ciphertext.clone();
+    tampered[AEAD_STREAM_SALT_LENGTH + chunk_length + 5] ^= 0x01;
+    assert!(vault
+        .aead_decrypt_stream(ctx, &tampered, aad)
+        .await
+        .is_err());
+
+    // Truncated at a chunk boundary, dropping the final chunk
+    let truncated = &ciphertext[..AEAD_STREAM_SALT_LENGTH + 2 * chunk_length];
+    assert!(vault
+        .aead_decrypt_stream(ctx, truncated, aad)
+        .await
+        .is_err());
+
+    // Truncated within the final chunk
+    let truncated = &ciphertext[..ciphertext.len() - 1];
+    assert!(vault
+        .aead_decrypt_stream(ctx, truncated, aad)
+        .await
+        .is_err());
+
+    // Swapped chunks
+    let mut swapped = ciphertext[..AEAD_STREAM_SALT_LENGTH].to_vec();
+    let first = AEAD_STREAM_SALT_LENGTH..AEAD_STREAM_SALT_LENGTH + chunk_length;
+    let second = first.end..first.end + chunk_length;
+    swapped.extend_from_slice(&ciphertext[second]);
+    swapped.extend_from_slice(&ciphertext[first.clone()]);
+    swapped.extend_from_slice(&ciphertext[first.end + chunk_length..]);
+    assert!(vault.aead_decrypt_stream(ctx, &swapped, aad).await.is_err());
+
+    // Different additional data
+    assert!(vault
+        .aead_decrypt_stream(ctx, &ciphertext, b"Other payload data")
+        .await
+        .is_err());
+}
@@ -0,0 +1,51 @@
+use crate::vault::{
+    KeyWrapVault, SecretAttributes, SecretPersistence, SecretType, SecretVault,
+    AES256_SECRET_LENGTH, CURVE25519_SECRET_LENGTH,
+};
+
+pub async fn wrap_secret(vault: &mut (impl KeyWrapVault + SecretVault)) {
+    let wrapping_attributes = SecretAttributes::new(
+        SecretType::Aes,
+        SecretPersistence::Ephemeral,
+        AES256_SECRET_LENGTH,
+    );
+    let target_attributes = SecretAttributes::new(
+        SecretType::X25519,
+        SecretPersistence::Ephemeral,
+        CURVE25519_SECRET_LENGTH,
+    );
+
+    let wrapping_key = vault.secret_generate(wrapping_attributes).await.unwrap();
+    let target = vault.secret_generate(target_attributes).await.unwrap();
+    let public_key = vault.secret_public_key_get(&target).await.unwrap();
+
+    let wrapped = vault.wrap_secret(&wrapping_key, &target).await.unwrap();
+    let exported = vault.secret_export(&target).await.unwrap();
+    assert_ne!(wrapped.as_slice(), exported.as_ref());
+
+    let unwrapped = vault
+        .unwrap_secret(&wrapping_key, &wrapped, target_attributes)
+        .await
+        .unwrap();
+    let unwrapped_public_key = vault.secret_public_key_get(&unwrapped).await.unwrap();
+    assert_eq!(public_key, unwrapped_public_key);
+
+    let mut tampered = wrapped.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 0x01;
+    let res = vault
+        .unwrap_secret(&wrapping_key, &tampered, target_attributes)
+        .await;
+    assert!(res.is_err());
+
+    // The attributes are bound to the wrapped secret
+    let other_attributes = SecretAttributes::new(
+        SecretType::Buffer,
+        SecretPersistence::Ephemeral,
+        CURVE25519_SECRET_LENGTH,
+    );
+    let res = vault
+        .unwrap_secret(&wrapping_key, &wrapped, other_attributes)
+        .await;
+    assert!(res.is_err());
+}
@@ -2,16 +2,20 @@
 //! [`ockam_core::vault`](crate::vault).
 #![allow(missing_docs)]
 
+mod aead_stream_impl;
 mod asymmetric_impl;
 mod hasher_impl;
 mod key_id_impl;
+mod key_wrap_impl;
 mod secret_impl;
 mod signer_impl;
 mod symmetric_impl;
 
+pub use aead_stream_impl::*;
 pub use asymmetric_impl::*;
 pub use hasher_impl::*;
 pub use key_id_impl::*;
+pub use key_wrap_impl::*;
 pub use secret_impl::*;
 pub use signer_impl::*;
 pub use symmetric_impl::*;
@@ -1,6 +1,7 @@
 use crate::vault::Secret;
 use cfg_if::cfg_if;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use zeroize::Zeroize;
 
 /// Curve25519 private key length.
@@ -11,6 +12,11 @@ pub const CURVE25519_PUBLIC_LENGTH: usize = 32;
 pub const AES256_SECRET_LENGTH: usize = 32;
 /// AES128 private key length.
 pub const AES128_SECRET_LENGTH: usize = 16;
+/// AES-GCM nonce length.
+pub const AES_GCM_NONCE_LENGTH: usize = 12;
+/// BLS private key length.
+#[cfg(feature = "bls")]
+pub const BLS_SECRET_LENGTH: usize = 32;
 
 cfg_if! {
     if #[cfg(not(feature = "alloc"))] {
@@ -116,6 +122,39 @@ impl PublicKey {
     }
 }
 
+impl PublicKey {
+    /// Length of a fingerprint's digest in bytes.
+    pub const FINGERPRINT_LENGTH: usize = 16;
+
+    /// Short, human-readable fingerprint of this key.
+    ///
+    /// The fingerprint is the hex-encoded, truncated SHA-256 digest of
+    /// the key type and data, in groups of four characters.  It is
+    /// meant for comparing keys out-of-band, e.g. when printed by a CLI.
+    pub fn fingerprint(&self) -> String {
+        let digest = Sha256::new()
+            .chain([self.stype as u8])
+            .chain(&self.data[..])
+            .finalize();
+        let hex = hex::encode(&digest[..Self::FINGERPRINT_LENGTH]);
+
+        let mut fingerprint = String::with_capacity(hex.len() + hex.len() / 4);
+        for (i, c) in hex.chars().enumerate() {
+            if i > 0 && i % 4 == 0 {
+                fingerprint.push(':');
+            }
+            fingerprint.push(c);
+        }
+        fingerprint
+    }
+}
+
+impl core::fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.pad(&self.fingerprint())
+    }
+}
+
 impl AsRef<[u8]> for PublicKey {
     fn as_ref(&self) -> &[u8] {
         &self.data
@@ -163,6 +202,22 @@ pub enum SecretType {
     Bls,
 }
 
+impl SecretType {
+    /// Infer the type of a public key from its length in bytes
+    ///
+    /// Only Curve25519 public keys are recognized, and X25519 and
+    /// Ed25519 public keys have the same length.  A 32-byte key is
+    /// therefore reported as [`SecretType::X25519`]; callers expecting
+    /// a signing key must use [`SecretType::Ed25519`] themselves.
+    /// Returns `None` for any other length.
+    pub fn infer_from_public_len(len: usize) -> Option<SecretType> {
+        match len {
+            CURVE25519_PUBLIC_LENGTH => Some(SecretType::X25519),
+            _ => None,
+        }
+    }
+}
+
 /// All possible [`SecretKey`] persistence types
 #[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
 pub enum SecretPersistence {
@@ -204,6 +259,20 @@ impl SecretAttributes {
             length,
         }
     }
+
+    /// Create a new secret attribute with the length implied by `stype`.
+    ///
+    /// AES keys default to 256 bits. Buffers have no inherent length and
+    /// default to 32 bytes; use [`SecretAttributes::new`] for other sizes.
+    pub fn for_type(stype: SecretType, persistence: SecretPersistence) -> Self {
+        let length = match stype {
+            SecretType::X25519 | SecretType::Ed25519 => CURVE25519_SECRET_LENGTH,
+            SecretType::Aes | SecretType::Buffer => AES256_SECRET_LENGTH,
+            #[cfg(feature = "bls")]
+            SecretType::Bls => BLS_SECRET_LENGTH,
+        };
+        SecretAttributes::new(stype, persistence, length)
+    }
 }
 
 /// A public key
@@ -231,3 +300,31 @@ impl KeyPair {
         Self { secret, public }
     }
 }
+
+#[test]
+fn public_key_fingerprint() {
+    let key = PublicKey::new(vec![1; CURVE25519_PUBLIC_LENGTH], SecretType::X25519);
+    let fingerprint = key.fingerprint();
+
+    assert_eq!(fingerprint.len(), 39);
+    assert_eq!(fingerprint.matches(':').count(), 7);
+    assert_eq!(fingerprint, key.clone().fingerprint());
+    assert_eq!(fingerprint, format!("{}", key));
+
+    let other_data = PublicKey::new(vec![2; CURVE25519_PUBLIC_LENGTH], SecretType::X25519);
+    assert_ne!(fingerprint, other_data.fingerprint());
+
+    let other_type = PublicKey::new(vec![1; CURVE25519_PUBLIC_LENGTH], SecretType::Ed25519);
+    assert_ne!(fingerprint, other_type.fingerprint());
+}
+
+#[test]
+fn secret_type_from_public_len() {
+    assert_eq!(
+        SecretType::infer_from_public_len(CURVE25519_PUBLIC_LENGTH),
+        Some(SecretType::X25519)
+    );
+    // Uncompressed NIST P-256 keys are not supported
+    assert_eq!(SecretType::infer_from_public_len(65), None);
+    assert_eq!(SecretType::infer_from_public_len(0), None);
+}
@@ -1,4 +1,25 @@
-use crate::{async_trait, compat::boxed::Box, Message, Result, Routed};
+use crate::{
+    async_trait,
+    compat::{boxed::Box, vec::Vec},
+    Message, Result, Routed,
+};
+
+/// The reason a worker is being shut down
+///
+/// Passed to [`Worker::shutdown_with_reason`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// The worker was stopped on its own, e.g. via `Context::stop_worker`
+    WorkerStop,
+    /// The node is shutting down gracefully
+    NodeGraceful,
+    /// The node is shutting down immediately
+    NodeImmediate,
+    /// The worker's mailbox closed without the node giving a reason,
+    /// e.g. because the node aborted after a graceful shutdown timed
+    /// out
+    Unknown,
+}
 
 /// Defines the core interface shared by all Ockam Workers.
 ///
@@ -24,11 +45,49 @@ pub trait Worker: Send + 'static {
         Ok(())
     }
 
+    /// Override initialisation behaviour for a worker taking over the
+    /// state of a previous instance.
+    ///
+    /// This is what the node calls when starting a worker.  `state`
+    /// is what the previous instance returned from
+    /// [`save_state`](Self::save_state), or `None` for a fresh
+    /// start.  The default implementation ignores the state and calls
+    /// [`initialize`](Self::initialize).
+    async fn initialize_with_state(
+        &mut self,
+        context: &mut Self::Context,
+        _state: Option<Vec<u8>>,
+    ) -> Result<()> {
+        self.initialize(context).await
+    }
+
     /// Override shutdown behaviour.
     async fn shutdown(&mut self, _context: &mut Self::Context) -> Result<()> {
         Ok(())
     }
 
+    /// Override shutdown behaviour depending on why the worker stops.
+    ///
+    /// This is what the node calls when stopping a worker.  The
+    /// default implementation ignores the reason and calls
+    /// [`shutdown`](Self::shutdown).
+    async fn shutdown_with_reason(
+        &mut self,
+        context: &mut Self::Context,
+        _reason: ShutdownReason,
+    ) -> Result<()> {
+        self.shutdown(context).await
+    }
+
+    /// Capture the worker's state to hand it to a replacement.
+    ///
+    /// This is only called after the worker was shut down, and only
+    /// if its state was requested when stopping it.  The default
+    /// implementation has no state to save.
+    fn save_state(&self) -> Option<Vec<u8>> {
+        None
+    }
+
     /// Try to open and handle a typed message.
     async fn handle_message(
         &mut self,
@@ -61,7 +61,7 @@ impl Worker for TcpInletService {
         ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
-        if msg.onward_route().recipient() == self.internal_address {
+        if msg.local_message().transport().onward_route.recipient() == self.internal_address {
             let inlet_address = Address::from_string(msg.body());
 
             if let Some(inlet_index) = self
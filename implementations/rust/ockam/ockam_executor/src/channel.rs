@@ -66,6 +66,16 @@ impl<T> Sender<T> {
         .await
     }
 
+    pub fn try_send(&self, value: T) -> Result<(), error::TrySendError<T>> {
+        match self.0.queue.enqueue(value) {
+            Ok(()) => {
+                self.0.wake_receiver.wake();
+                Ok(())
+            }
+            Err(value) => Err(error::TrySendError::Full(value)),
+        }
+    }
+
     pub async fn closed(&self) {
         unimplemented!();
     }
@@ -146,6 +156,19 @@ impl<T> Receiver<T> {
     pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
         ReceiveFuture { inner: &self.0 }.poll_unpin(cx)
     }
+
+    pub fn try_recv(&mut self) -> Result<T, error::TryRecvError> {
+        match self.0.queue.dequeue() {
+            Some(value) => {
+                self.0.wake_sender.wake();
+                Ok(value)
+            }
+            None if self.0.is_sender_closed.load(Ordering::Acquire) => {
+                Err(error::TryRecvError::Disconnected)
+            }
+            None => Err(error::TryRecvError::Empty),
+        }
+    }
 }
 
 impl<T> core::fmt::Debug for Receiver<T> {
@@ -191,4 +214,34 @@ pub mod error {
             write!(fmt, "SendError -> channel closed")
         }
     }
+
+    #[derive(Debug)]
+    pub enum TrySendError<T> {
+        Full(T),
+        Closed(T),
+    }
+
+    impl<T> fmt::Display for TrySendError<T> {
+        fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TrySendError::Full(_) => write!(fmt, "TrySendError -> channel full"),
+                TrySendError::Closed(_) => write!(fmt, "TrySendError -> channel closed"),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum TryRecvError {
+        Empty,
+        Disconnected,
+    }
+
+    impl fmt::Display for TryRecvError {
+        fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TryRecvError::Empty => write!(fmt, "TryRecvError -> channel empty"),
+                TryRecvError::Disconnected => write!(fmt, "TryRecvError -> channel closed"),
+            }
+        }
+    }
 }
@@ -1,9 +1,14 @@
+use core::fmt;
 use ockam_core::compat::vec::Vec;
-use ockam_core::{vault::PublicKey, Result};
-use serde::{Deserialize, Serialize};
+use ockam_core::{vault::PublicKey, Decodable, Encodable, Result};
+use serde::de::SeqAccess;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 
 pub use crate::signature::*;
-use crate::{CreateKeyChange, EventIdentifier, IdentityEventAttributes, RotateKeyChange};
+use crate::{
+    CreateKeyChange, EventIdentifier, IdentityError, IdentityEventAttributes, KeyAttributes,
+    RotateKeyChange,
+};
 
 /// Pre-defined keys in [`IdentityEventAttributes`] map
 #[non_exhaustive]
@@ -64,6 +69,13 @@ impl IdentityChange {
         }
     }
 
+    pub(crate) fn key_attributes(&self) -> &KeyAttributes {
+        match &self.change_type {
+            IdentityChangeType::CreateKey(change) => change.data().key_attributes(),
+            IdentityChangeType::RotateKey(change) => change.data().key_attributes(),
+        }
+    }
+
     pub(crate) fn public_key(&self) -> Result<PublicKey> {
         Ok(match &self.change_type {
             IdentityChangeType::CreateKey(change) => change.data().public_key(),
@@ -113,27 +125,91 @@ impl ChangeBlock {
 /// [`crate::Identity`]s are modified using change events mechanism. One event may have 1 or more [`IdentityChange`]s
 /// Proof is used to check whether this event comes from a party authorized to perform such updated
 /// Individual changes may include additional proofs, if needed
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// Binary encodings of schema version 1 are the same as those of events
+/// written before schema versions were introduced.  Newer versions are
+/// encoded with [`VERSIONED_EVENT_MARKER`] in place of the identifier,
+/// followed by the schema version and the separately encoded event
+/// body, so that the version is checked before anything whose layout
+/// may change between versions is decoded.
+/// Human-readable encodings carry the schema version as an additional
+/// field.  A missing version there means version 1.
+#[derive(Clone, Debug)]
 pub struct IdentityChangeEvent {
+    schema_version: u8,
+    body: ChangeEventBody,
+}
+
+/// Fields of an [`IdentityChangeEvent`] whose layout depends on the
+/// schema version
+///
+/// This is also the complete binary layout of schema version 1.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ChangeEventBody {
+    identifier: EventIdentifier,
+    change_block: ChangeBlock,
+    signatures: Vec<Signature>,
+}
+
+/// Written in place of the identifier of binary encoded events with a
+/// schema version above 1
+///
+/// Identifiers are SHA256 hashes, so no event of version 1 starts with
+/// this.
+pub const VERSIONED_EVENT_MARKER: [u8; 32] = [0; 32];
+
+/// Binary form of an [`IdentityChangeEvent`] with a schema version
+/// above 1
+#[derive(Serialize)]
+struct VersionedChangeEvent {
+    marker: [u8; 32],
+    schema_version: u8,
+    body: Vec<u8>,
+}
+
+/// Human-readable form of an [`IdentityChangeEvent`]
+#[derive(Deserialize)]
+struct HumanReadableChangeEvent {
+    #[serde(default = "legacy_schema_version")]
+    schema_version: u8,
     identifier: EventIdentifier,
     change_block: ChangeBlock,
     signatures: Vec<Signature>,
 }
 
+#[derive(Serialize)]
+struct HumanReadableChangeEventRef<'a> {
+    schema_version: u8,
+    identifier: &'a EventIdentifier,
+    change_block: &'a ChangeBlock,
+    signatures: &'a [Signature],
+}
+
+fn legacy_schema_version() -> u8 {
+    1
+}
+
 pub type Changes = Vec<IdentityChangeEvent>;
 
 impl IdentityChangeEvent {
+    /// Schema version of the event envelope written by this implementation
+    pub const SCHEMA_VERSION: u8 = 1;
+
+    /// Schema version of the event envelope
+    pub fn schema_version(&self) -> u8 {
+        self.schema_version
+    }
     /// Unique [`EventIdentifier`]
     pub fn identifier(&self) -> &EventIdentifier {
-        &self.identifier
+        &self.body.identifier
     }
     /// Set of changes been applied
     pub fn change_block(&self) -> &ChangeBlock {
-        &self.change_block
+        &self.body.change_block
     }
     /// Proof is used to check whether this event comes from a party authorized to perform such update
     pub fn signatures(&self) -> &[Signature] {
-        &self.signatures
+        &self.body.signatures
     }
 }
 
@@ -145,9 +221,154 @@ impl IdentityChangeEvent {
         signatures: Vec<Signature>,
     ) -> Self {
         IdentityChangeEvent {
-            identifier,
-            change_block,
-            signatures,
+            schema_version: Self::SCHEMA_VERSION,
+            body: ChangeEventBody {
+                identifier,
+                change_block,
+                signatures,
+            },
+        }
+    }
+
+    /// Reject events written with a schema version we don't understand
+    ///
+    /// There are no older schema versions yet, so anything but the
+    /// current one is an error.
+    pub(crate) fn check_schema_version(&self) -> Result<()> {
+        check_schema_version(self.schema_version)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_schema_version(mut self, schema_version: u8) -> Self {
+        self.schema_version = schema_version;
+        self
+    }
+}
+
+fn check_schema_version(schema_version: u8) -> Result<()> {
+    if schema_version != IdentityChangeEvent::SCHEMA_VERSION {
+        return Err(IdentityError::UnsupportedChangeSchemaVersion.into());
+    }
+    Ok(())
+}
+
+/// A binary encoded [`IdentityChangeEvent`], which may be of a schema
+/// version we don't understand
+///
+/// The body of such an event is skipped and `None` is stored instead,
+/// so that the error can be reported once the surrounding value has
+/// been decoded.
+pub(crate) struct DecodedChangeEvent(Option<IdentityChangeEvent>);
+
+impl DecodedChangeEvent {
+    pub(crate) fn into_event(self) -> Result<IdentityChangeEvent> {
+        self.0
+            .ok_or_else(|| IdentityError::UnsupportedChangeSchemaVersion.into())
+    }
+}
+
+impl<'de> Deserialize<'de> for DecodedChangeEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+        struct EventVisitor;
+
+        impl<'de> de::Visitor<'de> for EventVisitor {
+            type Value = DecodedChangeEvent;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an identity change event")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> core::result::Result<Self::Value, A::Error> {
+                let identifier: EventIdentifier = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+                if identifier.as_ref() != VERSIONED_EVENT_MARKER {
+                    let change_block = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                    let signatures = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                    return Ok(DecodedChangeEvent(Some(IdentityChangeEvent {
+                        schema_version: legacy_schema_version(),
+                        body: ChangeEventBody {
+                            identifier,
+                            change_block,
+                            signatures,
+                        },
+                    })));
+                }
+
+                let schema_version: u8 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let body: Vec<u8> = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                if check_schema_version(schema_version).is_err() {
+                    return Ok(DecodedChangeEvent(None));
+                }
+
+                let body = ChangeEventBody::decode(&body).map_err(de::Error::custom)?;
+                Ok(DecodedChangeEvent(Some(IdentityChangeEvent {
+                    schema_version,
+                    body,
+                })))
+            }
+        }
+
+        deserializer.deserialize_struct(
+            "IdentityChangeEvent",
+            &["identifier", "change_block", "signatures"],
+            EventVisitor,
+        )
+    }
+}
+
+impl Serialize for IdentityChangeEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            HumanReadableChangeEventRef {
+                schema_version: self.schema_version,
+                identifier: &self.body.identifier,
+                change_block: &self.body.change_block,
+                signatures: &self.body.signatures,
+            }
+            .serialize(serializer)
+        } else if self.schema_version == legacy_schema_version() {
+            self.body.serialize(serializer)
+        } else {
+            VersionedChangeEvent {
+                marker: VERSIONED_EVENT_MARKER,
+                schema_version: self.schema_version,
+                body: self.body.encode().map_err(ser::Error::custom)?,
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IdentityChangeEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let event = HumanReadableChangeEvent::deserialize(deserializer)?;
+            check_schema_version(event.schema_version).map_err(de::Error::custom)?;
+            Ok(IdentityChangeEvent {
+                schema_version: event.schema_version,
+                body: ChangeEventBody {
+                    identifier: event.identifier,
+                    change_block: event.change_block,
+                    signatures: event.signatures,
+                },
+            })
+        } else {
+            DecodedChangeEvent::deserialize(deserializer)?
+                .into_event()
+                .map_err(de::Error::custom)
         }
     }
 }
@@ -52,7 +52,7 @@ impl IdentityChangeHistory {
             .iter()
             .rev()
             .find(|e| e.change_block().change().has_label(label))
-            .ok_or_else(|| IdentityError::InvalidInternalState.into())
+            .ok_or_else(|| IdentityError::KeyNotFound.into())
     }
 
     pub(crate) fn find_last_key_event_public_key(
@@ -123,6 +123,8 @@ impl IdentityChangeHistory {
         new_change_event: &IdentityChangeEvent,
         vault: &mut impl IdentityVault,
     ) -> Result<bool> {
+        new_change_event.check_schema_version()?;
+
         let change_block = new_change_event.change_block();
         let change_block_binary = change_block
             .encode()
@@ -12,6 +12,8 @@ mod trust_policy;
 pub use trust_policy::*;
 mod local_info;
 pub use local_info::*;
+mod pending_secure_channel;
+pub use pending_secure_channel::*;
 
 pub struct IdentityAccessControlBuilder;
 
@@ -55,7 +57,7 @@ mod test {
     use crate::{Identity, IdentityTrait};
     use core::sync::atomic::{AtomicU8, Ordering};
     use ockam_core::compat::sync::Arc;
-    use ockam_core::{route, Any, Route, Routed, Worker};
+    use ockam_core::{route, Any, Encodable, Route, Routed, TransportMessage, Worker};
     use ockam_node::Context;
     use ockam_vault::Vault;
     use std::time::Duration;
@@ -104,6 +106,39 @@ mod test {
         ctx.stop().await
     }
 
+    #[ockam_macros::test]
+    async fn test_channel_keeps_transport_message_version(ctx: &mut Context) -> Result<()> {
+        let alice_vault = Vault::create();
+        let bob_vault = Vault::create();
+
+        let alice = Identity::create(ctx, &alice_vault).await?;
+        let bob = Identity::create(ctx, &bob_vault).await?;
+
+        let alice_trust_policy = TrustIdentifierPolicy::new(bob.identifier().await?);
+        let bob_trust_policy = TrustIdentifierPolicy::new(alice.identifier().await?);
+
+        bob.create_secure_channel_listener("bob_listener", bob_trust_policy)
+            .await?;
+
+        let alice_channel = alice
+            .create_secure_channel(route!["bob_listener"], alice_trust_policy)
+            .await?;
+
+        // The version signals the codec of the payload to the receiver
+        let msg = TransportMessage {
+            version: 2,
+            onward_route: route![alice_channel, ctx.address()],
+            return_route: route![ctx.address()],
+            payload: "Hello, Bob!".to_string().encode()?,
+        };
+        ctx.forward(LocalMessage::new(msg, vec![])).await?;
+
+        let msg = ctx.receive::<Any>().await?.take();
+        assert_eq!(msg.local_message().transport().version, 2);
+
+        ctx.stop().await
+    }
+
     #[ockam_macros::test]
     async fn test_tunneled_secure_channel_works(ctx: &mut Context) -> Result<()> {
         let vault = Vault::create();
@@ -366,4 +401,67 @@ mod test {
 
         ctx.stop().await
     }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn pending_channel__cancel__should_stop_worker_and_destroy_secrets(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let received_count = Arc::new(AtomicU8::new(0));
+        let receiver = Receiver {
+            received_count: received_count.clone(),
+        };
+
+        // A listener that never answers
+        ctx.start_worker("listener", receiver).await?;
+
+        let vault = Vault::create();
+        let alice = Identity::create(ctx, &vault).await?;
+
+        let secrets = vault.list_secrets().await.len();
+
+        let pending = alice
+            .start_secure_channel("listener", TrustEveryonePolicy)
+            .await?;
+
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(received_count.load(Ordering::Relaxed), 1);
+        assert!(vault.list_secrets().await.len() > secrets);
+
+        let address = pending.address().clone();
+        pending.cancel().await?;
+
+        sleep(Duration::from_millis(200)).await;
+        assert!(!ctx.list_workers().await?.contains(&address));
+        assert_eq!(vault.list_secrets().await.len(), secrets);
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn established_channel__stop__should_stop_underlying_channel(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let bob = Identity::create(ctx, &Vault::create()).await?;
+        bob.create_secure_channel_listener("listener", TrustEveryonePolicy)
+            .await?;
+
+        let vault = Vault::create();
+        let alice = Identity::create(ctx, &vault).await?;
+        let secrets = vault.list_secrets().await.len();
+
+        let channel = alice
+            .create_secure_channel("listener", TrustEveryonePolicy)
+            .await?;
+        assert!(vault.list_secrets().await.len() > secrets);
+
+        ctx.stop_worker(channel).await?;
+
+        // The keys of the underlying channel are destroyed when it stops
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(vault.list_secrets().await.len(), secrets);
+
+        ctx.stop().await
+    }
 }
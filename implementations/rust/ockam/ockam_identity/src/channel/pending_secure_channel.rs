@@ -0,0 +1,49 @@
+use crate::AuthenticationConfirmation;
+use core::time::Duration;
+use ockam_core::{Address, Result};
+use ockam_node::Context;
+
+/// SecureChannel whose handshake is still in progress
+///
+/// Either [`wait`](Self::wait) for the channel to be established or
+/// [`cancel`](Self::cancel) the handshake.
+pub struct PendingSecureChannel {
+    ctx: Context,
+    address: Address,
+    kex_callback_address: Address,
+}
+
+impl PendingSecureChannel {
+    pub(crate) fn new(ctx: Context, address: Address, kex_callback_address: Address) -> Self {
+        Self {
+            ctx,
+            address,
+            kex_callback_address,
+        }
+    }
+
+    /// Address of the SecureChannel once it is established
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Wait for the SecureChannel to be established and return its address
+    pub async fn wait(mut self, timeout: Duration) -> Result<Address> {
+        let _ = self
+            .ctx
+            .receive_timeout::<AuthenticationConfirmation>(timeout.as_secs())
+            .await?;
+
+        Ok(self.address)
+    }
+
+    /// Abort the handshake, stopping all workers started for the SecureChannel
+    /// and destroying the secrets of an unfinished key exchange
+    pub async fn cancel(self) -> Result<()> {
+        // Both may already be gone, depending on how far the handshake got
+        let _ = self.ctx.stop_worker(self.kex_callback_address).await;
+        let _ = self.ctx.stop_worker(self.address).await;
+
+        self.ctx.stop_worker(self.ctx.address()).await
+    }
+}
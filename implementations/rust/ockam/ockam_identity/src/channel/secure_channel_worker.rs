@@ -1,10 +1,9 @@
 use crate::{
     IdentityChannelMessage, IdentityError, IdentityIdentifier, IdentitySecureChannelLocalInfo,
-    IdentityTrait, SecureChannelTrustInfo, TrustPolicy,
+    IdentityTrait, PendingSecureChannel, SecureChannelTrustInfo, TrustPolicy,
 };
 use core::future::Future;
 use core::pin::Pin;
-use core::time::Duration;
 use ockam_channel::{
     CreateResponderChannelMessage, KeyExchangeCompleted, SecureChannel, SecureChannelInfo,
 };
@@ -81,16 +80,15 @@ pub(crate) struct SecureChannelWorker<I: IdentityTrait> {
 }
 
 impl<I: IdentityTrait> SecureChannelWorker<I> {
-    pub async fn create_initiator(
+    pub async fn start_initiator(
         ctx: &Context,
         route: Route,
         identity: I,
         trust_policy: Arc<dyn TrustPolicy>,
         vault: impl XXVault,
-        timeout: Duration,
-    ) -> Result<Address> {
+    ) -> Result<PendingSecureChannel> {
         let child_address = Address::random_local();
-        let mut child_ctx = ctx.new_context(child_address.clone()).await?;
+        let child_ctx = ctx.new_context(child_address.clone()).await?;
 
         // Generate 2 random fresh address for newly created SecureChannel.
         // One for local workers to encrypt their messages
@@ -102,11 +100,12 @@ impl<I: IdentityTrait> SecureChannelWorker<I> {
             .initiator()
             .await?;
         // Create regular secure channel and set self address as first responder
-        let temp_ctx = ctx.new_context(Address::random_local()).await?;
+        let mut temp_ctx = ctx.new_context(Address::random_local()).await?;
+        let kex_callback_address = temp_ctx.address();
         let self_remote_address_clone = self_remote_address.clone();
         let channel_future = Box::pin(async move {
-            SecureChannel::create_extended(
-                &temp_ctx,
+            SecureChannel::create_cancellable(
+                &mut temp_ctx,
                 route,
                 Some(self_remote_address_clone),
                 initiator,
@@ -140,11 +139,11 @@ impl<I: IdentityTrait> SecureChannelWorker<I> {
             &self_local_address, &self_remote_address
         );
 
-        let _ = child_ctx
-            .receive_timeout::<AuthenticationConfirmation>(timeout.as_secs())
-            .await?;
-
-        Ok(self_local_address)
+        Ok(PendingSecureChannel::new(
+            child_ctx,
+            self_local_address,
+            kex_callback_address,
+        ))
     }
 
     pub(crate) async fn create_responder(
@@ -155,7 +154,6 @@ impl<I: IdentityTrait> SecureChannelWorker<I> {
         msg: Routed<CreateResponderChannelMessage>,
     ) -> Result<()> {
         let mut onward_route = msg.onward_route();
-        onward_route.step()?;
         onward_route.modify().prepend(listener_address);
 
         let return_route = msg.return_route();
@@ -444,10 +442,10 @@ impl<I: IdentityTrait> SecureChannelWorker<I> {
 
         let mut onward_route = msg.onward_route();
         let mut return_route = msg.return_route();
+        let version = msg.local_message().transport().version;
         let payload = msg.payload().to_vec();
 
         // Send to the other party using local regular SecureChannel
-        let _ = onward_route.step()?;
         let onward_route = onward_route
             .modify()
             .prepend(state.remote_identity_secure_channel_address)
@@ -457,7 +455,12 @@ impl<I: IdentityTrait> SecureChannelWorker<I> {
             .modify()
             .prepend(self.self_remote_address.clone());
 
-        let transport_msg = TransportMessage::v1(onward_route, return_route, payload);
+        let transport_msg = TransportMessage {
+            version,
+            onward_route: onward_route.into(),
+            return_route: return_route.into(),
+            payload,
+        };
 
         ctx.forward(LocalMessage::new(transport_msg, Vec::new()))
             .await?;
@@ -482,7 +485,7 @@ impl<I: IdentityTrait> SecureChannelWorker<I> {
 
         self.state = Some(State::Initialized(state.clone()));
 
-        let mut onward_route = msg.onward_route();
+        let onward_route = msg.onward_route();
         let mut return_route = msg.return_route();
 
         // Ensure message came from dedicated SecureChannel
@@ -492,18 +495,23 @@ impl<I: IdentityTrait> SecureChannelWorker<I> {
 
         let local_msg = msg.into_local_message();
         let mut local_info = local_msg.local_info().to_vec();
-        let payload = local_msg.into_transport_message().payload;
+        let TransportMessage {
+            version, payload, ..
+        } = local_msg.into_transport_message();
 
         // Forward to local workers
-        let _ = onward_route.step()?;
-
         let return_route = return_route
             .modify()
             .pop_front()
             .pop_front()
             .prepend(self.self_local_address.clone());
 
-        let transport_msg = TransportMessage::v1(onward_route, return_route, payload);
+        let transport_msg = TransportMessage {
+            version,
+            onward_route,
+            return_route: return_route.into(),
+            payload,
+        };
 
         local_info.push(
             IdentitySecureChannelLocalInfo::new(state.their_identity_id.clone()).to_local_info()?,
@@ -549,6 +557,24 @@ impl<I: IdentityTrait> Worker for SecureChannelWorker<I> {
         Ok(())
     }
 
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // The underlying channel is of no use without this one, tear
+        // it down as well
+        let channel = match &self.state {
+            Some(State::InitiatorSendIdentity(s)) => Some(s.channel.address()),
+            Some(State::ResponderWaitForIdentity(s)) => {
+                Some(s.local_secure_channel_address.clone())
+            }
+            Some(State::Initialized(s)) => Some(s.local_secure_channel_address.clone()),
+            _ => None,
+        };
+        if let Some(channel) = channel {
+            let _ = ctx.stop_worker(channel).await;
+        }
+
+        Ok(())
+    }
+
     async fn handle_message(
         &mut self,
         ctx: &mut Self::Context,
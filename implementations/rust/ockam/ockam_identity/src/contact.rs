@@ -96,6 +96,21 @@ impl Contact {
 
         allow()
     }
+
+    /// Verify the signatures of a change event extending this [`Contact`]
+    ///
+    /// Every [`Signature`](crate::Signature) is checked against the key
+    /// its [`SignatureType`](crate::SignatureType) refers to: the key
+    /// introduced by the event itself, the current root key, or the
+    /// previous key with the same label.  The event is not added to
+    /// the change history.
+    pub async fn verify_event_signatures(
+        &self,
+        event: &IdentityChangeEvent,
+        vault: &mut impl IdentityVault,
+    ) -> Result<bool> {
+        IdentityChangeHistory::verify_event(self.change_events(), event, vault).await
+    }
 }
 
 impl Contact {
@@ -6,9 +6,9 @@ use crate::{
     CredentialFragment1, CredentialFragment2, CredentialOffer, CredentialPresentation,
     CredentialProof, CredentialProtocol, CredentialPublicKey, CredentialRequest,
     CredentialRequestFragment, CredentialSchema, CredentialVerificationResultMessage, Holder,
-    HolderWorker, Identity, Identity, IdentityCredential, IdentityCredentialResponse,
-    IdentityIdentifier, IdentityRequest, IdentityResponse, IdentityTrait, Issuer, ListenerWorker,
-    OfferId, PresentationFinishedMessage, PresentationManifest, PresenterWorker, ProofRequestId,
+    HolderWorker, Identity, IdentityCredential, IdentityCredentialResponse, IdentityIdentifier,
+    IdentityRequest, IdentityResponse, IdentityTrait, Issuer, ListenerWorker, OfferId,
+    PresentationFinishedMessage, PresentationManifest, PresenterWorker, ProofRequestId,
     SigningPublicKey, TrustPolicy, TrustPolicyImpl, VerifierWorker,
 };
 use ockam_core::{async_trait, compat::boxed::Box};
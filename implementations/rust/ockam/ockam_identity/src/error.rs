@@ -44,6 +44,8 @@ pub enum IdentityError {
     IssuerInvalidMessage,
     PresenterInvalidMessage,
     VerifierInvalidMessage,
+    UnsupportedChangeSchemaVersion,
+    KeyNotFound,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
@@ -56,8 +58,11 @@ impl core::fmt::Display for IdentityError {
 impl From<IdentityError> for Error {
     #[track_caller]
     fn from(err: IdentityError) -> Self {
-        let kind = Kind::Unknown; // FIXME: fill these in with more
-                                  // meaningful error kinds
+        let kind = match err {
+            IdentityError::KeyNotFound => Kind::NotFound,
+            _ => Kind::Unknown, // FIXME: fill these in with more
+                                // meaningful error kinds
+        };
         Error::new(Origin::Identity, kind, err)
     }
 }
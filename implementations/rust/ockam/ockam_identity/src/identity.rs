@@ -1,7 +1,7 @@
 use crate::{
     AuthenticationProof, Changes, Contact, ExportedIdentity, IdentityChangeEvent,
     IdentityChannelListener, IdentityIdentifier, IdentityState, IdentityTrait, IdentityVault,
-    Lease, SecureChannelWorker, TrustPolicy, TTL,
+    Lease, PendingSecureChannel, SecureChannelWorker, TrustPolicy, TTL,
 };
 use core::time::Duration;
 use ockam_core::compat::{string::String, sync::Arc, vec::Vec};
@@ -60,6 +60,10 @@ impl<V: IdentityVault> IdentityTrait for Identity<V> {
         self.state.write().await.rotate_root_secret_key().await
     }
 
+    async fn rotate_key(&self, label: String) -> Result<()> {
+        self.state.write().await.rotate_key(label).await
+    }
+
     async fn get_root_secret_key(&self) -> Result<Secret> {
         self.state.read().await.get_root_secret_key().await
     }
@@ -187,18 +191,10 @@ impl<V: IdentityVault> Identity<V> {
         route: impl Into<Route>,
         trust_policy: impl TrustPolicy,
     ) -> Result<Address> {
-        let vault = self.state.read().await.vault.async_try_clone().await?;
-        let identity_clone = self.async_try_clone().await?;
-
-        SecureChannelWorker::create_initiator(
-            &self.ctx,
-            route.into(),
-            identity_clone,
-            Arc::new(trust_policy),
-            vault,
-            Duration::from_secs(120),
-        )
-        .await
+        self.start_secure_channel(route, trust_policy)
+            .await?
+            .wait(Duration::from_secs(120))
+            .await
     }
 
     pub async fn create_secure_channel_extended(
@@ -207,16 +203,28 @@ impl<V: IdentityVault> Identity<V> {
         trust_policy: impl TrustPolicy,
         timeout: Duration,
     ) -> Result<Address> {
+        self.start_secure_channel(route, trust_policy)
+            .await?
+            .wait(timeout)
+            .await
+    }
+
+    /// Start creating a SecureChannel without waiting for its handshake,
+    /// returning a handle that can wait for or cancel it
+    pub async fn start_secure_channel(
+        &self,
+        route: impl Into<Route>,
+        trust_policy: impl TrustPolicy,
+    ) -> Result<PendingSecureChannel> {
         let vault = self.state.read().await.vault.async_try_clone().await?;
         let identity_clone = self.async_try_clone().await?;
 
-        SecureChannelWorker::create_initiator(
+        SecureChannelWorker::start_initiator(
             &self.ctx,
             route.into(),
             identity_clone,
             Arc::new(trust_policy),
             vault,
-            timeout,
         )
         .await
     }
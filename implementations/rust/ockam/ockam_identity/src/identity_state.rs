@@ -215,6 +215,20 @@ impl<V: IdentityVault> IdentityState<V> {
         self.add_change(event).await
     }
 
+    pub async fn rotate_key(&mut self, label: String) -> Result<()> {
+        let key_attribs =
+            IdentityChangeHistory::find_last_key_event(self.change_history().as_ref(), &label)?
+                .change_block()
+                .change()
+                .key_attributes()
+                .clone();
+
+        let event = self
+            .make_rotate_key_event(key_attribs, IdentityEventAttributes::new())
+            .await?;
+        self.add_change(event).await
+    }
+
     /// Get [`Secret`] key. Key is uniquely identified by label in [`KeyAttributes`]
     pub async fn get_root_secret_key(&self) -> Result<Secret> {
         self.get_secret_key(IdentityStateConst::ROOT_LABEL.to_string())
@@ -34,6 +34,7 @@ use ockam_channel::SecureChannelVault;
 use ockam_core::compat::{collections::HashMap, string::String, vec::Vec};
 use ockam_core::{AsyncTryClone, Decodable, Encodable, Result};
 use ockam_vault::{Hasher, KeyIdVault, SecretVault, Signer, Verifier};
+use serde::Deserialize;
 pub use traits::*;
 pub use worker::*;
 
@@ -110,10 +111,20 @@ impl IdentitySerializationUtil {
     }
 
     /// Deserialize [`Contact`] from binary form
+    ///
+    /// Fails with [`IdentityError::UnsupportedChangeSchemaVersion`] if
+    /// any of its change events was written with a newer schema version.
     pub fn deserialize_contact(contact: &[u8]) -> Result<Contact> {
-        let contact = Contact::decode(contact).map_err(|_| IdentityError::BareError)?;
+        #[derive(Deserialize)]
+        struct EncodedContact {
+            identifier: IdentityIdentifier,
+            change_history: Vec<DecodedChangeEvent>,
+        }
+
+        let contact = EncodedContact::decode(contact).map_err(|_| IdentityError::BareError)?;
+        let change_history = Self::decode_events(contact.change_history)?;
 
-        Ok(contact)
+        Ok(Contact::new(contact.identifier, change_history))
     }
 
     /// Serialize [`IdentityChangeEvent`]s to binary form for storing/transferring over the network
@@ -124,11 +135,21 @@ impl IdentitySerializationUtil {
     }
 
     /// Deserialize [`IdentityChangeEvent`]s from binary form
+    ///
+    /// Fails with [`IdentityError::UnsupportedChangeSchemaVersion`] if
+    /// any of the events was written with a newer schema version.
     pub fn deserialize_change_events(change_events: &[u8]) -> Result<Vec<IdentityChangeEvent>> {
-        let change_events = Vec::<IdentityChangeEvent>::decode(change_events)
+        let change_events = Vec::<DecodedChangeEvent>::decode(change_events)
             .map_err(|_| IdentityError::BareError)?;
 
-        Ok(change_events)
+        Self::decode_events(change_events)
+    }
+
+    fn decode_events(events: Vec<DecodedChangeEvent>) -> Result<Vec<IdentityChangeEvent>> {
+        events
+            .into_iter()
+            .map(DecodedChangeEvent::into_event)
+            .collect()
     }
 }
 
@@ -141,6 +162,7 @@ mod test {
     };
     use ockam_node::Context;
     use ockam_vault::Vault;
+    use serde::Serialize;
 
     fn test_error<S: Into<String>>(msg: S) -> Result<()> {
         Err(Error::new_without_cause(Origin::Identity, Kind::Unknown).context("msg", msg.into()))
@@ -222,6 +244,209 @@ mod test {
         Ok(())
     }
 
+    async fn test_change_events_schema_version(identity: &impl IdentityTrait) -> Result<()> {
+        let changes = identity.get_changes().await?;
+        let binary = IdentitySerializationUtil::serialize_change_events(&changes)?;
+
+        let decoded = IdentitySerializationUtil::deserialize_change_events(&binary)?;
+        if decoded.len() != changes.len()
+            || decoded
+                .iter()
+                .any(|e| e.schema_version() != IdentityChangeEvent::SCHEMA_VERSION)
+        {
+            return test_error("current schema version did not round-trip");
+        }
+
+        // Append a newer event, which must be rejected without
+        // decoding its body
+        let newer = changes[0]
+            .clone()
+            .with_schema_version(IdentityChangeEvent::SCHEMA_VERSION + 1);
+        let mut events = changes.clone();
+        events.push(newer);
+        let binary = IdentitySerializationUtil::serialize_change_events(&events)?;
+
+        let unsupported = Error::from(IdentityError::UnsupportedChangeSchemaVersion).code();
+        match IdentitySerializationUtil::deserialize_change_events(&binary) {
+            Err(e) if e.code() == unsupported => {}
+            Err(e) => return test_error(format!("unexpected error: {}", e)),
+            Ok(_) => return test_error("newer schema version was accepted"),
+        }
+
+        let contact = Contact::new(identity.identifier().await?, events);
+        let binary = IdentitySerializationUtil::serialize_contact(&contact)?;
+        match IdentitySerializationUtil::deserialize_contact(&binary) {
+            Err(e) if e.code() == unsupported => Ok(()),
+            Err(e) => test_error(format!("unexpected error: {}", e)),
+            Ok(_) => test_error("contact with newer schema version was accepted"),
+        }
+    }
+
+    /// Layout of events written before schema versions were introduced
+    #[derive(Serialize)]
+    struct LegacyEvent<'a> {
+        identifier: &'a EventIdentifier,
+        change_block: &'a ChangeBlock,
+        signatures: &'a [Signature],
+    }
+
+    impl<'a> From<&'a IdentityChangeEvent> for LegacyEvent<'a> {
+        fn from(event: &'a IdentityChangeEvent) -> Self {
+            Self {
+                identifier: event.identifier(),
+                change_block: event.change_block(),
+                signatures: event.signatures(),
+            }
+        }
+    }
+
+    async fn test_legacy_change_events(
+        identity: &impl IdentityTrait,
+        vault: &mut impl IdentityVault,
+    ) -> Result<()> {
+        let changes = identity.get_changes().await?;
+        let legacy: Vec<LegacyEvent> = changes.iter().map(LegacyEvent::from).collect();
+        if legacy.encode()? != IdentitySerializationUtil::serialize_change_events(&changes)? {
+            return test_error("events are not encoded in the legacy layout");
+        }
+
+        let decoded = IdentitySerializationUtil::deserialize_change_events(&legacy.encode()?)?;
+        if decoded.len() != changes.len() {
+            return test_error("legacy events were not decoded");
+        }
+
+        #[derive(Serialize)]
+        struct LegacyContact<'a> {
+            identifier: IdentityIdentifier,
+            change_history: Vec<LegacyEvent<'a>>,
+        }
+        let contact = LegacyContact {
+            identifier: identity.identifier().await?,
+            change_history: legacy,
+        };
+        let contact = IdentitySerializationUtil::deserialize_contact(&contact.encode()?)?;
+        if !contact.verify(vault).await? {
+            return test_error("legacy contact did not verify");
+        }
+
+        // Exported identities are stored as JSON
+        let mut json = serde_json::to_value(&changes).unwrap();
+        for event in json.as_array_mut().unwrap() {
+            event.as_object_mut().unwrap().remove("schema_version");
+        }
+        let decoded: Changes = serde_json::from_value(json.clone()).unwrap();
+        if decoded
+            .iter()
+            .any(|e| e.schema_version() != IdentityChangeEvent::SCHEMA_VERSION)
+        {
+            return test_error("legacy JSON events were not decoded");
+        }
+
+        json[0]["schema_version"] = (IdentityChangeEvent::SCHEMA_VERSION + 1).into();
+        if serde_json::from_value::<Changes>(json).is_ok() {
+            return test_error("newer JSON schema version was accepted");
+        }
+        Ok(())
+    }
+
+    async fn test_labeled_public_keys(identity: &impl IdentityTrait) -> Result<()> {
+        identity.create_key("Signing".to_string()).await?;
+        identity.create_key("Encryption".to_string()).await?;
+
+        let signing = identity.get_public_key("Signing".to_string()).await?;
+        let encryption = identity.get_public_key("Encryption".to_string()).await?;
+
+        if signing == encryption {
+            return test_error("labeled keys are not distinct");
+        }
+
+        if signing == identity.get_root_public_key().await? {
+            return test_error("labeled key is the root key");
+        }
+
+        match identity.get_public_key("Unknown".to_string()).await {
+            Err(e) if e.code() == Error::from(IdentityError::KeyNotFound).code() => Ok(()),
+            Err(e) => test_error(format!("unexpected error: {}", e)),
+            Ok(_) => test_error("found a key for an unknown label"),
+        }
+    }
+
+    async fn test_event_signatures(ctx: &Context) -> Result<()> {
+        let vault = Vault::create();
+        let identity = Identity::create(ctx, &vault).await?;
+        identity.create_key("Signing".to_string()).await?;
+
+        let vault = &mut vault.clone();
+        let identifier = identity.identifier().await?;
+        let changes = identity.get_changes().await?;
+        if changes.len() != 2 {
+            return test_error("expected the root key and one labeled key");
+        }
+
+        for (i, event) in changes.iter().enumerate() {
+            let contact = Contact::new(identifier.clone(), changes[..i].to_vec());
+            if !contact.verify_event_signatures(event, vault).await? {
+                return test_error(format!("signatures of event {} failed to verify", i));
+            }
+        }
+
+        // Replace the root signature of the labeled key by one made
+        // with the labeled key itself
+        let event = &changes[1];
+        let self_sign = event
+            .signatures()
+            .iter()
+            .find(|s| s.stype() == &SignatureType::SelfSign)
+            .unwrap();
+        let signatures = event
+            .signatures()
+            .iter()
+            .map(|s| match s.stype() {
+                SignatureType::RootSign => {
+                    Signature::new(SignatureType::RootSign, self_sign.data().clone())
+                }
+                _ => s.clone(),
+            })
+            .collect();
+        let forged = IdentityChangeEvent::new(
+            event.identifier().clone(),
+            event.change_block().clone(),
+            signatures,
+        );
+
+        let contact = Contact::new(identifier, changes[..1].to_vec());
+        if contact.verify_event_signatures(&forged, vault).await? {
+            return test_error("forged root signature was accepted");
+        }
+        Ok(())
+    }
+
+    async fn test_rotate_labeled_key(identity: &impl IdentityTrait) -> Result<()> {
+        let root = identity.get_root_public_key().await?;
+        let before = identity.get_public_key("Signing".to_string()).await?;
+
+        identity.rotate_key("Signing".to_string()).await?;
+
+        if !identity.verify_changes().await? {
+            return test_error("verify_changes failed");
+        }
+
+        let after = identity.get_public_key("Signing".to_string()).await?;
+        if before == after || before.stype() != after.stype() {
+            return test_error("labeled key was not rotated");
+        }
+
+        if root != identity.get_root_public_key().await? {
+            return test_error("root key changed while rotating a labeled key");
+        }
+
+        match identity.rotate_key("Unknown".to_string()).await {
+            Err(e) if e.code() == Error::from(IdentityError::KeyNotFound).code() => Ok(()),
+            Err(e) => test_error(format!("unexpected error: {}", e)),
+            Ok(_) => test_error("rotated a key for an unknown label"),
+        }
+    }
+
     #[ockam_macros::test]
     async fn async_tests(ctx: &mut Context) -> Result<()> {
         let alice_vault = Vault::create();
@@ -233,6 +458,11 @@ mod test {
         let mut results = vec![];
         results.push(test_basic_identity_key_ops(&mut alice).await);
         results.push(test_update_contact_after_change(&mut alice, &mut bob).await);
+        results.push(test_change_events_schema_version(&alice).await);
+        results.push(test_legacy_change_events(&alice, &mut alice_vault.clone()).await);
+        results.push(test_labeled_public_keys(&bob).await);
+        results.push(test_event_signatures(ctx).await);
+        results.push(test_rotate_labeled_key(&bob).await);
         ctx.stop().await?;
 
         for r in results {
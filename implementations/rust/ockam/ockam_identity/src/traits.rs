@@ -1,4 +1,7 @@
-use crate::{Changes, Contact, IdentityChangeEvent, IdentityIdentifier, Lease, TTL};
+use crate::{
+    Changes, Contact, IdentityChangeEvent, IdentityError, IdentityIdentifier, IdentityStateConst,
+    Lease, TTL,
+};
 use ockam_core::compat::{string::String, vec::Vec};
 use ockam_core::vault::{PublicKey, Secret};
 use ockam_core::{async_trait, compat::boxed::Box, AsyncTryClone};
@@ -21,6 +24,22 @@ pub trait IdentityTrait: AsyncTryClone + Send + Sync + 'static {
     /// Rotate existing key.
     async fn rotate_root_secret_key(&self) -> Result<()>;
 
+    /// Rotate the key with the given label.
+    ///
+    /// The new key has the same attributes as the one it replaces.
+    /// Fails with `IdentityError::KeyNotFound` if no key has this label.
+    ///
+    /// The default implementation only rotates the root key, using
+    /// [`rotate_root_secret_key`](Self::rotate_root_secret_key), and
+    /// fails with `IdentityError::NotImplemented` for any other label.
+    async fn rotate_key(&self, label: String) -> Result<()> {
+        if label == IdentityStateConst::ROOT_LABEL {
+            self.rotate_root_secret_key().await
+        } else {
+            Err(IdentityError::NotImplemented.into())
+        }
+    }
+
     /// Get [`Secret`] key.
     async fn get_root_secret_key(&self) -> Result<Secret>;
 
@@ -30,7 +49,9 @@ pub trait IdentityTrait: AsyncTryClone + Send + Sync + 'static {
     /// Get [`PublicKey`].
     async fn get_root_public_key(&self) -> Result<PublicKey>;
 
-    /// Get [`PublicKey`].
+    /// Get the current [`PublicKey`] of the key with the given label.
+    ///
+    /// Fails with `IdentityError::KeyNotFound` if no key has this label.
     async fn get_public_key(&self, label: String) -> Result<PublicKey>;
 
     /// Create an authentication proof based on the given state
@@ -38,6 +38,10 @@ pub trait KeyExchanger {
     async fn is_complete(&self) -> Result<bool>;
     /// Return the data and keys needed for channels. Key exchange must be completed prior to calling this function.
     async fn finalize(self) -> Result<CompletedKeyExchange>;
+    /// Destroy any secrets created by an unfinished key exchange. The key exchanger must not be used afterwards.
+    async fn abort(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// A creator of both initiator and responder peers of a key exchange.
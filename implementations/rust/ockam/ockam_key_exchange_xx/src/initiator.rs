@@ -79,4 +79,8 @@ impl<V: XXVault> KeyExchanger for Initiator<V> {
             _ => Err(XXError::InvalidState.into()),
         }
     }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.state_data.destroy_secrets().await
+    }
 }
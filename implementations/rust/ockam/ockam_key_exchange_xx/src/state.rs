@@ -1,7 +1,7 @@
 use crate::{XXError, XXVault, AES_GCM_TAGSIZE, SHA256_SIZE};
 use ockam_core::vault::{
-    PublicKey, Secret, SecretAttributes, SecretPersistence, SecretType, AES256_SECRET_LENGTH,
-    CURVE25519_PUBLIC_LENGTH, CURVE25519_SECRET_LENGTH,
+    derive_nonce, PublicKey, Secret, SecretAttributes, SecretPersistence, SecretType,
+    AES256_SECRET_LENGTH, CURVE25519_PUBLIC_LENGTH, CURVE25519_SECRET_LENGTH,
 };
 use ockam_core::{compat::vec::Vec, Result};
 use ockam_key_exchange_core::CompletedKeyExchange;
@@ -126,8 +126,7 @@ impl<V: XXVault> State<V> {
     ) -> Result<(Vec<u8>, [u8; 32])> {
         let h = &self.h.ok_or(XXError::InvalidState)?;
 
-        let mut nonce = [0u8; 12];
-        nonce[10..].copy_from_slice(&self.nonce.to_be_bytes());
+        let nonce = derive_nonce(self.nonce as u64, 0);
 
         let ciphertext_and_tag = {
             let key = self.dh_state.key().ok_or(XXError::InvalidState)?;
@@ -146,8 +145,7 @@ impl<V: XXVault> State<V> {
     ) -> Result<(Vec<u8>, [u8; 32])> {
         let h = &self.h.ok_or(XXError::InvalidState)?;
 
-        let mut nonce = [0u8; 12];
-        nonce[10..].copy_from_slice(&self.nonce.to_be_bytes());
+        let nonce = derive_nonce(self.nonce as u64, 0);
         let ciphertext = ciphertext.as_ref();
         let plaintext = {
             let key = self.dh_state.key().ok_or(XXError::InvalidState)?;
@@ -285,11 +283,34 @@ impl<V: XXVault> State<V> {
 
     pub(crate) async fn finalize_initiator(mut self) -> Result<CompletedKeyExchange> {
         let keys = { self.split().await? };
+        // Only the split keys outlive the handshake
+        self.destroy_secrets().await?;
 
         self.finalize(keys.1, keys.0)
     }
 }
 
+impl<V: XXVault> State<V> {
+    /// Destroy all secrets created so far
+    ///
+    /// Every secret is destroyed even if destroying another one
+    /// fails.  The first failure is returned.
+    pub(crate) async fn destroy_secrets(&mut self) -> Result<()> {
+        let secrets = [
+            self.identity_key.take(),
+            self.ephemeral_secret.take(),
+            self.dh_state.key.take(),
+            self.dh_state.ck.take(),
+        ];
+        let mut res = Ok(());
+        for secret in secrets.iter().flatten() {
+            let destroyed = self.vault.secret_destroy(secret.clone()).await;
+            res = res.and(destroyed);
+        }
+        res
+    }
+}
+
 impl<V: XXVault> State<V> {
     /// Decode the first message sent
     pub(crate) async fn decode_message_1<B: AsRef<[u8]>>(
@@ -377,6 +398,8 @@ impl<V: XXVault> State<V> {
 
     pub(crate) async fn finalize_responder(mut self) -> Result<CompletedKeyExchange> {
         let keys = { self.split().await? };
+        // Only the split keys outlive the handshake
+        self.destroy_secrets().await?;
 
         self.finalize(keys.0, keys.1)
     }
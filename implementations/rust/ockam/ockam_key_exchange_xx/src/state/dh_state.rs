@@ -80,6 +80,7 @@ impl<V: XXVault> DhState<V> {
             .vault
             .hkdf_sha256(ck, b"", Some(&ecdh), vec![attributes_ck, attributes_k])
             .await?;
+        self.vault.secret_destroy(ecdh).await?;
 
         if hkdf_output.len() != 2 {
             return Err(XXError::InternalVaultError.into());
@@ -6,23 +6,104 @@ use crate::tokio::{
     time::timeout,
 };
 use crate::{
+    delayed::DelayedEvents,
     error::*,
     parser,
-    relay::{CtrlSignal, ProcessorRelay, RelayMessage, WorkerRelay},
+    relay::{CtrlSignal, ProcessorRelay, RelayMessage, StopRequest, WorkerRelay},
     router::SenderPair,
-    Cancel, NodeMessage, ShutdownType,
+    Cancel, LifecycleEvents, NodeMessage, NodeReplyResult, ShutdownType,
 };
+use core::any::{Any, TypeId};
 use core::time::Duration;
-use ockam_core::compat::{boxed::Box, string::String, sync::Arc, vec::Vec};
+use ockam_core::compat::{
+    boxed::Box,
+    collections::VecDeque,
+    string::{String, ToString},
+    sync::{Arc, Mutex},
+    vec::Vec,
+};
 use ockam_core::{
     errcode::{Kind, Origin},
-    AccessControl, Address, AddressSet, AllowAll, AsyncTryClone, Error, LocalMessage, Message,
-    Processor, Result, Route, TransportMessage, TransportType, Worker,
+    route, AccessControl, Address, AddressSet, AllowAll, AsyncTryClone, Codec, DeadLetter,
+    DeliveryReport, DeliveryReportRequest, DeliveryStatus, Encodable, Error, LocalMessage, Message,
+    Processor, Result, RetryPolicy, Route, TransportMessage, TransportType, Worker, LOCAL,
 };
+use serde::Serialize;
 
 /// A default timeout in seconds
 pub const DEFAULT_TIMEOUT: u64 = 30;
 
+/// Time in seconds a processor consulting
+/// [`should_continue`](Context::should_continue) gets to stop by itself
+pub const PROCESSOR_STOP_TIMEOUT: u64 = 5;
+
+/// Time in seconds [`send_with_retry`](Context::send_with_retry)
+/// waits for a transport to report the delivery of a message
+pub const DELIVERY_REPORT_TIMEOUT: u64 = 5;
+
+/// A message set aside by a receive call
+///
+/// Remembers what the message was last parsed to, so that receiving it
+/// as the same type later doesn't parse it again.
+struct SkippedMessage {
+    msg: RelayMessage,
+    /// Behind a `Mutex` to keep `Context` `Sync`, as messages only
+    /// need to be `Send`
+    parsed: Mutex<Option<ParsedMessage>>,
+}
+
+/// The type a message was parsed as, and the value if that succeeded
+type ParsedMessage = (TypeId, Option<Box<dyn Any + Send>>);
+
+impl SkippedMessage {
+    fn new<M: Message>(msg: RelayMessage, parsed: Option<M>) -> Self {
+        Self {
+            msg,
+            parsed: Mutex::new(Some(Self::parsed(parsed))),
+        }
+    }
+
+    fn parsed<M: Message>(parsed: Option<M>) -> ParsedMessage {
+        let parsed = parsed.map(|m| -> Box<dyn Any + Send> { Box::new(m) });
+        (TypeId::of::<M>(), parsed)
+    }
+
+    /// Check the message's value as an `M`, parsing it unless that
+    /// was done before
+    fn matches<M, F>(&self, check: &F) -> bool
+    where
+        M: Message,
+        F: Fn(&M) -> bool,
+    {
+        let mut parsed = match self.parsed.lock() {
+            Ok(parsed) => parsed,
+            Err(_) => return false,
+        };
+        let id = TypeId::of::<M>();
+        if !matches!(*parsed, Some((parsed_id, _)) if parsed_id == id) {
+            *parsed = Some(Self::parsed(Context::parse_relay::<M>(&self.msg)));
+        }
+        parsed
+            .as_ref()
+            .and_then(|(_, m)| m.as_ref())
+            .and_then(|m| m.downcast_ref())
+            .map_or(false, check)
+    }
+
+    /// Split off the value the message was parsed to, if it is an `M`
+    fn into_parts<M: Message>(self) -> (RelayMessage, Option<M>) {
+        let parsed = self
+            .parsed
+            .lock()
+            .ok()
+            .and_then(|mut parsed| parsed.take())
+            .and_then(|(_, m)| m)
+            .and_then(|m| m.downcast().ok())
+            .map(|m| *m);
+        (self.msg, parsed)
+    }
+}
+
 enum AddressType {
     Worker,
     Processor,
@@ -43,7 +124,17 @@ pub struct Context {
     sender: Sender<NodeMessage>,
     rt: Arc<Runtime>,
     mailbox: Receiver<RelayMessage>,
+    #[cfg(feature = "std")]
+    prio_mailbox: Receiver<RelayMessage>,
+    /// Messages set aside by receive calls
+    ///
+    /// These have passed access control already and are handed out
+    /// before anything new from the mailbox.
+    skipped: VecDeque<SkippedMessage>,
     access_control: Box<dyn AccessControl>,
+    stop_request: Arc<StopRequest>,
+    strict_decoding: bool,
+    delayed: DelayedEvents,
 }
 
 #[ockam_core::async_trait]
@@ -59,25 +150,84 @@ impl Context {
         self.rt.clone()
     }
     /// Wait for the next message from the mailbox
-    pub(crate) async fn mailbox_next(&mut self) -> Result<Option<RelayMessage>> {
+    ///
+    /// Messages set aside by earlier receive calls come first, along
+    /// with their value if they were already parsed as an `M`.  After
+    /// those, messages waiting in the priority lane are always
+    /// returned before any message from the regular mailbox.
+    pub(crate) async fn mailbox_next<M: Message>(
+        &mut self,
+    ) -> Result<Option<(RelayMessage, Option<M>)>> {
+        if let Some(skipped) = self.skipped.pop_front() {
+            return Ok(Some(skipped.into_parts()));
+        }
+        Ok(self.mailbox_recv().await?.map(|msg| (msg, None)))
+    }
+
+    /// Wait for the next message that has not been seen yet
+    async fn mailbox_recv(&mut self) -> Result<Option<RelayMessage>> {
         loop {
-            let relay_msg = if let Some(msg) = self.mailbox.recv().await {
+            #[cfg(feature = "std")]
+            let next = tokio::select! {
+                biased;
+                Some(msg) = self.prio_mailbox.recv() => Some(msg),
+                msg = self.mailbox.recv() => msg,
+            };
+            #[cfg(not(feature = "std"))]
+            let next = self.mailbox.recv().await;
+
+            let relay_msg = if let Some(msg) = next {
                 trace!("{}: received new message!", self.address());
                 msg
             } else {
                 return Ok(None);
             };
 
-            if let RelayPayload::Direct(local_msg) = &relay_msg.data {
-                if !self.access_control.is_authorized(local_msg).await? {
-                    warn!("Message for {} did not pass access control", relay_msg.addr);
-                    continue;
-                }
+            if !self.is_authorized(&relay_msg).await? {
+                continue;
             }
 
             return Ok(Some(relay_msg));
         }
     }
+
+    /// Take the next message that has not been seen yet, without waiting
+    ///
+    /// Returns `None` if no message is currently queued.
+    async fn mailbox_try_recv(&mut self) -> Result<Option<RelayMessage>> {
+        loop {
+            #[cfg(feature = "std")]
+            let next = match self.prio_mailbox.try_recv() {
+                Ok(msg) => Some(msg),
+                Err(_) => self.mailbox.try_recv().ok(),
+            };
+            #[cfg(not(feature = "std"))]
+            let next = self.mailbox.try_recv().ok();
+
+            let relay_msg = if let Some(msg) = next {
+                trace!("{}: received new message!", self.address());
+                msg
+            } else {
+                return Ok(None);
+            };
+
+            if !self.is_authorized(&relay_msg).await? {
+                continue;
+            }
+
+            return Ok(Some(relay_msg));
+        }
+    }
+
+    async fn is_authorized(&self, relay_msg: &RelayMessage) -> Result<bool> {
+        if let RelayPayload::Direct(local_msg) = &relay_msg.data {
+            if !self.access_control.is_authorized(local_msg).await? {
+                warn!("Message for {} did not pass access control", relay_msg.addr);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
 }
 
 impl Context {
@@ -92,6 +242,12 @@ impl Context {
         access_control: impl AccessControl,
     ) -> (Self, SenderPair, Receiver<CtrlSignal>) {
         let (mailbox_tx, mailbox) = channel(32);
+        // Without `std` there is no way to select over both lanes,
+        // so priority messages share the regular mailbox
+        #[cfg(feature = "std")]
+        let (prio_tx, prio_mailbox) = channel(32);
+        #[cfg(not(feature = "std"))]
+        let prio_tx = mailbox_tx.clone();
         let (ctrl_tx, ctrl_rx) = channel(1);
         (
             Self {
@@ -99,10 +255,17 @@ impl Context {
                 sender,
                 address,
                 mailbox,
+                #[cfg(feature = "std")]
+                prio_mailbox,
+                skipped: VecDeque::new(),
                 access_control: Box::new(access_control),
+                stop_request: Arc::new(StopRequest::default()),
+                strict_decoding: false,
+                delayed: DelayedEvents::default(),
             },
             SenderPair {
                 msgs: mailbox_tx,
+                prio: prio_tx,
                 ctrl: ctrl_tx,
             },
             ctrl_rx,
@@ -114,11 +277,106 @@ impl Context {
         self.address.first()
     }
 
+    /// Report messages the worker can't decode
+    ///
+    /// By default a message whose payload does not decode as the
+    /// worker's message type is dropped with an error log.  In strict
+    /// mode a warning naming the worker and the first bytes of the
+    /// payload is logged, and the message is sent to the node's
+    /// dead-letter address if one is set.  Call this from
+    /// [`Worker::initialize`] to enable it for a worker.
+    pub fn set_strict_decoding(&mut self, strict: bool) {
+        self.strict_decoding = strict;
+    }
+
+    /// Return whether undecodable messages are reported
+    pub fn strict_decoding(&self) -> bool {
+        self.strict_decoding
+    }
+
     /// Return all addresses of the current worker
     pub fn aliases(&self) -> AddressSet {
         self.address.clone().into_iter().skip(1).collect()
     }
 
+    /// Check whether the current processor should keep running
+    ///
+    /// A processor whose [`process`](Processor::process) does not
+    /// return on its own must check this between iterations and
+    /// return once it is `false`.  Processors that do are given
+    /// [`PROCESSOR_STOP_TIMEOUT`] seconds to wind down when they are
+    /// stopped, after which they are stopped forcibly.  Processors
+    /// that never call this are stopped right away, as before.
+    ///
+    /// ```rust
+    /// use core::time::Duration;
+    /// use ockam_core::{async_trait, Processor, Result};
+    /// use ockam_node::Context;
+    ///
+    /// struct Ticker;
+    ///
+    /// #[async_trait]
+    /// impl Processor for Ticker {
+    ///     type Context = Context;
+    ///
+    ///     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
+    ///         while ctx.should_continue() {
+    ///             ctx.sleep(Duration::from_millis(100)).await;
+    ///         }
+    ///         Ok(false)
+    ///     }
+    /// }
+    ///
+    /// async fn start_ticker(ctx: &mut Context) -> Result<()> {
+    ///     ctx.start_processor("ticker", Ticker).await
+    /// }
+    /// ```
+    ///
+    /// Contexts of workers are never asked to stop this way, so this
+    /// always returns `true` for them.
+    pub fn should_continue(&self) -> bool {
+        !self.stop_request.poll()
+    }
+
+    /// Wait until the current processor is asked to stop
+    ///
+    /// This lets a processor that is blocked on some other operation
+    /// notice a stop request, by racing the two, e.g. with
+    /// `tokio::select!`.  Like [`should_continue`](Self::should_continue),
+    /// calling this gives the processor time to wind down when it is
+    /// stopped.
+    ///
+    /// Contexts of workers are never asked to stop this way, so this
+    /// never resolves for them.
+    #[cfg(feature = "std")]
+    pub async fn stop_requested(&self) {
+        self.stop_request.requested().await
+    }
+
+    pub(crate) fn stop_request(&self) -> Arc<StopRequest> {
+        self.stop_request.clone()
+    }
+
+    pub(crate) fn delayed_events(&self) -> DelayedEvents {
+        self.delayed.clone()
+    }
+
+    /// Return the destinations of all pending delayed events
+    ///
+    /// These are the [`DelayedEvent`](crate::DelayedEvent)s created
+    /// with this context which are scheduled but have not fired yet.
+    pub fn list_delayed(&self) -> Vec<Address> {
+        self.delayed.destinations()
+    }
+
+    /// Cancel all pending delayed events
+    ///
+    /// This is done automatically when the worker or processor owning
+    /// this context stops.
+    pub fn cancel_all_delayed(&self) {
+        self.delayed.cancel_all()
+    }
+
     /// Utility function to sleep tasks from other crates
     #[doc(hidden)]
     pub async fn sleep(&self, dur: Duration) {
@@ -131,20 +389,41 @@ impl Context {
     /// [`start_worker()`](Self::start_worker) is the recommended to
     /// way to create a new worker context.
     pub async fn new_context<S: Into<Address>>(&self, addr: S) -> Result<Context> {
-        self.new_context_impl(addr.into()).await
+        self.new_context_impl(addr.into(), AllowAll).await
     }
 
-    async fn new_context_impl(&self, addr: Address) -> Result<Context> {
+    /// Create a new context with the given access control
+    ///
+    /// Like [`new_context()`](Self::new_context), but messages for
+    /// the new context are dropped unless `access_control` authorizes
+    /// them.
+    pub async fn new_context_with_access_control<S, A>(
+        &self,
+        addr: S,
+        access_control: A,
+    ) -> Result<Context>
+    where
+        S: Into<Address>,
+        A: AccessControl,
+    {
+        self.new_context_impl(addr.into(), access_control).await
+    }
+
+    async fn new_context_impl(
+        &self,
+        addr: Address,
+        access_control: impl AccessControl,
+    ) -> Result<Context> {
         // Create a new context and get access to the mailbox senders
         let (ctx, sender, _) = Self::new(
             Arc::clone(&self.rt),
             self.sender.clone(),
             addr.clone().into(),
-            AllowAll,
+            access_control,
         );
 
         // Create a "bare relay" and register it with the router
-        let (msg, mut rx) = NodeMessage::start_worker(addr.into(), sender, true);
+        let (msg, mut rx) = NodeMessage::start_worker(addr.into(), Vec::new(), sender, true);
         self.sender
             .send(msg)
             .await
@@ -192,7 +471,58 @@ impl Context {
         NM: Message + Send + 'static,
         NW: Worker<Context = Context, Message = NM>,
     {
-        self.start_worker_impl(address.into(), worker, AllowAll)
+        self.start_worker_impl(address.into(), Vec::new(), worker, AllowAll, None)
+            .await
+    }
+
+    /// Start a new worker instance taking over a previous worker's state
+    ///
+    /// `state` is passed to the worker's
+    /// [`initialize_with_state`](Worker::initialize_with_state), and
+    /// is usually obtained via
+    /// [`stop_worker_with_state()`](Self::stop_worker_with_state).
+    /// With a `state` of `None` the worker starts fresh, as with
+    /// [`start_worker()`](Self::start_worker).
+    pub async fn start_worker_with_state<NM, NW, S>(
+        &self,
+        address: S,
+        worker: NW,
+        state: Option<Vec<u8>>,
+    ) -> Result<()>
+    where
+        S: Into<AddressSet>,
+        NM: Message + Send + 'static,
+        NW: Worker<Context = Context, Message = NM>,
+    {
+        self.start_worker_impl(address.into(), Vec::new(), worker, AllowAll, state)
+            .await
+    }
+
+    /// Start a new worker instance with a set of high-priority addresses
+    ///
+    /// Messages sent to any of the `priority` addresses are delivered
+    /// to the worker ahead of messages queued for its other
+    /// addresses.  Use this for internal control messages which
+    /// should not get stuck behind a backlog of data messages.  Each
+    /// priority address must also be part of `address`.
+    pub async fn start_worker_with_priority<NM, NW, S, P>(
+        &self,
+        address: S,
+        priority: P,
+        worker: NW,
+    ) -> Result<()>
+    where
+        S: Into<AddressSet>,
+        P: Into<AddressSet>,
+        NM: Message + Send + 'static,
+        NW: Worker<Context = Context, Message = NM>,
+    {
+        let address = address.into();
+        let priority: Vec<Address> = priority.into().into_iter().collect();
+        if let Some(addr) = priority.iter().find(|a| !address.contains(a)) {
+            return Err(NodeError::Address(addr.clone()).not_found());
+        }
+        self.start_worker_impl(address, priority, worker, AllowAll, None)
             .await
     }
 
@@ -211,15 +541,17 @@ impl Context {
         NW: Worker<Context = Context, Message = NM>,
         NA: AccessControl,
     {
-        self.start_worker_impl(address.into(), worker, access_control)
+        self.start_worker_impl(address.into(), Vec::new(), worker, access_control, None)
             .await
     }
 
     async fn start_worker_impl<NM, NW, NA>(
         &self,
         address: AddressSet,
+        priority: Vec<Address>,
         worker: NW,
         access_control: NA,
+        state: Option<Vec<u8>>,
     ) -> Result<()>
     where
         NM: Message + Send + 'static,
@@ -234,11 +566,8 @@ impl Context {
             access_control,
         );
 
-        // Then initialise the worker message relay
-        WorkerRelay::<NW, NM>::init(self.rt.as_ref(), worker, ctx, ctrl_rx);
-
         // Send start request to router
-        let (msg, mut rx) = NodeMessage::start_worker(address, sender, false);
+        let (msg, mut rx) = NodeMessage::start_worker(address, priority, sender, false);
         self.sender
             .send(msg)
             .await
@@ -248,6 +577,10 @@ impl Context {
         rx.recv()
             .await
             .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??;
+
+        // Only initialise the worker message relay once the router
+        // accepted the addresses.  A rejected worker is never run.
+        WorkerRelay::<NW, NM>::init(self.rt.as_ref(), worker, ctx, state, ctrl_rx);
         Ok(())
     }
 
@@ -296,6 +629,56 @@ impl Context {
         self.stop_address(addr.into(), AddressType::Worker).await
     }
 
+    /// Shut down a local worker and wait until its shutdown has run
+    ///
+    /// Unlike [`stop_worker`](Self::stop_worker), this only returns
+    /// once the worker's [`shutdown`](Worker::shutdown) has completed.
+    /// A worker must not call this to stop itself, as it would wait
+    /// for its own shutdown.
+    pub async fn stop_worker_and_wait<A: Into<Address>>(&self, addr: A) -> Result<()> {
+        let addr = addr.into();
+        debug!("Shutting down worker {} and waiting for it", addr);
+
+        let (req, mut rx) = NodeMessage::stop_worker_and_wait(addr);
+        self.sender
+            .send(req)
+            .await
+            .map_err(NodeError::from_send_err)?;
+        rx.recv()
+            .await
+            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??;
+        Ok(())
+    }
+
+    /// Shut down a local worker and return its saved state
+    ///
+    /// Once the worker was shut down its
+    /// [`save_state`](Worker::save_state) is called, and the result is
+    /// returned.  This is `None` for workers without state to save,
+    /// and for contexts which are not backed by a worker.  Fails
+    /// without stopping the worker if the request for its state can't
+    /// be delivered, e.g. because the worker is already shutting down.
+    pub async fn stop_worker_with_state<A: Into<Address>>(
+        &self,
+        addr: A,
+    ) -> Result<Option<Vec<u8>>> {
+        let addr = addr.into();
+        debug!("Shutting down worker {} and saving its state", addr);
+
+        let (req, mut rx, mut state_rx) = NodeMessage::stop_worker_saving_state(addr);
+        self.sender
+            .send(req)
+            .await
+            .map_err(NodeError::from_send_err)?;
+        rx.recv()
+            .await
+            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??;
+
+        // The state sender is dropped without a reply if nothing ran
+        // the worker's shutdown
+        Ok(state_rx.recv().await.flatten())
+    }
+
     /// Shut down a local processor by its address
     pub async fn stop_processor<A: Into<Address>>(&self, addr: A) -> Result<()> {
         self.stop_address(addr.into(), AddressType::Processor).await
@@ -350,7 +733,10 @@ impl Context {
     /// Signal to the local runtime to shut down
     ///
     /// This call will hang until a safe shutdown has been completed
-    /// or the desired timeout has been reached.
+    /// or the desired timeout has been reached.  The timeout applies
+    /// to the shutdown as a whole: each cluster is given what is left
+    /// of it, or its own timeout if that is shorter.  Once the timeout
+    /// has passed, the node is aborted.
     pub async fn stop_timeout(&mut self, seconds: u8) -> Result<()> {
         let (req, mut rx) = NodeMessage::stop_node(ShutdownType::Graceful(seconds));
         self.sender
@@ -410,6 +796,9 @@ impl Context {
     /// router addresses, which will re-address messages that need to
     /// be handled by specific domain workers.
     ///
+    /// If `msg` can not be encoded, the encoding error is returned and
+    /// nothing is sent.
+    ///
     /// [`Address`]: ockam_core::Address
     /// [`RouteBuilder`]: ockam_core::RouteBuilder
     ///
@@ -441,6 +830,25 @@ impl Context {
             .await
     }
 
+    /// Send a message encoded with a specific [`Codec`]
+    ///
+    /// The codec is signalled in the version of the transport
+    /// message.  Receivers must decode the message with
+    /// [`Routed::cast_with_codec`](ockam_core::Routed::cast_with_codec),
+    /// since typed workers and [`Context::receive`] always decode
+    /// with [`Codec::Bare`].
+    pub async fn send_with_codec<R, M>(&self, route: R, msg: M, codec: Codec) -> Result<()>
+    where
+        R: Into<Route>,
+        M: Message + Serialize + Send + 'static,
+    {
+        let payload = codec.encode(&msg)?;
+        let mut transport_msg = TransportMessage::v1(route, self.address(), payload);
+        transport_msg.version = codec.transport_version();
+        self.forward(LocalMessage::new(transport_msg, Vec::new()))
+            .await
+    }
+
     /// Send a message to an address or via a fully-qualified route
     ///
     /// Routes can be constructed from a set of [`Address`]es, or via
@@ -468,6 +876,98 @@ impl Context {
             .await
     }
 
+    /// Send a message, retrying on transient transport failures
+    ///
+    /// If the first hop of `route` is a transport address, the
+    /// transport is asked for a [`DeliveryReport`] of the message.
+    /// When it reports that the connection to the next hop is
+    /// unavailable, e.g. because it was refused or reset, or no report
+    /// arrives within [`DELIVERY_REPORT_TIMEOUT`] seconds, the message
+    /// is sent again after the next delay of `policy`.  Once the
+    /// policy is exhausted a transport I/O error is returned.  A
+    /// message rejected by the transport, e.g. because of an
+    /// unresolvable route, fails immediately.
+    ///
+    /// Messages to local workers are sent once, like with
+    /// [`send`](Self::send).
+    ///
+    /// The transport must support delivery reports, otherwise each
+    /// attempt waits for the timeout and the message may be delivered
+    /// more than once.
+    pub async fn send_with_retry<R, M>(&self, route: R, msg: M, policy: RetryPolicy) -> Result<()>
+    where
+        R: Into<Route>,
+        M: Message + Send + 'static,
+    {
+        let route = route.into();
+        if route.next()?.transport_type() == LOCAL {
+            return self.send(route, msg).await;
+        }
+
+        let payload = msg.encode()?;
+        let mut reports = self.new_context(Address::random_local()).await?;
+        let res = self
+            .send_with_reports(&mut reports, route, payload, policy)
+            .await;
+
+        let stopped = self.stop_worker(reports.address()).await;
+        res.and(stopped)
+    }
+
+    /// Send `payload` until `reports` receives a final delivery report
+    async fn send_with_reports(
+        &self,
+        reports: &mut Context,
+        route: Route,
+        payload: Vec<u8>,
+        mut policy: RetryPolicy,
+    ) -> Result<()> {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            let request = DeliveryReportRequest::new(reports.address(), attempt);
+            let transport_msg =
+                TransportMessage::v1(route.clone(), self.address(), payload.clone());
+            self.forward(LocalMessage::new(
+                transport_msg,
+                vec![request.to_local_info()?],
+            ))
+            .await?;
+
+            // Late reports of earlier attempts are ignored
+            let report = timeout(Duration::from_secs(DELIVERY_REPORT_TIMEOUT), async {
+                reports
+                    .next_from_mailbox(|r: &DeliveryReport| r.attempt() == attempt)
+                    .await
+            })
+            .await;
+
+            let reason = match report {
+                Ok(Ok((report, _, _))) => match report.into_status() {
+                    DeliveryStatus::Sent => return Ok(()),
+                    DeliveryStatus::Rejected(reason) => {
+                        return Err(Error::new(Origin::Transport, Kind::Invalid, reason))
+                    }
+                    DeliveryStatus::Unavailable(reason) => reason,
+                },
+                Ok(Err(e)) => return Err(e),
+                Err(_) => "no delivery report".to_string(),
+            };
+
+            match policy.next_delay() {
+                Some(delay) => {
+                    debug!(
+                        "Retrying in {:?} after transient failure: {}",
+                        delay, reason
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                None => return Err(Error::new(Origin::Transport, Kind::Io, reason)),
+            }
+        }
+    }
+
     async fn send_from_address_impl<M>(
         &self,
         route: Route,
@@ -481,37 +981,13 @@ impl Context {
             return Err(Error::new_without_cause(Origin::Node, Kind::Invalid));
         }
 
-        let (reply_tx, mut reply_rx) = channel(1);
-        let next = route.next().unwrap(); // TODO: communicate bad routes
-        let req = NodeMessage::SenderReq(next.clone(), reply_tx);
-
-        // First resolve the next hop in the route
-        self.sender
-            .send(req)
-            .await
-            .map_err(NodeError::from_send_err)?;
-        let (addr, sender, needs_wrapping) = reply_rx
-            .recv()
-            .await
-            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??
-            .take_sender()?;
-
         // Pack the payload into a TransportMessage
-        let payload = msg.encode().unwrap();
-        let mut transport_msg = TransportMessage::v1(route.clone(), Route::new(), payload);
+        let payload = msg.encode()?;
+        let mut transport_msg = TransportMessage::v1(route, Route::new(), payload);
         transport_msg.return_route.modify().append(sending_address);
         let local_msg = LocalMessage::new(transport_msg, Vec::new());
 
-        // Pack transport message into relay message wrapper
-        let msg = if needs_wrapping {
-            RelayMessage::pre_router(addr, local_msg, route)
-        } else {
-            RelayMessage::direct(addr, local_msg, route)
-        };
-
-        // Send the packed user message with associated route
-        sender.send(msg).await.map_err(NodeError::from_send_err)?;
-        Ok(())
+        self.forward(local_msg).await
     }
 
     /// Forward a transport message to its next routing destination
@@ -527,32 +1003,90 @@ impl Context {
     /// [`Context::send`]: crate::Context::send
     /// [`TransportMessage`]: ockam_core::TransportMessage
     pub async fn forward(&self, local_msg: LocalMessage) -> Result<()> {
+        // An empty route is a mistake of the caller rather than an
+        // undeliverable message, so it is never sent to the dead-letter
+        // address
+        let next = local_msg.transport().onward_route.next()?.clone();
+
         // Resolve the sender for the next hop in the messages route
-        let (reply_tx, mut reply_rx) = channel(1);
-        let next = local_msg.transport().onward_route.next().unwrap(); // TODO: communicate bad routes
-        let req = NodeMessage::SenderReq(next.clone(), reply_tx);
+        let (addr, sender, needs_wrapping) = match self.resolve_sender(next).await {
+            Ok(resolved) => resolved,
+            Err(e) => return self.send_dead_letter(local_msg, e).await,
+        };
+
+        // Pack the transport message into a relay message
+        let onward = local_msg.transport().onward_route.clone();
+        // let msg = RelayMessage::direct(addr, data, onward);
+        let msg = if needs_wrapping {
+            RelayMessage::pre_router(addr, local_msg, onward)
+        } else {
+            RelayMessage::direct(addr, local_msg, onward)
+        };
+        sender.send(msg).await.map_err(NodeError::from_send_err)?;
 
-        // First resolve the next hop in the route
+        Ok(())
+    }
+
+    /// Deliver a message directly to the local worker at `addr`
+    ///
+    /// Unlike [`forward`](Self::forward) the onward route of the
+    /// message is not followed, and the routes of the message are
+    /// left untouched.  The message never passes through a transport
+    /// router, but the access control of the target worker applies
+    /// as usual.  This is useful for dispatching workers.
+    pub async fn deliver_local(&self, addr: Address, local_msg: LocalMessage) -> Result<()> {
+        if addr.transport_type() != LOCAL {
+            return Err(NodeError::Address(addr).not_found());
+        }
+
+        let (addr, sender, _) = self.resolve_sender(addr).await?;
+        let onward = local_msg.transport().onward_route.clone();
+        let msg = RelayMessage::direct(addr, local_msg, onward);
+        sender.send(msg).await.map_err(NodeError::from_send_err)?;
+
+        Ok(())
+    }
+
+    /// Ask the router for the sender of the given next hop
+    async fn resolve_sender(&self, next: Address) -> Result<(Address, Sender<RelayMessage>, bool)> {
+        let (msg, mut reply_rx) = NodeMessage::sender_request(next);
         self.sender
-            .send(req)
+            .send(msg)
             .await
             .map_err(NodeError::from_send_err)?;
-        let (addr, sender, needs_wrapping) = reply_rx
+        reply_rx
             .recv()
             .await
             .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??
-            .take_sender()?;
+            .take_sender()
+    }
+
+    /// Hand an undeliverable message to the dead-letter address
+    ///
+    /// If no dead-letter address is set the original error is returned.
+    pub(crate) async fn send_dead_letter(&self, local_msg: LocalMessage, err: Error) -> Result<()> {
+        let dead_letter = match self.dead_letter_address().await? {
+            Some(addr) => addr,
+            None => return Err(err),
+        };
+        warn!(
+            "Sending undeliverable message to dead-letter address {}: {}",
+            dead_letter, err
+        );
+
+        let (addr, sender, needs_wrapping) = self.resolve_sender(dead_letter.clone()).await?;
+        let body = DeadLetter::new(err.to_string(), local_msg.into_transport_message());
+        let onward = route![dead_letter];
+        let mut transport_msg = TransportMessage::v1(onward.clone(), Route::new(), body.encode()?);
+        transport_msg.return_route.modify().append(self.address());
+        let local_msg = LocalMessage::new(transport_msg, Vec::new());
 
-        // Pack the transport message into a relay message
-        let onward = local_msg.transport().onward_route.clone();
-        // let msg = RelayMessage::direct(addr, data, onward);
         let msg = if needs_wrapping {
             RelayMessage::pre_router(addr, local_msg, onward)
         } else {
             RelayMessage::direct(addr, local_msg, onward)
         };
         sender.send(msg).await.map_err(NodeError::from_send_err)?;
-
         Ok(())
     }
 
@@ -563,7 +1097,7 @@ impl Context {
     /// [`receive`](Self::receive) and
     /// [`receive_timeout`](Self::receive_timeout).
     pub async fn receive_block<M: Message>(&mut self) -> Result<Cancel<'_, M>> {
-        let (msg, data, addr) = self.next_from_mailbox().await?;
+        let (msg, data, addr) = self.next_from_mailbox(|_: &M| true).await?;
         Ok(Cancel::new(msg, data, addr, self))
     }
 
@@ -589,13 +1123,37 @@ impl Context {
         timeout_secs: u64,
     ) -> Result<Cancel<'_, M>> {
         let (msg, data, addr) = timeout(Duration::from_secs(timeout_secs), async {
-            self.next_from_mailbox().await
+            self.next_from_mailbox(|_: &M| true).await
         })
         .await
         .map_err(|e| NodeError::Data.with_elapsed(e))??;
         Ok(Cancel::new(msg, data, addr, self))
     }
 
+    /// Take a typed message from the mailbox without waiting
+    ///
+    /// Returns `None` if no matching message is currently queued.
+    /// Messages of other types are left in the mailbox.
+    pub async fn try_receive<M: Message>(&mut self) -> Result<Option<Cancel<'_, M>>> {
+        let res = self.try_next_from_mailbox(&mut 0).await?;
+        Ok(res.map(|(msg, data, addr)| Cancel::new(msg, data, addr, self)))
+    }
+
+    /// Drain all typed messages currently queued in the mailbox
+    ///
+    /// Unlike [`receive`](Self::receive) this never waits for new
+    /// messages; the returned `Vec` is empty if nothing matching is
+    /// queued.  Messages of other types are left in the mailbox in
+    /// their original order.
+    pub async fn receive_all<M: Message>(&mut self) -> Result<Vec<M>> {
+        let mut msgs = Vec::new();
+        let mut pos = 0;
+        while let Some((msg, _, _)) = self.try_next_from_mailbox(&mut pos).await? {
+            msgs.push(msg)
+        }
+        Ok(msgs)
+    }
+
     /// Block the current worker to wait for a message satisfying a conditional
     ///
     /// Will return `Err` if the corresponding worker has been
@@ -610,16 +1168,7 @@ impl Context {
         F: Fn(&M) -> bool,
     {
         let (m, data, addr) = timeout(Duration::from_secs(DEFAULT_TIMEOUT), async {
-            loop {
-                match self.next_from_mailbox().await {
-                    Ok((m, data, addr)) if check(&m) => break Ok((m, data, addr)),
-                    Ok((_, data, _)) => {
-                        // Requeue
-                        self.forward(data).await?;
-                    }
-                    e => break e,
-                }
-            }
+            self.next_from_mailbox(check).await
         })
         .await
         .map_err(|e| NodeError::Data.with_elapsed(e))??;
@@ -641,9 +1190,31 @@ impl Context {
     /// `ockam.`!**
     ///
     /// Clusters are de-allocated in reverse order of their
-    /// initialisation when the node is stopped.
+    /// initialisation when the node is stopped.  Each cluster is
+    /// given what is left of the node's shutdown timeout to stop, or
+    /// less if a shorter timeout was set via
+    /// [`Context::set_cluster_with_timeout`].
     pub async fn set_cluster<S: Into<String>>(&self, label: S) -> Result<()> {
-        let (msg, mut rx) = NodeMessage::set_cluster(self.address(), label.into());
+        self.set_cluster_impl(label.into(), None).await
+    }
+
+    /// Assign the current worker to a cluster with its own shutdown timeout
+    ///
+    /// See [`Context::set_cluster`].  When the node is stopped
+    /// gracefully, the workers of this cluster are given `timeout` to
+    /// stop, as long as that leaves the node within the timeout passed
+    /// to [`Context::stop_timeout`].  Workers still stopping after that
+    /// are abandoned and the next cluster is stopped.
+    pub async fn set_cluster_with_timeout<S: Into<String>>(
+        &self,
+        label: S,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.set_cluster_impl(label.into(), Some(timeout)).await
+    }
+
+    async fn set_cluster_impl(&self, label: String, timeout: Option<Duration>) -> Result<()> {
+        let (msg, mut rx) = NodeMessage::set_cluster(self.address(), label, timeout);
         self.sender
             .send(msg)
             .await
@@ -654,7 +1225,48 @@ impl Context {
             .is_ok()
     }
 
+    /// Set the address that receives undeliverable messages
+    ///
+    /// Messages whose next hop can't be resolved are wrapped in a
+    /// [`DeadLetter`] and sent to this address, instead of failing
+    /// the send.
+    pub async fn set_dead_letter_address<A: Into<Address>>(&self, addr: A) -> Result<()> {
+        self.set_dead_letter_impl(Some(addr.into())).await
+    }
+
+    /// Stop sending undeliverable messages to a dead-letter address
+    pub async fn clear_dead_letter_address(&self) -> Result<()> {
+        self.set_dead_letter_impl(None).await
+    }
+
+    async fn set_dead_letter_impl(&self, addr: Option<Address>) -> Result<()> {
+        let (msg, mut rx) = NodeMessage::set_dead_letter(addr);
+        self.sender
+            .send(msg)
+            .await
+            .map_err(NodeError::from_send_err)?;
+        rx.recv()
+            .await
+            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??
+            .is_ok()
+    }
+
+    /// Return the address that receives undeliverable messages, if any
+    pub async fn dead_letter_address(&self) -> Result<Option<Address>> {
+        let (msg, mut rx) = NodeMessage::get_dead_letter();
+        self.sender
+            .send(msg)
+            .await
+            .map_err(NodeError::from_send_err)?;
+        rx.recv()
+            .await
+            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??
+            .take_dead_letter()
+    }
+
     /// Return a list of all available worker addresses on a node
+    ///
+    /// Workers that are being stopped are not included.
     pub async fn list_workers(&self) -> Result<Vec<Address>> {
         let (msg, mut reply_rx) = NodeMessage::list_workers();
 
@@ -703,30 +1315,91 @@ impl Context {
     /// to avoid the lifetime collision between the mutation on `self`
     /// and the ref to `Context` passed to `Cancel::new(..)`
     ///
-    /// This function will block and re-queue messages into the
-    /// mailbox until it can receive the correct message payload.
-    ///
-    /// WARNING: this will temporarily create a busyloop, this
-    /// mechanism should be replaced with a waker system that lets the
-    /// mailbox work not yield another message until the relay worker
-    /// has woken it.
-    async fn next_from_mailbox<M: Message>(&mut self) -> Result<(M, LocalMessage, Address)> {
+    /// This function will block until it can receive a message of
+    /// type `M` satisfying `check`.  Other messages are set aside
+    /// along with their parsed value, so each message is parsed as an
+    /// `M` at most once before it is delivered.
+    async fn next_from_mailbox<M, F>(&mut self, check: F) -> Result<(M, LocalMessage, Address)>
+    where
+        M: Message,
+        F: Fn(&M) -> bool,
+    {
+        if let Some(found) = self.take_skipped(&mut 0, &check) {
+            return Ok(found);
+        }
         loop {
             let msg = self
-                .mailbox_next()
+                .mailbox_recv()
                 .await?
                 .ok_or_else(|| NodeError::Data.not_found())?;
-            let (addr, data) = msg.local_msg();
 
-            // FIXME: make message parsing idempotent to avoid cloning
-            match parser::message(&data.transport().payload).ok() {
-                Some(msg) => break Ok((msg, data, addr)),
+            match Self::parse_relay::<M>(&msg) {
+                Some(m) if check(&m) => {
+                    let (addr, data) = msg.local_msg();
+                    break Ok((m, data, addr));
+                }
+                parsed => self.skipped.push_back(SkippedMessage::new(msg, parsed)),
+            }
+        }
+    }
+
+    /// Like [`next_from_mailbox`](Self::next_from_mailbox), but
+    /// without waiting for new messages
+    ///
+    /// Set-aside messages before `pos` have been parsed already and
+    /// are not looked at again.
+    async fn try_next_from_mailbox<M: Message>(
+        &mut self,
+        pos: &mut usize,
+    ) -> Result<Option<(M, LocalMessage, Address)>> {
+        if let Some(found) = self.take_skipped(pos, &|_: &M| true) {
+            return Ok(Some(found));
+        }
+        while let Some(msg) = self.mailbox_try_recv().await? {
+            match Self::parse_relay::<M>(&msg) {
+                Some(m) => {
+                    let (addr, data) = msg.local_msg();
+                    return Ok(Some((m, data, addr)));
+                }
                 None => {
-                    // Requeue
-                    self.forward(data).await?;
+                    self.skipped.push_back(SkippedMessage::new::<M>(msg, None));
+                    *pos = self.skipped.len();
                 }
             }
         }
+        Ok(None)
+    }
+
+    /// Remove the first set-aside message from `pos` onwards that
+    /// parses as `M` and satisfies `check`
+    ///
+    /// `pos` is moved past every message that was looked at and kept.
+    fn take_skipped<M, F>(
+        &mut self,
+        pos: &mut usize,
+        check: &F,
+    ) -> Option<(M, LocalMessage, Address)>
+    where
+        M: Message,
+        F: Fn(&M) -> bool,
+    {
+        while let Some(skipped) = self.skipped.get(*pos) {
+            if skipped.matches(check) {
+                let (msg, m) = self.skipped.remove(*pos)?.into_parts();
+                let (addr, data) = msg.local_msg();
+                return Some((m?, data, addr));
+            }
+            *pos += 1;
+        }
+        None
+    }
+
+    /// Parse the payload of a message addressed to a user worker
+    fn parse_relay<M: Message>(msg: &RelayMessage) -> Option<M> {
+        match &msg.data {
+            RelayPayload::Direct(data) => parser::message(data.transport()).ok(),
+            RelayPayload::PreRouter(..) => None,
+        }
     }
 
     /// Set access control for current context
@@ -744,19 +1417,69 @@ impl Context {
     }
 
     /// Wait for a particular address to become "ready"
+    ///
+    /// This waits for as long as the worker takes to initialise.  Use
+    /// [`wait_for_timeout`](Self::wait_for_timeout) to give up earlier.
     pub async fn wait_for<A: Into<Address>>(&mut self, addr: A) -> Result<()> {
-        let (msg, mut reply) = NodeMessage::get_ready(addr.into());
+        let mut reply = self.request_ready(addr.into()).await?;
+
+        // This call blocks until the address has become ready or is
+        // dropped by the router
+        reply
+            .recv()
+            .await
+            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??;
+        Ok(())
+    }
+
+    /// Wait for a particular address to become "ready" up to a specified timeout
+    ///
+    /// Returns an error of kind [`Timeout`](ockam_core::errcode::Kind::Timeout)
+    /// if the address is not ready after `dur`.
+    pub async fn wait_for_timeout<A: Into<Address>>(
+        &mut self,
+        addr: A,
+        dur: Duration,
+    ) -> Result<()> {
+        let addr = addr.into();
+        let mut reply = self.request_ready(addr.clone()).await?;
+        timeout(dur, reply.recv())
+            .await
+            .map_err(|e| NodeError::Address(addr).with_elapsed(e))?
+            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??;
+        Ok(())
+    }
+
+    /// Ask the router to notify us once `addr` is ready
+    async fn request_ready(&self, addr: Address) -> Result<Receiver<NodeReplyResult>> {
+        let (msg, reply) = NodeMessage::get_ready(addr);
         self.sender
             .send(msg)
             .await
             .map_err(NodeError::from_send_err)?;
+        Ok(reply)
+    }
 
-        // This call blocks until the address has become ready or is
-        // dropped by the router
+    /// Subscribe to the lifecycle transitions of workers on this node
+    ///
+    /// The returned stream yields an event whenever a worker is
+    /// starting, becomes ready, is stopping, or has stopped.  Only
+    /// transitions after this call are observed.  Processors and
+    /// contexts are not reported.
+    ///
+    /// The router never waits for a subscriber, so events are dropped
+    /// if the stream is not consumed fast enough.
+    pub async fn subscribe_lifecycle(&self) -> Result<LifecycleEvents> {
+        let (tx, rx) = channel(32);
+        let (msg, mut reply) = NodeMessage::subscribe_lifecycle(tx);
+        self.sender
+            .send(msg)
+            .await
+            .map_err(NodeError::from_send_err)?;
         reply
             .recv()
             .await
             .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??;
-        Ok(())
+        Ok(LifecycleEvents::new(rx))
     }
 }
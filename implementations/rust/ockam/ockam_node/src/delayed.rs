@@ -1,16 +1,75 @@
 use crate::Context;
 use core::time::Duration;
 use futures::future::{AbortHandle, Abortable};
+use ockam_core::compat::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    vec::Vec,
+};
 use ockam_core::{Address, Message, Result};
 
+/// Delayed events scheduled through a context
+#[derive(Clone)]
+pub(crate) struct DelayedEvents {
+    inner: Arc<Mutex<DelayedEventsInner>>,
+}
+
+impl Default for DelayedEvents {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(DelayedEventsInner::default())),
+        }
+    }
+}
+
+#[derive(Default)]
+struct DelayedEventsInner {
+    next_id: u64,
+    /// Destination and abort handle of every pending event
+    pending: BTreeMap<u64, (Address, AbortHandle)>,
+}
+
+impl DelayedEvents {
+    fn insert(&self, destination: Address, handle: AbortHandle) -> u64 {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.pending.insert(id, (destination, handle));
+        id
+    }
+
+    fn remove(&self, id: u64) {
+        self.inner.lock().unwrap().pending.remove(&id);
+    }
+
+    /// Destinations of all pending events
+    pub(crate) fn destinations(&self) -> Vec<Address> {
+        let inner = self.inner.lock().unwrap();
+        inner.pending.values().map(|(a, _)| a.clone()).collect()
+    }
+
+    /// Abort all pending events
+    pub(crate) fn cancel_all(&self) {
+        let pending = core::mem::take(&mut self.inner.lock().unwrap().pending);
+        for (_, (_, handle)) in pending {
+            handle.abort()
+        }
+    }
+}
+
 /// Allow to send message to destination address periodically after some delay
 /// Only one scheduled heartbeat allowed at a time
 /// Dropping this handle cancels scheduled heartbeat
+///
+/// Scheduled heartbeats are also cancelled when the worker or
+/// processor owning the context the heartbeat was created with stops.
+/// See [`Context::list_delayed`] and [`Context::cancel_all_delayed`].
 pub struct DelayedEvent<M: Message + Clone> {
     ctx: Context,
     destination_addr: Address,
     msg: M,
-    abort_handle: Option<AbortHandle>,
+    abort_handle: Option<(u64, AbortHandle)>,
+    registry: DelayedEvents,
 }
 
 impl<M: Message + Clone> Drop for DelayedEvent<M> {
@@ -21,6 +80,10 @@ impl<M: Message + Clone> Drop for DelayedEvent<M> {
 
 impl<M: Message + Clone> DelayedEvent<M> {
     /// Create a heartbeat
+    ///
+    /// The heartbeat is cancelled when the worker or processor owning
+    /// `ctx` stops.  A worker should thus create its heartbeats with
+    /// its own context, e.g. in `initialize`.
     pub async fn create(
         ctx: &Context,
         destination_addr: impl Into<Address>,
@@ -33,6 +96,7 @@ impl<M: Message + Clone> DelayedEvent<M> {
             destination_addr: destination_addr.into(),
             abort_handle: None,
             msg,
+            registry: ctx.delayed_events(),
         };
 
         Ok(heartbeat)
@@ -42,8 +106,9 @@ impl<M: Message + Clone> DelayedEvent<M> {
 impl<M: Message + Clone> DelayedEvent<M> {
     /// Cancel heartbeat
     pub fn cancel(&mut self) {
-        if let Some(handle) = self.abort_handle.take() {
-            handle.abort()
+        if let Some((id, handle)) = self.abort_handle.take() {
+            handle.abort();
+            self.registry.remove(id);
         }
     }
 
@@ -56,9 +121,14 @@ impl<M: Message + Clone> DelayedEvent<M> {
         let msg = self.msg.clone();
 
         let (handle, reg) = AbortHandle::new_pair();
+        let id = self
+            .registry
+            .insert(destination_addr.clone(), handle.clone());
+        let registry = self.registry.clone();
         let future = Abortable::new(
             async move {
                 child_ctx.sleep(duration).await;
+                registry.remove(id);
 
                 let res = child_ctx.send(destination_addr.clone(), msg).await;
 
@@ -71,7 +141,7 @@ impl<M: Message + Clone> DelayedEvent<M> {
             reg,
         );
 
-        self.abort_handle = Some(handle);
+        self.abort_handle = Some((id, handle));
         self.ctx.runtime().spawn(future);
 
         Ok(())
@@ -83,7 +153,12 @@ mod tests {
     use crate::{start_node, Context, DelayedEvent};
     use core::sync::atomic::Ordering;
     use core::time::Duration;
-    use ockam_core::compat::{boxed::Box, string::ToString, sync::Arc};
+    use ockam_core::compat::{
+        boxed::Box,
+        string::{String, ToString},
+        sync::Arc,
+        vec::Vec,
+    };
     use ockam_core::{async_trait, Any};
     use ockam_core::{Result, Routed, Worker};
     use std::sync::atomic::AtomicI8;
@@ -272,4 +347,101 @@ mod tests {
 
         Ok(())
     }
+
+    struct SchedulingWorker {
+        events: Arc<std::sync::Mutex<Vec<DelayedEvent<String>>>>,
+    }
+
+    #[async_trait]
+    impl Worker for SchedulingWorker {
+        type Context = Context;
+        type Message = String;
+
+        async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+            let mut events = Vec::new();
+            for _ in 0..2 {
+                let mut event =
+                    DelayedEvent::create(ctx, "counting_worker", "Hello".to_string()).await?;
+                event.schedule(Duration::from_millis(100)).await?;
+                events.push(event);
+            }
+            self.events.lock().unwrap().extend(events);
+            Ok(())
+        }
+
+        async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+            let pending: Vec<String> = ctx.list_delayed().iter().map(|a| a.to_string()).collect();
+            ctx.send(msg.return_route(), pending.join(",")).await
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn stop_context__starting_context__keeps_worker_events() -> Result<()> {
+        let (mut ctx, mut executor) = start_node();
+        executor
+            .execute(async move {
+                let msgs_count = Arc::new(AtomicI8::new(0));
+                let worker = CountingWorker {
+                    msgs_count: msgs_count.clone(),
+                };
+                ctx.start_worker("counting_worker", worker).await.unwrap();
+
+                let creator = ctx.new_context("creator").await.unwrap();
+                let worker = SchedulingWorker {
+                    events: Arc::new(std::sync::Mutex::new(Vec::new())),
+                };
+                creator
+                    .start_worker("scheduling_worker", worker)
+                    .await
+                    .unwrap();
+                ctx.stop_worker("creator").await.unwrap();
+                sleep(Duration::from_millis(150)).await;
+
+                assert_eq!(2, msgs_count.load(Ordering::Relaxed));
+
+                ctx.stop().await.unwrap();
+            })
+            .unwrap();
+
+        Ok(())
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn stop_worker__scheduling_worker__aborts_its_events() -> Result<()> {
+        let (mut ctx, mut executor) = start_node();
+        executor
+            .execute(async move {
+                let msgs_count = Arc::new(AtomicI8::new(0));
+                let worker = CountingWorker {
+                    msgs_count: msgs_count.clone(),
+                };
+                ctx.start_worker("counting_worker", worker).await.unwrap();
+
+                // Keep the events alive past the worker
+                let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+                let worker = SchedulingWorker {
+                    events: events.clone(),
+                };
+                ctx.start_worker("scheduling_worker", worker).await.unwrap();
+
+                ctx.send("scheduling_worker", "list".to_string())
+                    .await
+                    .unwrap();
+                let pending = ctx.receive::<String>().await.unwrap().take().body();
+                assert_eq!(pending, "0#counting_worker,0#counting_worker");
+
+                ctx.stop_worker("scheduling_worker").await.unwrap();
+                sleep(Duration::from_millis(150)).await;
+
+                assert_eq!(0, msgs_count.load(Ordering::Relaxed));
+                assert_eq!(events.lock().unwrap().len(), 2);
+
+                ctx.stop().await.unwrap();
+            })
+            .unwrap();
+
+        Ok(())
+    }
 }
@@ -14,6 +14,8 @@ pub enum NodeError {
     ///
     /// An address either refers to a Worker or a Processor
     Address(Address),
+    /// An address is already used by another worker or processor
+    AddressAlreadyInUse(Address),
     /// Sending a message to a recipient failed
     Recipient(Route),
     /// A data retrieval operation failed
@@ -68,6 +70,7 @@ impl fmt::Display for NodeError {
             "{}",
             match self {
                 Self::Address(addr) => format!("operation failed for address {}", addr),
+                Self::AddressAlreadyInUse(addr) => format!("address {} is already in use", addr),
                 Self::Recipient(route) => format!("operation failed for recipient {}", route),
                 Self::Data => "failed to load data".into(),
                 Self::NodeState(reason) => format!("failed because node state: {}", reason),
@@ -37,6 +37,7 @@ mod context;
 mod delayed;
 mod error;
 mod executor;
+mod lifecycle;
 mod messages;
 mod node;
 mod parser;
@@ -48,6 +49,7 @@ pub use context::*;
 pub use delayed::*;
 pub use error::*;
 pub use executor::*;
+pub use lifecycle::*;
 pub use messages::*;
 
 pub use node::{start_node, NullWorker};
@@ -0,0 +1,64 @@
+//! Worker lifecycle notifications
+
+use crate::tokio::sync::mpsc::Receiver;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures::Stream;
+use ockam_core::Address;
+
+/// A stage in the lifecycle of a worker
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifecycleState {
+    /// The worker was registered and is running its `initialize` hook
+    Starting,
+    /// The worker has been initialised and handles messages
+    Ready,
+    /// The worker was asked to stop and is running its `shutdown` hook
+    Stopping,
+    /// The worker has stopped
+    Stopped,
+}
+
+/// A lifecycle transition of a single worker
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LifecycleEvent {
+    address: Address,
+    state: LifecycleState,
+}
+
+impl LifecycleEvent {
+    pub(crate) fn new(address: Address, state: LifecycleState) -> Self {
+        Self { address, state }
+    }
+
+    /// The primary address of the worker
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// The state the worker has entered
+    pub fn state(&self) -> LifecycleState {
+        self.state
+    }
+}
+
+/// A stream of worker lifecycle events
+///
+/// Created by [`Context::subscribe_lifecycle`](crate::Context::subscribe_lifecycle).
+pub struct LifecycleEvents {
+    rx: Receiver<LifecycleEvent>,
+}
+
+impl LifecycleEvents {
+    pub(crate) fn new(rx: Receiver<LifecycleEvent>) -> Self {
+        Self { rx }
+    }
+}
+
+impl Stream for LifecycleEvents {
+    type Item = LifecycleEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
@@ -3,11 +3,16 @@ use crate::{
     error::{NodeError, NodeReason, RouterReason, WorkerReason},
     relay::RelayMessage,
     router::SenderPair,
+    LifecycleEvent,
 };
 use core::fmt;
+use core::time::Duration;
 use ockam_core::compat::{string::String, vec::Vec};
 use ockam_core::{Address, AddressSet, Error, Result, TransportType};
 
+/// Sender for the state a stopped worker hands to its replacement
+pub type StateSender = Sender<Option<Vec<u8>>>;
+
 /// Messages sent from the Node to the Executor
 #[derive(Debug)]
 pub enum NodeMessage {
@@ -15,6 +20,8 @@ pub enum NodeMessage {
     StartWorker {
         /// The set of addresses in use by this worker
         addrs: AddressSet,
+        /// Addresses whose messages use the priority mailbox lane
+        priority: Vec<Address>,
         /// Pair of senders to the worker relay (msgs and ctrl)
         senders: SenderPair,
         /// A bare worker runs no relay state
@@ -24,10 +31,13 @@ pub enum NodeMessage {
     },
     /// Return a list of all worker addresses
     ListWorkers(Sender<NodeReplyResult>),
-    /// Add an existing address to a cluster
-    SetCluster(Address, String, Sender<NodeReplyResult>),
-    /// Stop an existing worker
-    StopWorker(Address, Sender<NodeReplyResult>),
+    /// Add an existing address to a cluster, optionally setting the
+    /// cluster's shutdown timeout
+    SetCluster(Address, String, Option<Duration>, Sender<NodeReplyResult>),
+    /// Stop an existing worker, optionally asking for its saved state
+    StopWorker(Address, Option<StateSender>, Sender<NodeReplyResult>),
+    /// Stop an existing worker and reply once its shutdown has run
+    StopWorkerAndWait(Address, Sender<NodeReplyResult>),
     /// Start a new processor
     StartProcessor(Address, SenderPair, Sender<NodeReplyResult>),
     /// Stop an existing processor
@@ -36,6 +46,8 @@ pub enum NodeMessage {
     StopNode(ShutdownType, Sender<NodeReplyResult>),
     /// Immediately stop the node runtime
     AbortNode,
+    /// The given phase of a graceful shutdown has timed out
+    ShutdownTimeout(usize),
     /// Let the router know a particular address has stopped
     StopAck(Address),
     /// Request the sender for a worker address
@@ -46,6 +58,12 @@ pub enum NodeMessage {
     SetReady(Address),
     /// Check whether an address has been marked as "ready"
     CheckReady(Address, Sender<NodeReplyResult>),
+    /// Set (or clear) the address for undeliverable messages
+    SetDeadLetter(Option<Address>, Sender<NodeReplyResult>),
+    /// Request the address for undeliverable messages
+    GetDeadLetter(Sender<NodeReplyResult>),
+    /// Subscribe to worker lifecycle events
+    SubscribeLifecycle(Sender<LifecycleEvent>, Sender<NodeReplyResult>),
 }
 
 impl fmt::Display for NodeMessage {
@@ -53,17 +71,22 @@ impl fmt::Display for NodeMessage {
         match self {
             NodeMessage::StartWorker { .. } => write!(f, "StartWorker"),
             NodeMessage::ListWorkers(_) => write!(f, "ListWorkers"),
-            NodeMessage::SetCluster(_, _, _) => write!(f, "SetCluster"),
-            NodeMessage::StopWorker(_, _) => write!(f, "StopWorker"),
+            NodeMessage::SetCluster(_, _, _, _) => write!(f, "SetCluster"),
+            NodeMessage::StopWorker(_, _, _) => write!(f, "StopWorker"),
+            NodeMessage::StopWorkerAndWait(_, _) => write!(f, "StopWorkerAndWait"),
             NodeMessage::StartProcessor(_, _, _) => write!(f, "StartProcessor"),
             NodeMessage::StopProcessor(_, _) => write!(f, "StopProcessor"),
             NodeMessage::StopNode(_, _) => write!(f, "StopNode"),
             NodeMessage::AbortNode => write!(f, "AbortNode"),
+            NodeMessage::ShutdownTimeout(_) => write!(f, "ShutdownTimeout"),
             NodeMessage::StopAck(_) => write!(f, "StopAck"),
             NodeMessage::SenderReq(_, _) => write!(f, "SenderReq"),
             NodeMessage::Router(_, _, _) => write!(f, "Router"),
             NodeMessage::SetReady(_) => write!(f, "SetReady"),
             NodeMessage::CheckReady(_, _) => write!(f, "CheckReady"),
+            NodeMessage::SetDeadLetter(_, _) => write!(f, "SetDeadLetter"),
+            NodeMessage::GetDeadLetter(_) => write!(f, "GetDeadLetter"),
+            NodeMessage::SubscribeLifecycle(_, _) => write!(f, "SubscribeLifecycle"),
         }
     }
 }
@@ -71,6 +94,9 @@ impl fmt::Display for NodeMessage {
 impl NodeMessage {
     /// Create a start worker message
     ///
+    /// * `priority`: addresses (from `addrs`) whose messages are
+    ///   delivered via the relay's priority lane
+    ///
     /// * `senders`: message and command senders for the relay
     ///
     /// * `bare`: indicate whether this worker address has a full
@@ -79,6 +105,7 @@ impl NodeMessage {
     ///   router
     pub fn start_worker(
         addrs: AddressSet,
+        priority: Vec<Address>,
         senders: SenderPair,
         bare: bool,
     ) -> (Self, Receiver<NodeReplyResult>) {
@@ -86,6 +113,7 @@ impl NodeMessage {
         (
             Self::StartWorker {
                 addrs,
+                priority,
                 senders,
                 bare,
                 reply,
@@ -116,15 +144,36 @@ impl NodeMessage {
     }
 
     /// Create a set cluster message and reply receiver
-    pub fn set_cluster(addr: Address, label: String) -> (Self, Receiver<NodeReplyResult>) {
+    pub fn set_cluster(
+        addr: Address,
+        label: String,
+        timeout: Option<Duration>,
+    ) -> (Self, Receiver<NodeReplyResult>) {
         let (tx, rx) = channel(1);
-        (Self::SetCluster(addr, label, tx), rx)
+        (Self::SetCluster(addr, label, timeout, tx), rx)
     }
 
     /// Create a stop worker message and reply receiver
     pub fn stop_worker(address: Address) -> (Self, Receiver<NodeReplyResult>) {
         let (tx, rx) = channel(1);
-        (Self::StopWorker(address, tx), rx)
+        (Self::StopWorker(address, None, tx), rx)
+    }
+
+    /// Create a stop worker message and reply receiver, which gets a
+    /// reply only once the worker has shut down
+    pub fn stop_worker_and_wait(address: Address) -> (Self, Receiver<NodeReplyResult>) {
+        let (tx, rx) = channel(1);
+        (Self::StopWorkerAndWait(address, tx), rx)
+    }
+
+    /// Create a stop worker message, reply receiver, and a receiver
+    /// for the worker's saved state
+    pub fn stop_worker_saving_state(
+        address: Address,
+    ) -> (Self, Receiver<NodeReplyResult>, Receiver<Option<Vec<u8>>>) {
+        let (tx, rx) = channel(1);
+        let (state_tx, state_rx) = channel(1);
+        (Self::StopWorker(address, Some(state_tx), tx), rx, state_rx)
     }
 
     /// Create a stop node message
@@ -149,6 +198,26 @@ impl NodeMessage {
         let (tx, rx) = channel(1);
         (Self::CheckReady(addr, tx), rx)
     }
+
+    /// Create a SetDeadLetter message and reply receiver
+    pub fn set_dead_letter(addr: Option<Address>) -> (Self, Receiver<NodeReplyResult>) {
+        let (tx, rx) = channel(1);
+        (Self::SetDeadLetter(addr, tx), rx)
+    }
+
+    /// Create a GetDeadLetter message and reply receiver
+    pub fn get_dead_letter() -> (Self, Receiver<NodeReplyResult>) {
+        let (tx, rx) = channel(1);
+        (Self::GetDeadLetter(tx), rx)
+    }
+
+    /// Create a SubscribeLifecycle message and reply receiver
+    pub fn subscribe_lifecycle(
+        events: Sender<LifecycleEvent>,
+    ) -> (Self, Receiver<NodeReplyResult>) {
+        let (tx, rx) = channel(1);
+        (Self::SubscribeLifecycle(events, tx), rx)
+    }
 }
 
 /// The reply/result of a Node
@@ -173,6 +242,8 @@ pub enum RouterReply {
     },
     /// Indicate the 'ready' state of an address
     State(bool),
+    /// The address for undeliverable messages, if any
+    DeadLetter(Option<Address>),
 }
 
 /// Specify the type of node shutdown
@@ -229,14 +300,19 @@ impl RouterReply {
         Ok(RouterReply::State(b))
     }
 
+    /// Return [NodeReply::DeadLetter]
+    pub fn dead_letter(a: Option<Address>) -> NodeReplyResult {
+        Ok(RouterReply::DeadLetter(a))
+    }
+
     /// Return [NodeError::NoSuchAddress]
     pub fn no_such_address(a: Address) -> NodeReplyResult {
         Err(NodeError::Address(a).not_found())
     }
 
-    /// Return [NodeError::WorkerExists] for the given address
+    /// Return [NodeError::AddressAlreadyInUse] for the given address
     pub fn worker_exists(a: Address) -> NodeReplyResult {
-        Err(NodeError::Address(a).already_exists())
+        Err(NodeError::AddressAlreadyInUse(a).already_exists())
     }
 
     /// Return [NodeError::RouterExists]
@@ -288,6 +364,14 @@ impl RouterReply {
         }
     }
 
+    /// Consume the wrapper and return [NodeReply::DeadLetter]
+    pub fn take_dead_letter(self) -> Result<Option<Address>> {
+        match self {
+            Self::DeadLetter(a) => Ok(a),
+            _ => Err(NodeError::NodeState(NodeReason::Unknown).internal()),
+        }
+    }
+
     /// Returns Ok if self is [NodeReply::Ok]
     pub fn is_ok(self) -> Result<()> {
         match self {
@@ -1,10 +1,12 @@
-use ockam_core::{Message, Result};
+use ockam_core::{Message, Result, TransportMessage};
 
 // TODO: this function can not mutate the data vector it is given, and
 // thus copies its contents when using the fallback parsing strategy.
 // Ideally fallback parsing could create a slice between the length
 // and a vector without needing to move any data.
-pub(crate) fn message<M: Message>(vec: &[u8]) -> Result<M> {
+pub(crate) fn message<M: Message>(msg: &TransportMessage) -> Result<M> {
+    M::check_version(msg.version)?;
+    let vec = &msg.payload;
     M::decode(vec).or_else(|_| {
         trace!("Parsing payload without inner length...");
 
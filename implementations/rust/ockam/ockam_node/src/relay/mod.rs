@@ -1,5 +1,7 @@
+use crate::StateSender;
+use core::sync::atomic::{AtomicBool, Ordering};
 use ockam_core::compat::vec::Vec;
-use ockam_core::{Address, Encodable, LocalMessage, Route};
+use ockam_core::{Address, Encodable, LocalMessage, Route, ShutdownReason};
 
 mod processor_relay;
 mod worker_relay;
@@ -7,6 +9,58 @@ mod worker_relay;
 pub use processor_relay::*;
 pub use worker_relay::*;
 
+/// Stop state shared between a processor's context and its relay
+#[derive(Debug, Default)]
+pub struct StopRequest {
+    /// The relay was told to stop the processor
+    requested: AtomicBool,
+    /// The processor checks for stop requests itself
+    cooperative: AtomicBool,
+    /// Wakes processors waiting for a stop request
+    #[cfg(feature = "std")]
+    notify: tokio::sync::Notify,
+}
+
+impl StopRequest {
+    /// Ask the processor to stop
+    #[cfg(feature = "std")]
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::Release);
+        self.notify.notify_waiters();
+    }
+
+    /// Wait for a stop request on behalf of the processor
+    #[cfg(feature = "std")]
+    pub async fn requested(&self) {
+        self.cooperative.store(true, Ordering::Relaxed);
+        loop {
+            // Register before checking, so a request in between is not missed
+            let notified = self.notify.notified();
+            if self.is_requested() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Whether the processor was asked to stop
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::Acquire)
+    }
+
+    /// Check for a stop request on behalf of the processor
+    pub fn poll(&self) -> bool {
+        self.cooperative.store(true, Ordering::Relaxed);
+        self.is_requested()
+    }
+
+    /// Whether the processor ever checked for a stop request
+    #[cfg(feature = "std")]
+    pub fn is_cooperative(&self) -> bool {
+        self.cooperative.load(Ordering::Relaxed)
+    }
+}
+
 /// A message addressed to a relay
 #[derive(Clone, Debug)]
 pub struct RelayMessage {
@@ -62,4 +116,10 @@ pub enum CtrlSignal {
     Interrupt,
     /// Interrupt current message execution and shut down
     InterruptStop,
+    /// Tell a worker why it is about to be stopped
+    ///
+    /// The worker keeps draining its mailbox until it is closed.  If
+    /// a state sender is given, the worker's saved state is sent to
+    /// it after the worker was shut down.
+    Shutdown(ShutdownReason, Option<StateSender>),
 }
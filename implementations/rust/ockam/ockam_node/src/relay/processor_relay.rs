@@ -1,5 +1,9 @@
 use crate::tokio::sync::mpsc::Receiver;
+#[cfg(feature = "std")]
+use crate::PROCESSOR_STOP_TIMEOUT;
 use crate::{relay::CtrlSignal, tokio::runtime::Runtime, Context};
+#[cfg(feature = "std")]
+use core::time::Duration;
 use ockam_core::{Processor, Result};
 
 pub struct ProcessorRelay<P>
@@ -40,6 +44,8 @@ where
             error!("Failed to mark processor '{}' as 'ready': {}", ctx_addr, e);
         }
 
+        let stop = ctx.stop_request();
+
         // This future encodes the main processor run loop logic
         let run_loop = async {
             loop {
@@ -47,7 +53,7 @@ where
                 crate::tokio::task::yield_now().await;
 
                 let should_continue = processor.process(&mut ctx).await?;
-                if !should_continue {
+                if !should_continue || stop.is_requested() {
                     break;
                 }
             }
@@ -57,6 +63,8 @@ where
 
         #[cfg(feature = "std")]
         {
+            tokio::pin!(run_loop);
+
             // This future resolves when a stop control signal is received
             let shutdown_signal = async { ctrl_rx.recv().await };
 
@@ -64,8 +72,18 @@ where
             tokio::select! {
                 _ = shutdown_signal => {
                     debug!("Shutting down processor {}", ctx_addr);
+                    stop.request();
+
+                    // Give processors that check for stop requests
+                    // the chance to finish their current iteration
+                    if stop.is_cooperative() {
+                        let deadline = Duration::from_secs(PROCESSOR_STOP_TIMEOUT);
+                        if tokio::time::timeout(deadline, &mut run_loop).await.is_err() {
+                            warn!("Processor {} did not stop in time; stopping it", ctx_addr);
+                        }
+                    }
                 },
-                _ = run_loop => {}
+                _ = &mut run_loop => {}
             };
         }
 
@@ -84,6 +102,9 @@ where
             }
         }
 
+        // Timers scheduled by the processor must not outlive it
+        ctx.cancel_all_delayed();
+
         // Finally send the router a stop ACK -- log errors
         trace!("Sending shutdown ACK");
         if let Err(e) = ctx.send_stop_ack().await {
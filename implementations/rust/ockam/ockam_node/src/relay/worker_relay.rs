@@ -1,9 +1,14 @@
 use crate::relay::{CtrlSignal, RelayMessage, RelayPayload};
 use crate::tokio::{runtime::Runtime, sync::mpsc::Receiver};
-use crate::{parser, Context};
+use crate::{parser, Context, StateSender};
 use core::marker::PhantomData;
-use ockam_core::compat::vec::Vec;
-use ockam_core::{Address, LocalMessage, Message, Result, Route, Routed, TransportMessage, Worker};
+use ockam_core::compat::{format, vec::Vec};
+use ockam_core::{
+    errcode::{Kind, Origin},
+    Address, Error, LocalMessage, Message, Result, Route, Routed, ShutdownReason, TransportMessage,
+    Worker,
+};
+use tracing::Instrument;
 
 /// Worker relay machinery
 ///
@@ -17,6 +22,7 @@ where
 {
     worker: W,
     ctx: Context,
+    state: Option<Vec<u8>>,
     _phantom: PhantomData<M>,
 }
 
@@ -25,29 +31,42 @@ where
     W: Worker<Context = Context, Message = M>,
     M: Message + Send + 'static,
 {
-    pub fn new(worker: W, ctx: Context) -> Self {
+    pub fn new(worker: W, ctx: Context, state: Option<Vec<u8>>) -> Self {
         Self {
             worker,
             ctx,
+            state,
             _phantom: PhantomData,
         }
     }
 
     /// Convenience function to handle an incoming direct message
     #[inline]
-    fn handle_direct(msg: &LocalMessage, msg_addr: Address) -> Result<(M, Route)> {
-        let TransportMessage {
-            ref payload,
-            ref return_route,
-            ..
-        } = msg.transport();
-
-        parser::message::<M>(payload)
-            .map_err(|e| {
-                error!("Failed to decode message payload for worker {}", msg_addr);
-                e
-            })
-            .map(|m| (m, return_route.clone()))
+    fn handle_direct(msg: &LocalMessage) -> Result<(M, Route)> {
+        let transport = msg.transport();
+        parser::message::<M>(transport).map(|m| (m, transport.return_route.clone()))
+    }
+
+    /// Report a message the worker can't decode in strict mode
+    async fn reject_undecodable(
+        ctx: &Context,
+        msg_addr: Address,
+        msg: LocalMessage,
+        e: Error,
+    ) -> Result<()> {
+        let payload = &msg.transport().payload;
+        warn!(
+            "Worker {} can't decode message: {} (first bytes: {:02x?})",
+            msg_addr,
+            e,
+            &payload[..payload.len().min(16)]
+        );
+        let reason = Error::new(
+            Origin::Node,
+            Kind::Serialization,
+            format!("worker {} can't decode message: {}", msg_addr, e),
+        );
+        ctx.send_dead_letter(msg, reason).await
     }
 
     #[inline]
@@ -67,8 +86,8 @@ where
     /// Report errors as they occur, and signal whether the loop should
     /// continue running or not
     async fn recv_message(&mut self) -> Result<bool> {
-        let RelayMessage { addr, data, .. } = match self.ctx.mailbox_next().await? {
-            Some(msg) => msg,
+        let (RelayMessage { addr, data, .. }, parsed) = match self.ctx.mailbox_next().await? {
+            Some(next) => next,
             None => {
                 trace!("No more messages for worker {}", self.ctx.address());
                 return Ok(false);
@@ -79,24 +98,29 @@ where
         // wrap state.  Messages addressed to a router will be of
         // type `RouterMessage`, while generic userspace workers
         // can provide any type they want.
-        let (msg, _, local_msg) = (|data| -> Result<(M, Route, LocalMessage)> {
-            Ok(match data {
-                RelayPayload::Direct(local_msg) => Self::handle_direct(&local_msg, addr.clone())
-                    .map(|(msg, r)| (msg, r, local_msg))?,
-                RelayPayload::PreRouter(enc_msg, route) => {
-                    Self::handle_pre_router(&enc_msg, addr.clone()).map(|m| {
-                        (
-                            m,
-                            route.clone(),
-                            LocalMessage::new(
-                                TransportMessage::v1(Route::new(), route, enc_msg),
-                                Vec::new(),
-                            ),
-                        )
-                    })?
+        let (msg, local_msg) = match (data, parsed) {
+            // Parsed already while it was set aside by a receive call
+            (RelayPayload::Direct(local_msg), Some(msg)) => (msg, local_msg),
+            (RelayPayload::Direct(local_msg), None) => match Self::handle_direct(&local_msg) {
+                Ok((msg, _)) => (msg, local_msg),
+                Err(e) if self.ctx.strict_decoding() => {
+                    Self::reject_undecodable(&self.ctx, addr, local_msg, e).await?;
+                    return Ok(true);
                 }
-            })
-        })(data)?;
+                Err(e) => {
+                    error!("Failed to decode message payload for worker {}", addr);
+                    return Err(e);
+                }
+            },
+            (RelayPayload::PreRouter(enc_msg, route), _) => {
+                let msg = Self::handle_pre_router(&enc_msg, addr.clone())?;
+                let local_msg = LocalMessage::new(
+                    TransportMessage::v1(Route::new(), route, enc_msg),
+                    Vec::new(),
+                );
+                (msg, local_msg)
+            }
+        };
 
         // Wrap the user message in a `Routed` to provide return
         // route information via a composition side-channel
@@ -112,7 +136,12 @@ where
     #[cfg_attr(not(feature = "std"), allow(unused_mut))]
     #[cfg_attr(not(feature = "std"), allow(unused_variables))]
     async fn run(mut self, mut ctrl_rx: Receiver<CtrlSignal>) {
-        match self.worker.initialize(&mut self.ctx).await {
+        let state = self.state.take();
+        match self
+            .worker
+            .initialize_with_state(&mut self.ctx, state)
+            .await
+        {
             Ok(()) => {}
             Err(e) => {
                 error!(
@@ -129,30 +158,60 @@ where
             error!("Failed to mark worker '{}' as 'ready': {}", address, e);
         }
 
+        #[cfg_attr(not(feature = "std"), allow(unused_mut))]
+        let mut reason = None;
+        #[cfg_attr(not(feature = "std"), allow(unused_mut))]
+        let mut state_reply: Option<StateSender> = None;
+
         #[cfg(feature = "std")]
         loop {
-            let _ = crate::tokio::select! {
-                result = self.recv_message() => {
-                    match result {
-                        // Successful message handling -- keep running
-                        Ok(true) => {},
-                        // Successful message handling -- stop now
-                        Ok(false) => {
-                            break;
-                        },
-                        // An error occurred -- log and continue
-                        Err(e) => error!("Error encountered during '{}' message handling: {}", address, e),
+            // A shutdown reason must not interrupt the message that
+            // is currently being handled, so keep polling the same
+            // future until it completes or we are told to stop
+            let recv = self.recv_message();
+            crate::tokio::pin!(recv);
+            let result = loop {
+                crate::tokio::select! {
+                    result = &mut recv => break Some(result),
+                    signal = ctrl_rx.recv() => match signal {
+                        // Remember why we are stopping and drain the mailbox
+                        Some(CtrlSignal::Shutdown(r, s)) => {
+                            reason = Some(r);
+                            state_reply = s;
+                        }
+                        Some(_) => {
+                            debug!("Relay received shutdown signal, terminating!");
+                            break None;
+                        }
+                        // The router dropped our record -- stop immediately
+                        None => {
+                            debug!("Relay control channel closed, terminating!");
+                            break None;
+                        }
                     }
-                },
-                result = ctrl_rx.recv() => {
-                    if result.is_some() {
-                        debug!("Relay received shutdown signal, terminating!");
-                        break;
-                    }
-
-                    // We are stopping
                 }
             };
+
+            match result {
+                // Successful message handling -- keep running
+                Some(Ok(true)) => {}
+                // Successful message handling -- stop now
+                Some(Ok(false)) | None => break,
+                // An error occurred -- log and continue
+                Some(Err(e)) => error!(
+                    "Error encountered during '{}' message handling: {}",
+                    address, e
+                ),
+            }
+        }
+
+        // The mailbox may have closed before the reason was picked up
+        #[cfg(feature = "std")]
+        if reason.is_none() {
+            if let Ok(CtrlSignal::Shutdown(r, s)) = ctrl_rx.try_recv() {
+                reason = Some(r);
+                state_reply = s;
+            }
         }
         #[cfg(not(feature = "std"))]
         loop {
@@ -172,7 +231,12 @@ where
         }
 
         // Run the shutdown hook for this worker
-        match self.worker.shutdown(&mut self.ctx).await {
+        let reason = reason.unwrap_or(ShutdownReason::Unknown);
+        match self
+            .worker
+            .shutdown_with_reason(&mut self.ctx, reason)
+            .await
+        {
             Ok(()) => {}
             Err(e) => {
                 error!(
@@ -183,6 +247,16 @@ where
             }
         }
 
+        // Timers scheduled by the worker must not outlive it
+        self.ctx.cancel_all_delayed();
+
+        // Hand the worker's state to whoever asked for it
+        if let Some(reply) = state_reply {
+            if reply.send(self.worker.save_state()).await.is_err() {
+                debug!("Nobody is waiting for the state of '{}'", address);
+            }
+        }
+
         // Finally send the router a stop ACK -- log errors
         trace!("Sending shutdown ACK");
         if let Err(e) = self.ctx.send_stop_ack().await {
@@ -191,8 +265,17 @@ where
     }
 
     /// Build and spawn a new worker relay, returning a send handle to it
-    pub(crate) fn init(rt: &Runtime, worker: W, ctx: Context, ctrl_rx: Receiver<CtrlSignal>) {
-        let relay = WorkerRelay::<W, M>::new(worker, ctx);
-        rt.spawn(relay.run(ctrl_rx));
+    pub(crate) fn init(
+        rt: &Runtime,
+        worker: W,
+        ctx: Context,
+        state: Option<Vec<u8>>,
+        ctrl_rx: Receiver<CtrlSignal>,
+    ) {
+        // Everything the worker logs carries its address, so that
+        // its events can be filtered with e.g. `[worker{address=..}]`
+        let span = debug_span!("worker", address = %ctx.address());
+        let relay = WorkerRelay::<W, M>::new(worker, ctx, state);
+        rt.spawn(relay.run(ctrl_rx).instrument(span));
     }
 }
@@ -10,19 +10,22 @@ mod utils;
 use record::{AddressMeta, AddressRecord, InternalMap};
 use state::{NodeState, RouterState};
 
-use crate::tokio::sync::mpsc::{channel, Receiver, Sender};
+use crate::tokio::sync::mpsc::{channel, error::TrySendError, Receiver, Sender};
 use crate::{
     error::{NodeError, NodeReason},
     relay::{CtrlSignal, RelayMessage},
-    NodeMessage, RouterReply, ShutdownType,
+    LifecycleEvent, LifecycleState, NodeMessage, RouterReply, ShutdownType,
 };
-use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::{collections::BTreeMap, vec::Vec};
 use ockam_core::{Address, Result, TransportType};
 
 /// A pair of senders to a worker relay
+///
+/// The `prio` sender feeds the relay's high-priority mailbox lane.
 #[derive(Debug)]
 pub struct SenderPair {
     pub msgs: Sender<RelayMessage>,
+    pub prio: Sender<RelayMessage>,
     pub ctrl: Sender<CtrlSignal>,
 }
 
@@ -42,6 +45,10 @@ pub struct Router {
     map: InternalMap,
     /// Externally registered router components
     external: BTreeMap<TransportType, Address>,
+    /// Where to send messages that can't be delivered
+    dead_letter: Option<Address>,
+    /// Subscribers to worker lifecycle events
+    lifecycle: Vec<Sender<LifecycleEvent>>,
     /// Receiver for messages from node
     receiver: Receiver<NodeMessage>,
 }
@@ -66,6 +73,8 @@ impl Router {
             state: RouterState::new(sender),
             map: InternalMap::default(),
             external: BTreeMap::new(),
+            dead_letter: None,
+            lifecycle: Vec::new(),
             receiver,
         }
     }
@@ -75,8 +84,7 @@ impl Router {
             addr.clone(),
             AddressRecord::new(
                 addr.clone().into(),
-                senders.msgs,
-                senders.ctrl,
+                senders,
                 AddressMeta {
                     processor: false,
                     bare: true,
@@ -90,6 +98,24 @@ impl Router {
         self.state.sender.clone()
     }
 
+    /// Notify all lifecycle subscribers of a worker state transition
+    ///
+    /// The router never waits for a subscriber: events for a
+    /// subscriber that is lagging behind are dropped, and subscribers
+    /// that went away are removed.
+    fn notify_lifecycle(&mut self, addr: &Address, state: LifecycleState) {
+        self.lifecycle.retain(
+            |tx| match tx.try_send(LifecycleEvent::new(addr.clone(), state)) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!("Dropping lifecycle event for {}: subscriber lagging", addr);
+                    true
+                }
+                Err(TrySendError::Closed(_)) => false,
+            },
+        );
+    }
+
     /// A utility facade to hide failures that are not really failures
     pub async fn run(&mut self) -> Result<()> {
         match self.run_inner().await {
@@ -131,11 +157,17 @@ impl Router {
             //// ==! Basic worker control
             StartWorker {
                 addrs,
+                priority,
                 senders,
                 bare,
                 ref reply,
-            } => start_worker::exec(self, addrs, senders, bare, reply).await?,
-            StopWorker(ref addr, ref reply) => stop_worker::exec(self, addr, reply).await?,
+            } => start_worker::exec(self, addrs, priority, senders, bare, reply).await?,
+            StopWorker(ref addr, state, ref reply) => {
+                stop_worker::exec(self, addr, state, false, reply).await?
+            }
+            StopWorkerAndWait(ref addr, ref reply) => {
+                stop_worker::exec(self, addr, None, true, reply).await?
+            }
 
             //// ==! Basic processor control
             StartProcessor(addr, senders, ref reply) => {
@@ -172,12 +204,29 @@ impl Router {
                 }
             }
 
+            ShutdownTimeout(phase) => {
+                if shutdown::timeout(self, phase).await? {
+                    info!("No more workers left.  Goodbye!");
+                    if let Some(sender) = self.state.stop_reply() {
+                        sender
+                            .send(RouterReply::ok())
+                            .await
+                            .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?;
+                        return Ok(true);
+                    }
+                }
+            }
+
             StopAck(addr) if self.state.running() => {
                 debug!("Received shutdown ACK for address {}", addr);
                 if let Some(rec) = self.map.internal.remove(&addr) {
                     rec.address_set().iter().for_each(|addr| {
                         self.map.addr_map.remove(addr);
                     });
+                    if rec.is_worker() {
+                        self.notify_lifecycle(&addr, LifecycleState::Stopped);
+                    }
+                    rec.notify_stopped().await;
                 }
             }
 
@@ -194,16 +243,22 @@ impl Router {
                 }
             }
 
+            // Workers that are stopping are no longer reachable
             ListWorkers(sender) => sender
                 .send(RouterReply::workers(
-                    self.map.internal.keys().cloned().collect(),
+                    self.map
+                        .internal
+                        .keys()
+                        .filter(|addr| self.map.addr_map.contains_key(addr))
+                        .cloned()
+                        .collect(),
                 ))
                 .await
                 .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?,
 
-            SetCluster(addr, label, reply) => {
+            SetCluster(addr, label, timeout, reply) => {
                 debug!("Setting cluster on address {}", addr);
-                let msg = self.map.set_cluster(label, addr);
+                let msg = self.map.set_cluster(label, addr, timeout);
                 reply
                     .send(msg)
                     .await
@@ -212,13 +267,20 @@ impl Router {
 
             SetReady(addr) => {
                 trace!("Marking address {} as ready!", addr);
-                match self.map.set_ready(addr) {
+                let is_worker = self
+                    .map
+                    .internal
+                    .get(&addr)
+                    .map_or(false, |r| r.is_worker());
+                match self.map.set_ready(addr.clone()) {
                     Err(e) => warn!("Failed to set address as ready: {}", e),
                     Ok(waiting) => {
+                        if is_worker {
+                            self.notify_lifecycle(&addr, LifecycleState::Ready);
+                        }
+                        // Pollers may have given up waiting already
                         for sender in waiting {
-                            sender.send(RouterReply::ok()).await.map_err(|_| {
-                                NodeError::NodeState(NodeReason::Unknown).internal()
-                            })?;
+                            let _ = sender.send(RouterReply::ok()).await;
                         }
                     }
                 }
@@ -234,6 +296,29 @@ impl Router {
                 }
             }
 
+            SetDeadLetter(addr, reply) => {
+                debug!("Setting dead-letter address to {:?}", addr);
+                self.dead_letter = addr;
+                reply
+                    .send(RouterReply::ok())
+                    .await
+                    .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?;
+            }
+
+            GetDeadLetter(reply) => reply
+                .send(RouterReply::dead_letter(self.dead_letter.clone()))
+                .await
+                .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?,
+
+            SubscribeLifecycle(events, reply) => {
+                debug!("Adding lifecycle event subscriber");
+                self.lifecycle.push(events);
+                reply
+                    .send(RouterReply::ok())
+                    .await
+                    .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?;
+            }
+
             // Handle route/ sender requests
             SenderReq(ref addr, ref reply) => match determine_type(addr) {
                 RouteType::Internal(ref addr) => utils::resolve(self, addr, reply, false).await?,
@@ -1,16 +1,18 @@
 use crate::relay::{CtrlSignal, RelayMessage};
+use crate::router::SenderPair;
 use crate::tokio::sync::mpsc::Sender;
 use crate::{
     error::{NodeError, NodeReason},
-    NodeReplyResult, RouterReply,
+    NodeReplyResult, RouterReply, StateSender,
 };
+use core::time::Duration;
 use ockam_core::{
     compat::{
         collections::{BTreeMap, BTreeSet},
         string::String,
         vec::Vec,
     },
-    Address, AddressSet, Result,
+    Address, AddressSet, Result, ShutdownReason,
 };
 
 /// Address states and associated logic
@@ -24,13 +26,23 @@ pub struct InternalMap {
     cluster_order: Vec<String>,
     /// Cluster data records
     clusters: BTreeMap<String, BTreeSet<Address>>,
+    /// Shutdown timeouts of clusters which don't use the node's
+    cluster_timeouts: BTreeMap<String, Duration>,
     /// Track stop information
     stopping: BTreeSet<Address>,
 }
 
 impl InternalMap {
     /// Add an address to a particular cluster
-    pub(super) fn set_cluster(&mut self, label: String, primary: Address) -> NodeReplyResult {
+    ///
+    /// A `timeout` replaces the shutdown timeout previously set for
+    /// this cluster, if any.
+    pub(super) fn set_cluster(
+        &mut self,
+        label: String,
+        primary: Address,
+        timeout: Option<Duration>,
+    ) -> NodeReplyResult {
         let rec = self
             .internal
             .get(&primary)
@@ -42,6 +54,10 @@ impl InternalMap {
             self.cluster_order.push(label.clone());
         }
 
+        if let Some(timeout) = timeout {
+            self.cluster_timeouts.insert(label.clone(), timeout);
+        }
+
         // Add all addresses to the cluster set
         for addr in rec.address_set().clone() {
             self.clusters
@@ -69,11 +85,14 @@ impl InternalMap {
             .map_or(false, |rec| rec.ready(reply))
     }
 
-    /// Retrieve the next cluster in reverse-initialsation order
-    pub(super) fn next_cluster(&mut self) -> Option<Vec<&mut AddressRecord>> {
+    /// Retrieve the next cluster in reverse-initialsation order,
+    /// together with its shutdown timeout, if one was set
+    pub(super) fn next_cluster(&mut self) -> Option<(Option<Duration>, Vec<&mut AddressRecord>)> {
         let name = self.cluster_order.pop()?;
         let addrs = self.clusters.remove(&name)?;
-        Some(
+        let timeout = self.cluster_timeouts.remove(&name);
+        Some((
+            timeout,
             self.internal
                 .iter_mut()
                 .filter_map(|(primary, rec)| {
@@ -84,7 +103,7 @@ impl InternalMap {
                     }
                 })
                 .collect(),
-        )
+        ))
     }
 
     /// Mark this address as "having started to stop"
@@ -97,6 +116,11 @@ impl InternalMap {
         self.stopping.is_empty()
     }
 
+    /// Get all addresses which have started, but not finished, to stop
+    pub(super) fn stopping(&self) -> Vec<Address> {
+        self.stopping.iter().cloned().collect()
+    }
+
     /// Get all addresses of workers not in a cluster
     pub(super) fn non_cluster_workers(&mut self) -> Vec<&mut AddressRecord> {
         let clustered = self
@@ -122,13 +146,15 @@ impl InternalMap {
     }
 
     /// Permanently free all remainin resources associated to a particular address
-    pub(super) fn free_address(&mut self, primary: Address) {
+    ///
+    /// Returns the removed record, if there was one.
+    pub(super) fn free_address(&mut self, primary: Address) -> Option<AddressRecord> {
         self.stopping.remove(&primary);
-        if let Some(record) = self.internal.remove(&primary) {
-            for addr in record.address_set {
-                self.addr_map.remove(&addr);
-            }
+        let record = self.internal.remove(&primary)?;
+        for addr in record.address_set.iter() {
+            self.addr_map.remove(addr);
         }
+        Some(record)
     }
 }
 
@@ -143,35 +169,57 @@ pub struct AddressMeta {
 pub struct AddressRecord {
     address_set: AddressSet,
     sender: Option<Sender<RelayMessage>>,
+    prio_sender: Option<Sender<RelayMessage>>,
+    /// Addresses whose messages are delivered via the priority lane
+    priority: BTreeSet<Address>,
     ctrl_tx: Sender<CtrlSignal>,
     state: AddressState,
     ready: ReadyState,
     meta: AddressMeta,
+    /// Pollers waiting for this worker's shutdown to complete
+    stop_waiters: Vec<Sender<NodeReplyResult>>,
 }
 
 impl AddressRecord {
     pub fn address_set(&self) -> &AddressSet {
         &self.address_set
     }
+    /// Whether this record belongs to a full worker, i.e. neither a
+    /// processor nor a bare context
+    pub fn is_worker(&self) -> bool {
+        !self.meta.processor && !self.meta.bare
+    }
     pub fn sender(&self) -> Sender<RelayMessage> {
         self.sender.clone().expect("No such sender!")
     }
+    /// Return the sender for the mailbox lane serving `addr`
+    pub fn sender_for(&self, addr: &Address) -> Sender<RelayMessage> {
+        if self.priority.contains(addr) {
+            self.prio_sender.clone().expect("No such sender!")
+        } else {
+            self.sender()
+        }
+    }
     pub fn sender_drop(&mut self) {
         self.sender = None;
+        self.prio_sender = None;
     }
-    pub fn new(
-        address_set: AddressSet,
-        sender: Sender<RelayMessage>,
-        ctrl_tx: Sender<CtrlSignal>,
-        meta: AddressMeta,
-    ) -> Self {
+    /// Mark a set of addresses to be served by the priority lane
+    pub fn set_priority(&mut self, priority: Vec<Address>) {
+        self.priority = priority.into_iter().collect();
+    }
+    pub fn new(address_set: AddressSet, senders: SenderPair, meta: AddressMeta) -> Self {
+        let SenderPair { msgs, prio, ctrl } = senders;
         AddressRecord {
             address_set,
-            sender: Some(sender),
-            ctrl_tx,
+            sender: Some(msgs),
+            prio_sender: Some(prio),
+            priority: BTreeSet::new(),
+            ctrl_tx: ctrl,
             state: AddressState::Running,
             ready: ReadyState::Initialising(vec![]),
             meta,
+            stop_waiters: vec![],
         }
     }
 
@@ -183,12 +231,54 @@ impl AddressRecord {
                 .await
                 .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?;
         } else {
-            self.sender = None;
+            self.sender_drop();
         }
         self.state = AddressState::Stopping;
         Ok(())
     }
 
+    /// Tell a worker why it is about to be stopped
+    ///
+    /// This does not stop the worker by itself.  Returns whether the
+    /// signal was delivered.  Processors don't receive a reason.
+    pub fn signal_shutdown(&self, reason: ShutdownReason, state: Option<StateSender>) -> bool {
+        if self.meta.processor {
+            return false;
+        }
+        #[cfg(feature = "std")]
+        if self
+            .ctrl_tx
+            .try_send(CtrlSignal::Shutdown(reason, state))
+            .is_err()
+        {
+            trace!(
+                "Failed to signal shutdown reason to {}",
+                self.address_set.first()
+            );
+            return false;
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = (reason, state);
+        cfg!(feature = "std")
+    }
+
+    /// Register a poller to be replied to once this worker has stopped
+    pub fn wait_stopped(&mut self, reply: Sender<NodeReplyResult>) {
+        self.stop_waiters.push(reply);
+    }
+
+    /// Reply to all pollers waiting for this worker to stop
+    pub async fn notify_stopped(self) {
+        for reply in self.stop_waiters {
+            if reply.send(RouterReply::ok()).await.is_err() {
+                trace!(
+                    "Nobody is waiting for {} to stop anymore",
+                    self.address_set.first()
+                );
+            }
+        }
+    }
+
     /// Check the integrity of this record
     pub fn check(&self) -> bool {
         self.state == AddressState::Running
@@ -236,3 +326,31 @@ pub enum ReadyState {
     /// The runner is still processing user init code and contains a list of waiting polling addresses
     Initialising(Vec<Sender<NodeReplyResult>>),
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{AddressMeta, AddressRecord};
+    use crate::router::SenderPair;
+    use crate::tokio::sync::mpsc::channel;
+    use ockam_core::{Address, ShutdownReason};
+
+    #[test]
+    fn signal_shutdown_reports_a_full_control_channel() {
+        let (msgs, _msgs_rx) = channel(1);
+        let (prio, _prio_rx) = channel(1);
+        let (ctrl, _ctrl_rx) = channel(1);
+        let meta = AddressMeta {
+            processor: false,
+            bare: false,
+        };
+        let record = AddressRecord::new(
+            Address::random_local().into(),
+            SenderPair { msgs, prio, ctrl },
+            meta,
+        );
+
+        assert!(record.signal_shutdown(ShutdownReason::NodeGraceful, None));
+        let (state_tx, _state_rx) = channel(1);
+        assert!(!record.signal_shutdown(ShutdownReason::WorkerStop, Some(state_tx)));
+    }
+}
@@ -1,10 +1,7 @@
 use super::Router;
-use crate::{
-    error::{NodeError, NodeReason},
-    tokio::sync::mpsc::Sender,
-    NodeReplyResult, RouterReply,
-};
-use ockam_core::{Address, Result};
+use crate::{tokio::sync::mpsc::Sender, LifecycleState, NodeReplyResult, RouterReply};
+use core::time::Duration;
+use ockam_core::{Address, Result, ShutdownReason};
 
 /// Register a stop ACK
 ///
@@ -14,7 +11,12 @@ pub(super) async fn ack(router: &mut Router, addr: Address) -> Result<bool> {
     debug!("Handling shutdown ACK for {}", addr);
 
     // Permanently remove the address and corresponding worker
-    router.map.free_address(addr);
+    if let Some(rec) = router.map.free_address(addr.clone()) {
+        if rec.is_worker() {
+            router.notify_lifecycle(&addr, LifecycleState::Stopped);
+        }
+        rec.notify_stopped().await;
+    }
 
     // If there are workers left in the cluster: keep waiting
     if !router.map.cluster_done() {
@@ -27,14 +29,26 @@ pub(super) async fn ack(router: &mut Router, addr: Address) -> Result<bool> {
 
 async fn stop_next_cluster(r: &mut Router) -> Result<bool> {
     match r.map.next_cluster() {
-        Some(mut vec) => {
+        Some((timeout, mut vec)) => {
+            // A cluster never gets more than what is left of the
+            // node's shutdown timeout
+            let remaining = r.state.remaining_shutdown_time();
+            let timeout = timeout.map_or(remaining, |t| t.min(remaining));
             let mut addrs = vec![];
             for record in vec.iter_mut() {
+                record.signal_shutdown(ShutdownReason::NodeGraceful, None);
                 record.stop().await?;
-                addrs.push(record.address_set().first().clone());
+                addrs.push((record.address_set().first().clone(), record.is_worker()));
+            }
+
+            for (addr, is_worker) in addrs {
+                if is_worker {
+                    r.notify_lifecycle(&addr, LifecycleState::Stopping);
+                }
+                r.map.init_stop(addr);
             }
 
-            addrs.into_iter().for_each(|addr| r.map.init_stop(addr));
+            start_timeout(r, timeout);
             Ok(false)
         }
         // If not, we are done!
@@ -42,8 +56,68 @@ async fn stop_next_cluster(r: &mut Router) -> Result<bool> {
     }
 }
 
-/// Implement the graceful shutdown strategy
+/// Interrupt the current shutdown phase once `timeout` has passed
 #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+fn start_timeout(router: &mut Router, timeout: Duration) {
+    let phase = router.state.next_shutdown_phase();
+
+    #[cfg(feature = "std")]
+    {
+        use crate::NodeMessage;
+        use tokio::{task, time};
+
+        let sender = router.sender();
+        task::spawn(async move {
+            time::sleep(timeout).await;
+            if sender
+                .send(NodeMessage::ShutdownTimeout(phase))
+                .await
+                .is_err()
+            {
+                error!("Failed to send shutdown timeout signal to router");
+            }
+        });
+    }
+}
+
+/// Abort the node once the shutdown `timeout` of the whole node has passed
+#[cfg_attr(not(feature = "std"), allow(unused_variables))]
+fn start_deadline(router: &mut Router, timeout: Duration) {
+    #[cfg(feature = "std")]
+    {
+        use crate::NodeMessage;
+        use tokio::{task, time};
+
+        let sender = router.sender();
+        task::spawn(async move {
+            time::sleep(timeout).await;
+            warn!("Shutdown timeout reached; aborting node!");
+            if sender.send(NodeMessage::AbortNode).await.is_err() {
+                error!("Failed to send node abort signal to router");
+            }
+        });
+    }
+}
+
+/// Handle the timeout of a shutdown phase
+///
+/// Addresses which didn't acknowledge their stop within the phase's
+/// grace period are abandoned, and the next cluster is stopped.
+pub(super) async fn timeout(router: &mut Router, phase: usize) -> Result<bool> {
+    // This phase completed in time
+    if phase != router.state.shutdown_phase() {
+        return Ok(false);
+    }
+
+    warn!("Shutdown timeout reached; abandoning workers which are still stopping!");
+    for addr in router.map.stopping() {
+        router.map.free_address(addr);
+    }
+
+    stop_next_cluster(router).await
+}
+
+/// Implement the graceful shutdown strategy
 pub(super) async fn graceful(
     router: &mut Router,
     seconds: u8,
@@ -51,15 +125,25 @@ pub(super) async fn graceful(
 ) -> Result<bool> {
     // Mark the router as shutting down to prevent spawning
     info!("Initiate graceful node shutdown");
-    router.state.shutdown(reply);
+    let timeout = Duration::from_secs(seconds as u64);
+    router.state.shutdown(reply, timeout);
+    start_deadline(router, timeout);
 
     // Start by shutting down clusterless workers
     let mut cluster = vec![];
+    let mut workers = vec![];
     for rec in router.map.non_cluster_workers().iter_mut() {
         debug!("Stopping address {}", rec.address_set().first());
+        rec.signal_shutdown(ShutdownReason::NodeGraceful, None);
         rec.stop().await?;
+        if rec.is_worker() {
+            workers.push(rec.address_set().first());
+        }
         cluster.push(rec.address_set().first());
     }
+    for addr in workers {
+        router.notify_lifecycle(&addr, LifecycleState::Stopping);
+    }
 
     // If there _are_ no clusterless workers we go to the next cluster
     if cluster.is_empty() {
@@ -72,22 +156,7 @@ pub(super) async fn graceful(
         .for_each(|addr| router.map.init_stop(addr));
 
     // Start a timeout task to interrupt us...
-    #[cfg(feature = "std")]
-    {
-        use crate::NodeMessage;
-        use core::time::Duration;
-        use tokio::{task, time};
-
-        let sender = router.sender();
-        let dur = Duration::from_secs(seconds as u64);
-        task::spawn(async move {
-            time::sleep(dur).await;
-            warn!("Shutdown timeout reached; aborting node!");
-            if sender.send(NodeMessage::AbortNode).await.is_err() {
-                error!("Failed to send node abort signal to router");
-            }
-        });
-    }
+    start_timeout(router, timeout);
 
     // Return but DO NOT stop the router
     Ok(false)
@@ -101,11 +170,20 @@ pub(super) async fn graceful(
 /// Messages sent during the shutdown phase may not be delivered and
 /// shutdown hooks may be suddenly interrupted by thread-deallocation.
 pub(super) async fn immediate(router: &mut Router, reply: Sender<NodeReplyResult>) -> Result<()> {
+    let mut workers = vec![];
+    for rec in router.map.internal.values() {
+        rec.signal_shutdown(ShutdownReason::NodeImmediate, None);
+        if rec.is_worker() {
+            workers.push(rec.address_set().first());
+        }
+    }
+    for addr in workers {
+        router.notify_lifecycle(&addr, LifecycleState::Stopping);
+    }
     router.map.internal.clear();
     router.state.kill();
-    reply
-        .send(RouterReply::ok())
-        .await
-        .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?;
+    // `Context::stop_now` doesn't wait for the reply, so the receiver
+    // may already be gone.  That must not keep the router alive.
+    let _ = reply.send(RouterReply::ok()).await;
     Ok(())
 }
@@ -28,12 +28,10 @@ async fn start(
     reply: &Sender<NodeReplyResult>,
 ) -> Result<()> {
     debug!("Starting new processor '{}'", &addr);
-    let SenderPair { msgs, ctrl } = senders;
 
     let record = AddressRecord::new(
         addr.clone().into(),
-        msgs,
-        ctrl,
+        senders,
         AddressMeta {
             processor: true,
             bare: false,
@@ -2,20 +2,22 @@ use super::{AddressMeta, AddressRecord, NodeState, Router, SenderPair};
 use crate::tokio::sync::mpsc::Sender;
 use crate::{
     error::{NodeError, NodeReason},
-    NodeReplyResult, RouterReply,
+    LifecycleState, NodeReplyResult, RouterReply,
 };
-use ockam_core::{AddressSet, Result};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Address, AddressSet, Result};
 
 /// Execute a `StartWorker` command
 pub(super) async fn exec(
     router: &mut Router,
     addrs: AddressSet,
+    priority: Vec<Address>,
     senders: SenderPair,
     bare: bool,
     reply: &Sender<NodeReplyResult>,
 ) -> Result<()> {
     match router.state.node_state() {
-        NodeState::Running => start(router, addrs, senders, bare, reply).await,
+        NodeState::Running => start(router, addrs, priority, senders, bare, reply).await,
         NodeState::Stopping(_) => reject(reply).await,
         NodeState::Dead => unreachable!(),
     }?;
@@ -25,24 +27,38 @@ pub(super) async fn exec(
 async fn start(
     router: &mut Router,
     addrs: AddressSet,
+    priority: Vec<Address>,
     senders: SenderPair,
     bare: bool,
     reply: &Sender<NodeReplyResult>,
 ) -> Result<()> {
     debug!("Starting new worker '{}'", addrs.first());
-    let SenderPair { msgs, ctrl } = senders;
+
+    // Every address of the new worker must be unused
+    if let Some(addr) = addrs.iter().find(|a| router.map.addr_map.contains_key(a)) {
+        debug!(
+            "Rejecting worker '{}': address {} in use",
+            addrs.first(),
+            addr
+        );
+        reply
+            .send(RouterReply::worker_exists(addr.clone()))
+            .await
+            .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?;
+        return Ok(());
+    }
 
     // Create an address record and insert it into the internal map
     let primary_addr = addrs.first();
-    let address_record = AddressRecord::new(
+    let mut address_record = AddressRecord::new(
         addrs.clone(),
-        msgs,
-        ctrl,
+        senders,
         AddressMeta {
             processor: false,
             bare,
         },
     );
+    address_record.set_priority(priority);
     router
         .map
         .internal
@@ -62,6 +78,10 @@ async fn start(
             .insert(addr.clone(), primary_addr.clone());
     });
 
+    if !bare {
+        router.notify_lifecycle(&primary_addr, LifecycleState::Starting);
+    }
+
     // For now we just send an OK back -- in the future we need to
     // communicate the current executor state
     reply
@@ -2,6 +2,7 @@
 
 use crate::messages::{NodeMessage, NodeReplyResult};
 use crate::tokio::sync::mpsc::Sender;
+use core::time::Duration;
 
 pub enum NodeState {
     Running,
@@ -12,6 +13,13 @@ pub enum NodeState {
 pub struct RouterState {
     pub(super) sender: Sender<NodeMessage>,
     node_state: NodeState,
+    /// Graceful shutdown timeout of the whole node
+    shutdown_timeout: Duration,
+    /// When the graceful shutdown of the node is aborted
+    #[cfg(feature = "std")]
+    shutdown_deadline: Option<crate::tokio::time::Instant>,
+    /// Counts the phases of a graceful shutdown, one per cluster
+    shutdown_phase: usize,
 }
 
 impl RouterState {
@@ -19,12 +27,40 @@ impl RouterState {
         Self {
             sender,
             node_state: NodeState::Running,
+            shutdown_timeout: Duration::from_secs(1),
+            #[cfg(feature = "std")]
+            shutdown_deadline: None,
+            shutdown_phase: 0,
         }
     }
 
     /// Toggle this router to shut down soon
-    pub(super) fn shutdown(&mut self, reply: Sender<NodeReplyResult>) {
-        self.node_state = NodeState::Stopping(reply)
+    pub(super) fn shutdown(&mut self, reply: Sender<NodeReplyResult>, timeout: Duration) {
+        self.node_state = NodeState::Stopping(reply);
+        self.shutdown_timeout = timeout;
+        #[cfg(feature = "std")]
+        {
+            self.shutdown_deadline = Some(crate::tokio::time::Instant::now() + timeout);
+        }
+    }
+
+    /// Return the time left until the graceful shutdown is aborted
+    pub(super) fn remaining_shutdown_time(&self) -> Duration {
+        #[cfg(feature = "std")]
+        if let Some(deadline) = self.shutdown_deadline {
+            return deadline.saturating_duration_since(crate::tokio::time::Instant::now());
+        }
+        self.shutdown_timeout
+    }
+
+    /// Enter the next phase of a graceful shutdown and return its number
+    pub(super) fn next_shutdown_phase(&mut self) -> usize {
+        self.shutdown_phase += 1;
+        self.shutdown_phase
+    }
+
+    pub(super) fn shutdown_phase(&self) -> usize {
+        self.shutdown_phase
     }
 
     /// Ungracefully kill the router
@@ -1,14 +1,17 @@
 use super::Router;
 use crate::tokio::sync::mpsc::Sender;
 use crate::{
+    error::WorkerReason,
     error::{NodeError, NodeReason},
-    NodeReplyResult, RouterReply,
+    LifecycleState, NodeReplyResult, RouterReply, StateSender,
 };
-use ockam_core::{Address, Result};
+use ockam_core::{Address, Result, ShutdownReason};
 
 pub(super) async fn exec(
     router: &mut Router,
     addr: &Address,
+    state: Option<StateSender>,
+    wait: bool,
     reply: &Sender<NodeReplyResult>,
 ) -> Result<()> {
     trace!("Stopping worker '{}'", addr);
@@ -38,18 +41,40 @@ pub(super) async fn exec(
         }
     };
 
+    // Tell the worker why it is stopped.  A state request must reach
+    // it, or the caller would silently get no state.
+    let wants_state = state.is_some() && record.is_worker();
+    if !record.signal_shutdown(ShutdownReason::WorkerStop, state) && wants_state {
+        reply
+            .send(RouterReply::worker_rejected(WorkerReason::Shutdown))
+            .await
+            .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?;
+
+        return Ok(());
+    }
+
     for addr in record.address_set().iter() {
         router.map.addr_map.remove(addr);
     }
 
-    reply
-        .send(RouterReply::ok())
-        .await
-        .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?;
+    // Bare contexts never acknowledge their shutdown, so there is
+    // nothing to wait for
+    if wait && record.is_worker() {
+        record.wait_stopped(reply.clone());
+    } else {
+        reply
+            .send(RouterReply::ok())
+            .await
+            .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?;
+    }
 
     // Drop worker's Sender to close the worker's mailbox channel
     // and trigger the worker to start a graceful self-shutdown.
     record.sender_drop();
 
+    if record.is_worker() {
+        router.notify_lifecycle(&primary_address, LifecycleState::Stopping);
+    }
+
     Ok(())
 }
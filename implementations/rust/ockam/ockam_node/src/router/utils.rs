@@ -34,7 +34,11 @@ pub(super) async fn resolve(
     match router.map.internal.get(&primary_address) {
         Some(record) if record.check() => {
             trace!("{} OK", base);
-            reply.send(RouterReply::sender(addr.clone(), record.sender(), wrap))
+            reply.send(RouterReply::sender(
+                addr.clone(),
+                record.sender_for(addr),
+                wrap,
+            ))
         }
         Some(_) => {
             trace!("{} REJECTED; worker shutting down", base);
@@ -1,4 +1,4 @@
-use crate::{start_node, Context};
+use crate::{start_node, Context, NullWorker};
 use core::sync::atomic::{AtomicBool, Ordering};
 use core::time::Duration;
 use ockam_core::compat::{
@@ -6,8 +6,14 @@ use ockam_core::compat::{
     string::{String, ToString},
     sync::Arc,
 };
-use ockam_core::{async_trait, Address, Any, Decodable, Message, LOCAL};
-use ockam_core::{route, Processor, Result, Routed, Worker};
+use ockam_core::{
+    async_trait,
+    errcode::{Kind, Origin},
+    Address, AllowSourceAddress, Any, Codec, DeadLetter, Decodable, DeliveryReportRequest,
+    DeliveryStatus, DenyAll, Encodable, LocalInfo, LocalMessage, Message, RetryPolicy, Route,
+    TransportMessage, TransportType, LOCAL,
+};
+use ockam_core::{route, Processor, RateLimit, Result, Routed, ShutdownReason, Worker};
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicI8, AtomicU32};
 use tokio::time::sleep;
@@ -390,6 +396,22 @@ async fn wait_for_worker(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+#[ockam_macros::test(crate = "crate")]
+async fn wait_for_worker_times_out(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("slow", WaitForWorker).await?;
+
+    let err = ctx
+        .wait_for_timeout("slow", Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code().kind, Kind::Timeout);
+
+    // Giving up must not affect later pollers
+    ctx.wait_for("slow").await?;
+
+    ctx.stop().await
+}
+
 /// Test the, unexpected, case where a payload is received that does not
 /// code its length at the start. This _may_ happen when dealing with a
 /// payload sent by a non-Rust implementation.
@@ -409,17 +431,20 @@ fn parse_payload_without_inner_length() {
         152, 128, 240, 159, 152, 128, 240, 159, 152, 128, 240, 159, 152, 128, 240, 159, 152, 128,
         240, 159, 152, 128,
     ];
-    let r = parser::message::<String>(&payload).unwrap();
+    let msg = TransportMessage::v1(route![], route![], payload.to_vec());
+    let r = parser::message::<String>(&msg).unwrap();
     assert_eq!("😀".repeat(32), r);
 
     // A String payload of 32 smiley chars that is missing its inner length.
     let payload = "😀".repeat(32);
-    let r = parser::message::<String>(payload.as_bytes()).unwrap();
+    let msg = TransportMessage::v1(route![], route![], payload.into_bytes());
+    let r = parser::message::<String>(&msg).unwrap();
     assert_eq!("😀".repeat(32), r);
 
     // A 100KiB String payload of smiley chars that is missing its inner length.
     let payload = "😀".repeat(25600);
-    let r = parser::message::<String>(payload.as_bytes()).unwrap();
+    let msg = TransportMessage::v1(route![], route![], payload.into_bytes());
+    let r = parser::message::<String>(&msg).unwrap();
     assert_eq!("😀".repeat(25600), r);
 }
 
@@ -547,3 +572,1173 @@ fn use_context_send_and_receive() {
         .unwrap()
         .unwrap();
 }
+
+struct PriorityWorker {
+    handled: u32,
+}
+
+#[async_trait]
+impl Worker for PriorityWorker {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        if msg.msg_addr() == "priority_worker.ctrl".into() {
+            // Report how many data messages were handled before us
+            return ctx.send(msg.return_route(), self.handled.to_string()).await;
+        }
+
+        // Simulate slow data processing so that a backlog builds up
+        sleep(Duration::from_millis(50)).await;
+        self.handled += 1;
+        Ok(())
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn priority_lane_overtakes_data_messages(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker_with_priority(
+        vec!["priority_worker", "priority_worker.ctrl"],
+        "priority_worker.ctrl",
+        PriorityWorker { handled: 0 },
+    )
+    .await?;
+
+    for _ in 0..10 {
+        ctx.send("priority_worker", "data".to_string()).await?;
+    }
+    ctx.send("priority_worker.ctrl", "ctrl".to_string()).await?;
+
+    let handled: u32 = ctx
+        .receive::<String>()
+        .await?
+        .take()
+        .body()
+        .parse()
+        .unwrap();
+    assert!(
+        handled < 10,
+        "control message waited for {} data messages",
+        handled
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn priority_address_must_belong_to_worker(ctx: &mut Context) -> Result<()> {
+    let res = ctx
+        .start_worker_with_priority("priority_worker", "other", PriorityWorker { handled: 0 })
+        .await;
+    assert!(res.is_err());
+
+    ctx.stop().await
+}
+
+static COUNTED_PARSES: AtomicU32 = AtomicU32::new(0);
+
+/// A message which counts how often it is parsed
+#[derive(Serialize, Debug, PartialEq)]
+struct Counted(u32);
+
+impl Message for Counted {}
+
+impl<'de> Deserialize<'de> for Counted {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        COUNTED_PARSES.fetch_add(1, Ordering::SeqCst);
+        u32::deserialize(d).map(Counted)
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn receive_match_parses_each_message_once(ctx: &mut Context) -> Result<()> {
+    let mut receiver = ctx.new_context("receiver").await?;
+    ctx.send("receiver", Counted(1)).await?;
+    ctx.send("receiver", Counted(2)).await?;
+
+    let sender = ctx.new_context(Address::random_local()).await?;
+    tokio::spawn(async move {
+        sleep(Duration::from_millis(100)).await;
+        sender.send("receiver", Counted(3)).await
+    });
+
+    let m = receiver.receive_match(|m: &Counted| m.0 == 3).await?;
+    assert_eq!(m.take().body(), Counted(3));
+    assert_eq!(COUNTED_PARSES.load(Ordering::SeqCst), 3);
+
+    // Skipped messages are delivered afterwards, in order
+    let m = receiver.receive::<Counted>().await?;
+    assert_eq!(m.take().body(), Counted(1));
+    let m = receiver.receive::<Counted>().await?;
+    assert_eq!(m.take().body(), Counted(2));
+    // Both were parsed already while they were set aside
+    assert_eq!(COUNTED_PARSES.load(Ordering::SeqCst), 3);
+
+    ctx.stop().await
+}
+
+static RELAYED_PARSES: AtomicU32 = AtomicU32::new(0);
+
+/// A message which counts how often it is parsed
+#[derive(Serialize, Debug, PartialEq)]
+struct Relayed(u32);
+
+impl Message for Relayed {}
+
+impl<'de> Deserialize<'de> for Relayed {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        RELAYED_PARSES.fetch_add(1, Ordering::SeqCst);
+        u32::deserialize(d).map(Relayed)
+    }
+}
+
+/// Waits for the last message before handling any of the others
+struct WaitForLastWorker;
+
+#[async_trait]
+impl Worker for WaitForLastWorker {
+    type Context = Context;
+    type Message = Relayed;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        let m = ctx.receive_match(|m: &Relayed| m.0 == 3).await?.take();
+        ctx.send("app", m.body().0.to_string()).await
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Relayed>) -> Result<()> {
+        ctx.send("app", msg.body().0.to_string()).await
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn worker_reuses_messages_parsed_by_receive_match(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("waiting_worker", WaitForLastWorker)
+        .await?;
+    ctx.send("waiting_worker", Relayed(1)).await?;
+    ctx.send("waiting_worker", Relayed(2)).await?;
+    ctx.send("waiting_worker", Relayed(3)).await?;
+
+    for expected in ["3", "1", "2"] {
+        assert_eq!(ctx.receive::<String>().await?.take().body(), expected);
+    }
+    assert_eq!(RELAYED_PARSES.load(Ordering::SeqCst), 3);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn receive_match_skips_more_than_mailbox_capacity(ctx: &mut Context) -> Result<()> {
+    let mut receiver = ctx.new_context("skipping_receiver").await?;
+
+    let sender = ctx.new_context(Address::random_local()).await?;
+    tokio::spawn(async move {
+        for i in 0..100 {
+            sender.send("skipping_receiver", i.to_string()).await?;
+        }
+        sender.send("skipping_receiver", "last".to_string()).await
+    });
+
+    let m = receiver.receive_match(|m: &String| m == "last").await?;
+    assert_eq!(m.take().body(), "last");
+
+    for i in 0..100 {
+        assert_eq!(
+            receiver.receive::<String>().await?.take().body(),
+            i.to_string()
+        );
+    }
+
+    ctx.stop().await
+}
+
+struct ShutdownReasonWorker {
+    reason: Arc<std::sync::Mutex<Option<ShutdownReason>>>,
+}
+
+#[async_trait]
+impl Worker for ShutdownReasonWorker {
+    type Message = String;
+    type Context = Context;
+
+    async fn shutdown_with_reason(
+        &mut self,
+        _context: &mut Self::Context,
+        reason: ShutdownReason,
+    ) -> Result<()> {
+        *self.reason.lock().unwrap() = Some(reason);
+        Ok(())
+    }
+}
+
+#[test]
+fn shutdown_reason_for_stop_worker() {
+    let reason = Arc::new(std::sync::Mutex::new(None));
+    let worker = ShutdownReasonWorker {
+        reason: reason.clone(),
+    };
+
+    let (mut ctx, mut executor) = start_node();
+    executor
+        .execute(async move {
+            ctx.start_worker("reason_worker", worker).await.unwrap();
+            ctx.stop_worker("reason_worker").await.unwrap();
+            sleep(Duration::from_millis(100)).await;
+            ctx.stop().await.unwrap();
+        })
+        .unwrap();
+
+    assert_eq!(*reason.lock().unwrap(), Some(ShutdownReason::WorkerStop));
+}
+
+#[test]
+fn shutdown_reason_for_graceful_node_stop() {
+    let reason = Arc::new(std::sync::Mutex::new(None));
+    let worker = ShutdownReasonWorker {
+        reason: reason.clone(),
+    };
+
+    let (mut ctx, mut executor) = start_node();
+    executor
+        .execute(async move {
+            ctx.start_worker("reason_worker", worker).await.unwrap();
+            ctx.stop().await.unwrap();
+        })
+        .unwrap();
+
+    assert_eq!(*reason.lock().unwrap(), Some(ShutdownReason::NodeGraceful));
+}
+
+#[test]
+fn shutdown_reason_for_immediate_node_stop() {
+    let reason = Arc::new(std::sync::Mutex::new(None));
+    let worker = ShutdownReasonWorker {
+        reason: reason.clone(),
+    };
+
+    let (mut ctx, mut executor) = start_node();
+    executor
+        .execute(async move {
+            ctx.start_worker("reason_worker", worker).await.unwrap();
+            ctx.stop_now().await.unwrap();
+            // Immediate shutdown doesn't wait for shutdown hooks
+            sleep(Duration::from_millis(100)).await;
+        })
+        .unwrap();
+
+    assert_eq!(*reason.lock().unwrap(), Some(ShutdownReason::NodeImmediate));
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn unresolvable_route_goes_to_dead_letter(ctx: &mut Context) -> Result<()> {
+    // Without a dead-letter address the send fails
+    assert!(ctx
+        .send(route!["no_such_worker"], "Hello".to_string())
+        .await
+        .is_err());
+
+    ctx.set_dead_letter_address(ctx.address()).await?;
+    assert_eq!(ctx.dead_letter_address().await?, Some(ctx.address()));
+    ctx.send(route!["no_such_worker"], "Hello".to_string())
+        .await?;
+
+    let dead_letter = ctx.receive::<DeadLetter>().await?.take().body();
+    assert!(dead_letter.reason().contains("no_such_worker"));
+    assert_eq!(dead_letter.message().onward_route, route!["no_such_worker"]);
+    assert_eq!(String::decode(&dead_letter.message().payload)?, "Hello");
+
+    ctx.clear_dead_letter_address().await?;
+    assert!(ctx
+        .send(route!["no_such_worker"], "Hello".to_string())
+        .await
+        .is_err());
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn send_to_empty_route_fails(ctx: &mut Context) -> Result<()> {
+    // Even with a dead-letter address an empty route is an error
+    ctx.set_dead_letter_address(ctx.address()).await?;
+
+    let err = ctx
+        .send(Route::new(), "Hello".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code().kind, Kind::Misuse);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn forward_exhausted_route_fails(ctx: &mut Context) -> Result<()> {
+    let payload = "Hello".to_string().encode()?;
+    let transport_msg = TransportMessage::v1(Route::new(), route![ctx.address()], payload);
+    let err = ctx
+        .forward(LocalMessage::new(transport_msg, vec![]))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code().kind, Kind::Misuse);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn start_worker_on_used_address_fails(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("used", NullWorker).await?;
+
+    let err = ctx.start_worker("used", NullWorker).await.unwrap_err();
+    assert_eq!(err.code().kind, Kind::AlreadyExists);
+    assert!(err.to_string().contains("address 0#used is already in use"));
+
+    // The first worker is still registered
+    ctx.stop_worker("used").await?;
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn receive_all_drains_queued_messages(ctx: &mut Context) -> Result<()> {
+    let mut child = ctx.new_context("drain").await?;
+
+    ctx.send("drain", "one".to_string()).await?;
+    // Not valid UTF-8, so this does not parse as a `String`
+    ctx.send("drain", vec![0xffu8]).await?;
+    ctx.send("drain", "two".to_string()).await?;
+    ctx.send("drain", "three".to_string()).await?;
+
+    let msgs = child.receive_all::<String>().await?;
+    assert_eq!(msgs, vec!["one", "two", "three"]);
+    assert!(child.receive_all::<String>().await?.is_empty());
+
+    // The message of a different type is still queued
+    assert_eq!(*child.receive::<Vec<u8>>().await?, vec![0xff]);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn try_receive_skips_more_than_mailbox_capacity(ctx: &mut Context) -> Result<()> {
+    let mut child = ctx.new_context("try_drain").await?;
+
+    let sender = ctx.new_context(Address::random_local()).await?;
+    tokio::spawn(async move {
+        for _ in 0..50 {
+            sender.send("try_drain", vec![0xffu8]).await?;
+        }
+        sender.send("try_drain", "done".to_string()).await
+    });
+
+    let done = loop {
+        match child.try_receive::<String>().await? {
+            Some(msg) => break msg.take().body(),
+            None => sleep(Duration::from_millis(10)).await,
+        }
+    };
+    assert_eq!(done, "done");
+
+    // All skipped messages are still queued, in order
+    let msgs = child.receive_all::<Vec<u8>>().await?;
+    assert_eq!(msgs, vec![vec![0xffu8]; 50]);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn lifecycle_events_are_observed_in_order(ctx: &mut Context) -> Result<()> {
+    use crate::LifecycleState::*;
+    use futures::StreamExt;
+
+    let mut events = ctx.subscribe_lifecycle().await?;
+
+    ctx.start_worker("observed", NullWorker).await?;
+    ctx.wait_for("observed").await?;
+    ctx.stop_worker("observed").await?;
+
+    let mut states = Vec::new();
+    while states.last() != Some(&Stopped) {
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .expect("timed out waiting for lifecycle event")
+            .expect("lifecycle stream closed");
+        assert_eq!(event.address(), &Address::from("observed"));
+        states.push(event.state());
+    }
+    assert_eq!(states, vec![Starting, Ready, Stopping, Stopped]);
+
+    ctx.stop().await
+}
+
+struct CooperativeProcessor {
+    returned: Arc<AtomicBool>,
+    shut_down: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl Processor for CooperativeProcessor {
+    type Context = Context;
+
+    async fn shutdown(&mut self, _ctx: &mut Context) -> Result<()> {
+        self.shut_down.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
+        // Never returns unless asked to stop
+        while ctx.should_continue() {
+            sleep(Duration::from_millis(10)).await;
+        }
+        self.returned.store(true, Ordering::Relaxed);
+        Ok(true)
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn cooperative_processor_stops_gracefully(ctx: &mut Context) -> Result<()> {
+    let returned = Arc::new(AtomicBool::new(false));
+    let shut_down = Arc::new(AtomicBool::new(false));
+    let processor = CooperativeProcessor {
+        returned: returned.clone(),
+        shut_down: shut_down.clone(),
+    };
+    ctx.start_processor("cooperative", processor).await?;
+    sleep(Duration::from_millis(50)).await;
+
+    ctx.stop().await?;
+
+    assert!(returned.load(Ordering::Relaxed));
+    assert!(shut_down.load(Ordering::Relaxed));
+    Ok(())
+}
+
+/// Waits for items on a channel that never sees any
+struct BlockedProcessor {
+    rx: tokio::sync::mpsc::Receiver<u32>,
+    stopped: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl Processor for BlockedProcessor {
+    type Context = Context;
+
+    async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
+        tokio::select! {
+            _ = ctx.stop_requested() => {
+                self.stopped.store(true, Ordering::Relaxed);
+                Ok(false)
+            }
+            item = self.rx.recv() => Ok(item.is_some()),
+        }
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn blocked_processor_observes_stop_request(ctx: &mut Context) -> Result<()> {
+    let (_tx, rx) = tokio::sync::mpsc::channel(1);
+    let stopped = Arc::new(AtomicBool::new(false));
+    let processor = BlockedProcessor {
+        rx,
+        stopped: stopped.clone(),
+    };
+    ctx.start_processor("blocked", processor).await?;
+    sleep(Duration::from_millis(50)).await;
+
+    let start = tokio::time::Instant::now();
+    ctx.stop_processor("blocked").await?;
+    while !stopped.load(Ordering::Relaxed) {
+        assert!(start.elapsed() < Duration::from_secs(1));
+        sleep(Duration::from_millis(10)).await;
+    }
+
+    ctx.stop().await
+}
+
+/// Hands every message to one of two downstream workers, based on its content
+struct Dispatcher;
+
+#[async_trait]
+impl Worker for Dispatcher {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        let target = if msg.as_body().starts_with("left") {
+            "left"
+        } else {
+            "right"
+        };
+        ctx.deliver_local(target.into(), msg.into_local_message())
+            .await
+    }
+}
+
+/// Replies with its own address and the message it received
+struct Downstream;
+
+#[async_trait]
+impl Worker for Downstream {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        let reply = format!("{}: {}", msg.msg_addr(), msg.as_body());
+        ctx.send(msg.return_route(), reply).await
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn dispatcher_delivers_locally_by_content(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("dispatcher", Dispatcher).await?;
+    ctx.start_worker("left", Downstream).await?;
+    ctx.start_worker("right", Downstream).await?;
+
+    ctx.send("dispatcher", "left one".to_string()).await?;
+    let reply = ctx.receive::<String>().await?.take().body();
+    assert_eq!(reply, "0#left: left one");
+
+    ctx.send("dispatcher", "right two".to_string()).await?;
+    let reply = ctx.receive::<String>().await?.take().body();
+    assert_eq!(reply, "0#right: right two");
+
+    let local_msg = LocalMessage::new(
+        TransportMessage::v1(route!["left"], route![], "x".to_string().encode()?),
+        vec![],
+    );
+    assert!(ctx
+        .deliver_local(Address::new(TransportType::new(1), "left"), local_msg)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
+
+type StopTimes = Arc<std::sync::Mutex<Vec<(&'static str, tokio::time::Instant)>>>;
+
+/// Never finishes its shutdown, recording when it was asked to stop
+struct StuckInCluster {
+    cluster: &'static str,
+    timeout: Duration,
+    stopping: StopTimes,
+}
+
+#[async_trait]
+impl Worker for StuckInCluster {
+    type Message = ();
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        ctx.set_cluster_with_timeout(self.cluster, self.timeout)
+            .await
+    }
+
+    async fn shutdown(&mut self, _ctx: &mut Context) -> Result<()> {
+        let now = tokio::time::Instant::now();
+        self.stopping.lock().unwrap().push((self.cluster, now));
+        sleep(Duration::from_secs(60)).await;
+        Ok(())
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn clusters_get_their_own_shutdown_timeout(ctx: &mut Context) -> Result<()> {
+    let stopping = StopTimes::default();
+    for (cluster, millis) in [("persistence", 600), ("transport", 200)].iter() {
+        let addr = Address::random_local();
+        let worker = StuckInCluster {
+            cluster,
+            timeout: Duration::from_millis(*millis),
+            stopping: stopping.clone(),
+        };
+        ctx.start_worker(addr.clone(), worker).await?;
+        ctx.wait_for(addr).await?;
+    }
+
+    // Clusters are stopped in reverse order, each given its own
+    // timeout instead of the node's
+    ctx.stop_timeout(10).await?;
+    let stopped = tokio::time::Instant::now();
+
+    let stopping = stopping.lock().unwrap();
+    assert_eq!(stopping.len(), 2);
+    let (first, transport) = stopping[0];
+    let (second, persistence) = stopping[1];
+    assert_eq!(first, "transport");
+    assert_eq!(second, "persistence");
+
+    let transport_grace = persistence - transport;
+    assert!(transport_grace >= Duration::from_millis(200));
+    assert!(transport_grace < Duration::from_millis(600));
+
+    let persistence_grace = stopped - persistence;
+    assert!(persistence_grace >= Duration::from_millis(600));
+    assert!(persistence_grace < Duration::from_secs(2));
+
+    Ok(())
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn cluster_timeouts_share_the_node_shutdown_timeout(ctx: &mut Context) -> Result<()> {
+    let stopping = StopTimes::default();
+    for cluster in ["persistence", "transport"].iter() {
+        let addr = Address::random_local();
+        let worker = StuckInCluster {
+            cluster,
+            timeout: Duration::from_secs(5),
+            stopping: stopping.clone(),
+        };
+        ctx.start_worker(addr.clone(), worker).await?;
+        ctx.wait_for(addr).await?;
+    }
+
+    // Both clusters together must not take longer than the node
+    let started = tokio::time::Instant::now();
+    ctx.stop_timeout(1).await?;
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    Ok(())
+}
+
+const FLAKY: TransportType = TransportType::new(42);
+
+/// Transport router whose connection is down for the first `failures`
+/// messages, or which rejects all messages
+struct FlakyTransport {
+    failures: u32,
+    reject: bool,
+    attempts: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl Worker for FlakyTransport {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let local_msg = LocalMessage::decode(msg.payload())?;
+        let request = DeliveryReportRequest::find_info(&local_msg).unwrap();
+        let attempt = self.attempts.fetch_add(1, Ordering::SeqCst);
+
+        let status = if self.reject {
+            DeliveryStatus::Rejected("unknown peer".into())
+        } else if attempt < self.failures {
+            DeliveryStatus::Unavailable("connection refused".into())
+        } else {
+            // "Deliver" the message to the local worker following the
+            // transport address
+            let mut transport_msg = local_msg.into_transport_message();
+            transport_msg.onward_route.step()?;
+            ctx.forward(LocalMessage::new(transport_msg, vec![]))
+                .await?;
+            DeliveryStatus::Sent
+        };
+        ctx.send(request.report_to().clone(), request.report(status))
+            .await
+    }
+}
+
+async fn start_flaky_transport(
+    ctx: &Context,
+    failures: u32,
+    reject: bool,
+) -> Result<Arc<AtomicU32>> {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let router = Address::random_local();
+    let transport = FlakyTransport {
+        failures,
+        reject,
+        attempts: attempts.clone(),
+    };
+    ctx.start_worker(router.clone(), transport).await?;
+    ctx.register(FLAKY, router).await?;
+    Ok(attempts)
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn send_with_retry_recovers_from_transient_failure(ctx: &mut Context) -> Result<()> {
+    let attempts = start_flaky_transport(ctx, 1, false).await?;
+    let mut child = ctx.new_context(Address::random_local()).await?;
+    let policy = RetryPolicy::new(3).initial_delay(Duration::from_millis(10));
+
+    let route = route![Address::new(FLAKY, "peer"), child.address()];
+    ctx.send_with_retry(route, "Hello".to_string(), policy)
+        .await?;
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert_eq!(child.receive::<String>().await?.take().body(), "Hello");
+    // The failed attempt was not delivered
+    assert!(child.try_receive::<String>().await?.is_none());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn send_with_retry_gives_up_when_exhausted(ctx: &mut Context) -> Result<()> {
+    let attempts = start_flaky_transport(ctx, 10, false).await?;
+    let policy = RetryPolicy::new(2).initial_delay(Duration::from_millis(10));
+
+    let route = route![Address::new(FLAKY, "peer"), "app"];
+    let err = ctx
+        .send_with_retry(route, "Hello".to_string(), policy)
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.code().origin, Origin::Transport);
+    assert_eq!(err.code().kind, Kind::Io);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn send_with_retry_does_not_retry_rejected_messages(ctx: &mut Context) -> Result<()> {
+    let attempts = start_flaky_transport(ctx, 0, true).await?;
+    let policy = RetryPolicy::new(5).initial_delay(Duration::from_secs(10));
+    let started = tokio::time::Instant::now();
+
+    let route = route![Address::new(FLAKY, "peer"), "app"];
+    let err = ctx
+        .send_with_retry(route, "Hello".to_string(), policy)
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.code().kind, Kind::Invalid);
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn send_with_retry_fails_fast_on_bad_route(ctx: &mut Context) -> Result<()> {
+    let policy = RetryPolicy::new(5).initial_delay(Duration::from_secs(10));
+    let started = tokio::time::Instant::now();
+
+    let err = ctx
+        .send_with_retry(route!["nobody-home"], "Hello".to_string(), policy)
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.code().kind, Kind::NotFound);
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    ctx.stop().await
+}
+
+/// Transport router which reports the previous attempt as sent before
+/// reporting the current one as unavailable, up to `failures` times
+struct LateReportTransport {
+    failures: u32,
+    attempts: Arc<AtomicU32>,
+    previous: Option<DeliveryReportRequest>,
+}
+
+#[async_trait]
+impl Worker for LateReportTransport {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let local_msg = LocalMessage::decode(msg.payload())?;
+        let request = DeliveryReportRequest::find_info(&local_msg).unwrap();
+        let attempt = self.attempts.fetch_add(1, Ordering::SeqCst);
+
+        if let Some(previous) = self.previous.replace(request.clone()) {
+            ctx.send(
+                previous.report_to().clone(),
+                previous.report(DeliveryStatus::Sent),
+            )
+            .await?;
+        }
+
+        let status = if attempt < self.failures {
+            DeliveryStatus::Unavailable("connection reset".into())
+        } else {
+            DeliveryStatus::Sent
+        };
+        ctx.send(request.report_to().clone(), request.report(status))
+            .await
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn send_with_retry_ignores_reports_of_earlier_attempts(ctx: &mut Context) -> Result<()> {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let router = Address::random_local();
+    let transport = LateReportTransport {
+        failures: 2,
+        attempts: attempts.clone(),
+        previous: None,
+    };
+    ctx.start_worker(router.clone(), transport).await?;
+    ctx.register(FLAKY, router).await?;
+    let workers = ctx.list_workers().await?.len();
+
+    let policy = RetryPolicy::new(3).initial_delay(Duration::from_millis(10));
+    let route = route![Address::new(FLAKY, "peer"), "app"];
+    ctx.send_with_retry(route, "Hello".to_string(), policy)
+        .await?;
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+    // The context receiving the reports was stopped
+    assert_eq!(ctx.list_workers().await?.len(), workers);
+
+    ctx.stop().await
+}
+
+/// Counts the messages it received, carrying the count over to its
+/// replacement
+#[derive(Default)]
+struct CountingWorker {
+    count: u32,
+}
+
+#[async_trait]
+impl Worker for CountingWorker {
+    type Message = String;
+    type Context = Context;
+
+    async fn initialize_with_state(
+        &mut self,
+        _ctx: &mut Context,
+        state: Option<Vec<u8>>,
+    ) -> Result<()> {
+        if let Some(state) = state {
+            self.count = u32::from_be_bytes(state[..].try_into().unwrap());
+        }
+        Ok(())
+    }
+
+    fn save_state(&self) -> Option<Vec<u8>> {
+        Some(self.count.to_be_bytes().to_vec())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        self.count += 1;
+        ctx.send(msg.return_route(), self.count.to_string()).await
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn worker_state_is_handed_to_replacement(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("counter", CountingWorker::default())
+        .await?;
+    for i in 1..=3 {
+        ctx.send("counter", "count".to_string()).await?;
+        assert_eq!(ctx.receive::<String>().await?.take().body(), i.to_string());
+    }
+
+    let state = ctx.stop_worker_with_state("counter").await?;
+    assert!(state.is_some());
+
+    ctx.start_worker_with_state("counter.v2", CountingWorker::default(), state)
+        .await?;
+    ctx.send("counter.v2", "count".to_string()).await?;
+    assert_eq!(ctx.receive::<String>().await?.take().body(), "4");
+
+    // Without a state the worker starts fresh
+    ctx.start_worker_with_state("counter.v3", CountingWorker::default(), None)
+        .await?;
+    ctx.send("counter.v3", "count".to_string()).await?;
+    assert_eq!(ctx.receive::<String>().await?.take().body(), "1");
+
+    // Workers without state to save hand over nothing
+    ctx.start_worker("null", NullWorker).await?;
+    assert_eq!(ctx.stop_worker_with_state("null").await?, None);
+
+    ctx.stop().await
+}
+
+/// Takes a while to shut down
+struct SlowShutdownWorker {
+    done: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl Worker for SlowShutdownWorker {
+    type Message = Any;
+    type Context = Context;
+
+    async fn shutdown(&mut self, _ctx: &mut Context) -> Result<()> {
+        sleep(Duration::from_millis(200)).await;
+        self.done.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn stop_worker_and_wait_returns_after_shutdown(ctx: &mut Context) -> Result<()> {
+    let done = Arc::new(AtomicBool::new(false));
+    ctx.start_worker("slow", SlowShutdownWorker { done: done.clone() })
+        .await?;
+
+    ctx.stop_worker_and_wait("slow").await?;
+    assert!(done.load(Ordering::Relaxed));
+    assert!(ctx.stop_worker_and_wait("slow").await.is_err());
+
+    // Bare contexts have no shutdown to wait for
+    let child = ctx.new_context("child").await?;
+    ctx.stop_worker_and_wait(child.address()).await?;
+
+    ctx.stop().await
+}
+
+/// Replies to every message it receives
+struct EchoWorker;
+
+#[async_trait]
+impl Worker for EchoWorker {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn rate_limit_drops_burst_excess(ctx: &mut Context) -> Result<()> {
+    let limit = RateLimit::new(3, Duration::from_millis(500));
+    ctx.start_worker_with_access_control("limited", EchoWorker, limit.clone())
+        .await?;
+
+    let mut received = 0;
+    for i in 0..5 {
+        ctx.send("limited", i.to_string()).await?;
+    }
+    while let Ok(msg) =
+        tokio::time::timeout(Duration::from_millis(200), ctx.receive::<String>()).await
+    {
+        msg?;
+        received += 1;
+    }
+    assert_eq!(received, 3);
+    assert_eq!(limit.dropped(), 2);
+
+    // Once the interval is over messages pass again
+    sleep(Duration::from_millis(500)).await;
+    ctx.send("limited", "late".to_string()).await?;
+    assert_eq!(ctx.receive::<String>().await?.take().body(), "late");
+    assert_eq!(limit.dropped(), 2);
+
+    ctx.stop().await
+}
+
+/// Echoes strings and reports messages it can't decode
+struct StrictWorker;
+
+#[async_trait]
+impl Worker for StrictWorker {
+    type Message = String;
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        ctx.set_strict_decoding(true);
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn undecodable_message_goes_to_dead_letter_in_strict_mode(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("strict", StrictWorker).await?;
+    ctx.set_dead_letter_address(ctx.address()).await?;
+
+    // Not a valid string, with or without a length prefix
+    let payload = vec![0xff, 0xff];
+    let msg = TransportMessage::v1(route!["strict"], route![ctx.address()], payload.clone());
+    ctx.forward(LocalMessage::new(msg, Vec::new())).await?;
+
+    let dead_letter = ctx.receive::<DeadLetter>().await?.take().body();
+    assert!(dead_letter.reason().contains("strict"));
+    assert_eq!(dead_letter.message().payload, payload);
+
+    // The worker keeps handling well-formed messages
+    ctx.send("strict", "Hello".to_string()).await?;
+    assert_eq!(ctx.receive::<String>().await?.take().body(), "Hello");
+
+    ctx.clear_dead_letter_address().await?;
+    ctx.stop().await
+}
+
+/// Replies with the local info attached to each message
+struct LocalInfoWorker;
+
+#[async_trait]
+impl Worker for LocalInfoWorker {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        let info: Vec<String> = msg
+            .local_info()
+            .iter()
+            .map(|i| format!("{}={:?}", i.type_identifier(), i.data()))
+            .collect();
+        ctx.send(
+            msg.return_route(),
+            format!("{}: {}", msg.as_body(), info.join(",")),
+        )
+        .await
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn worker_reads_local_info(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("info", LocalInfoWorker).await?;
+
+    let msg = TransportMessage::v1(route!["info"], route![ctx.address()], "Hello".encode()?);
+    let info = LocalInfo::new("trace_id".into(), vec![1, 2]);
+    ctx.forward(LocalMessage::new(msg, vec![info])).await?;
+
+    let reply = ctx.receive::<String>().await?.take().body();
+    assert_eq!(reply, "Hello: trace_id=[1, 2]");
+
+    ctx.stop().await
+}
+
+/// Records the routes of every message, then passes it on
+struct HopWorker {
+    seen: Arc<std::sync::Mutex<Vec<(Route, Route)>>>,
+}
+
+#[async_trait]
+impl Worker for HopWorker {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        self.seen.lock().unwrap().push(msg.routes());
+
+        let mut local_msg = msg.into_local_message();
+        let transport_msg = local_msg.transport_mut();
+        transport_msg.onward_route.step()?;
+        transport_msg.return_route.modify().prepend(ctx.address());
+        ctx.forward(local_msg).await
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn worker_sees_onward_and_return_routes(ctx: &mut Context) -> Result<()> {
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    ctx.start_worker("hop", HopWorker { seen: seen.clone() })
+        .await?;
+
+    let sender = ctx.new_context("sender").await?;
+    sender
+        .send(route!["hop", ctx.address()], "Hello".to_string())
+        .await?;
+
+    let msg = ctx.receive::<String>().await?.take();
+    assert_eq!(msg.routes(), (route![], route!["hop", "sender"]));
+    assert_eq!(
+        seen.lock().unwrap().as_slice(),
+        &[(route![ctx.address()], route!["sender"])]
+    );
+
+    ctx.stop().await
+}
+
+/// A message which always fails to encode
+#[derive(Deserialize)]
+struct Unencodable;
+
+impl Message for Unencodable {}
+
+impl Serialize for Unencodable {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> core::result::Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("cannot encode"))
+    }
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn send_returns_encode_error(ctx: &mut Context) -> Result<()> {
+    let mut receiver = ctx.new_context("receiver").await?;
+    let err = ctx.send("receiver", Unencodable).await.unwrap_err();
+    assert_eq!(err.code().origin, Origin::Core);
+    assert_eq!(err.to_string(), "cannot encode");
+
+    // The sender is still usable afterwards
+    ctx.send("receiver", "Hello".to_string()).await?;
+    assert_eq!(receiver.receive::<String>().await?.take().body(), "Hello");
+
+    ctx.stop().await
+}
+
+#[derive(Serialize, Deserialize, Message, Debug, PartialEq)]
+struct Reading {
+    sensor: String,
+    value: u32,
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn send_with_codec_round_trips(ctx: &mut Context) -> Result<()> {
+    let mut receiver = ctx.new_context("receiver").await?;
+
+    for codec in [Codec::Bare, Codec::Json] {
+        let reading = Reading {
+            sensor: "t1".into(),
+            value: 21,
+        };
+        ctx.send_with_codec("receiver", reading, codec).await?;
+
+        let msg = receiver.receive::<Any>().await?.take();
+        assert_eq!(msg.codec(), Some(codec));
+        assert_eq!(msg.return_route(), route![ctx.address()]);
+        if codec == Codec::Json {
+            assert_eq!(msg.payload(), br#"{"sensor":"t1","value":21}"#);
+        }
+
+        let msg = msg.cast_with_codec::<Reading>()?;
+        assert_eq!(
+            msg.body(),
+            Reading {
+                sensor: "t1".into(),
+                value: 21,
+            }
+        );
+    }
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn typed_decoding_rejects_json_payload(ctx: &mut Context) -> Result<()> {
+    let mut receiver = ctx.new_context("receiver").await?;
+
+    // A JSON string is valid BARE, so it must be rejected by its version
+    ctx.send_with_codec("receiver", "hello".to_string(), Codec::Json)
+        .await?;
+    assert!(receiver.receive_timeout::<String>(1).await.is_err());
+
+    let msg = receiver.receive::<Any>().await?.take();
+    let err = msg.cast::<String>().err().unwrap();
+    assert_eq!(err.code().kind, Kind::Serialization);
+
+    ctx.stop_worker(receiver.address()).await?;
+    ctx.stop().await
+}
+
+#[ockam_macros::test(crate = "crate")]
+async fn child_context_with_access_control(ctx: &mut Context) -> Result<()> {
+    let mut denied = ctx
+        .new_context_with_access_control("denied", DenyAll)
+        .await?;
+    ctx.send("denied", "hello".to_string()).await?;
+    assert!(denied.receive_timeout::<String>(1).await.is_err());
+
+    // Only messages from "trusted" reach the child
+    let mut child = ctx
+        .new_context_with_access_control("child", AllowSourceAddress("trusted".into()))
+        .await?;
+    let trusted = ctx.new_context("trusted").await?;
+    ctx.send("child", "untrusted".to_string()).await?;
+    trusted.send("child", "trusted".to_string()).await?;
+    assert_eq!(child.receive::<String>().await?.take().body(), "trusted");
+
+    ctx.stop().await
+}
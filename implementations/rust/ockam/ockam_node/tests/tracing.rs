@@ -0,0 +1,74 @@
+use ockam_core::{Result, Routed, Worker};
+use ockam_node::Context;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tracing::Level;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Collects everything the fmt subscriber writes
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Capture {
+    type Writer = Capture;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+struct Logger;
+
+#[ockam_core::worker]
+impl Worker for Logger {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        tracing::info!("Logging {}", msg.as_body());
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+#[allow(non_snake_case)]
+#[test]
+fn worker__log_event__should_carry_address() {
+    // Must be installed before the node sets up its own subscriber
+    let capture = Capture::default();
+    tracing_subscriber::fmt()
+        .with_max_level(Level::TRACE)
+        .with_ansi(false)
+        .with_writer(capture.clone())
+        .init();
+
+    let (mut ctx, mut executor) = ockam_node::start_node();
+    executor
+        .execute(async move {
+            ctx.start_worker("logger", Logger).await?;
+            ctx.send("logger", "Hello".to_string()).await?;
+            assert_eq!(ctx.receive::<String>().await?, "Hello".to_string());
+
+            ctx.stop().await
+        })
+        .unwrap()
+        .unwrap();
+
+    let logs = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    assert!(
+        logs.lines()
+            .any(|l| l.contains("worker{address=0#logger}") && l.contains("Logging Hello")),
+        "no event for the logger worker in:\n{}",
+        logs
+    );
+}
@@ -0,0 +1,201 @@
+use crate::TransportError;
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{route, Result, TransportMessage};
+
+/// Header byte of a deflate-compressed frame payload
+///
+/// Uncompressed frame payloads start with the version of their
+/// `TransportMessage`, so this must not be a valid message version.
+const DEFLATE: u8 = u8::MAX;
+
+/// Fast deflate compression level
+const DEFLATE_LEVEL: u8 = 1;
+
+/// Payload of the message announcing that a peer accepts compressed frames
+const ANNOUNCEMENT: &[u8] = b"ockam.compression.deflate";
+
+/// Largest payload accepted for compression or produced by decompression
+pub const MAX_DECOMPRESSED_LENGTH: usize = 1024 * 1024;
+
+/// Optional per-frame payload compression
+///
+/// Compression is negotiated per connection: an end only compresses
+/// frames once the other end sent an
+/// [`announcement`](Compression::announcement), and answers the first
+/// announcement it receives with its own.  By default an end never
+/// announces first, because receivers without compression support
+/// can't handle announcements.  Ends created with
+/// [`announcing`](Compression::announcing) announce right after
+/// connecting, so they must only be used with peers which support
+/// compression.  Until the negotiation completes, and with peers which
+/// don't support compression, frames are sent as is.
+///
+/// Compressed frame payloads start with a header byte which can't be
+/// the first byte of an uncompressed payload, so both kinds of frames
+/// may be received on the same connection.  Payloads shorter than the
+/// threshold, or which would not shrink, are sent uncompressed, so
+/// peers may use different thresholds.
+///
+/// ```
+/// # use ockam_transport_core::Compression;
+/// let compression = Compression::new(64);
+/// let payload = vec![0u8; 1024];
+///
+/// let compressed = compression.compress(payload.clone()).unwrap();
+/// assert!(compressed.len() < payload.len());
+/// assert_eq!(compression.decompress(&compressed).unwrap(), payload);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Compression {
+    threshold: usize,
+    announce: bool,
+}
+
+impl Default for Compression {
+    /// Compress payloads of at least 512 bytes
+    fn default() -> Self {
+        Self::new(512)
+    }
+}
+
+impl Compression {
+    /// Compress payloads of at least `threshold` bytes
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            announce: false,
+        }
+    }
+
+    /// Announce compression right after connecting, instead of waiting
+    /// for the peer to announce it
+    ///
+    /// Peers without compression support don't understand the
+    /// announcement, so this must only be used if all peers support
+    /// compression.
+    pub fn announcing(mut self) -> Self {
+        self.announce = true;
+        self
+    }
+
+    /// Return whether compression is announced right after connecting
+    pub fn announces(&self) -> bool {
+        self.announce
+    }
+
+    /// Return the minimum length of a payload to be compressed
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Compress `payload` if worthwhile, prefixing it with the
+    /// compression header
+    pub fn compress(&self, payload: Vec<u8>) -> Result<Vec<u8>> {
+        if payload.len() > MAX_DECOMPRESSED_LENGTH {
+            return Err(TransportError::Capacity.into());
+        }
+
+        if payload.len() >= self.threshold {
+            let mut compressed = compress_to_vec(&payload, DEFLATE_LEVEL);
+            if compressed.len() < payload.len() {
+                compressed.insert(0, DEFLATE);
+                return Ok(compressed);
+            }
+        }
+
+        Ok(payload)
+    }
+
+    /// Decompress `frame` if it carries the compression header, or
+    /// return it as is otherwise
+    pub fn decompress(&self, frame: &[u8]) -> Result<Vec<u8>> {
+        match frame.split_first() {
+            Some((&DEFLATE, payload)) => {
+                decompress_to_vec_with_limit(payload, MAX_DECOMPRESSED_LENGTH)
+                    .map_err(|_| TransportError::RecvBadMessage.into())
+            }
+            Some(_) => Ok(frame.to_vec()),
+            None => Err(TransportError::Protocol.into()),
+        }
+    }
+
+    /// Message telling the peer that compressed frames are accepted
+    ///
+    /// The message has an empty onward route.  Receivers without
+    /// compression support try to route it and fail, so it must only
+    /// be sent to peers which announced compression themselves, or
+    /// which are known to support it.
+    pub fn announcement() -> TransportMessage {
+        TransportMessage::v1(route![], route![], ANNOUNCEMENT.to_vec())
+    }
+
+    /// Check whether `msg` is a compression announcement
+    pub fn is_announcement(msg: &TransportMessage) -> bool {
+        msg.onward_route.next().is_err() && msg.payload == ANNOUNCEMENT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_frame_header, encode_frame, FRAME_HEADER_LENGTH};
+    use ockam_core::{Decodable, Encodable};
+
+    #[test]
+    fn compressible_payload_is_smaller_on_the_wire() {
+        let payload = b"ockam ".repeat(5000);
+        let compression = Compression::default();
+
+        let plain = encode_frame(payload.clone()).unwrap();
+        let compressed = encode_frame(compression.compress(payload.clone()).unwrap()).unwrap();
+        assert!(compressed.len() < plain.len());
+
+        let len = decode_frame_header([compressed[0], compressed[1]]);
+        assert_eq!(len, compressed.len() - FRAME_HEADER_LENGTH);
+
+        let received = compression
+            .decompress(&compressed[FRAME_HEADER_LENGTH..])
+            .unwrap();
+        assert_eq!(received, payload);
+    }
+
+    #[test]
+    fn small_payload_is_not_compressed() {
+        let compression = Compression::new(64);
+
+        let frame = compression.compress(b"hello".to_vec()).unwrap();
+        assert_eq!(frame, b"hello");
+        assert_eq!(compression.decompress(&frame).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn uncompressed_frames_are_accepted() {
+        let compression = Compression::new(64);
+        let msg = TransportMessage::v1(route!["app"], route!["sender"], vec![0u8; 1024]);
+        let payload = msg.encode().unwrap();
+
+        // As sent by a peer without compression
+        let received = compression.decompress(&payload).unwrap();
+        assert_eq!(TransportMessage::decode(&received).unwrap(), msg);
+    }
+
+    #[test]
+    fn empty_and_corrupt_frames_are_rejected() {
+        let compression = Compression::default();
+
+        assert!(compression.decompress(&[]).is_err());
+        assert!(compression.decompress(&[DEFLATE, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn announcement_is_recognised() {
+        let announcement = Compression::announcement().encode().unwrap();
+        let announcement = TransportMessage::decode(&announcement).unwrap();
+        assert!(Compression::is_announcement(&announcement));
+
+        let heartbeat = TransportMessage::v1(route![], route![], vec![]);
+        assert!(!Compression::is_announcement(&heartbeat));
+    }
+}
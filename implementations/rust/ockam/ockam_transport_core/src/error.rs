@@ -38,6 +38,8 @@ pub enum TransportError {
     PortalInvalidState,
     /// InvalidRouterResponseType
     InvalidRouterResponseType,
+    /// The portal peer speaks an incompatible protocol version
+    PortalIncompatibleVersion,
 }
 
 impl ockam_core::compat::error::Error for TransportError {}
@@ -59,6 +61,7 @@ impl core::fmt::Display for TransportError {
             Self::GenericIo => write!(f, "generic I/O failure"),
             Self::PortalInvalidState => write!(f, "portal entered invalid state"),
             Self::InvalidRouterResponseType => write!(f, "router responded with invalid type"),
+            Self::PortalIncompatibleVersion => write!(f, "incompatible portal protocol version"),
         }
     }
 }
@@ -73,7 +76,7 @@ impl From<TransportError> for Error {
             BindFailed => Kind::Io,
             ConnectionDrop => Kind::Io,
             AlreadyConnected => Kind::Io,
-            PeerNotFound => Kind::Misuse,
+            PeerNotFound => Kind::NotFound,
             PeerBusy => Kind::Io,
             UnknownRoute => Kind::Misuse,
             InvalidAddress => Kind::Misuse,
@@ -83,6 +86,7 @@ impl From<TransportError> for Error {
             GenericIo => Kind::Io,
             PortalInvalidState => Kind::Invalid,
             InvalidRouterResponseType => Kind::Invalid,
+            PortalIncompatibleVersion => Kind::Protocol,
         };
 
         Error::new(Origin::Transport, kind, err)
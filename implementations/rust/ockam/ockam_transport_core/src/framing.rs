@@ -0,0 +1,46 @@
+use crate::TransportError;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+/// Length of a frame header in bytes
+pub const FRAME_HEADER_LENGTH: usize = 2;
+
+/// Maximum payload length of a single frame
+pub const MAX_FRAME_LENGTH: usize = u16::MAX as usize;
+
+/// Prefix `payload` with its length to form a single frame
+///
+/// The length-prefix is encoded as a big-endian 16-bit unsigned
+/// integer.  Payloads longer than [`MAX_FRAME_LENGTH`] are rejected.
+///
+/// ```
+/// # use ockam_transport_core::{decode_frame_header, encode_frame};
+/// let frame = encode_frame(b"hello".to_vec()).unwrap();
+/// assert_eq!(decode_frame_header([frame[0], frame[1]]), 5);
+/// assert_eq!(&frame[2..], b"hello");
+/// ```
+pub fn encode_frame(mut payload: Vec<u8>) -> Result<Vec<u8>> {
+    if payload.len() > MAX_FRAME_LENGTH {
+        return Err(TransportError::Capacity.into());
+    }
+
+    // Create a buffer that includes the message length in big endian
+    let mut len = (payload.len() as u16).to_be_bytes().to_vec();
+
+    // Fun fact: reversing a vector in place, appending the length,
+    // and then reversing it again is faster for large message sizes
+    // than adding the large chunk of data.
+    //
+    // https://play.rust-lang.org/?version=stable&mode=release&edition=2018&gist=8669a640004ac85c7be38b19e3e73dcb
+    payload.reverse();
+    len.reverse();
+    payload.append(&mut len);
+    payload.reverse();
+
+    Ok(payload)
+}
+
+/// Return the payload length announced by a frame header
+pub fn decode_frame_header(header: [u8; FRAME_HEADER_LENGTH]) -> usize {
+    u16::from_be_bytes(header) as usize
+}
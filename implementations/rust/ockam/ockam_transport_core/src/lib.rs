@@ -1,5 +1,20 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub use compression::{Compression, MAX_DECOMPRESSED_LENGTH};
 pub use error::TransportError;
+pub use framing::{decode_frame_header, encode_frame, FRAME_HEADER_LENGTH, MAX_FRAME_LENGTH};
+pub use ockam_core::RetryPolicy;
+#[cfg(feature = "std")]
+pub use resolver::{
+    parse_socket_addr, ResolutionCache, DEFAULT_NEGATIVE_RESOLUTION_TTL, DEFAULT_RESOLUTION_TTL,
+};
+pub use router::{route_to_connection, start_router, ConnectionMap};
+pub use transport::Transport;
 
+mod compression;
 mod error;
+mod framing;
+#[cfg(feature = "std")]
+mod resolver;
+mod router;
+mod transport;
@@ -0,0 +1,209 @@
+use crate::TransportError;
+use core::time::Duration;
+use ockam_core::compat::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    sync::{Arc, Mutex},
+    vec::Vec,
+};
+use ockam_core::Result;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::Instant;
+
+/// How long a successful resolution is reused by default
+pub const DEFAULT_RESOLUTION_TTL: Duration = Duration::from_secs(60);
+
+/// How long a failed resolution is remembered by default
+pub const DEFAULT_NEGATIVE_RESOLUTION_TTL: Duration = Duration::from_secs(5);
+
+/// Parse a `host:port` string with a literal IP address
+pub fn parse_socket_addr<S: AsRef<str>>(s: S) -> Result<SocketAddr> {
+    Ok(s.as_ref()
+        .parse()
+        .map_err(|_| TransportError::InvalidAddress)?)
+}
+
+type Resolver = dyn Fn(&str) -> io::Result<Vec<SocketAddr>> + Send + Sync;
+
+/// A cache of hostname resolutions
+///
+/// Resolved addresses are reused for `ttl`, and failed resolutions
+/// are remembered for `negative_ttl`, so that frequently contacted
+/// peers don't cause a lookup on every connect.  Clones share their
+/// entries.
+///
+/// ```
+/// # use ockam_transport_core::ResolutionCache;
+/// let cache = ResolutionCache::default();
+/// let addrs = cache.resolve("127.0.0.1:4000").unwrap();
+/// assert_eq!(addrs, vec!["127.0.0.1:4000".parse().unwrap()]);
+/// ```
+#[derive(Clone)]
+pub struct ResolutionCache {
+    ttl: Duration,
+    negative_ttl: Duration,
+    resolver: Arc<Resolver>,
+    entries: Arc<Mutex<BTreeMap<String, Entry>>>,
+}
+
+struct Entry {
+    resolved_at: Instant,
+    /// `None` if the resolution failed
+    addrs: Option<Vec<SocketAddr>>,
+}
+
+impl Default for ResolutionCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_RESOLUTION_TTL, DEFAULT_NEGATIVE_RESOLUTION_TTL)
+    }
+}
+
+impl ResolutionCache {
+    /// Create a cache using the system resolver
+    pub fn new(ttl: Duration, negative_ttl: Duration) -> Self {
+        Self::with_resolver(ttl, negative_ttl, |host| {
+            host.to_socket_addrs().map(Iterator::collect)
+        })
+    }
+
+    /// Create a cache using a custom resolver
+    ///
+    /// `resolver` is given a `host:port` string, as accepted by
+    /// [`ToSocketAddrs`].
+    pub fn with_resolver<F>(ttl: Duration, negative_ttl: Duration, resolver: F) -> Self
+    where
+        F: Fn(&str) -> io::Result<Vec<SocketAddr>> + Send + Sync + 'static,
+    {
+        Self {
+            ttl,
+            negative_ttl,
+            resolver: Arc::new(resolver) as Arc<Resolver>,
+            entries: Default::default(),
+        }
+    }
+
+    /// Resolve a `host:port` string to its socket addresses
+    ///
+    /// Fails with [`TransportError::InvalidAddress`] if `host` can not
+    /// be resolved, or resolves to no address.
+    pub fn resolve(&self, host: &str) -> Result<Vec<SocketAddr>> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+
+        if let Some(entry) = entries.get(host) {
+            let ttl = match entry.addrs {
+                Some(_) => self.ttl,
+                None => self.negative_ttl,
+            };
+            if now.duration_since(entry.resolved_at) < ttl {
+                return entry
+                    .addrs
+                    .clone()
+                    .ok_or_else(|| TransportError::InvalidAddress.into());
+            }
+        }
+
+        // Drop everything that has expired while we are at it
+        let (ttl, negative_ttl) = (self.ttl, self.negative_ttl);
+        entries.retain(|_, e| {
+            let ttl = if e.addrs.is_some() { ttl } else { negative_ttl };
+            now.duration_since(e.resolved_at) < ttl
+        });
+
+        let addrs = (self.resolver)(host).ok().filter(|addrs| !addrs.is_empty());
+        entries.insert(
+            host.to_string(),
+            Entry {
+                resolved_at: now,
+                addrs: addrs.clone(),
+            },
+        );
+
+        addrs.ok_or_else(|| TransportError::InvalidAddress.into())
+    }
+
+    /// Resolve `peer` to the socket address to connect to
+    ///
+    /// `peer` is either a socket address or a `hostname:port`.  Also
+    /// returns the hostnames `peer` was resolved from, which are
+    /// registered as aliases of the connection.
+    pub fn resolve_peer(&self, peer: &str) -> Result<(SocketAddr, Vec<String>)> {
+        if let Ok(peer_addr) = parse_socket_addr(peer) {
+            return Ok((peer_addr, vec![]));
+        }
+
+        // FIXME: We only take ipv4 for now
+        let peer_addr = self
+            .resolve(peer)?
+            .into_iter()
+            .find(|x| x.is_ipv4())
+            .ok_or(TransportError::InvalidAddress)?;
+
+        Ok((peer_addr, vec![peer.to_string()]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    fn counting_cache(ttl: Duration, fail: bool) -> (ResolutionCache, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let cache = ResolutionCache::with_resolver(ttl, ttl, {
+            let calls = calls.clone();
+            move |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                if fail {
+                    Err(io::ErrorKind::NotFound.into())
+                } else {
+                    Ok(vec!["10.0.0.1:4000".parse().unwrap()])
+                }
+            }
+        });
+        (cache, calls)
+    }
+
+    #[test]
+    fn resolves_once_within_ttl() {
+        let (cache, calls) = counting_cache(Duration::from_secs(60), false);
+        let first = cache.resolve("peer:4000").unwrap();
+        let second = cache.resolve("peer:4000").unwrap();
+        assert_eq!(first, second);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        cache.resolve("other:4000").unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn remembers_failures() {
+        let (cache, calls) = counting_cache(Duration::from_secs(60), true);
+        assert!(cache.resolve("peer:4000").is_err());
+        assert!(cache.resolve("peer:4000").is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn resolves_peer_hostnames_only() {
+        let (cache, calls) = counting_cache(Duration::from_secs(60), false);
+
+        let (addr, hostnames) = cache.resolve_peer("127.0.0.1:4000").unwrap();
+        assert_eq!(addr, "127.0.0.1:4000".parse().unwrap());
+        assert!(hostnames.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let (addr, hostnames) = cache.resolve_peer("peer:4000").unwrap();
+        assert_eq!(addr, "10.0.0.1:4000".parse().unwrap());
+        assert_eq!(hostnames, vec!["peer:4000".to_string()]);
+    }
+
+    #[test]
+    fn resolves_again_after_ttl() {
+        let (cache, calls) = counting_cache(Duration::from_secs(0), false);
+        cache.resolve("peer:4000").unwrap();
+        cache.resolve("peer:4000").unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::TransportError;
+use ockam_core::compat::{collections::BTreeMap, vec::Vec};
+use ockam_core::{Address, LocalMessage, Result, TransportType, Worker};
+use ockam_node::Context;
+use tracing::{error, trace};
+
+/// Start a transport `router` and register it with the node
+///
+/// The router is started at all of `addrs`, and messages for
+/// addresses of `transport_type` are delivered to the first of them.
+pub async fn start_router<W>(
+    ctx: &Context,
+    transport_type: TransportType,
+    addrs: Vec<Address>,
+    router: W,
+) -> Result<()>
+where
+    W: Worker<Context = Context>,
+{
+    let main_addr = addrs
+        .first()
+        .cloned()
+        .ok_or(TransportError::InvalidAddress)?;
+
+    ctx.start_worker(addrs, router).await?;
+    trace!("Registering router for type = {}", transport_type);
+    ctx.register(transport_type, main_addr).await
+}
+
+/// Hand `msg` to the connection worker at `next`
+///
+/// The next hop of the onward route, i.e. the peer address, is
+/// replaced by the address of the connection worker.
+pub fn route_to_connection(msg: &mut LocalMessage, next: Address) -> Result<()> {
+    msg.transport_mut().onward_route.step()?;
+    msg.transport_mut().onward_route.modify().prepend(next);
+    Ok(())
+}
+
+/// The connection workers registered with a transport router
+///
+/// Every peer address a connection accepts, i.e. the address of the
+/// peer and any hostnames it was reached by, maps to the address of
+/// the worker sending to that peer.
+#[derive(Default)]
+pub struct ConnectionMap {
+    map: BTreeMap<Address, Address>,
+}
+
+impl ConnectionMap {
+    /// Register the connection worker at `self_addr` for all of `accepts`
+    ///
+    /// Fails if `accepts` is empty, or if any of its addresses is
+    /// already registered.
+    pub fn register(&mut self, accepts: Vec<Address>, self_addr: Address) -> Result<()> {
+        if accepts.is_empty() {
+            error!("Registration request failed, no address to accept was given");
+            return Err(TransportError::InvalidAddress.into());
+        }
+
+        if let Some(accept) = accepts.iter().find(|a| self.map.contains_key(a)) {
+            error!(
+                "Registration request failed, this address is already connected: {}",
+                accept
+            );
+            return Err(TransportError::AlreadyConnected.into());
+        }
+
+        for accept in accepts {
+            self.map.insert(accept, self_addr.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Add `accept` as an alias of the connection worker at `self_addr`
+    pub fn alias(&mut self, accept: Address, self_addr: Address) {
+        self.map.insert(accept, self_addr);
+    }
+
+    /// Drop all registrations of the connection worker at `self_addr`
+    pub fn unregister(&mut self, self_addr: &Address) {
+        self.map.retain(|_, a| a != self_addr);
+    }
+
+    /// Keep only the registrations for which `f` returns `true`
+    ///
+    /// `f` is given the accepted address and the connection worker
+    /// address of each registration.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Address, &Address) -> bool,
+    {
+        self.map.retain(|accept, self_addr| f(accept, self_addr))
+    }
+
+    /// Return the connection worker registered for `accept`
+    pub fn get(&self, accept: &Address) -> Option<&Address> {
+        self.map.get(accept)
+    }
+
+    /// Hand `msg` to the connection worker registered for its next hop
+    ///
+    /// Returns the address of the connection worker, or fails with
+    /// [`TransportError::UnknownRoute`] if there is none.
+    pub fn route(&self, msg: &mut LocalMessage) -> Result<Address> {
+        let onward = msg.transport().onward_route.next()?;
+        let next = self
+            .get(onward)
+            .cloned()
+            .ok_or(TransportError::UnknownRoute)?;
+
+        route_to_connection(msg, next.clone())?;
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::errcode::Kind;
+    use ockam_core::{route, TransportMessage};
+
+    #[test]
+    fn register_rejects_known_and_empty_accepts() {
+        let mut map = ConnectionMap::default();
+        map.register(vec!["1#peer".into(), "1#alias".into()], "tx".into())
+            .unwrap();
+
+        let err = map
+            .register(vec!["1#other".into(), "1#alias".into()], "tx2".into())
+            .unwrap_err();
+        assert_eq!(err.code().kind, Kind::Io);
+        assert_eq!(map.get(&"1#other".into()), None);
+
+        let err = map.register(vec![], "tx2".into()).unwrap_err();
+        assert_eq!(err.code().kind, Kind::Misuse);
+
+        map.unregister(&"tx".into());
+        assert_eq!(map.get(&"1#peer".into()), None);
+        assert_eq!(map.get(&"1#alias".into()), None);
+    }
+
+    #[test]
+    fn route_replaces_peer_with_connection_worker() {
+        let mut map = ConnectionMap::default();
+        map.register(vec!["1#peer".into()], "tx".into()).unwrap();
+
+        let msg = TransportMessage::v1(route!["1#peer", "app"], route!["me"], vec![]);
+        let mut msg = LocalMessage::new(msg, vec![]);
+        assert_eq!(map.route(&mut msg).unwrap(), "tx".into());
+        assert_eq!(msg.transport().onward_route, route!["tx", "app"]);
+
+        let msg = TransportMessage::v1(route!["1#unknown"], route!["me"], vec![]);
+        let err = map.route(&mut LocalMessage::new(msg, vec![])).unwrap_err();
+        assert_eq!(err.code().kind, Kind::Misuse);
+    }
+}
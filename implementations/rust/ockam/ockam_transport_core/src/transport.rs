@@ -0,0 +1,37 @@
+use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
+use ockam_core::{async_trait, Address, Result, TransportType};
+
+/// Generic interface of an Ockam transport
+///
+/// A transport registers a router for its
+/// [`transport_type`](Transport::transport_type) with the node when it
+/// is created.  Messages addressed to `(transport_type, peer)` are
+/// then handed to that router, which passes them on to the connection
+/// worker registered for the peer.  Connection workers exchange
+/// [`TransportMessage`](ockam_core::TransportMessage)s with their
+/// peers as length-prefixed frames (see
+/// [`encode_frame`](crate::encode_frame)).
+///
+/// [`start_router`](crate::start_router) starts and registers the
+/// router, which keeps its connection workers in a
+/// [`ConnectionMap`](crate::ConnectionMap).
+#[async_trait]
+pub trait Transport: Send + Sync + 'static {
+    /// Return the address type routed by this transport
+    fn transport_type(&self) -> TransportType;
+
+    /// Start listening for incoming connections on `addr`
+    async fn bind(&self, addr: String) -> Result<()>;
+
+    /// Establish an outgoing connection to `peer`
+    ///
+    /// Returns the address of the worker sending to `peer`.
+    async fn connect(&self, peer: String) -> Result<Address>;
+
+    /// Close the outgoing connection to `peer`
+    async fn disconnect(&self, peer: String) -> Result<()>;
+
+    /// Route messages for any of `accepts` to the connection worker
+    /// at `self_addr`
+    async fn register(&self, accepts: Vec<Address>, self_addr: Address) -> Result<()>;
+}
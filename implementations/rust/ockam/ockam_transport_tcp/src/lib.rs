@@ -35,30 +35,24 @@ pub(crate) use workers::*;
 
 mod transport;
 
+pub use ockam_transport_core::{Compression, Transport};
 pub use transport::*;
 
-use ockam_core::compat::net::SocketAddr;
-use ockam_core::{Result, TransportType};
-use ockam_transport_core::TransportError;
+use ockam_core::TransportType;
+use ockam_transport_core::parse_socket_addr;
 
 /// TCP address type constant
 pub const TCP: TransportType = TransportType::new(1);
 
 pub(crate) const CLUSTER_NAME: &str = "_internals.transport.tcp";
 
-fn parse_socket_addr<S: AsRef<str>>(s: S) -> Result<SocketAddr> {
-    Ok(s.as_ref()
-        .parse()
-        .map_err(|_| TransportError::InvalidAddress)?)
-}
-
 #[cfg(test)]
 mod test {
     use core::fmt::Debug;
     use ockam_core::{Error, Result};
 
     use crate::parse_socket_addr;
-    use crate::TransportError;
+    use ockam_transport_core::TransportError;
 
     fn assert_transport_error<T>(result: Result<T>, error: TransportError)
     where
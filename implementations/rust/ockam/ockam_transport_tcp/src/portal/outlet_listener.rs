@@ -1,9 +1,11 @@
-use crate::{PortalMessage, TcpRouterHandle};
+use crate::{
+    negotiate_version, PortalMessage, TcpRouterHandle, PORTAL_MIN_VERSION, PORTAL_VERSION,
+};
 use ockam_core::{async_trait, AsyncTryClone};
 use ockam_core::{Address, Result, Routed, Worker};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// A TCP Portal Outlet listen worker
 ///
@@ -40,19 +42,34 @@ impl Worker for TcpOutletListenWorker {
 
     async fn handle_message(
         &mut self,
-        _ctx: &mut Self::Context,
+        ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
         let return_route = msg.return_route();
 
-        if let PortalMessage::Ping = msg.body() {
-        } else {
-            return Err(TransportError::Protocol.into());
-        }
+        let msg = msg.body();
+        let offered = msg.ping_version().ok_or(TransportError::Protocol)?;
+        let version = match negotiate_version(offered) {
+            Ok(version) => version,
+            Err(e) => {
+                warn!("Rejecting inlet speaking portal version {}", offered);
+                // Inlets predating version negotiation only understand
+                // being disconnected
+                let reply = match msg {
+                    PortalMessage::Ping => PortalMessage::Disconnect,
+                    _ => PortalMessage::IncompatibleVersion {
+                        min_version: PORTAL_MIN_VERSION,
+                        max_version: PORTAL_VERSION,
+                    },
+                };
+                ctx.send(return_route, reply).await?;
+                return Err(e);
+            }
+        };
 
         let address = self
             .router_handle
-            .connect_outlet(self.peer.clone(), return_route.clone())
+            .connect_outlet(self.peer.clone(), return_route.clone(), version)
             .await?;
 
         debug!("Created Tcp Outlet at {}", &address);
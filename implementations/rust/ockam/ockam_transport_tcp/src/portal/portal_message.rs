@@ -1,7 +1,24 @@
-use ockam_core::Message;
+use ockam_core::{Message, Result};
+use ockam_transport_core::TransportError;
 use serde::{Deserialize, Serialize};
 
+/// The portal protocol version spoken by this implementation
+pub const PORTAL_VERSION: u8 = 1;
+
+/// The oldest portal protocol version this implementation understands
+pub const PORTAL_MIN_VERSION: u8 = 1;
+
+/// The portal protocol version of peers which predate version
+/// negotiation
+///
+/// It is negotiated with the payload-less `Ping` and `Pong`, which
+/// such peers understand.
+pub const PORTAL_LEGACY_VERSION: u8 = 1;
+
 /// A command message type for a Portal
+///
+/// New variants must be added at the end to keep the encoding of the
+/// existing ones.
 #[derive(Serialize, Deserialize, Message)]
 pub enum PortalMessage {
     /// First message that Inlet sends to the Outlet
@@ -13,6 +30,51 @@ pub enum PortalMessage {
     Disconnect,
     /// Message with binary payload
     Payload(Vec<u8>),
+    /// `Ping` carrying the highest protocol version the Inlet speaks
+    VersionedPing(u8),
+    /// `Pong` carrying the protocol version both sides will use
+    VersionedPong(u8),
+    /// Answer to a `VersionedPing` whose version the Outlet doesn't
+    /// understand, carrying the versions it supports
+    IncompatibleVersion { min_version: u8, max_version: u8 },
+}
+
+impl PortalMessage {
+    /// Ping offering protocol `version`
+    pub(crate) fn ping(version: u8) -> Self {
+        if version == PORTAL_LEGACY_VERSION {
+            Self::Ping
+        } else {
+            Self::VersionedPing(version)
+        }
+    }
+
+    /// Pong choosing protocol `version`
+    pub(crate) fn pong(version: u8) -> Self {
+        if version == PORTAL_LEGACY_VERSION {
+            Self::Pong
+        } else {
+            Self::VersionedPong(version)
+        }
+    }
+
+    /// Return the version offered by a ping
+    pub(crate) fn ping_version(&self) -> Option<u8> {
+        match self {
+            Self::Ping => Some(PORTAL_LEGACY_VERSION),
+            Self::VersionedPing(version) => Some(*version),
+            _ => None,
+        }
+    }
+
+    /// Return the version chosen by a pong
+    pub(crate) fn pong_version(&self) -> Option<u8> {
+        match self {
+            Self::Pong => Some(PORTAL_LEGACY_VERSION),
+            Self::VersionedPong(version) => Some(*version),
+            _ => None,
+        }
+    }
 }
 
 /// An internal message type for a Portal
@@ -23,3 +85,52 @@ pub enum PortalInternalMessage {
     /// Message with binary payload
     Payload(Vec<u8>),
 }
+
+/// Pick the protocol version to use with a peer speaking `version`
+///
+/// Both sides use the lower of the two versions.  Peers that are
+/// too old to understand any version we support are rejected.
+pub(crate) fn negotiate_version(version: u8) -> Result<u8> {
+    if version < PORTAL_MIN_VERSION {
+        return Err(TransportError::PortalIncompatibleVersion.into());
+    }
+    Ok(version.min(PORTAL_VERSION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiate_same_version() {
+        assert_eq!(negotiate_version(PORTAL_VERSION).unwrap(), PORTAL_VERSION);
+    }
+
+    #[test]
+    fn negotiate_newer_peer_downgrades() {
+        assert_eq!(
+            negotiate_version(PORTAL_VERSION + 1).unwrap(),
+            PORTAL_VERSION
+        );
+    }
+
+    #[test]
+    fn legacy_version_uses_payload_less_messages() {
+        let ping = PortalMessage::ping(PORTAL_LEGACY_VERSION);
+        assert!(matches!(ping, PortalMessage::Ping));
+        assert_eq!(ping.ping_version(), Some(PORTAL_LEGACY_VERSION));
+
+        let pong = PortalMessage::pong(PORTAL_LEGACY_VERSION + 1);
+        assert!(matches!(pong, PortalMessage::VersionedPong(_)));
+        assert_eq!(pong.pong_version(), Some(PORTAL_LEGACY_VERSION + 1));
+    }
+
+    #[test]
+    fn negotiate_rejects_old_version() {
+        let err = negotiate_version(PORTAL_MIN_VERSION - 1).unwrap_err();
+        assert_eq!(
+            err.code(),
+            ockam_core::Error::from(TransportError::PortalIncompatibleVersion).code()
+        );
+    }
+}
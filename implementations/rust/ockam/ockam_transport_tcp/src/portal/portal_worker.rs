@@ -1,4 +1,7 @@
-use crate::{PortalInternalMessage, PortalMessage, TcpPortalRecvProcessor};
+use crate::{
+    PortalInternalMessage, PortalMessage, TcpPortalRecvProcessor, PORTAL_MIN_VERSION,
+    PORTAL_VERSION,
+};
 use core::time::Duration;
 use ockam_core::{async_trait, compat::boxed::Box, Decodable};
 use ockam_core::{Address, Any, Result, Route, Routed, Worker};
@@ -18,7 +21,7 @@ use tracing::{debug, info, trace, warn};
 /// `Inlet`: `SendPing` -> `ReceivePong` -> `Initialized`
 enum State {
     SendPing { ping_route: Route },
-    SendPong { pong_route: Route },
+    SendPong { pong_route: Route, version: u8 },
     ReceivePong,
     Initialized { onward_route: Route },
 }
@@ -46,6 +49,8 @@ pub(crate) struct TcpPortalWorker {
     receiver_address: Address,
     is_disconnecting: bool,
     type_name: TypeName,
+    /// Portal protocol version agreed with the other side
+    version: u8,
 }
 
 impl TcpPortalWorker {
@@ -73,11 +78,15 @@ impl TcpPortalWorker {
         ctx: &Context,
         peer: SocketAddr,
         pong_route: Route,
+        version: u8,
     ) -> Result<Address> {
         Self::start(
             ctx,
             peer,
-            State::SendPong { pong_route },
+            State::SendPong {
+                pong_route,
+                version,
+            },
             None,
             TypeName::Outlet,
         )
@@ -119,6 +128,7 @@ impl TcpPortalWorker {
             receiver_address,
             is_disconnecting: false,
             type_name,
+            version: PORTAL_VERSION,
         };
 
         ctx.start_worker(vec![internal_addr, remote_addr.clone()], sender)
@@ -222,21 +232,29 @@ impl Worker for TcpPortalWorker {
         match state {
             State::SendPing { ping_route } => {
                 // Force creation of Outlet on the other side
-                ctx.send_from_address(ping_route, PortalMessage::Ping, self.remote_address.clone())
-                    .await?;
+                ctx.send_from_address(
+                    ping_route,
+                    PortalMessage::ping(PORTAL_VERSION),
+                    self.remote_address.clone(),
+                )
+                .await?;
 
                 debug!("Inlet at: {} sent ping", self.internal_address);
 
                 self.state = Some(State::ReceivePong);
             }
-            State::SendPong { pong_route } => {
+            State::SendPong {
+                pong_route,
+                version,
+            } => {
                 // Respond to Inlet
                 ctx.send_from_address(
                     pong_route.clone(),
-                    PortalMessage::Pong,
+                    PortalMessage::pong(version),
                     self.remote_address.clone(),
                 )
                 .await?;
+                self.version = version;
 
                 if self.tx.is_none() {
                     let stream = TcpStream::connect(self.peer)
@@ -277,8 +295,8 @@ impl Worker for TcpPortalWorker {
 
         // Remove our own address from the route so the other end
         // knows what to do with the incoming message
-        let mut onward_route = msg.onward_route();
-        let recipient = onward_route.step()?;
+        let onward_route = msg.onward_route();
+        let recipient = msg.msg_addr();
 
         let return_route = msg.return_route();
 
@@ -295,15 +313,40 @@ impl Worker for TcpPortalWorker {
                 }
 
                 let msg = PortalMessage::decode(msg.payload())?;
+                let version = match msg.pong_version() {
+                    Some(version) => version,
+                    None => {
+                        if let PortalMessage::IncompatibleVersion {
+                            min_version,
+                            max_version,
+                        } = msg
+                        {
+                            warn!(
+                                "Inlet at: {} was rejected, outlet speaks versions {} to {}",
+                                self.internal_address, min_version, max_version
+                            );
+                            return Err(TransportError::PortalIncompatibleVersion.into());
+                        }
+                        return Err(TransportError::Protocol.into());
+                    }
+                };
 
-                if let PortalMessage::Pong = msg {
-                } else {
-                    return Err(TransportError::Protocol.into());
+                // The Outlet must pick a version we offered and understand
+                if !(PORTAL_MIN_VERSION..=PORTAL_VERSION).contains(&version) {
+                    warn!(
+                        "Inlet at: {} received pong with unsupported version {}",
+                        self.internal_address, version
+                    );
+                    return Err(TransportError::PortalIncompatibleVersion.into());
                 }
+                self.version = version;
 
                 self.start_receiver(ctx).await?;
 
-                debug!("Inlet at: {} received pong", self.internal_address);
+                debug!(
+                    "Inlet at: {} received pong, using protocol version {}",
+                    self.internal_address, self.version
+                );
 
                 self.state = Some(State::Initialized {
                     onward_route: return_route,
@@ -368,7 +411,11 @@ impl Worker for TcpPortalWorker {
                         PortalMessage::Disconnect => {
                             self.start_disconnection(ctx, None).await?;
                         }
-                        PortalMessage::Ping | PortalMessage::Pong => {
+                        PortalMessage::Ping
+                        | PortalMessage::Pong
+                        | PortalMessage::VersionedPing(_)
+                        | PortalMessage::VersionedPong(_)
+                        | PortalMessage::IncompatibleVersion { .. } => {
                             return Err(TransportError::Protocol.into());
                         }
                     }
@@ -1,12 +1,12 @@
 use crate::{
-    parse_socket_addr, TcpInletListenProcessor, TcpListenProcessor, TcpPortalWorker,
-    TcpRouterRequest, TcpRouterResponse, WorkerPair, TCP,
+    TcpInletListenProcessor, TcpListenProcessor, TcpPortalWorker, TcpRouterRequest,
+    TcpRouterResponse, WorkerPair, TCP,
 };
-use ockam_core::compat::net::{SocketAddr, ToSocketAddrs};
+use ockam_core::compat::net::SocketAddr;
 use ockam_core::{async_trait, compat::boxed::Box};
 use ockam_core::{Address, AsyncTryClone, Result, Route};
 use ockam_node::Context;
-use ockam_transport_core::TransportError;
+use ockam_transport_core::{Compression, ResolutionCache, RetryPolicy, TransportError};
 
 /// A handle to connect to a TcpRouter
 ///
@@ -14,26 +14,57 @@ use ockam_transport_core::TransportError;
 pub(crate) struct TcpRouterHandle {
     ctx: Context,
     api_addr: Address,
+    compression: Option<Compression>,
+    resolver: ResolutionCache,
+    connect_policy: RetryPolicy,
 }
 
 #[async_trait]
 impl AsyncTryClone for TcpRouterHandle {
     async fn async_try_clone(&self) -> Result<Self> {
         let child_ctx = self.ctx.new_context(Address::random_local()).await?;
-        Ok(Self::new(child_ctx, self.api_addr.clone()))
+        Ok(Self::new(
+            child_ctx,
+            self.api_addr.clone(),
+            self.compression,
+            self.resolver.clone(),
+            self.connect_policy.clone(),
+        ))
     }
 }
 
 impl TcpRouterHandle {
     /// Create a new `TcpRouterHandle` with the given address
-    pub(crate) fn new(ctx: Context, api_addr: Address) -> Self {
-        TcpRouterHandle { ctx, api_addr }
+    pub(crate) fn new(
+        ctx: Context,
+        api_addr: Address,
+        compression: Option<Compression>,
+        resolver: ResolutionCache,
+        connect_policy: RetryPolicy,
+    ) -> Self {
+        TcpRouterHandle {
+            ctx,
+            api_addr,
+            compression,
+            resolver,
+            connect_policy,
+        }
     }
 
     /// Return a reference to the router handle's [`Context`]
     pub fn ctx(&self) -> &Context {
         &self.ctx
     }
+
+    /// Return the frame compression used by connections of this router
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    /// Return the policy for retrying failed connection attempts
+    pub fn connect_policy(&self) -> RetryPolicy {
+        self.connect_policy.clone()
+    }
 }
 
 impl TcpRouterHandle {
@@ -106,6 +137,11 @@ impl TcpRouterHandle {
         );
         let self_addr = pair.tx_addr();
 
+        self.register_accepts(accepts, self_addr).await
+    }
+
+    /// Register the connection worker at `self_addr` for all addresses in `accepts`
+    pub async fn register_accepts(&self, accepts: Vec<Address>, self_addr: Address) -> Result<()> {
         let mut child_ctx = self.ctx.new_context(Address::random_local()).await?;
         child_ctx
             .send(
@@ -150,36 +186,6 @@ impl TcpRouterHandle {
             Err(TransportError::InvalidRouterResponseType.into())
         }
     }
-
-    /// Resolve the given peer to a [`SocketAddr`](std::net::SocketAddr)
-    pub(crate) fn resolve_peer(peer: impl Into<String>) -> Result<(SocketAddr, Vec<String>)> {
-        let peer_str = peer.into();
-        let peer_addr;
-        let hostnames;
-
-        // Try to parse as SocketAddr
-        if let Ok(p) = parse_socket_addr(peer_str.clone()) {
-            peer_addr = p;
-            hostnames = vec![];
-        }
-        // Try to resolve hostname
-        else if let Ok(mut iter) = peer_str.to_socket_addrs() {
-            // FIXME: We only take ipv4 for now
-            if let Some(p) = iter.find(|x| x.is_ipv4()) {
-                peer_addr = p;
-            } else {
-                return Err(TransportError::InvalidAddress.into());
-            }
-
-            hostnames = vec![peer_str];
-        }
-        // Nothing worked, return an error
-        else {
-            return Err(TransportError::InvalidAddress.into());
-        }
-
-        Ok((peer_addr, hostnames))
-    }
 }
 
 impl TcpRouterHandle {
@@ -198,14 +204,18 @@ impl TcpRouterHandle {
     }
 
     /// Establish an outgoing TCP connection for Portal Outlet
+    ///
+    /// `version` is the portal protocol version agreed with the Inlet.
     pub async fn connect_outlet(
         &self,
         peer: impl Into<String>,
         pong_route: Route,
+        version: u8,
     ) -> Result<Address> {
-        let (peer_addr, _) = Self::resolve_peer(peer)?;
+        let (peer_addr, _) = self.resolver.resolve_peer(&peer.into())?;
 
-        let address = TcpPortalWorker::new_outlet(&self.ctx, peer_addr, pong_route).await?;
+        let address =
+            TcpPortalWorker::new_outlet(&self.ctx, peer_addr, pong_route, version).await?;
 
         Ok(address)
     }
@@ -1,12 +1,23 @@
-use crate::{TcpRouterHandle, TcpRouterRequest, TcpRouterResponse, TcpSendWorker, TCP};
+use crate::{
+    report_delivery, TcpRouterHandle, TcpRouterRequest, TcpRouterResponse, TcpSendWorker, TCP,
+};
 use core::ops::Deref;
-use ockam_core::{async_trait, Any};
-use ockam_core::{Address, Decodable, LocalMessage, Result, Routed, Worker};
-use ockam_node::Context;
-use ockam_transport_core::TransportError;
-use std::collections::BTreeMap;
+use core::time::Duration;
+use ockam_core::errcode::Kind;
+use ockam_core::{async_trait, Any, DeliveryReportRequest, DeliveryStatus};
+use ockam_core::{Address, Decodable, Error, LocalMessage, Result, Routed, Worker};
+use ockam_node::{Context, DelayedEvent};
+use ockam_transport_core::{
+    route_to_connection, start_router, Compression, ConnectionMap, ResolutionCache, RetryPolicy,
+    TransportError,
+};
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
 use tracing::{debug, error, trace};
 
+/// How often the router drops registrations of stopped connections
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
 /// A TCP address router and connection listener
 ///
 /// In order to create new TCP connection workers you need a router to
@@ -19,15 +30,31 @@ pub(crate) struct TcpRouter {
     ctx: Context,
     main_addr: Address,
     api_addr: Address,
-    map: BTreeMap<Address, Address>,
+    sweep_addr: Address,
+    /// Created on initialization, so that it belongs to the router
+    sweep_timer: Option<DelayedEvent<()>>,
+    map: ConnectionMap,
     allow_auto_connection: bool,
+    compression: Option<Compression>,
+    resolver: ResolutionCache,
+    connect_policy: RetryPolicy,
 }
 
 impl TcpRouter {
     /// Create and register a new TCP router with the node context
-    pub async fn register(ctx: &Context) -> Result<TcpRouterHandle> {
+    ///
+    /// All connections of this router use the given frame `compression`,
+    /// peer hostnames are looked up through `resolver`, and failed
+    /// connection attempts are retried according to `connect_policy`.
+    pub async fn register(
+        ctx: &Context,
+        compression: Option<Compression>,
+        resolver: ResolutionCache,
+        connect_policy: RetryPolicy,
+    ) -> Result<TcpRouterHandle> {
         let main_addr = Address::random_local();
         let api_addr = Address::random_local();
+        let sweep_addr = Address::random_local();
         debug!("Initialising new TcpRouter with address {}", &main_addr);
 
         let child_ctx = ctx.new_context(Address::random_local()).await?;
@@ -36,16 +63,18 @@ impl TcpRouter {
             ctx: child_ctx,
             main_addr: main_addr.clone(),
             api_addr: api_addr.clone(),
-            map: BTreeMap::new(),
+            sweep_addr: sweep_addr.clone(),
+            sweep_timer: None,
+            map: ConnectionMap::default(),
             allow_auto_connection: true,
+            compression,
+            resolver,
+            connect_policy,
         };
 
         let handle = router.create_self_handle().await?;
 
-        ctx.start_worker(vec![main_addr.clone(), api_addr], router)
-            .await?;
-        trace!("Registering TCP router for type = {}", TCP);
-        ctx.register(TCP, main_addr).await?;
+        start_router(ctx, TCP, vec![main_addr, api_addr, sweep_addr], router).await?;
 
         Ok(handle)
     }
@@ -53,7 +82,13 @@ impl TcpRouter {
     /// Create a new `TcpRouterHandle` representing this router
     async fn create_self_handle(&self) -> Result<TcpRouterHandle> {
         let handle_ctx = self.ctx.new_context(Address::random_local()).await?;
-        let handle = TcpRouterHandle::new(handle_ctx, self.api_addr.clone());
+        let handle = TcpRouterHandle::new(
+            handle_ctx,
+            self.api_addr.clone(),
+            self.compression,
+            self.resolver.clone(),
+            self.connect_policy.clone(),
+        );
         Ok(handle)
     }
 }
@@ -62,28 +97,8 @@ impl TcpRouter {
     /// Handle any [`TcpRouterRequest::Register`] messages received by
     /// this node's worker
     async fn handle_register(&mut self, accepts: Vec<Address>, self_addr: Address) -> Result<()> {
-        if let Some(f) = accepts.first().cloned() {
-            trace!("TCP registration request: {} => {}", f, self_addr);
-        } else {
-            error!("TCP registration request failed due to an invalid address list. Please provide at least one valid Address.");
-            return Err(TransportError::InvalidAddress.into());
-        }
-
-        for accept in &accepts {
-            if self.map.contains_key(accept) {
-                error!(
-                    "TCP registration request failed, this address is already connected: {}",
-                    accept
-                );
-                return Err(TransportError::AlreadyConnected.into());
-            }
-        }
-
-        for accept in accepts {
-            self.map.insert(accept.clone(), self_addr.clone());
-        }
-
-        Ok(())
+        trace!("TCP registration request: {:?} => {}", accepts, self_addr);
+        self.map.register(accepts, self_addr)
     }
 
     /// Handle any [`TcpRouterRequest::Unregister`] messages received by
@@ -91,10 +106,38 @@ impl TcpRouter {
     async fn handle_unregister(&mut self, self_addr: Address) -> Result<()> {
         trace!("TCP unregistration request: {}", &self_addr);
 
-        self.map.retain(|_, self_addr_i| self_addr_i != &self_addr);
+        self.map.unregister(&self_addr);
+
+        Ok(())
+    }
+
+    /// Drop all registrations whose connection worker has stopped
+    ///
+    /// A connection worker that is stopped without unregistering,
+    /// e.g. when it was stopped by its address directly, would
+    /// otherwise stay registered and keep receiving messages it can
+    /// no longer handle.
+    async fn sweep(&mut self) -> Result<()> {
+        let workers: BTreeSet<Address> = self.ctx.list_workers().await?.into_iter().collect();
+
+        self.map.retain(|accept, self_addr| {
+            let running = workers.contains(self_addr);
+            if !running {
+                debug!(
+                    "Dropping stale TCP registration: {} => {}",
+                    accept, self_addr
+                );
+            }
+            running
+        });
 
         Ok(())
     }
+
+    /// Check whether the connection worker `self_addr` is still running
+    async fn is_running(&self, self_addr: &Address) -> Result<bool> {
+        Ok(self.ctx.list_workers().await?.contains(self_addr))
+    }
 }
 
 impl TcpRouter {
@@ -104,9 +147,21 @@ impl TcpRouter {
     /// This handler starts a `(TcpSendWorker, TcpRecvProcessor)` pair
     /// that open and manage a connection to the given peer and
     /// finally register the given peer with this `TcpRouter`.
+    ///
+    /// If a running connection to the peer already exists its sender
+    /// address is returned instead.  Connect requests are handled one at a
+    /// time by this worker, so two simultaneous requests for the same
+    /// peer can not both open a connection.
     async fn handle_connect(&mut self, peer: String) -> Result<Address> {
         // Resolve peer address
-        let (peer_addr, hostnames) = TcpRouterHandle::resolve_peer(peer)?;
+        let (peer_addr, hostnames) = self.resolver.resolve_peer(&peer)?;
+
+        // Never hand out the address of a connection that has stopped
+        self.sweep().await?;
+        if let Some(existing) = self.existing_connection(peer_addr, &hostnames) {
+            debug!("Reusing existing connection to peer {}", peer_addr);
+            return Ok(existing);
+        }
 
         // Start a new `WorkerPair` for the given peer containing a
         // `TcpSendWorker` and `TcpRecvprocessor`
@@ -130,7 +185,7 @@ impl TcpRouter {
     /// Handle any [`TcpRouterRequest::Disconnect`] messages received by this
     /// nodes worker
     async fn handle_disconnect(&mut self, peer: String) -> Result<()> {
-        let (peer_addr, _hostnames) = TcpRouterHandle::resolve_peer(peer)?;
+        let (peer_addr, _hostnames) = self.resolver.resolve_peer(&peer)?;
         let tcp_address: Address = format!("{}#{}", TCP, peer_addr).into();
 
         let self_address = if let Some(self_address) = self.map.get(&tcp_address) {
@@ -162,11 +217,7 @@ impl TcpRouter {
         let next = self.resolve_route(onward).await?;
 
         // Modify the transport message route
-        let _ = msg.transport_mut().onward_route.step()?;
-        msg.transport_mut()
-            .onward_route
-            .modify()
-            .prepend(next.clone());
+        route_to_connection(&mut msg, next.clone())?;
 
         // Send the transport message to the connection worker
         ctx.send(next.clone(), msg).await?;
@@ -176,25 +227,25 @@ impl TcpRouter {
 
     /// Resolve the route to the provided onward address
     async fn resolve_route(&mut self, onward: &Address) -> Result<Address> {
-        // Check if the connection already exists
-        if let Some(n) = self.map.get(onward) {
-            return Ok(n.clone());
+        // Check if the connection already exists and is still running
+        if let Some(n) = self.map.get(onward).cloned() {
+            if self.is_running(&n).await? {
+                return Ok(n);
+            }
+            self.sweep().await?;
         }
 
         // Try resolve a tcp address for the onward address
         let peer =
             String::from_utf8(onward.deref().clone()).map_err(|_| TransportError::UnknownRoute)?;
-        let (peer_addr, hostnames) = TcpRouterHandle::resolve_peer(peer.clone())?;
-        let tcp_address = Address::new(TCP, peer_addr.to_string());
+        let (peer_addr, hostnames) = self.resolver.resolve_peer(&peer)?;
 
         // Check for existing connection under different name
-        if let Some(n) = self.map.get(&tcp_address).cloned() {
-            // Add new aliases for existing connection
-            for accept in hostnames.iter().map(|x| Address::new(TCP, x)) {
-                self.map.insert(accept, n.clone());
+        if let Some(n) = self.existing_connection(peer_addr, &hostnames) {
+            if self.is_running(&n).await? {
+                return Ok(n);
             }
-
-            return Ok(n);
+            self.sweep().await?;
         }
 
         // No existing connection
@@ -208,6 +259,26 @@ impl TcpRouter {
             Err(TransportError::UnknownRoute.into())
         }
     }
+
+    /// Return the sender address of an existing connection to `peer_addr`
+    ///
+    /// Any `hostnames` not yet known are added as aliases for the
+    /// existing connection.
+    fn existing_connection(
+        &mut self,
+        peer_addr: SocketAddr,
+        hostnames: &[String],
+    ) -> Option<Address> {
+        let tcp_address = Address::new(TCP, peer_addr.to_string());
+        let n = self.map.get(&tcp_address).cloned()?;
+
+        // Add new aliases for existing connection
+        for accept in hostnames.iter().map(|x| Address::new(TCP, x)) {
+            self.map.alias(accept, n.clone());
+        }
+
+        Some(n)
+    }
 }
 
 #[async_trait]
@@ -217,6 +288,9 @@ impl Worker for TcpRouter {
 
     async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
         ctx.set_cluster(crate::CLUSTER_NAME).await?;
+        let mut sweep_timer = DelayedEvent::create(ctx, self.sweep_addr.clone(), ()).await?;
+        sweep_timer.schedule(SWEEP_INTERVAL).await?;
+        self.sweep_timer = Some(sweep_timer);
         Ok(())
     }
 
@@ -226,7 +300,11 @@ impl Worker for TcpRouter {
 
         if msg_addr == self.main_addr {
             let msg = LocalMessage::decode(msg.payload())?;
-            self.handle_route(ctx, msg).await?;
+            let report_request = DeliveryReportRequest::find_info(&msg);
+            if let Err(e) = self.handle_route(ctx, msg).await {
+                report_delivery(ctx, report_request, delivery_status(&e)).await;
+                return Err(e);
+            }
         } else if msg_addr == self.api_addr {
             let msg = TcpRouterRequest::decode(msg.payload())?;
             match msg {
@@ -255,6 +333,11 @@ impl Worker for TcpRouter {
                         .await?;
                 }
             };
+        } else if msg_addr == self.sweep_addr {
+            self.sweep().await?;
+            if let Some(sweep_timer) = &mut self.sweep_timer {
+                sweep_timer.schedule(SWEEP_INTERVAL).await?;
+            }
         } else {
             error!(
                 "TCP router received a message for an invalid address: {}",
@@ -266,3 +349,15 @@ impl Worker for TcpRouter {
         Ok(())
     }
 }
+
+/// Return the delivery status to report when routing a message failed
+///
+/// Failing to reach the peer, e.g. because the connection was refused
+/// or its sender stopped in the meantime, may go away when sending
+/// again.  All other failures, e.g. malformed routes, are final.
+fn delivery_status(e: &Error) -> DeliveryStatus {
+    match e.code().kind {
+        Kind::Io | Kind::NotFound | Kind::Timeout => DeliveryStatus::Unavailable(e.to_string()),
+        _ => DeliveryStatus::Rejected(e.to_string()),
+    }
+}
@@ -1,7 +1,9 @@
-use crate::{parse_socket_addr, TcpOutletListenWorker, TcpRouter, TcpRouterHandle};
+use crate::{parse_socket_addr, TcpOutletListenWorker, TcpRouter, TcpRouterHandle, TCP};
+use core::time::Duration;
 use ockam_core::compat::boxed::Box;
-use ockam_core::{Address, AsyncTryClone, Result, Route};
+use ockam_core::{async_trait, Address, AsyncTryClone, Result, Route, TransportType};
 use ockam_node::Context;
+use ockam_transport_core::{Compression, ResolutionCache, RetryPolicy, Transport};
 
 /// High level management interface for TCP transports
 ///
@@ -59,7 +61,93 @@ impl TcpTransport {
     /// # Ok(()) }
     /// ```
     pub async fn create(ctx: &Context) -> Result<Self> {
-        let router = TcpRouter::register(ctx).await?;
+        let router =
+            TcpRouter::register(ctx, None, ResolutionCache::default(), RetryPolicy::new(0)).await?;
+
+        Ok(Self {
+            router_handle: router,
+        })
+    }
+
+    /// Create a new TCP transport whose connections compress large frames
+    ///
+    /// Frames are only compressed once the peer announced that it
+    /// accepts compressed frames.  Unless `compression` is
+    /// [`announcing`](Compression::announcing), connections only ever
+    /// answer the peer's announcement, so peers without compression
+    /// support can still be connected to.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{Compression, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create_with_compression(&ctx, Compression::default()).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_with_compression(ctx: &Context, compression: Compression) -> Result<Self> {
+        let router = TcpRouter::register(
+            ctx,
+            Some(compression),
+            ResolutionCache::default(),
+            RetryPolicy::new(0),
+        )
+        .await?;
+
+        Ok(Self {
+            router_handle: router,
+        })
+    }
+
+    /// Create a new TCP transport which reuses hostname resolutions
+    ///
+    /// Successful resolutions are reused for `ttl`, failed ones are
+    /// remembered for `negative_ttl`.  [`create`](Self::create) uses
+    /// [`DEFAULT_RESOLUTION_TTL`](ockam_transport_core::DEFAULT_RESOLUTION_TTL)
+    /// and [`DEFAULT_NEGATIVE_RESOLUTION_TTL`](ockam_transport_core::DEFAULT_NEGATIVE_RESOLUTION_TTL).
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::TcpTransport;
+    /// # use core::time::Duration;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let ttl = Duration::from_secs(300);
+    /// let negative_ttl = Duration::from_secs(1);
+    /// let tcp = TcpTransport::create_with_resolution_ttl(&ctx, ttl, negative_ttl).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_with_resolution_ttl(
+        ctx: &Context,
+        ttl: Duration,
+        negative_ttl: Duration,
+    ) -> Result<Self> {
+        let resolver = ResolutionCache::new(ttl, negative_ttl);
+        let router = TcpRouter::register(ctx, None, resolver, RetryPolicy::new(0)).await?;
+
+        Ok(Self {
+            router_handle: router,
+        })
+    }
+
+    /// Create a new TCP transport which retries failed connection attempts
+    ///
+    /// Every connection retries opening its socket with a fresh copy
+    /// of `policy`, e.g. while the peer is restarting.  The other
+    /// constructors don't retry.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::TcpTransport;
+    /// # use core::time::Duration;
+    /// # use ockam_core::{Result, RetryPolicy};
+    /// # use ockam_node::Context;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let policy = RetryPolicy::new(5).initial_delay(Duration::from_millis(100));
+    /// let tcp = TcpTransport::create_with_retry_policy(&ctx, policy).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_with_retry_policy(ctx: &Context, policy: RetryPolicy) -> Result<Self> {
+        let router = TcpRouter::register(ctx, None, ResolutionCache::default(), policy).await?;
 
         Ok(Self {
             router_handle: router,
@@ -105,6 +193,31 @@ impl TcpTransport {
     }
 }
 
+#[async_trait]
+impl Transport for TcpTransport {
+    fn transport_type(&self) -> TransportType {
+        TCP
+    }
+
+    async fn bind(&self, addr: String) -> Result<()> {
+        self.listen(addr).await
+    }
+
+    async fn connect(&self, peer: String) -> Result<Address> {
+        TcpTransport::connect(self, peer).await
+    }
+
+    async fn disconnect(&self, peer: String) -> Result<()> {
+        TcpTransport::disconnect(self, peer).await
+    }
+
+    async fn register(&self, accepts: Vec<Address>, self_addr: Address) -> Result<()> {
+        self.router_handle
+            .register_accepts(accepts, self_addr)
+            .await
+    }
+}
+
 impl TcpTransport {
     /// Create Tcp Inlet that listens on bind_addr, transforms Tcp stream into Ockam Routable
     /// Messages and forward them to Outlet using outlet_route. Inlet is bidirectional: Ockam
@@ -2,7 +2,7 @@ use crate::TcpSendWorkerMsg;
 use ockam_core::async_trait;
 use ockam_core::{Address, Decodable, LocalMessage, Processor, Result, TransportMessage};
 use ockam_node::Context;
-use ockam_transport_core::TransportError;
+use ockam_transport_core::{decode_frame_header, Compression, TransportError, FRAME_HEADER_LENGTH};
 use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
 use tracing::{error, info, trace};
 
@@ -18,17 +18,34 @@ pub(crate) struct TcpRecvProcessor {
     rx: OwnedReadHalf,
     peer_addr: Address,
     sender_internal_address: Address,
+    compression: Option<Compression>,
 }
 
 impl TcpRecvProcessor {
     /// Create a new `TcpRecvProcessor`
-    pub fn new(rx: OwnedReadHalf, peer_addr: Address, sender_internal_address: Address) -> Self {
+    pub fn new(
+        rx: OwnedReadHalf,
+        peer_addr: Address,
+        sender_internal_address: Address,
+        compression: Option<Compression>,
+    ) -> Self {
         Self {
             rx,
             peer_addr,
             sender_internal_address,
+            compression,
         }
     }
+
+    /// Notify the sender that the connection was closed
+    async fn notify_closed(&self, ctx: &Context) -> Result<()> {
+        self.notify_sender(ctx, TcpSendWorkerMsg::ConnectionClosed)
+            .await
+    }
+
+    async fn notify_sender(&self, ctx: &Context, msg: TcpSendWorkerMsg) -> Result<()> {
+        ctx.send(self.sender_internal_address.clone(), msg).await
+    }
 }
 
 #[async_trait]
@@ -53,21 +70,18 @@ impl Processor for TcpRecvProcessor {
     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
         // Run in a loop until TcpWorkerPair::stop() is called
         // First read a message length header...
-        let len = match self.rx.read_u16().await {
-            Ok(len) => len,
+        //
+        // `read_exact` keeps reading until the buffer is full, so
+        // frames split across any number of reads are reassembled.
+        let mut header = [0; FRAME_HEADER_LENGTH];
+        let len = match self.rx.read_exact(&mut header).await {
+            Ok(_) => decode_frame_header(header),
             Err(_e) => {
                 info!(
                     "Connection to peer '{}' was closed; dropping stream",
                     self.peer_addr
                 );
-
-                // Notify sender tx is closed
-                ctx.send(
-                    self.sender_internal_address.clone(),
-                    TcpSendWorkerMsg::ConnectionClosed,
-                )
-                .await?;
-
+                self.notify_closed(ctx).await?;
                 return Ok(false);
             }
         };
@@ -75,23 +89,53 @@ impl Processor for TcpRecvProcessor {
         trace!("Received message header for {} bytes", len);
 
         // Allocate a buffer of that size
-        let mut buf = vec![0; len as usize];
+        let mut buf = vec![0; len];
 
         // Then read into the buffer
-        match self.rx.read_exact(&mut buf).await {
-            Ok(_) => {}
-            _ => {
-                error!("Failed to receive message of length: {}", len);
-                return Ok(true);
-            }
+        //
+        // A frame cut short leaves the stream at an unknown position,
+        // so we can't carry on reading from it
+        if let Err(e) = self.rx.read_exact(&mut buf).await {
+            error!(
+                "Failed to receive message of length {} from peer '{}': {}",
+                len, self.peer_addr, e
+            );
+            self.notify_closed(ctx).await?;
+            return Ok(false);
+        }
+
+        // Undo the compression, if enabled.  A peer only compresses
+        // frames after we announced compression.
+        if let Some(compression) = &self.compression {
+            buf = match compression.decompress(&buf) {
+                Ok(buf) => buf,
+                Err(e) => {
+                    error!(
+                        "Failed to decompress message from peer '{}': {}",
+                        self.peer_addr, e
+                    );
+                    self.notify_closed(ctx).await?;
+                    return Ok(false);
+                }
+            };
         }
 
         // Deserialize the message now
         let mut msg = TransportMessage::decode(&buf).map_err(|_| TransportError::RecvBadMessage)?;
 
-        // Heartbeat message
+        // Heartbeat or compression announcement, there is nothing to
+        // forward
         if msg.onward_route.next().is_err() {
-            trace!("Got heartbeat message from: {}", self.peer_addr);
+            if Compression::is_announcement(&msg) {
+                trace!("Got compression announcement from: {}", self.peer_addr);
+                if self.compression.is_some() {
+                    self.notify_sender(ctx, TcpSendWorkerMsg::PeerCompresses)
+                        .await?;
+                }
+            } else {
+                trace!("Got heartbeat message from: {}", self.peer_addr);
+            }
+            return Ok(true);
         }
 
         // Insert the peer address into the return route so that
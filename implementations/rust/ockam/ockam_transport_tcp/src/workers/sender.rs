@@ -1,15 +1,17 @@
 use crate::{TcpRecvProcessor, TcpRouterHandle};
 use core::time::Duration;
-use ockam_core::{async_trait, route, Any, Decodable, LocalMessage};
+use ockam_core::{
+    async_trait, route, Any, Decodable, DeliveryReportRequest, DeliveryStatus, LocalMessage,
+};
 use ockam_core::{Address, Encodable, Message, Result, Routed, TransportMessage, Worker};
 use ockam_node::{Context, DelayedEvent};
-use ockam_transport_core::TransportError;
+use ockam_transport_core::{encode_frame, Compression, RetryPolicy, TransportError};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use tokio::io::AsyncWriteExt;
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
-use tracing::{debug, trace, warn};
+use tracing::{debug, debug_span, trace, warn, Instrument, Span};
 
 /// Provides the transmit and receive parts of a TCP connection
 #[derive(Debug)]
@@ -40,6 +42,8 @@ impl WorkerPair {
 pub(crate) enum TcpSendWorkerMsg {
     Heartbeat,
     ConnectionClosed,
+    /// The peer announced that it accepts compressed frames
+    PeerCompresses,
 }
 
 /// A TCP sending message worker
@@ -57,8 +61,18 @@ pub(crate) struct TcpSendWorker {
     peer: SocketAddr,
     internal_addr: Address,
     rx_addr: Option<Address>,
-    heartbeat: DelayedEvent<TcpSendWorkerMsg>,
+    /// Created on initialization, so that it belongs to this worker
+    heartbeat: Option<DelayedEvent<TcpSendWorkerMsg>>,
     heartbeat_interval: Option<Duration>,
+    compression: Option<Compression>,
+    /// Retries of a failed attempt to open the connection
+    connect_policy: RetryPolicy,
+    /// Whether the peer announced that it accepts compressed frames
+    peer_compresses: bool,
+    /// Whether we announced that we accept compressed frames
+    announced: bool,
+    /// Span carrying the peer and tx address of this connection
+    span: Span,
 }
 
 impl TcpSendWorker {
@@ -67,8 +81,8 @@ impl TcpSendWorker {
         router_handle: TcpRouterHandle,
         stream: Option<TcpStream>,
         peer: SocketAddr,
+        tx_addr: &Address,
         internal_addr: Address,
-        heartbeat: DelayedEvent<TcpSendWorkerMsg>,
     ) -> Self {
         let (rx, tx) = match stream {
             Some(s) => {
@@ -79,14 +93,19 @@ impl TcpSendWorker {
         };
 
         Self {
+            compression: router_handle.compression(),
+            connect_policy: router_handle.connect_policy(),
             router_handle,
             rx,
             tx,
             peer,
             internal_addr,
             rx_addr: None,
-            heartbeat,
+            heartbeat: None,
             heartbeat_interval: Some(Duration::from_secs(5 * 60)),
+            peer_compresses: false,
+            announced: false,
+            span: debug_span!("tcp_sender", peer = %peer, tx_addr = %tx_addr),
         }
     }
 
@@ -103,16 +122,17 @@ impl TcpSendWorker {
 
         let tx_addr = Address::random_local();
         let internal_addr = Address::random_local();
-        let sender = TcpSendWorker::new(
-            router_handle,
-            stream,
-            peer,
-            internal_addr.clone(),
-            DelayedEvent::create(ctx, internal_addr.clone(), TcpSendWorkerMsg::Heartbeat).await?,
-        );
+        let sender =
+            TcpSendWorker::new(router_handle, stream, peer, &tx_addr, internal_addr.clone());
 
-        ctx.start_worker(vec![tx_addr.clone(), internal_addr], sender)
-            .await?;
+        // Heartbeats and connection-closed notifications are delivered
+        // ahead of any queued outgoing data
+        ctx.start_worker_with_priority(
+            vec![tx_addr.clone(), internal_addr.clone()],
+            internal_addr,
+            sender,
+        )
+        .await?;
 
         // Return a handle to the worker pair
         Ok(WorkerPair {
@@ -124,12 +144,34 @@ impl TcpSendWorker {
 
     /// Schedule a heartbeat
     async fn schedule_heartbeat(&mut self) -> Result<()> {
-        let heartbeat_interval = match &self.heartbeat_interval {
-            Some(hi) => *hi,
-            None => return Ok(()),
-        };
+        match (&mut self.heartbeat, self.heartbeat_interval) {
+            (Some(heartbeat), Some(interval)) => heartbeat.schedule(interval).await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Shut down the write half of the connection, if it is still open
+    ///
+    /// Writes are not buffered, so everything written so far has
+    /// already been handed to the OS.  This closes the write half at a
+    /// known point, and logs failures, instead of leaving it to drop.
+    async fn close_tx(&mut self) {
+        if let Some(mut tx) = self.tx.take() {
+            if let Err(e) = tx.shutdown().await {
+                debug!(
+                    "Failed to shut down connection to peer {}: {}",
+                    self.peer, e
+                );
+            }
+        }
+    }
 
-        self.heartbeat.schedule(heartbeat_interval).await
+    /// Return the compression to use for frames sent to the peer
+    ///
+    /// Frames are only compressed once the peer announced that it
+    /// accepts compressed frames.
+    fn frame_compression(&self) -> Option<Compression> {
+        self.compression.filter(|_| self.peer_compresses)
     }
 
     async fn stop_and_unregister(&self, ctx: &Context) -> Result<()> {
@@ -141,21 +183,34 @@ impl TcpSendWorker {
     }
 }
 
-#[async_trait]
-impl Worker for TcpSendWorker {
-    type Context = Context;
-    type Message = Any;
-
-    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+impl TcpSendWorker {
+    async fn initialize_impl(&mut self, ctx: &mut Context) -> Result<()> {
         ctx.set_cluster(crate::CLUSTER_NAME).await?;
 
+        let heartbeat =
+            DelayedEvent::create(ctx, self.internal_addr.clone(), TcpSendWorkerMsg::Heartbeat)
+                .await?;
+        self.heartbeat = Some(heartbeat);
+
         if self.tx.is_none() {
-            let connection = match TcpStream::connect(self.peer).await {
-                Ok(c) => c,
-                Err(e) => {
-                    self.stop_and_unregister(ctx).await?;
+            let connection = loop {
+                let e = match TcpStream::connect(self.peer).await {
+                    Ok(c) => break c,
+                    Err(e) => e,
+                };
+                match self.connect_policy.next_delay() {
+                    Some(delay) => {
+                        debug!(
+                            "Failed to connect to {}, retrying in {:?}",
+                            self.peer, delay
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                    None => {
+                        self.stop_and_unregister(ctx).await?;
 
-                    return Err(TransportError::from(e).into());
+                        return Err(TransportError::from(e).into());
+                    }
                 }
             };
             let (rx, tx) = connection.into_split();
@@ -170,17 +225,33 @@ impl Worker for TcpSendWorker {
             rx,
             format!("{}#{}", crate::TCP, self.peer).into(),
             self.internal_addr.clone(),
+            self.compression,
         );
         ctx.start_processor(rx_addr.clone(), receiver).await?;
 
         self.rx_addr = Some(rx_addr);
 
+        // Tell the peer that we accept compressed frames, if it is
+        // known to understand the announcement.  Otherwise we wait for
+        // the peer to announce compression first.
+        if self.compression.map_or(false, |c| c.announces()) {
+            let msg = prepare_message(Compression::announcement(), None)?;
+            let tx = self.tx.as_mut().ok_or(TransportError::GenericIo)?;
+            if tx.write_all(&msg).await.is_err() {
+                warn!("Failed to announce compression to peer {}", self.peer);
+            }
+            self.announced = true;
+        }
+
         self.schedule_heartbeat().await?;
 
         Ok(())
     }
 
-    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+    async fn shutdown_impl(&mut self, ctx: &mut Context) -> Result<()> {
+        // Close the write half before the receiver stops
+        self.close_tx().await;
+
         if let Some(rx_addr) = self.rx_addr.take() {
             let _ = ctx.stop_processor(rx_addr).await;
         }
@@ -188,28 +259,34 @@ impl Worker for TcpSendWorker {
         Ok(())
     }
 
-    // TcpSendWorker will receive messages from the TcpRouter to send
-    // across the TcpStream to our friend
-    async fn handle_message(
-        &mut self,
-        ctx: &mut Context,
-        msg: Routed<Self::Message>,
-    ) -> Result<()> {
-        self.heartbeat.cancel();
+    async fn handle_message_impl(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        if let Some(heartbeat) = &mut self.heartbeat {
+            heartbeat.cancel();
+        }
 
+        let recipient = msg.msg_addr();
+        let compression = self.frame_compression();
         let tx = match &mut self.tx {
             Some(tx) => tx,
-            None => return Err(TransportError::PeerNotFound.into()),
+            None => {
+                if recipient != self.internal_addr {
+                    let request = LocalMessage::decode(msg.payload())
+                        .ok()
+                        .and_then(|m| DeliveryReportRequest::find_info(&m));
+                    let reason = format!("no connection to peer {}", self.peer);
+                    report_delivery(ctx, request, DeliveryStatus::Unavailable(reason)).await;
+                }
+                return Err(TransportError::PeerNotFound.into());
+            }
         };
 
-        let recipient = msg.msg_addr();
         if recipient == self.internal_addr {
             let msg = TcpSendWorkerMsg::decode(msg.payload())?;
 
             match msg {
                 TcpSendWorkerMsg::Heartbeat => {
                     let msg = TransportMessage::v1(route![], route![], vec![]);
-                    let msg = prepare_message(msg)?;
+                    let msg = prepare_message(msg, compression)?;
                     // Sending empty heartbeat
                     if tx.write_all(&msg).await.is_err() {
                         warn!("Failed to send heartbeat to peer {}", self.peer);
@@ -225,25 +302,47 @@ impl Worker for TcpSendWorker {
                     // No need to stop Receiver as it notified us about connection drop and will
                     // stop itself
                     self.rx_addr = None;
+                    self.close_tx().await;
                     self.stop_and_unregister(ctx).await?;
 
                     return Ok(());
                 }
+                TcpSendWorkerMsg::PeerCompresses => {
+                    debug!("Peer {} accepts compressed frames", self.peer);
+                    self.peer_compresses = true;
+
+                    // The peer understands announcements, so answer
+                    // with ours
+                    if !self.announced {
+                        let msg = prepare_message(Compression::announcement(), None)?;
+                        if tx.write_all(&msg).await.is_err() {
+                            warn!("Failed to announce compression to peer {}", self.peer);
+                        }
+                        self.announced = true;
+                    }
+                }
             }
         } else {
-            let mut msg = LocalMessage::decode(msg.payload())?.into_transport_message();
+            let local_msg = LocalMessage::decode(msg.payload())?;
+            let report_request = DeliveryReportRequest::find_info(&local_msg);
+            let mut msg = local_msg.into_transport_message();
             // Remove our own address from the route so the other end
             // knows what to do with the incoming message
             msg.onward_route.step()?;
             // Create a message buffer with pre-pended length
-            let msg = prepare_message(msg)?;
+            let msg = prepare_message(msg, compression)?;
+            trace!("Sending {} bytes", msg.len());
 
             if tx.write_all(msg.as_slice()).await.is_err() {
                 warn!("Failed to send message to peer {}", self.peer);
+                let reason = format!("failed to write to peer {}", self.peer);
+                report_delivery(ctx, report_request, DeliveryStatus::Unavailable(reason)).await;
                 self.stop_and_unregister(ctx).await?;
 
                 return Ok(());
             }
+
+            report_delivery(ctx, report_request, DeliveryStatus::Sent).await;
         }
 
         self.schedule_heartbeat().await?;
@@ -252,26 +351,54 @@ impl Worker for TcpSendWorker {
     }
 }
 
-/// Helper that creates a length-prefixed buffer containing the given
-/// `TransportMessage`'s payload
-///
-/// The length-prefix is encoded as a big-endian 16-bit unsigned
-/// integer.
-fn prepare_message(msg: TransportMessage) -> Result<Vec<u8>> {
-    let mut msg_buf = msg.encode().map_err(|_| TransportError::SendBadMessage)?;
+#[async_trait]
+impl Worker for TcpSendWorker {
+    type Context = Context;
+    type Message = Any;
 
-    // Create a buffer that includes the message length in big endian
-    let mut len = (msg_buf.len() as u16).to_be_bytes().to_vec();
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        let span = self.span.clone();
+        self.initialize_impl(ctx).instrument(span).await
+    }
 
-    // Fun fact: reversing a vector in place, appending the length,
-    // and then reversing it again is faster for large message sizes
-    // than adding the large chunk of data.
-    //
-    // https://play.rust-lang.org/?version=stable&mode=release&edition=2018&gist=8669a640004ac85c7be38b19e3e73dcb
-    msg_buf.reverse();
-    len.reverse();
-    msg_buf.append(&mut len);
-    msg_buf.reverse();
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        let span = self.span.clone();
+        self.shutdown_impl(ctx).instrument(span).await
+    }
 
-    Ok(msg_buf)
+    // TcpSendWorker will receive messages from the TcpRouter to send
+    // across the TcpStream to our friend
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let span = self.span.clone();
+        self.handle_message_impl(ctx, msg).instrument(span).await
+    }
+}
+
+/// Helper that creates a length-prefixed buffer containing the given
+/// `TransportMessage`'s payload, compressed if enabled
+fn prepare_message(msg: TransportMessage, compression: Option<Compression>) -> Result<Vec<u8>> {
+    let mut msg_buf = msg.encode().map_err(|_| TransportError::SendBadMessage)?;
+    if let Some(compression) = compression {
+        msg_buf = compression.compress(msg_buf)?;
+    }
+    encode_frame(msg_buf)
+}
+
+/// Send a [`DeliveryReport`](ockam_core::DeliveryReport) if the sender
+/// of a message asked for one
+pub(crate) async fn report_delivery(
+    ctx: &Context,
+    request: Option<DeliveryReportRequest>,
+    status: DeliveryStatus,
+) {
+    if let Some(request) = request {
+        let report = request.report(status);
+        if let Err(e) = ctx.send(request.report_to().clone(), report).await {
+            debug!("Failed to send delivery report: {}", e);
+        }
+    }
 }
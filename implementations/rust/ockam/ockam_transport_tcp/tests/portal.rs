@@ -1,7 +1,8 @@
-use ockam_core::{route, Result};
+use ockam_core::{route, Address, Message, Result};
 use ockam_node::Context;
 use ockam_transport_tcp::TcpTransport;
 use rand::{random, Rng};
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
@@ -93,3 +94,109 @@ async fn portal__reverse_flow__should_succeed(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+/// Portal messages of an Inlet which predates version negotiation
+#[derive(Serialize, Deserialize, Message, Debug, PartialEq)]
+enum LegacyPortalMessage {
+    Ping,
+    Pong,
+    Disconnect,
+    Payload(Vec<u8>),
+}
+
+/// Portal messages of an Inlet which negotiates versions
+#[derive(Serialize, Deserialize, Message, Debug, PartialEq)]
+enum VersionedPortalMessage {
+    Ping,
+    Pong,
+    Disconnect,
+    Payload(Vec<u8>),
+    VersionedPing(u8),
+    VersionedPong(u8),
+    IncompatibleVersion { min_version: u8, max_version: u8 },
+}
+
+async fn setup_outlet(ctx: &Context) -> Result<TcpListener> {
+    let target_port = rand::thread_rng().gen_range(10000, 65535);
+    let target_addr = format!("127.0.0.1:{}", target_port);
+    let listener = TcpListener::bind(target_addr.clone()).await.unwrap();
+
+    let tcp = TcpTransport::create(ctx).await?;
+    tcp.create_outlet("outlet", target_addr).await?;
+
+    Ok(listener)
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__legacy_inlet__should_succeed(ctx: &mut Context) -> Result<()> {
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let listener = setup_outlet(ctx).await?;
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        read_assert_binary(&mut stream, payload1).await;
+        write_binary(&mut stream, payload2).await;
+    });
+
+    let mut inlet = ctx.new_context(Address::random_local()).await?;
+    inlet
+        .send(route!["outlet"], LegacyPortalMessage::Ping)
+        .await?;
+
+    let pong = inlet.receive::<LegacyPortalMessage>().await?.take();
+    let outlet_route = pong.return_route();
+    assert_eq!(pong.body(), LegacyPortalMessage::Pong);
+
+    inlet
+        .send(
+            outlet_route,
+            LegacyPortalMessage::Payload(payload1.to_vec()),
+        )
+        .await?;
+    let reply = inlet.receive::<LegacyPortalMessage>().await?.take().body();
+    assert_eq!(reply, LegacyPortalMessage::Payload(payload2.to_vec()));
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__newer_inlet__should_use_outlet_version(ctx: &mut Context) -> Result<()> {
+    let _listener = setup_outlet(ctx).await?;
+
+    let mut inlet = ctx.new_context(Address::random_local()).await?;
+    inlet
+        .send(route!["outlet"], VersionedPortalMessage::VersionedPing(2))
+        .await?;
+
+    // The Outlet speaks version 1, which needs no version in the pong
+    let pong = inlet.receive::<VersionedPortalMessage>().await?.take();
+    assert_eq!(pong.body(), VersionedPortalMessage::Pong);
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__incompatible_inlet__should_be_rejected(ctx: &mut Context) -> Result<()> {
+    let _listener = setup_outlet(ctx).await?;
+
+    let mut inlet = ctx.new_context(Address::random_local()).await?;
+    inlet
+        .send(route!["outlet"], VersionedPortalMessage::VersionedPing(0))
+        .await?;
+
+    let reply = inlet.receive::<VersionedPortalMessage>().await?.take();
+    assert_eq!(
+        reply.body(),
+        VersionedPortalMessage::IncompatibleVersion {
+            min_version: 1,
+            max_version: 1,
+        }
+    );
+
+    ctx.stop().await
+}
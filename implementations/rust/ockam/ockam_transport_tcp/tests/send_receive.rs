@@ -1,10 +1,19 @@
 use core::iter;
+use core::time::Duration;
 
-use ockam_core::{route, Address, Result, Routed, Worker};
+use ockam_core::errcode::Kind;
+use ockam_core::{
+    route, Address, Decodable, Encodable, LocalMessage, Result, RetryPolicy, Routed,
+    TransportMessage, Worker,
+};
 use ockam_node::Context;
+use ockam_transport_core::{decode_frame_header, encode_frame, FRAME_HEADER_LENGTH};
 use rand::Rng;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::sleep;
 
-use ockam_transport_tcp::{TcpTransport, TCP};
+use ockam_transport_tcp::{Compression, TcpTransport, TCP};
 
 #[ockam_macros::test]
 async fn send_receive(ctx: &mut Context) -> Result<()> {
@@ -40,6 +49,128 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+#[ockam_macros::test]
+async fn send_receive_compressed(ctx: &mut Context) -> Result<()> {
+    let rand_port = rand::thread_rng().gen_range(10000, 65535);
+    let bind_address = format!("127.0.0.1:{}", rand_port);
+
+    let compression = Compression::default().announcing();
+    let transport = TcpTransport::create_with_compression(ctx, compression).await?;
+    transport.listen(&bind_address).await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    // Compression is negotiated while the first messages are exchanged
+    let mut child_ctx = ctx.new_context(Address::random_local()).await?;
+    let r = route![(TCP, bind_address), "echoer"];
+    child_ctx.send(r.clone(), "Hello".to_string()).await?;
+    child_ctx.receive::<String>().await?;
+
+    // Too large for a single uncompressed frame
+    let msg = "ockam ".repeat(20_000);
+    child_ctx.send(r, msg.clone()).await?;
+
+    let reply = child_ctx.receive::<String>().await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    ctx.stop().await
+}
+
+/// Read a single frame from a raw connection
+async fn read_frame(stream: &mut TcpStream) -> Vec<u8> {
+    let mut header = [0; FRAME_HEADER_LENGTH];
+    stream.read_exact(&mut header).await.unwrap();
+    let mut buf = vec![0; decode_frame_header(header)];
+    stream.read_exact(&mut buf).await.unwrap();
+    buf
+}
+
+/// Exchange a message with a compressing node over a raw connection,
+/// optionally announcing compression first, and return the reply frame
+async fn echo_over_raw_connection(ctx: &mut Context, announce: bool) -> Result<(String, Vec<u8>)> {
+    let rand_port = rand::thread_rng().gen_range(10000, 65535);
+    let bind_address = format!("127.0.0.1:{}", rand_port);
+
+    let transport = TcpTransport::create_with_compression(ctx, Compression::default()).await?;
+    transport.listen(&bind_address).await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let mut stream = TcpStream::connect(&bind_address).await.unwrap();
+    if announce {
+        let frame = encode_frame(Compression::announcement().encode()?)?;
+        stream.write_all(&frame).await.unwrap();
+    }
+
+    // Large enough to be compressed
+    let msg = "ockam ".repeat(200);
+    let transport_msg = TransportMessage::v1(route!["echoer"], route!["sender"], msg.encode()?);
+    let frame = encode_frame(transport_msg.encode()?)?;
+    stream.write_all(&frame).await.unwrap();
+
+    // The node answers our announcement before sending anything else
+    if announce {
+        let announcement = TransportMessage::decode(&read_frame(&mut stream).await)?;
+        assert!(Compression::is_announcement(&announcement));
+    }
+
+    Ok((msg, read_frame(&mut stream).await))
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_compression__peer_without_compression__should_interoperate(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (msg, reply) = echo_over_raw_connection(ctx, false).await?;
+
+    // The peer never announced compression, so the node neither
+    // announced compression nor compressed the reply
+    let reply = TransportMessage::decode(&reply)?;
+    assert_eq!(reply.onward_route, route!["sender"]);
+    assert_eq!(String::decode(&reply.payload)?, msg);
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_compression__announcing_peer__should_receive_compressed_frames(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (msg, reply) = echo_over_raw_connection(ctx, true).await?;
+
+    let decompressed = Compression::default().decompress(&reply)?;
+    assert!(reply.len() < decompressed.len());
+    let reply = TransportMessage::decode(&decompressed)?;
+    assert_eq!(String::decode(&reply.payload)?, msg);
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_compression__receiver_without_compression__should_get_routable_frames(
+    ctx: &mut Context,
+) -> Result<()> {
+    // Stands in for a peer which doesn't know about compression, and
+    // can't route frames with an empty onward route
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+
+    let transport = TcpTransport::create_with_compression(ctx, Compression::default()).await?;
+    transport.connect(&bind_address).await?;
+
+    let msg = "ockam ".repeat(200);
+    ctx.send(route![(TCP, bind_address), "echoer"], msg.clone())
+        .await?;
+
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let received = TransportMessage::decode(&read_frame(&mut stream).await)?;
+    assert_eq!(received.onward_route, route!["echoer"]);
+    assert_eq!(String::decode(&received.payload)?, msg);
+
+    ctx.stop().await
+}
+
 pub struct Echoer;
 
 #[ockam_core::worker]
@@ -108,3 +239,244 @@ async fn tcp_lifecycle__reconnect__should_not_error(ctx: &mut Context) -> Result
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_connect__same_peer_twice__should_reuse_connection(ctx: &mut Context) -> Result<()> {
+    let rand_port = rand::thread_rng().gen_range(10000, 65535);
+    let bind_address = format!("127.0.0.1:{}", rand_port);
+    let bind_address = bind_address.as_str();
+
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    transport.listen(bind_address).await?;
+
+    let first = transport.connect(bind_address).await?;
+    let second = transport.connect(bind_address).await?;
+    assert_eq!(first, second, "Should reuse the existing connection");
+
+    // The same peer reached through its hostname is also deduplicated
+    let third = transport
+        .connect(format!("localhost:{}", rand_port))
+        .await?;
+    assert_eq!(first, third, "Should reuse the existing connection");
+
+    let mut child_ctx = ctx.new_context(Address::random_local()).await?;
+    child_ctx
+        .send(route![(TCP, bind_address), "echoer"], "Hello".to_string())
+        .await?;
+    let reply = child_ctx.receive::<String>().await?.take().body();
+    assert_eq!(reply, "Hello", "Should receive the same message");
+
+    ctx.stop().await?;
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_connect__stopped_sender__should_not_be_reused(ctx: &mut Context) -> Result<()> {
+    let rand_port = rand::thread_rng().gen_range(10000, 65535);
+    let bind_address = format!("127.0.0.1:{}", rand_port);
+    let bind_address = bind_address.as_str();
+
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    transport.listen(bind_address).await?;
+
+    // Stop the sender without unregistering it from the router
+    let first = transport.connect(bind_address).await?;
+    ctx.stop_worker(first.clone()).await?;
+
+    let second = transport.connect(bind_address).await?;
+    assert_ne!(first, second, "Should not reuse the stopped connection");
+
+    let mut child_ctx = ctx.new_context(Address::random_local()).await?;
+    child_ctx
+        .send(route![(TCP, bind_address), "echoer"], "Hello".to_string())
+        .await?;
+    let reply = child_ctx.receive::<String>().await?.take().body();
+    assert_eq!(reply, "Hello", "Should receive the same message");
+
+    ctx.stop().await?;
+
+    Ok(())
+}
+
+/// Stopping the sender right after handing it a message must not
+/// lose the message
+///
+/// The sender does not buffer writes, so this checks the order of
+/// sending and stopping, not the shutdown of the write half.
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_sender__stopped_after_send__should_deliver(ctx: &mut Context) -> Result<()> {
+    let rand_port = rand::thread_rng().gen_range(10000, 65535);
+    let bind_address = format!("127.0.0.1:{}", rand_port);
+    let bind_address = bind_address.as_str();
+
+    let transport = TcpTransport::create(ctx).await?;
+    transport.listen(bind_address).await?;
+    let tx_address = transport.connect(bind_address).await?;
+
+    let msg: String = {
+        let mut rng = rand::thread_rng();
+        iter::repeat(())
+            .map(|()| rng.sample(&rand::distributions::Alphanumeric))
+            .take(32 * 1024)
+            .collect()
+    };
+
+    // Hand the message to the sender as the router would; it loops
+    // back into this node over the connection
+    let mut child_ctx = ctx.new_context(Address::random_local()).await?;
+    let onward = route![tx_address.clone(), child_ctx.address()];
+    let transport_msg = TransportMessage::v1(onward, route![child_ctx.address()], msg.encode()?);
+    child_ctx
+        .send(tx_address.clone(), LocalMessage::new(transport_msg, vec![]))
+        .await?;
+    ctx.stop_worker(tx_address).await?;
+
+    let reply = child_ctx.receive::<String>().await?.take().body();
+    assert_eq!(reply, msg, "Should receive the full message");
+
+    ctx.stop().await?;
+
+    Ok(())
+}
+
+/// Write a single frame to a raw connection, `chunk` bytes at a time
+async fn send_frame_in_chunks(ctx: &mut Context, chunk: usize) -> Result<()> {
+    let rand_port = rand::thread_rng().gen_range(10000, 65535);
+    let bind_address = format!("127.0.0.1:{}", rand_port);
+
+    let transport = TcpTransport::create(ctx).await?;
+    transport.listen(&bind_address).await?;
+
+    let mut child_ctx = ctx.new_context(Address::random_local()).await?;
+    let msg = "ockam ".repeat(20);
+    let transport_msg =
+        TransportMessage::v1(route![child_ctx.address()], route!["sender"], msg.encode()?);
+    let frame = encode_frame(transport_msg.encode()?)?;
+
+    let mut stream = TcpStream::connect(&bind_address).await.unwrap();
+    stream.set_nodelay(true).unwrap();
+    for bytes in frame.chunks(chunk) {
+        stream.write_all(bytes).await.unwrap();
+        stream.flush().await.unwrap();
+        sleep(Duration::from_millis(1)).await;
+    }
+
+    let reply = child_ctx.receive::<String>().await?.take();
+    assert_eq!(reply.return_route().recipient(), "sender".into());
+    assert_eq!(reply.body(), msg, "Should receive the full message");
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_receiver__frame_in_single_bytes__should_reassemble(ctx: &mut Context) -> Result<()> {
+    send_frame_in_chunks(ctx, 1).await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_receiver__frame_in_odd_chunks__should_reassemble(ctx: &mut Context) -> Result<()> {
+    send_frame_in_chunks(ctx, 7).await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_send_with_retry__refused_connection__should_retry(ctx: &mut Context) -> Result<()> {
+    // Reserve a port nobody listens on
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    drop(listener);
+
+    TcpTransport::create(ctx).await?;
+
+    let policy = RetryPolicy::new(2).initial_delay(Duration::from_millis(10));
+    let err = ctx
+        .send_with_retry(
+            route![(TCP, bind_address), "echoer"],
+            "Hello".to_string(),
+            policy,
+        )
+        .await
+        .unwrap_err();
+
+    // Rejected messages fail with `Kind::Invalid` without being retried
+    assert_eq!(err.code().kind, Kind::Io);
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_send_with_retry__stopped_sender__should_reconnect(ctx: &mut Context) -> Result<()> {
+    let rand_port = rand::thread_rng().gen_range(10000, 65535);
+    let bind_address = format!("127.0.0.1:{}", rand_port);
+    let bind_address = bind_address.as_str();
+
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    transport.listen(bind_address).await?;
+
+    // Stop the sender without unregistering it from the router
+    let tx_address = transport.connect(bind_address).await?;
+    ctx.stop_worker(tx_address).await?;
+
+    // The stopped sender is not used, so the first attempt succeeds
+    let mut child_ctx = ctx.new_context(Address::random_local()).await?;
+    let policy = RetryPolicy::new(0);
+    child_ctx
+        .send_with_retry(
+            route![(TCP, bind_address), "echoer"],
+            "Hello".to_string(),
+            policy,
+        )
+        .await?;
+
+    let reply = child_ctx.receive::<String>().await?.take().body();
+    assert_eq!(reply, "Hello", "Should receive the same message");
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_connect__peer_not_yet_listening__should_retry(ctx: &mut Context) -> Result<()> {
+    // Reserve a port nobody listens on yet
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    drop(listener);
+
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let policy = RetryPolicy::new(20)
+        .initial_delay(Duration::from_millis(50))
+        .multiplier(1.0);
+    let transport = TcpTransport::create_with_retry_policy(ctx, policy).await?;
+    let tx_address = transport.connect(&bind_address).await?;
+
+    // The connection is only opened after a few failed attempts
+    sleep(Duration::from_millis(200)).await;
+    transport.listen(&bind_address).await?;
+
+    // Without retries the first sender would have stopped by now
+    assert_eq!(tx_address, transport.connect(&bind_address).await?);
+
+    let mut child_ctx = ctx.new_context(Address::random_local()).await?;
+    child_ctx
+        .send(route![(TCP, bind_address), "echoer"], "Hello".to_string())
+        .await?;
+
+    let reply = child_ctx.receive::<String>().await?.take().body();
+    assert_eq!(reply, "Hello", "Should receive the same message");
+
+    ctx.stop().await
+}
@@ -0,0 +1,87 @@
+use ockam_core::{route, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpTransport, TCP};
+use rand::Rng;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tracing::Level;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Collects everything the fmt subscriber writes
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Capture {
+    type Writer = Capture;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+#[allow(non_snake_case)]
+#[test]
+fn tcp_sender__send_message__events_should_carry_peer() {
+    // Must be installed before the node sets up its own subscriber
+    let capture = Capture::default();
+    tracing_subscriber::fmt()
+        .with_max_level(Level::TRACE)
+        .with_ansi(false)
+        .with_writer(capture.clone())
+        .init();
+
+    let rand_port = rand::thread_rng().gen_range(10000, 65535);
+    let bind_address = format!("127.0.0.1:{}", rand_port);
+
+    let (mut ctx, mut executor) = ockam_node::start_node();
+    executor
+        .execute({
+            let bind_address = bind_address.clone();
+            async move {
+                let transport = TcpTransport::create(&ctx).await?;
+                transport.listen(&bind_address).await?;
+                ctx.start_worker("echoer", Echoer).await?;
+
+                let r = route![(TCP, bind_address), "echoer"];
+                ctx.send(r, "Hello".to_string()).await?;
+                assert_eq!(ctx.receive::<String>().await?, "Hello".to_string());
+
+                ctx.stop().await
+            }
+        })
+        .unwrap()
+        .unwrap();
+
+    let logs = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    let peer = format!("peer={}", bind_address);
+    assert!(
+        logs.lines()
+            .any(|l| l.contains("tcp_sender{") && l.contains(&peer) && l.contains("Sending")),
+        "no sender event for {} in:\n{}",
+        peer,
+        logs
+    );
+}
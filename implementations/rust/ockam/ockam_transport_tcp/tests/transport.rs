@@ -0,0 +1,175 @@
+use std::sync::{Arc, Mutex};
+
+use ockam_core::{
+    async_trait, route, Address, Any, Decodable, Encodable, LocalMessage, Result, Routed,
+    TransportMessage, TransportType, Worker,
+};
+use ockam_node::Context;
+use ockam_transport_core::{
+    decode_frame_header, encode_frame, start_router, ConnectionMap, Transport, TransportError,
+    FRAME_HEADER_LENGTH,
+};
+use rand::Rng;
+
+use ockam_transport_tcp::TcpTransport;
+
+/// Address type of the loopback transport
+const LOOPBACK: TransportType = TransportType::new(100);
+
+type SharedConnectionMap = Arc<Mutex<ConnectionMap>>;
+
+/// A trivial transport whose connections deliver every message back
+/// into the local node
+struct LoopbackTransport {
+    ctx: Context,
+    map: SharedConnectionMap,
+}
+
+impl LoopbackTransport {
+    async fn create(ctx: &Context) -> Result<Self> {
+        let map = SharedConnectionMap::default();
+        let router = LoopbackRouter { map: map.clone() };
+        start_router(ctx, LOOPBACK, vec![Address::random_local()], router).await?;
+
+        Ok(Self {
+            ctx: ctx.new_context(Address::random_local()).await?,
+            map,
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for LoopbackTransport {
+    fn transport_type(&self) -> TransportType {
+        LOOPBACK
+    }
+
+    async fn bind(&self, _addr: String) -> Result<()> {
+        Ok(())
+    }
+
+    async fn connect(&self, peer: String) -> Result<Address> {
+        let self_addr = Address::random_local();
+        self.ctx
+            .start_worker(self_addr.clone(), LoopbackConnection)
+            .await?;
+        self.register(vec![Address::new(LOOPBACK, peer)], self_addr.clone())
+            .await?;
+        Ok(self_addr)
+    }
+
+    async fn disconnect(&self, peer: String) -> Result<()> {
+        let self_addr = {
+            let mut map = self.map.lock().unwrap();
+            let self_addr = map
+                .get(&Address::new(LOOPBACK, peer))
+                .cloned()
+                .ok_or(TransportError::PeerNotFound)?;
+            map.unregister(&self_addr);
+            self_addr
+        };
+
+        self.ctx.stop_worker(self_addr).await
+    }
+
+    async fn register(&self, accepts: Vec<Address>, self_addr: Address) -> Result<()> {
+        self.map.lock().unwrap().register(accepts, self_addr)
+    }
+}
+
+/// Hands messages for loopback addresses to their connection worker
+struct LoopbackRouter {
+    map: SharedConnectionMap,
+}
+
+#[async_trait]
+impl Worker for LoopbackRouter {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let mut msg = LocalMessage::decode(msg.payload())?;
+        let next = self.map.lock().unwrap().route(&mut msg)?;
+        ctx.send(next, msg).await
+    }
+}
+
+/// Frames every message as it would be sent over the wire, then
+/// unframes it again as the peer would
+struct LoopbackConnection;
+
+#[async_trait]
+impl Worker for LoopbackConnection {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let mut msg = LocalMessage::decode(msg.payload())?.into_transport_message();
+        msg.onward_route.step()?;
+
+        let frame = encode_frame(msg.encode()?)?;
+        let (header, payload) = frame.split_at(FRAME_HEADER_LENGTH);
+        let len = decode_frame_header([header[0], header[1]]);
+        assert_eq!(len, payload.len());
+
+        let msg = TransportMessage::decode(payload)?;
+        ctx.forward(LocalMessage::new(msg, Vec::new())).await
+    }
+}
+
+struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+/// Send a message through any transport to the local echoer and back
+async fn echo_through<T: Transport>(
+    ctx: &mut Context,
+    transport: &T,
+    bind_addr: &str,
+    peer: &str,
+) -> Result<()> {
+    transport.bind(bind_addr.into()).await?;
+    transport.connect(peer.into()).await?;
+
+    let mut child_ctx = ctx.new_context(Address::random_local()).await?;
+    let r = route![Address::new(transport.transport_type(), peer), "echoer"];
+    child_ctx.send(r, "Hello".to_string()).await?;
+
+    let reply = child_ctx.receive::<String>().await?;
+    assert_eq!(reply, "Hello".to_string());
+
+    transport.disconnect(peer.into()).await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn transport__loopback__should_echo(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = LoopbackTransport::create(ctx).await?;
+    echo_through(ctx, &transport, "", "peer").await?;
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn transport__tcp__should_echo(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let rand_port = rand::thread_rng().gen_range(10000, 65535);
+    let bind_address = format!("127.0.0.1:{}", rand_port);
+
+    let transport = TcpTransport::create(ctx).await?;
+    echo_through(ctx, &transport, &bind_address, &bind_address).await?;
+
+    ctx.stop().await
+}
@@ -27,10 +27,8 @@ extern crate core;
 #[macro_use]
 extern crate tracing;
 
-use std::net::SocketAddr;
-
-use ockam_core::{Result, TransportType};
-use ockam_transport_core::TransportError;
+use ockam_core::TransportType;
+use ockam_transport_core::parse_socket_addr;
 pub use transport::*;
 
 use crate::router::{WebSocketRouter, WebSocketRouterHandle};
@@ -44,9 +42,3 @@ mod workers;
 pub const WS: TransportType = TransportType::new(3);
 
 pub(crate) const CLUSTER_NAME: &str = "_internals.transport.ws";
-
-fn parse_socket_addr<S: AsRef<str>>(s: S) -> Result<SocketAddr> {
-    Ok(s.as_ref()
-        .parse()
-        .map_err(|_| TransportError::InvalidAddress)?)
-}
@@ -1,13 +1,13 @@
 use core::str::FromStr;
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::net::SocketAddr;
 
 use ockam_core::{async_trait, Address, AsyncTryClone, Result};
 use ockam_node::Context;
-use ockam_transport_core::TransportError;
+use ockam_transport_core::ResolutionCache;
 
 use crate::router::WebSocketRouterMessage;
 use crate::workers::{WebSocketListenProcessor, WorkerPair};
-use crate::{parse_socket_addr, WebSocketAddress};
+use crate::WebSocketAddress;
 
 /// A handle to connect to a WebSocketRouter.
 ///
@@ -15,19 +15,28 @@ use crate::{parse_socket_addr, WebSocketAddress};
 pub(crate) struct WebSocketRouterHandle {
     ctx: Context,
     api_addr: Address,
+    resolver: ResolutionCache,
 }
 
 #[async_trait]
 impl AsyncTryClone for WebSocketRouterHandle {
     async fn async_try_clone(&self) -> Result<Self> {
         let child_ctx = self.ctx.new_context(Address::random_local()).await?;
-        Ok(Self::new(child_ctx, self.api_addr.clone()))
+        Ok(Self::new(
+            child_ctx,
+            self.api_addr.clone(),
+            self.resolver.clone(),
+        ))
     }
 }
 
 impl WebSocketRouterHandle {
-    pub(crate) fn new(ctx: Context, api_addr: Address) -> Self {
-        Self { ctx, api_addr }
+    pub(crate) fn new(ctx: Context, api_addr: Address, resolver: ResolutionCache) -> Self {
+        Self {
+            ctx,
+            api_addr,
+            resolver,
+        }
     }
 
     /// Register a new connection worker with this router.
@@ -55,38 +64,10 @@ impl WebSocketRouterHandle {
         WebSocketListenProcessor::start(&self.ctx, self.async_try_clone().await?, socket_addr).await
     }
 
-    /// Return the peer's `SocketAddr` and `hostnames` given a plain `String` address.
-    pub(crate) fn resolve_peer(peer: impl Into<String>) -> Result<(SocketAddr, Vec<String>)> {
-        let peer_str = peer.into();
-        let peer_addr;
-        let hostnames;
-
-        // Try to parse as SocketAddr
-        if let Ok(p) = parse_socket_addr(peer_str.clone()) {
-            peer_addr = p;
-            hostnames = vec![];
-        }
-        // Try to resolve hostname
-        else if let Ok(mut iter) = peer_str.to_socket_addrs() {
-            // FIXME: We only take ipv4 for now
-            if let Some(p) = iter.find(|x| x.is_ipv4()) {
-                peer_addr = p;
-            } else {
-                return Err(TransportError::InvalidAddress.into());
-            }
-
-            hostnames = vec![peer_str];
-        } else {
-            return Err(TransportError::InvalidAddress.into());
-        }
-
-        Ok((peer_addr, hostnames))
-    }
-
     /// Establish an outgoing WS connection on an existing transport.
     pub(crate) async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
         // Get peer address and connect to it.
-        let (peer_addr, hostnames) = Self::resolve_peer(peer.as_ref())?;
+        let (peer_addr, hostnames) = self.resolver.resolve_peer(peer.as_ref())?;
 
         // Create a new `WorkerPair` for the given peer, initializing a new pair
         // of sender worker and receiver processor.
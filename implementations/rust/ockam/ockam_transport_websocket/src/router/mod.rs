@@ -1,5 +1,4 @@
 use core::str::FromStr;
-use std::collections::BTreeMap;
 use std::ops::Deref;
 
 pub(crate) use handle::WebSocketRouterHandle;
@@ -7,7 +6,9 @@ use ockam_core::{
     async_trait, Address, Any, Decodable, LocalMessage, Message, Result, Routed, Worker,
 };
 use ockam_node::Context;
-use ockam_transport_core::TransportError;
+use ockam_transport_core::{
+    route_to_connection, start_router, ConnectionMap, ResolutionCache, TransportError,
+};
 
 use crate::workers::WorkerPair;
 use crate::{WebSocketAddress, WS};
@@ -38,13 +39,19 @@ pub(crate) struct WebSocketRouter {
     ctx: Context,
     main_addr: Address,
     api_addr: Address,
-    map: BTreeMap<Address, Address>,
+    map: ConnectionMap,
     allow_auto_connection: bool,
+    resolver: ResolutionCache,
 }
 
 impl WebSocketRouter {
     /// Create and register a new WebSocket router with the node context.
-    pub(crate) async fn register(ctx: &Context) -> Result<WebSocketRouterHandle> {
+    ///
+    /// Peer hostnames are looked up through `resolver`.
+    pub(crate) async fn register(
+        ctx: &Context,
+        resolver: ResolutionCache,
+    ) -> Result<WebSocketRouterHandle> {
         let main_addr = Address::random_local();
         let api_addr = Address::random_local();
         debug!(
@@ -60,23 +67,22 @@ impl WebSocketRouter {
             ctx: child_ctx,
             main_addr: main_addr.clone(),
             api_addr: api_addr.clone(),
-            map: BTreeMap::new(),
+            map: ConnectionMap::default(),
             allow_auto_connection: true,
+            resolver,
         };
 
         let handle = router.create_self_handle(ctx).await?;
 
-        ctx.start_worker(vec![main_addr.clone(), api_addr], router)
-            .await?;
-        trace!("Registering WS router for type = {}", WS);
-        ctx.register(WS, main_addr).await?;
+        start_router(ctx, WS, vec![main_addr, api_addr], router).await?;
 
         Ok(handle)
     }
 
     async fn create_self_handle(&self, ctx: &Context) -> Result<WebSocketRouterHandle> {
         let handle_ctx = ctx.new_context(Address::random_local()).await?;
-        let handle = WebSocketRouterHandle::new(handle_ctx, self.api_addr.clone());
+        let handle =
+            WebSocketRouterHandle::new(handle_ctx, self.api_addr.clone(), self.resolver.clone());
         Ok(handle)
     }
 }
@@ -143,12 +149,8 @@ impl WebSocketRouter {
             }
         }
 
-        let _ = msg.transport_mut().onward_route.step()?;
         // Modify the transport message route
-        msg.transport_mut()
-            .onward_route
-            .modify()
-            .prepend(next.clone());
+        route_to_connection(&mut msg, next.clone())?;
 
         // Send the transport message to the connection worker
         ctx.send(next.clone(), msg).await?;
@@ -157,34 +159,13 @@ impl WebSocketRouter {
     }
 
     async fn handle_register(&mut self, accepts: Vec<Address>, self_addr: Address) -> Result<()> {
-        // The `accepts` vector should always contain at least one address.
-        if let Some(f) = accepts.first().cloned() {
-            trace!("WS registration request: {} => {}", f, self_addr);
-        }
-        // Otherwise, the router is not being used properly and returns an error.
-        else {
-            error!("Tried to register a new client without passing any `Address`");
-            return Err(TransportError::InvalidAddress.into());
-        }
-
-        // Do not connect twice.
-        for accept in &accepts {
-            if self.map.contains_key(accept) {
-                return Err(TransportError::AlreadyConnected.into());
-            }
-        }
-
-        // Add a new entry for each hostname/address pair.
-        for accept in accepts {
-            self.map.insert(accept.clone(), self_addr.clone());
-        }
-
-        Ok(())
+        trace!("WS registration request: {:?} => {}", accepts, self_addr);
+        self.map.register(accepts, self_addr)
     }
 
     async fn connect(&mut self, peer: String) -> Result<Address> {
         // Get peer address and connect to it.
-        let (peer_addr, hostnames) = WebSocketRouterHandle::resolve_peer(peer)?;
+        let (peer_addr, hostnames) = self.resolver.resolve_peer(&peer)?;
 
         // Create a new `WorkerPair` for the given peer, initializing a new pair
         // of sender worker and receiver processor.
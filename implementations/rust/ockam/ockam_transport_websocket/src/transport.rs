@@ -1,9 +1,11 @@
 use core::fmt;
+use core::time::Duration;
 use std::net::SocketAddr;
 use std::str::FromStr;
 
 use ockam_core::{Address, Result};
 use ockam_node::Context;
+use ockam_transport_core::ResolutionCache;
 
 use crate::{parse_socket_addr, WebSocketRouter, WebSocketRouterHandle, WS};
 
@@ -61,7 +63,35 @@ impl WebSocketTransport {
     /// # Ok(()) }
     /// ```
     pub async fn create(ctx: &Context) -> Result<WebSocketTransport> {
-        let router_handle = WebSocketRouter::register(ctx).await?;
+        let router_handle = WebSocketRouter::register(ctx, ResolutionCache::default()).await?;
+        Ok(Self { router_handle })
+    }
+
+    /// Create a new WebSocket transport which reuses hostname resolutions.
+    ///
+    /// Successful resolutions are reused for `ttl`, failed ones are
+    /// remembered for `negative_ttl`.  [`create`](Self::create) uses
+    /// [`DEFAULT_RESOLUTION_TTL`](ockam_transport_core::DEFAULT_RESOLUTION_TTL)
+    /// and [`DEFAULT_NEGATIVE_RESOLUTION_TTL`](ockam_transport_core::DEFAULT_NEGATIVE_RESOLUTION_TTL).
+    ///
+    /// ```rust
+    /// use ockam_transport_websocket::WebSocketTransport;
+    /// # use core::time::Duration;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let ttl = Duration::from_secs(300);
+    /// let negative_ttl = Duration::from_secs(1);
+    /// let ws = WebSocketTransport::create_with_resolution_ttl(&ctx, ttl, negative_ttl).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_with_resolution_ttl(
+        ctx: &Context,
+        ttl: Duration,
+        negative_ttl: Duration,
+    ) -> Result<WebSocketTransport> {
+        let resolver = ResolutionCache::new(ttl, negative_ttl);
+        let router_handle = WebSocketRouter::register(ctx, resolver).await?;
         Ok(Self { router_handle })
     }
 
@@ -49,11 +49,7 @@ impl WorkerPair {
         trace!("Creating new WS worker pair");
 
         let internal_addr = Address::random_local();
-        let sender = WebSocketSendWorker::<TcpClientStream>::new(
-            peer,
-            internal_addr.clone(),
-            DelayedEvent::create(ctx, internal_addr.clone(), vec![]).await?,
-        );
+        let sender = WebSocketSendWorker::<TcpClientStream>::new(peer, internal_addr.clone());
 
         let tx_addr = Address::random_local();
         ctx.start_worker(vec![tx_addr.clone(), internal_addr], sender)
@@ -78,12 +74,8 @@ impl WorkerPair {
         trace!("Creating new WS worker pair");
 
         let internal_addr = Address::random_local();
-        let sender = WebSocketSendWorker::<TcpServerStream>::new(
-            stream,
-            peer,
-            internal_addr.clone(),
-            DelayedEvent::create(ctx, internal_addr.clone(), vec![]).await?,
-        );
+        let sender =
+            WebSocketSendWorker::<TcpServerStream>::new(stream, peer, internal_addr.clone());
 
         let tx_addr = Address::random_local();
         ctx.start_worker(vec![tx_addr.clone(), internal_addr], sender)
@@ -111,7 +103,8 @@ where
     ws_sink: Option<SplitSink<WebSocketStream<S>, WebSocketMessage>>,
     peer: SocketAddr,
     internal_addr: Address,
-    heartbeat: DelayedEvent<Vec<u8>>,
+    /// Created on initialization, so that it belongs to this worker
+    heartbeat: Option<DelayedEvent<Vec<u8>>>,
     heartbeat_interval: Option<Duration>,
 }
 
@@ -129,23 +122,25 @@ where
         }
 
         ctx.set_cluster(crate::CLUSTER_NAME).await?;
+        let heartbeat = DelayedEvent::create(ctx, self.internal_addr.clone(), vec![]).await?;
+        self.heartbeat = Some(heartbeat);
         self.schedule_heartbeat().await?;
         Ok(())
     }
 
     async fn schedule_heartbeat(&mut self) -> Result<()> {
-        let heartbeat_interval = match &self.heartbeat_interval {
-            Some(hi) => *hi,
-            None => return Ok(()),
-        };
-
-        self.heartbeat.schedule(heartbeat_interval).await
+        match (&mut self.heartbeat, self.heartbeat_interval) {
+            (Some(heartbeat), Some(interval)) => heartbeat.schedule(interval).await,
+            _ => Ok(()),
+        }
     }
 
     /// Receive messages from the `WebSocketRouter` to send
     /// across the `WebSocketStream` to the next remote peer.
     async fn handle_msg(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
-        self.heartbeat.cancel();
+        if let Some(heartbeat) = &mut self.heartbeat {
+            heartbeat.cancel();
+        }
 
         let ws_sink = if let Some(ws_sink) = &mut self.ws_sink {
             ws_sink
@@ -195,7 +190,6 @@ impl WebSocketSendWorker<TcpServerStream> {
         stream: WebSocketStream<TcpServerStream>,
         peer: SocketAddr,
         internal_addr: Address,
-        heartbeat: DelayedEvent<Vec<u8>>,
     ) -> Self {
         let (ws_sink, ws_stream) = stream.split();
         Self {
@@ -203,20 +197,20 @@ impl WebSocketSendWorker<TcpServerStream> {
             ws_stream: Some(ws_stream),
             peer,
             internal_addr,
-            heartbeat,
+            heartbeat: None,
             heartbeat_interval: None,
         }
     }
 }
 
 impl WebSocketSendWorker<TcpClientStream> {
-    fn new(peer: SocketAddr, internal_addr: Address, heartbeat: DelayedEvent<Vec<u8>>) -> Self {
+    fn new(peer: SocketAddr, internal_addr: Address) -> Self {
         Self {
             ws_stream: None,
             ws_sink: None,
             peer,
             internal_addr,
-            heartbeat,
+            heartbeat: None,
             heartbeat_interval: None,
         }
     }
@@ -0,0 +1,344 @@
+use crate::{Vault, VaultError};
+use aes_gcm::aead::{generic_array::GenericArray, Aead, NewAead, Payload};
+use aes_gcm::{Aes128Gcm, Aes256Gcm};
+use core::convert::TryFrom;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::vec::Vec;
+use ockam_core::vault::{
+    AeadStreamVault, Buffer, Secret, SecretType, AEAD_STREAM_CHUNK_LENGTH, AEAD_STREAM_SALT_LENGTH,
+    AEAD_STREAM_TAG_LENGTH, AES128_SECRET_LENGTH, AES256_SECRET_LENGTH, AES_GCM_NONCE_LENGTH,
+};
+use ockam_core::{async_trait, compat::boxed::Box, Result};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+/// HKDF info binding the derived keys to chunked AEAD payloads
+const AEAD_STREAM_INFO: &[u8] = b"ockam_aead_stream";
+
+/// Build the nonce of chunk `index`
+///
+/// Fails with `error` if there are too many chunks to number.
+fn chunk_nonce(index: usize, last: bool, error: VaultError) -> Result<[u8; AES_GCM_NONCE_LENGTH]> {
+    let index = u32::try_from(index).map_err(|_| error)?;
+
+    let index = index.to_be_bytes();
+
+    let mut nonce = [0u8; AES_GCM_NONCE_LENGTH];
+    nonce[AES_GCM_NONCE_LENGTH - 1 - index.len()..AES_GCM_NONCE_LENGTH - 1].copy_from_slice(&index);
+    nonce[AES_GCM_NONCE_LENGTH - 1] = last as u8;
+    Ok(nonce)
+}
+
+/// AES-GCM keyed with the subkey of a single chunked payload
+enum StreamCipher {
+    Aes128(Box<Aes128Gcm>),
+    Aes256(Box<Aes256Gcm>),
+}
+
+impl StreamCipher {
+    fn encrypt(&self, nonce: &[u8], payload: Payload) -> Result<Buffer<u8>> {
+        let nonce = GenericArray::from_slice(nonce);
+        match self {
+            StreamCipher::Aes128(cipher) => cipher.encrypt(nonce, payload),
+            StreamCipher::Aes256(cipher) => cipher.encrypt(nonce, payload),
+        }
+        .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
+    }
+
+    fn decrypt(&self, nonce: &[u8], payload: Payload) -> Result<Buffer<u8>> {
+        let nonce = GenericArray::from_slice(nonce);
+        match self {
+            StreamCipher::Aes128(cipher) => cipher.decrypt(nonce, payload),
+            StreamCipher::Aes256(cipher) => cipher.decrypt(nonce, payload),
+        }
+        .map_err(|_| VaultError::AeadAuthFailed.into())
+    }
+}
+
+/// Encrypts a chunked AEAD payload one chunk at a time.
+///
+/// Obtained from [`Vault::aead_stream_encryptor`]. The payload starts
+/// with the [`salt`](Self::salt), followed by the output of every
+/// [`encrypt_chunk`](Self::encrypt_chunk) call and finally that of
+/// [`finish`](Self::finish). Only one chunk has to be in memory at a
+/// time.
+pub struct AeadStreamEncryptor {
+    cipher: StreamCipher,
+    salt: [u8; AEAD_STREAM_SALT_LENGTH],
+    aad: Vec<u8>,
+    index: usize,
+}
+
+impl AeadStreamEncryptor {
+    /// The random salt which starts the payload.
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    /// Encrypt a chunk of exactly [`AEAD_STREAM_CHUNK_LENGTH`] bytes,
+    /// which is not the last one.
+    pub fn encrypt_chunk(&mut self, chunk: &[u8]) -> Result<Buffer<u8>> {
+        if chunk.len() != AEAD_STREAM_CHUNK_LENGTH {
+            return Err(VaultError::AeadAesGcmEncrypt.into());
+        }
+        let encrypted = self.encrypt(chunk, false)?;
+        self.index += 1;
+        Ok(encrypted)
+    }
+
+    /// Encrypt the last chunk, of at most [`AEAD_STREAM_CHUNK_LENGTH`]
+    /// bytes and possibly empty.
+    pub fn finish(self, chunk: &[u8]) -> Result<Buffer<u8>> {
+        if chunk.len() > AEAD_STREAM_CHUNK_LENGTH {
+            return Err(VaultError::AeadAesGcmEncrypt.into());
+        }
+        self.encrypt(chunk, true)
+    }
+
+    fn encrypt(&self, chunk: &[u8], last: bool) -> Result<Buffer<u8>> {
+        let nonce = chunk_nonce(self.index, last, VaultError::AeadAesGcmEncrypt)?;
+        let payload = Payload {
+            aad: &self.aad,
+            msg: chunk,
+        };
+        self.cipher.encrypt(&nonce, payload)
+    }
+}
+
+/// Decrypts a chunked AEAD payload one chunk at a time.
+///
+/// Obtained from [`Vault::aead_stream_decryptor`] with the salt which
+/// starts the payload. Every chunk but the last one is
+/// [`AEAD_STREAM_CHUNK_LENGTH`] + [`AEAD_STREAM_TAG_LENGTH`] bytes long
+/// and goes to [`decrypt_chunk`](Self::decrypt_chunk); the remaining
+/// bytes go to [`finish`](Self::finish). A payload that is never
+/// finished may have been truncated, so its plaintext must not be
+/// trusted before `finish` succeeds.
+pub struct AeadStreamDecryptor {
+    cipher: StreamCipher,
+    aad: Vec<u8>,
+    index: usize,
+}
+
+impl AeadStreamDecryptor {
+    /// Decrypt a chunk which is not the last one.
+    pub fn decrypt_chunk(&mut self, chunk: &[u8]) -> Result<Buffer<u8>> {
+        if chunk.len() != AEAD_STREAM_CHUNK_LENGTH + AEAD_STREAM_TAG_LENGTH {
+            return Err(VaultError::AeadAesGcmDecrypt.into());
+        }
+        let decrypted = self.decrypt(chunk, false)?;
+        self.index += 1;
+        Ok(decrypted)
+    }
+
+    /// Decrypt the last chunk.
+    pub fn finish(self, chunk: &[u8]) -> Result<Buffer<u8>> {
+        if chunk.len() > AEAD_STREAM_CHUNK_LENGTH + AEAD_STREAM_TAG_LENGTH {
+            return Err(VaultError::AeadAesGcmDecrypt.into());
+        }
+        self.decrypt(chunk, true)
+    }
+
+    fn decrypt(&self, chunk: &[u8], last: bool) -> Result<Buffer<u8>> {
+        let nonce = chunk_nonce(self.index, last, VaultError::AeadAesGcmDecrypt)?;
+        let payload = Payload {
+            aad: &self.aad,
+            msg: chunk,
+        };
+        self.cipher.decrypt(&nonce, payload)
+    }
+}
+
+impl Vault {
+    /// Start encrypting a chunked AEAD payload with the AES key
+    /// `context`.
+    pub async fn aead_stream_encryptor(
+        &self,
+        context: &Secret,
+        aad: &[u8],
+    ) -> Result<AeadStreamEncryptor> {
+        let mut salt = [0u8; AEAD_STREAM_SALT_LENGTH];
+        thread_rng().fill_bytes(&mut salt);
+        Ok(AeadStreamEncryptor {
+            cipher: self.stream_cipher(context, &salt).await?,
+            salt,
+            aad: aad.to_vec(),
+            index: 0,
+        })
+    }
+
+    /// Start decrypting a chunked AEAD payload which starts with `salt`
+    /// with the AES key `context`.
+    pub async fn aead_stream_decryptor(
+        &self,
+        context: &Secret,
+        salt: &[u8],
+        aad: &[u8],
+    ) -> Result<AeadStreamDecryptor> {
+        if salt.len() != AEAD_STREAM_SALT_LENGTH {
+            return Err(VaultError::AeadAesGcmDecrypt.into());
+        }
+        Ok(AeadStreamDecryptor {
+            cipher: self.stream_cipher(context, salt).await?,
+            aad: aad.to_vec(),
+            index: 0,
+        })
+    }
+
+    /// Derive the cipher of the payload starting with `salt` from the
+    /// AES key `context`
+    async fn stream_cipher(&self, context: &Secret, salt: &[u8]) -> Result<StreamCipher> {
+        let entries = self.data.entries.read().await;
+        let entry = entries
+            .get(&context.index())
+            .ok_or(VaultError::EntryNotFound)?;
+
+        if entry.key_attributes().stype() != SecretType::Aes {
+            return Err(VaultError::WrongSecretType.into());
+        }
+
+        let key = entry.key().as_ref();
+        let length = entry.key_attributes().length();
+        if key.len() != length {
+            return Err(VaultError::InvalidKeyLength.into());
+        }
+
+        let mut subkey = Zeroizing::new(vec![0u8; length]);
+        hkdf::Hkdf::<Sha256>::new(Some(salt), key)
+            .expand(AEAD_STREAM_INFO, subkey.as_mut_slice())
+            .map_err(|_| VaultError::HkdfExpandError)?;
+
+        match length {
+            AES128_SECRET_LENGTH => Ok(StreamCipher::Aes128(Box::new(Aes128Gcm::new(
+                GenericArray::from_slice(&subkey),
+            )))),
+            AES256_SECRET_LENGTH => Ok(StreamCipher::Aes256(Box::new(Aes256Gcm::new(
+                GenericArray::from_slice(&subkey),
+            )))),
+            _ => Err(VaultError::InvalidKeyLength.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl AeadStreamVault for Vault {
+    async fn aead_encrypt_stream(
+        &self,
+        context: &Secret,
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<Buffer<u8>> {
+        let mut encryptor = self.aead_stream_encryptor(context, aad).await?;
+
+        let chunks = (plaintext.len() / AEAD_STREAM_CHUNK_LENGTH) + 1;
+        let mut cipher_text = Buffer::with_capacity(
+            AEAD_STREAM_SALT_LENGTH + plaintext.len() + chunks * AEAD_STREAM_TAG_LENGTH,
+        );
+        cipher_text.extend_from_slice(encryptor.salt());
+
+        let mut remaining = plaintext;
+        while remaining.len() > AEAD_STREAM_CHUNK_LENGTH {
+            let (chunk, rest) = remaining.split_at(AEAD_STREAM_CHUNK_LENGTH);
+            cipher_text.extend_from_slice(&encryptor.encrypt_chunk(chunk)?);
+            remaining = rest;
+        }
+        cipher_text.extend_from_slice(&encryptor.finish(remaining)?);
+        Ok(cipher_text)
+    }
+
+    async fn aead_decrypt_stream(
+        &self,
+        context: &Secret,
+        cipher_text: &[u8],
+        aad: &[u8],
+    ) -> Result<Buffer<u8>> {
+        if cipher_text.len() < AEAD_STREAM_SALT_LENGTH + AEAD_STREAM_TAG_LENGTH {
+            return Err(VaultError::AeadAesGcmDecrypt.into());
+        }
+
+        let (salt, mut remaining) = cipher_text.split_at(AEAD_STREAM_SALT_LENGTH);
+        let mut decryptor = self.aead_stream_decryptor(context, salt, aad).await?;
+        let mut plaintext = Buffer::with_capacity(remaining.len());
+
+        let chunk_length = AEAD_STREAM_CHUNK_LENGTH + AEAD_STREAM_TAG_LENGTH;
+        while remaining.len() > chunk_length {
+            let (chunk, rest) = remaining.split_at(chunk_length);
+            plaintext.extend_from_slice(&decryptor.decrypt_chunk(chunk)?);
+            remaining = rest;
+        }
+        plaintext.extend_from_slice(&decryptor.finish(remaining)?);
+        Ok(plaintext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vault;
+    use ockam_core::vault::{
+        AeadStreamVault, SecretAttributes, SecretPersistence, SecretType, SecretVault,
+        AEAD_STREAM_CHUNK_LENGTH, AEAD_STREAM_SALT_LENGTH, AEAD_STREAM_TAG_LENGTH,
+        AES256_SECRET_LENGTH,
+    };
+
+    fn new_vault() -> Vault {
+        Vault::default()
+    }
+
+    #[ockam_macros::vault_test]
+    fn aead_stream() {}
+
+    #[ockam_macros::vault_test]
+    fn aead_stream_tampered() {}
+
+    #[tokio::test]
+    async fn aead_stream_chunk_by_chunk() {
+        let vault = new_vault();
+        let attributes = SecretAttributes::new(
+            SecretType::Aes,
+            SecretPersistence::Ephemeral,
+            AES256_SECRET_LENGTH,
+        );
+        let ctx = &vault.secret_generate(attributes).await.unwrap();
+        let aad = b"Extra payload data";
+        let message: Vec<u8> = (0..2 * AEAD_STREAM_CHUNK_LENGTH + 10)
+            .map(|i| i as u8)
+            .collect();
+        let (full, last) = message.split_at(2 * AEAD_STREAM_CHUNK_LENGTH);
+
+        // Encrypted in pieces, decrypted at once
+        let mut encryptor = vault.aead_stream_encryptor(ctx, aad).await.unwrap();
+        let mut ciphertext = encryptor.salt().to_vec();
+        for chunk in full.chunks(AEAD_STREAM_CHUNK_LENGTH) {
+            ciphertext.extend(encryptor.encrypt_chunk(chunk).unwrap());
+        }
+        ciphertext.extend(encryptor.finish(last).unwrap());
+        let plaintext = vault
+            .aead_decrypt_stream(ctx, &ciphertext, aad)
+            .await
+            .unwrap();
+        assert_eq!(plaintext, message);
+
+        // Encrypted at once, decrypted in pieces
+        let ciphertext = vault.aead_encrypt_stream(ctx, &message, aad).await.unwrap();
+        let (salt, mut remaining) = ciphertext.split_at(AEAD_STREAM_SALT_LENGTH);
+        let mut decryptor = vault.aead_stream_decryptor(ctx, salt, aad).await.unwrap();
+        let mut plaintext = Vec::new();
+        let chunk_length = AEAD_STREAM_CHUNK_LENGTH + AEAD_STREAM_TAG_LENGTH;
+        while remaining.len() > chunk_length {
+            let (chunk, rest) = remaining.split_at(chunk_length);
+            plaintext.extend(decryptor.decrypt_chunk(chunk).unwrap());
+            remaining = rest;
+        }
+        plaintext.extend(decryptor.finish(remaining).unwrap());
+        assert_eq!(plaintext, message);
+
+        // A chunk that is not the last one must be full
+        let mut encryptor = vault.aead_stream_encryptor(ctx, aad).await.unwrap();
+        assert!(encryptor.encrypt_chunk(last).is_err());
+
+        // A payload truncated after a full chunk can't be finished
+        let decryptor = vault.aead_stream_decryptor(ctx, salt, aad).await.unwrap();
+        let first = &ciphertext[AEAD_STREAM_SALT_LENGTH..AEAD_STREAM_SALT_LENGTH + chunk_length];
+        assert!(decryptor.finish(first).is_err());
+    }
+}
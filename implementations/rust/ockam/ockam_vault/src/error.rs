@@ -43,6 +43,14 @@ pub enum VaultError {
     InvalidBlsSecret,
     /// IO error when saving
     StorageError,
+    /// Invalid Ed25519 secret length
+    InvalidEd25519SecretLength,
+    /// Secret has the wrong type for this operation
+    WrongSecretType,
+    /// Key has an unsupported length for this operation
+    InvalidKeyLength,
+    /// AEAD authentication failed, e.g. the ciphertext was tampered with
+    AeadAuthFailed,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -67,6 +75,10 @@ impl core::fmt::Display for VaultError {
             Self::InvalidBlsSecretLength => write!(f, "invalid BLS secret length"),
             Self::InvalidBlsSecret => write!(f, "invalid BLS secret"),
             Self::StorageError => write!(f, "invalid storage"),
+            Self::InvalidEd25519SecretLength => write!(f, "invalid Ed25519 secret length"),
+            Self::WrongSecretType => write!(f, "wrong secret type"),
+            Self::InvalidKeyLength => write!(f, "invalid key length"),
+            Self::AeadAuthFailed => write!(f, "aead authentication failed"),
         }
     }
 }
@@ -82,7 +94,10 @@ impl From<VaultError> for Error {
             | InvalidAesKeyLength
             | InvalidHkdfOutputType
             | InvalidPrivateKeyLen
-            | InvalidX25519SecretLength => Kind::Misuse,
+            | InvalidX25519SecretLength
+            | InvalidEd25519SecretLength
+            | WrongSecretType
+            | InvalidKeyLength => Kind::Misuse,
             UnknownEcdhKeyType | EntryNotFound | SecretNotFound => Kind::NotFound,
             _ => Kind::Invalid,
         };
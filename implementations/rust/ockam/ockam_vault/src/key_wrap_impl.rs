@@ -0,0 +1,60 @@
+use crate::{Vault, VaultError};
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::vault::{
+    Buffer, KeyWrapVault, Secret, SecretAttributes, SecretVault, SymmetricVault,
+    AES_GCM_NONCE_LENGTH,
+};
+use ockam_core::{async_trait, compat::boxed::Box, Encodable, Result};
+use zeroize::Zeroizing;
+
+#[async_trait]
+impl KeyWrapVault for Vault {
+    async fn wrap_secret(&self, wrapping_key: &Secret, target: &Secret) -> Result<Buffer<u8>> {
+        let aad = self.secret_attributes_get(target).await?.encode()?;
+        let target = self.secret_export(target).await?;
+
+        let mut nonce = [0u8; AES_GCM_NONCE_LENGTH];
+        thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = self
+            .aead_aes_gcm_encrypt(wrapping_key, target.as_ref(), &nonce, &aad)
+            .await?;
+
+        let mut wrapped = Buffer::with_capacity(nonce.len() + ciphertext.len());
+        wrapped.extend_from_slice(&nonce);
+        wrapped.extend_from_slice(&ciphertext);
+        Ok(wrapped)
+    }
+
+    async fn unwrap_secret(
+        &self,
+        wrapping_key: &Secret,
+        wrapped: &[u8],
+        attributes: SecretAttributes,
+    ) -> Result<Secret> {
+        if wrapped.len() < AES_GCM_NONCE_LENGTH {
+            return Err(VaultError::AeadAesGcmDecrypt.into());
+        }
+
+        let (nonce, ciphertext) = wrapped.split_at(AES_GCM_NONCE_LENGTH);
+        let aad = attributes.encode()?;
+        let target = Zeroizing::new(
+            self.aead_aes_gcm_decrypt(wrapping_key, ciphertext, nonce, &aad)
+                .await?,
+        );
+
+        self.secret_import(&target, attributes).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vault;
+
+    fn new_vault() -> Vault {
+        Vault::default()
+    }
+
+    #[ockam_macros::vault_test]
+    fn wrap_secret() {}
+}
@@ -21,10 +21,12 @@ extern crate alloc;
 
 pub use ockam_core;
 
+mod aead_stream_impl;
 mod asymmetric_impl;
 mod error;
 mod hasher_impl;
 mod key_id_impl;
+mod key_wrap_impl;
 mod secret_impl;
 mod signer_impl;
 
@@ -41,6 +43,7 @@ pub use ockam_core::vault::{
     Hasher, KeyIdVault, PublicKey, Secret, SecretAttributes, SecretVault, Signer, Verifier,
 };
 
+pub use aead_stream_impl::*;
 pub use asymmetric_impl::*;
 pub use error::*;
 pub use hasher_impl::*;
@@ -8,7 +8,12 @@ use ockam_core::vault::{
     KeyId, KeyIdVault, PublicKey, Secret, SecretAttributes, SecretKey, SecretPersistence,
     SecretType, SecretVault, AES128_SECRET_LENGTH, AES256_SECRET_LENGTH, CURVE25519_SECRET_LENGTH,
 };
-use ockam_core::{async_trait, compat::boxed::Box, Result};
+use ockam_core::{
+    async_trait,
+    compat::{boxed::Box, vec::Vec},
+    Result,
+};
+use sha2::Sha256;
 cfg_if! {
     if #[cfg(feature = "bls")] {
         use signature_bbs_plus::PublicKey as BlsPublicKey;
@@ -16,6 +21,9 @@ cfg_if! {
     }
 }
 
+/// HKDF salt for secrets generated from a seed
+const SEED_SALT: &[u8] = b"ockam_vault secret_generate_from_seed";
+
 impl Vault {
     /// Compute key id from secret and attributes. Only Curve25519 and Buffer types are supported
     async fn compute_key_id(
@@ -86,13 +94,31 @@ impl Vault {
         }
         Ok(())
     }
-}
 
-#[async_trait]
-impl SecretVault for Vault {
-    /// Generate fresh secret. Only Curve25519 and Buffer types are supported
-    async fn secret_generate(&self, attributes: SecretAttributes) -> Result<Secret> {
-        let key = match attributes.stype() {
+    /// Validate that the requested length matches the secret type.
+    fn check_attributes(attributes: &SecretAttributes) -> Result<()> {
+        let length = attributes.length();
+        match attributes.stype() {
+            SecretType::X25519 if length != CURVE25519_SECRET_LENGTH => {
+                Err(VaultError::InvalidX25519SecretLength.into())
+            }
+            SecretType::Ed25519 if length != CURVE25519_SECRET_LENGTH => {
+                Err(VaultError::InvalidEd25519SecretLength.into())
+            }
+            SecretType::Aes if length != AES256_SECRET_LENGTH && length != AES128_SECRET_LENGTH => {
+                Err(VaultError::InvalidAesKeyLength.into())
+            }
+            #[cfg(feature = "bls")]
+            SecretType::Bls if length != BlsSecretKey::BYTES => {
+                Err(VaultError::InvalidBlsSecretLength.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Generate the key material for a fresh secret
+    fn generate_key(attributes: &SecretAttributes) -> Result<SecretKey> {
+        Ok(match attributes.stype() {
             SecretType::X25519 | SecretType::Ed25519 => {
                 let bytes = {
                     let mut rng = thread_rng();
@@ -117,11 +143,6 @@ impl SecretVault for Vault {
                 SecretKey::new(key)
             }
             SecretType::Aes => {
-                if attributes.length() != AES256_SECRET_LENGTH
-                    && attributes.length() != AES128_SECRET_LENGTH
-                {
-                    return Err(VaultError::InvalidAesKeyLength.into());
-                };
                 if attributes.persistence() != SecretPersistence::Ephemeral {
                     return Err(VaultError::InvalidKeyType.into());
                 };
@@ -141,7 +162,11 @@ impl SecretVault for Vault {
 
                 SecretKey::new(bls_secret_key.to_bytes().to_vec())
             }
-        };
+        })
+    }
+
+    /// Store `key` as a new vault entry and return its secret
+    async fn insert_secret(&self, key: SecretKey, attributes: SecretAttributes) -> Result<Secret> {
         let key_id = self.compute_key_id(key.as_ref(), &attributes).await?;
 
         let next_id = self.data.next_id.fetch_add(1, Ordering::Relaxed) + 1;
@@ -154,6 +179,92 @@ impl SecretVault for Vault {
         Ok(Secret::new(next_id))
     }
 
+    /// Generate several fresh secrets at once
+    ///
+    /// The vault entries are locked only once for the whole batch.
+    /// The returned secrets correspond positionally to `attributes`.
+    /// If any of the attributes is invalid no secret is stored.
+    pub async fn secret_generate_batch(
+        &self,
+        attributes: &[SecretAttributes],
+    ) -> Result<Vec<Secret>> {
+        let mut keys = Vec::with_capacity(attributes.len());
+        for attrs in attributes {
+            Self::check_attributes(attrs)?;
+            let key = Self::generate_key(attrs)?;
+            let key_id = self.compute_key_id(key.as_ref(), attrs).await?;
+            keys.push(VaultEntry::new(key_id, *attrs, key));
+        }
+
+        let mut entries = self.data.entries.write().await;
+        Ok(keys
+            .into_iter()
+            .map(|entry| {
+                let next_id = self.data.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+                entries.insert(next_id, entry);
+                Secret::new(next_id)
+            })
+            .collect())
+    }
+
+    /// Derive the key material for a secret from `seed`
+    fn derive_key(seed: &[u8], attributes: &SecretAttributes) -> Result<SecretKey> {
+        let label: &[u8] = match attributes.stype() {
+            SecretType::X25519 => b"X25519",
+            SecretType::Ed25519 => b"Ed25519",
+            SecretType::Buffer | SecretType::Aes => {
+                if attributes.persistence() != SecretPersistence::Ephemeral {
+                    return Err(VaultError::InvalidKeyType.into());
+                }
+                if attributes.stype() == SecretType::Aes {
+                    b"AES"
+                } else {
+                    b"Buffer"
+                }
+            }
+            #[cfg(feature = "bls")]
+            SecretType::Bls => {
+                let bls_secret_key =
+                    BlsSecretKey::hash(seed).ok_or(VaultError::InvalidBlsSecret)?;
+                return Ok(SecretKey::new(bls_secret_key.to_bytes().to_vec()));
+            }
+        };
+
+        let mut key = vec![0u8; attributes.length()];
+        hkdf::Hkdf::<Sha256>::new(Some(SEED_SALT), seed)
+            .expand(label, &mut key)
+            .map_err(|_| VaultError::HkdfExpandError)?;
+
+        Ok(SecretKey::new(key))
+    }
+
+    /// Deterministically generate a secret from `seed`
+    ///
+    /// The same seed and attributes always yield the same key, and
+    /// thus the same public key.  This is meant for reproducible test
+    /// identities and key provisioning only: the secret is exactly as
+    /// strong as the seed, so use
+    /// [`secret_generate`](SecretVault::secret_generate) otherwise.
+    pub async fn secret_generate_from_seed(
+        &self,
+        seed: &[u8],
+        attributes: SecretAttributes,
+    ) -> Result<Secret> {
+        Self::check_attributes(&attributes)?;
+        let key = Self::derive_key(seed, &attributes)?;
+        self.insert_secret(key, attributes).await
+    }
+}
+
+#[async_trait]
+impl SecretVault for Vault {
+    /// Generate fresh secret. Only Curve25519 and Buffer types are supported
+    async fn secret_generate(&self, attributes: SecretAttributes) -> Result<Secret> {
+        Self::check_attributes(&attributes)?;
+        let key = Self::generate_key(&attributes)?;
+        self.insert_secret(key, attributes).await
+    }
+
     #[tracing::instrument(skip_all, err)]
     async fn secret_import(&self, secret: &[u8], attributes: SecretAttributes) -> Result<Secret> {
         self.check_secret(secret, &attributes)?;
@@ -390,4 +501,100 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn secret_generate_for_type() {
+        let vault = new_vault();
+        for stype in [SecretType::X25519, SecretType::Ed25519, SecretType::Aes] {
+            let attrs = SecretAttributes::for_type(stype, SecretPersistence::Ephemeral);
+            let secret = vault.secret_generate(attrs).await.unwrap();
+            let key = vault.secret_export(&secret).await.unwrap();
+            assert_eq!(key.as_ref().len(), attrs.length());
+        }
+    }
+
+    #[tokio::test]
+    async fn secret_generate_batch_mixed_types() {
+        let vault = new_vault();
+        let attrs = [
+            SecretAttributes::for_type(SecretType::X25519, SecretPersistence::Ephemeral),
+            SecretAttributes::for_type(SecretType::Aes, SecretPersistence::Ephemeral),
+            SecretAttributes::new(SecretType::Aes, SecretPersistence::Ephemeral, 16),
+            SecretAttributes::for_type(SecretType::Ed25519, SecretPersistence::Ephemeral),
+            SecretAttributes::new(SecretType::Buffer, SecretPersistence::Ephemeral, 7),
+        ];
+
+        let secrets = vault.secret_generate_batch(&attrs).await.unwrap();
+        assert_eq!(secrets.len(), attrs.len());
+        for (secret, attrs) in secrets.iter().zip(attrs.iter()) {
+            assert_eq!(&vault.secret_attributes_get(secret).await.unwrap(), attrs);
+            let key = vault.secret_export(secret).await.unwrap();
+            assert_eq!(key.as_ref().len(), attrs.length());
+        }
+    }
+
+    #[tokio::test]
+    async fn secret_generate_batch_rejects_invalid_attributes() {
+        let vault = new_vault();
+        let attrs = [
+            SecretAttributes::for_type(SecretType::X25519, SecretPersistence::Ephemeral),
+            SecretAttributes::new(SecretType::X25519, SecretPersistence::Ephemeral, 16),
+        ];
+
+        assert!(vault.secret_generate_batch(&attrs).await.is_err());
+        assert!(vault.list_secrets().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn secret_generate_rejects_mismatched_length() {
+        let vault = new_vault();
+        for stype in [SecretType::X25519, SecretType::Ed25519] {
+            let attrs = SecretAttributes::new(stype, SecretPersistence::Ephemeral, 16);
+            assert!(vault.secret_generate(attrs).await.is_err());
+        }
+        let attrs = SecretAttributes::new(SecretType::Aes, SecretPersistence::Ephemeral, 24);
+        assert!(vault.secret_generate(attrs).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn secret_generate_from_seed_is_deterministic() {
+        let vault = new_vault();
+        for stype in [SecretType::X25519, SecretType::Ed25519] {
+            let attrs = SecretAttributes::for_type(stype, SecretPersistence::Ephemeral);
+            let a = vault
+                .secret_generate_from_seed(b"seed", attrs)
+                .await
+                .unwrap();
+            let b = vault
+                .secret_generate_from_seed(b"seed", attrs)
+                .await
+                .unwrap();
+            let c = vault
+                .secret_generate_from_seed(b"other", attrs)
+                .await
+                .unwrap();
+
+            let a = vault.secret_public_key_get(&a).await.unwrap();
+            let b = vault.secret_public_key_get(&b).await.unwrap();
+            let c = vault.secret_public_key_get(&c).await.unwrap();
+            assert_eq!(a, b);
+            assert_ne!(a, c);
+        }
+
+        // Another vault derives the same key
+        let attrs = SecretAttributes::for_type(SecretType::Ed25519, SecretPersistence::Ephemeral);
+        let other_vault = new_vault();
+        let a = vault
+            .secret_generate_from_seed(b"seed", attrs)
+            .await
+            .unwrap();
+        let b = other_vault
+            .secret_generate_from_seed(b"seed", attrs)
+            .await
+            .unwrap();
+        assert_eq!(
+            vault.secret_export(&a).await.unwrap(),
+            other_vault.secret_export(&b).await.unwrap()
+        );
+    }
 }
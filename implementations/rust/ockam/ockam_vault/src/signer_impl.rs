@@ -1,74 +1,125 @@
 use crate::vault::Vault;
 use crate::VaultError;
-use ockam_core::vault::{Secret, SecretType, Signature, Signer};
+use ockam_core::vault::{Secret, SecretKey, SecretType, Signature, Signer};
 use ockam_core::{async_trait, compat::boxed::Box, Result};
 
-#[async_trait]
-impl Signer for Vault {
-    /// Sign data with xeddsa algorithm. Only curve25519 is supported.
-    async fn sign(&self, secret_key: &Secret, data: &[u8]) -> Result<Signature> {
-        let entries = self.data.entries.read().await;
-        let entry = entries
-            .get(&secret_key.index())
-            .ok_or(VaultError::EntryNotFound)?;
+/// A signing key that has already been looked up and parsed.
+///
+/// Obtained from [`Vault::prepare_signer`]. Signing with a prepared
+/// signer does not touch the vault storage, which makes it suitable for
+/// issuing many signatures with the same key. The signer owns a copy of
+/// the key material, so it stays valid (and keeps signing with the same
+/// key) even if the secret is later destroyed in the vault.
+pub struct PreparedSigner {
+    key: PreparedKey,
+}
 
-        let key = entry.key().as_ref();
-        match entry.key_attributes().stype() {
+enum PreparedKey {
+    X25519(x25519_dalek::StaticSecret),
+    Ed25519(ed25519_dalek::Keypair),
+    #[cfg(feature = "bls")]
+    Bls(signature_bls::SecretKey),
+}
+
+impl PreparedSigner {
+    fn new(stype: SecretType, key: &SecretKey) -> Result<Self> {
+        let key = key.as_ref();
+        let key = match stype {
             SecretType::X25519 => {
-                use crate::xeddsa::XEddsaSigner;
                 use arrayref::array_ref;
-                use ockam_core::compat::rand::{thread_rng, RngCore};
                 use ockam_core::vault::CURVE25519_SECRET_LENGTH;
                 if key.len() != CURVE25519_SECRET_LENGTH {
                     return Err(VaultError::InvalidX25519SecretLength.into());
                 }
+                PreparedKey::X25519(x25519_dalek::StaticSecret::from(*array_ref!(
+                    key,
+                    0,
+                    CURVE25519_SECRET_LENGTH
+                )))
+            }
+            SecretType::Ed25519 => {
+                let secret = ed25519_dalek::SecretKey::from_bytes(key)
+                    .map_err(|_| VaultError::InvalidEd25519Secret)?;
+                let public = ed25519_dalek::PublicKey::from(&secret);
+                PreparedKey::Ed25519(ed25519_dalek::Keypair { public, secret })
+            }
+            #[cfg(feature = "bls")]
+            SecretType::Bls => {
+                use arrayref::array_ref;
+                if key.len() != 32 {
+                    return Err(VaultError::InvalidKeyType.into());
+                }
+                let secret = signature_bls::SecretKey::from_bytes(array_ref!(key, 0, 32));
+                if secret.is_none().into() {
+                    return Err(VaultError::InvalidBlsSecret.into());
+                }
+                PreparedKey::Bls(secret.unwrap())
+            }
+            SecretType::Buffer | SecretType::Aes => return Err(VaultError::InvalidKeyType.into()),
+        };
+        Ok(Self { key })
+    }
+
+    /// Sign data with the prepared key.
+    pub fn sign(&self, data: &[u8]) -> Result<Signature> {
+        match &self.key {
+            PreparedKey::X25519(key) => {
+                use crate::xeddsa::XEddsaSigner;
+                use ockam_core::compat::rand::{thread_rng, RngCore};
 
                 let mut rng = thread_rng();
                 let mut nonce = [0u8; 64];
                 rng.fill_bytes(&mut nonce);
-                let sig =
-                    x25519_dalek::StaticSecret::from(*array_ref!(key, 0, CURVE25519_SECRET_LENGTH))
-                        .xeddsa_sign(data.as_ref(), &nonce);
+                let sig = key.xeddsa_sign(data, &nonce);
                 Ok(Signature::new(sig.to_vec()))
             }
-            SecretType::Ed25519 => {
+            PreparedKey::Ed25519(kp) => {
                 use ed25519_dalek::Signer;
-                let sk = ed25519_dalek::SecretKey::from_bytes(key).unwrap();
-                let pk = ed25519_dalek::PublicKey::from(&sk);
-
-                let kp = ed25519_dalek::Keypair {
-                    public: pk,
-                    secret: sk,
-                };
-
-                let sig = kp.sign(data.as_ref());
+                let sig = kp.sign(data);
                 Ok(Signature::new(sig.to_bytes().to_vec()))
             }
             #[cfg(feature = "bls")]
-            SecretType::Bls => {
-                use arrayref::array_ref;
+            PreparedKey::Bls(key) => {
                 use signature_bbs_plus::{Issuer, MessageGenerators};
-                use signature_bls::SecretKey;
                 use signature_core::lib::Message;
 
-                if key.len() == 32 {
-                    let bls_secret_key = SecretKey::from_bytes(array_ref!(key, 0, 32)).unwrap();
-                    let generators = MessageGenerators::from_secret_key(&bls_secret_key, 1);
-                    let messages = [Message::hash(data)];
-                    let sig = Issuer::sign(&bls_secret_key, &generators, &messages).unwrap();
-                    Ok(Signature::new(sig.to_bytes().to_vec()))
-                } else {
-                    Err(VaultError::InvalidKeyType.into())
-                }
+                let generators = MessageGenerators::from_secret_key(key, 1);
+                let messages = [Message::hash(data)];
+                let sig = Issuer::sign(key, &generators, &messages)
+                    .map_err(|_| VaultError::InvalidBlsSecret)?;
+                Ok(Signature::new(sig.to_bytes().to_vec()))
             }
-            SecretType::Buffer | SecretType::Aes => Err(VaultError::InvalidKeyType.into()),
         }
     }
 }
 
+impl Vault {
+    /// Look up and parse a signing key once, returning a handle that
+    /// can sign repeatedly without accessing the vault storage.
+    pub async fn prepare_signer(&self, secret: &Secret) -> Result<PreparedSigner> {
+        let entries = self.data.entries.read().await;
+        let entry = entries
+            .get(&secret.index())
+            .ok_or(VaultError::EntryNotFound)?;
+        PreparedSigner::new(entry.key_attributes().stype(), entry.key())
+    }
+}
+
+#[async_trait]
+impl Signer for Vault {
+    /// Sign data with xeddsa algorithm. Only curve25519 is supported.
+    async fn sign(&self, secret_key: &Secret, data: &[u8]) -> Result<Signature> {
+        self.prepare_signer(secret_key).await?.sign(data)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Vault;
+    use ockam_core::vault::{
+        SecretAttributes, SecretPersistence, SecretType, SecretVault, Signer, Verifier,
+        CURVE25519_SECRET_LENGTH,
+    };
 
     fn new_vault() -> Vault {
         Vault::default()
@@ -76,4 +127,85 @@ mod tests {
 
     #[ockam_macros::vault_test]
     fn sign() {}
+
+    fn attributes(stype: SecretType) -> SecretAttributes {
+        SecretAttributes::new(
+            stype,
+            SecretPersistence::Ephemeral,
+            CURVE25519_SECRET_LENGTH,
+        )
+    }
+
+    #[tokio::test]
+    async fn prepared_signer_matches_trait() {
+        let vault = new_vault();
+        let data = b"hello world!";
+
+        // Ed25519 signatures are deterministic, so both paths must agree.
+        let secret = vault
+            .secret_generate(attributes(SecretType::Ed25519))
+            .await
+            .unwrap();
+        let signer = vault.prepare_signer(&secret).await.unwrap();
+        let expected = vault.sign(&secret, data).await.unwrap();
+        assert_eq!(signer.sign(data).unwrap().as_ref(), expected.as_ref());
+
+        // XEdDSA uses a random nonce, so only check the signature verifies.
+        let secret = vault
+            .secret_generate(attributes(SecretType::X25519))
+            .await
+            .unwrap();
+        let public = vault.secret_public_key_get(&secret).await.unwrap();
+        let signer = vault.prepare_signer(&secret).await.unwrap();
+        let signature = signer.sign(data).unwrap();
+        assert!(vault.verify(&signature, &public, data).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn prepared_signer_outlives_secret() {
+        let vault = new_vault();
+        let secret = vault
+            .secret_generate(attributes(SecretType::Ed25519))
+            .await
+            .unwrap();
+        let public = vault.secret_public_key_get(&secret).await.unwrap();
+        let signer = vault.prepare_signer(&secret).await.unwrap();
+        vault.secret_destroy(secret.clone()).await.unwrap();
+
+        let signature = signer.sign(b"data").unwrap();
+        assert!(vault.verify(&signature, &public, b"data").await.unwrap());
+        assert!(vault.prepare_signer(&secret).await.is_err());
+    }
+
+    /// Compare the prepared signer with the trait method.
+    /// Run with `cargo test -p ockam_vault --release -- --ignored --nocapture`.
+    #[tokio::test]
+    #[ignore]
+    async fn bench_prepared_signer() {
+        const N: u32 = 1000;
+        let vault = new_vault();
+        let data = [0u8; 256];
+        let secret = vault
+            .secret_generate(attributes(SecretType::Ed25519))
+            .await
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        for _ in 0..N {
+            vault.sign(&secret, &data).await.unwrap();
+        }
+        let trait_time = start.elapsed();
+
+        let signer = vault.prepare_signer(&secret).await.unwrap();
+        let start = std::time::Instant::now();
+        for _ in 0..N {
+            signer.sign(&data).unwrap();
+        }
+        let prepared_time = start.elapsed();
+
+        println!(
+            "{} signatures: Vault::sign {:?}, PreparedSigner::sign {:?}",
+            N, trait_time, prepared_time
+        );
+    }
 }
@@ -21,7 +21,7 @@ impl SymmetricVault for Vault {
             .ok_or(VaultError::EntryNotFound)?;
 
         if entry.key_attributes().stype() != SecretType::Aes {
-            return Err(VaultError::AeadAesGcmEncrypt.into());
+            return Err(VaultError::WrongSecretType.into());
         }
 
         let nonce = GenericArray::from_slice(nonce);
@@ -34,7 +34,7 @@ impl SymmetricVault for Vault {
         match entry.key_attributes().length() {
             AES128_SECRET_LENGTH => {
                 if key.len() != AES128_SECRET_LENGTH {
-                    return Err(VaultError::AeadAesGcmEncrypt.into());
+                    return Err(VaultError::InvalidKeyLength.into());
                 }
 
                 let key = GenericArray::from_slice(key);
@@ -44,7 +44,7 @@ impl SymmetricVault for Vault {
             }
             AES256_SECRET_LENGTH => {
                 if key.len() != AES256_SECRET_LENGTH {
-                    return Err(VaultError::AeadAesGcmEncrypt.into());
+                    return Err(VaultError::InvalidKeyLength.into());
                 }
 
                 let key = GenericArray::from_slice(key);
@@ -52,7 +52,7 @@ impl SymmetricVault for Vault {
                     .encrypt(nonce, payload)
                     .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
             }
-            _ => Err(VaultError::AeadAesGcmEncrypt.into()),
+            _ => Err(VaultError::InvalidKeyLength.into()),
         }
     }
 
@@ -69,7 +69,7 @@ impl SymmetricVault for Vault {
             .ok_or(VaultError::EntryNotFound)?;
 
         if entry.key_attributes().stype() != SecretType::Aes {
-            return Err(VaultError::AeadAesGcmEncrypt.into());
+            return Err(VaultError::WrongSecretType.into());
         }
 
         let nonce = GenericArray::from_slice(nonce);
@@ -82,34 +82,82 @@ impl SymmetricVault for Vault {
         match entry.key_attributes().length() {
             AES128_SECRET_LENGTH => {
                 if key.len() != AES128_SECRET_LENGTH {
-                    return Err(VaultError::AeadAesGcmEncrypt.into());
+                    return Err(VaultError::InvalidKeyLength.into());
                 }
                 let key = GenericArray::from_slice(key);
                 Aes128Gcm::new(key)
                     .decrypt(nonce, payload)
-                    .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
+                    .map_err(|_| VaultError::AeadAuthFailed.into())
             }
             AES256_SECRET_LENGTH => {
                 if key.len() != AES256_SECRET_LENGTH {
-                    return Err(VaultError::AeadAesGcmEncrypt.into());
+                    return Err(VaultError::InvalidKeyLength.into());
                 }
                 let key = GenericArray::from_slice(key);
                 Aes256Gcm::new(key)
                     .decrypt(nonce, payload)
-                    .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
+                    .map_err(|_| VaultError::AeadAuthFailed.into())
             }
-            _ => Err(VaultError::AeadAesGcmEncrypt.into()),
+            _ => Err(VaultError::InvalidKeyLength.into()),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Vault;
+    use crate::{Vault, VaultError};
+    use ockam_core::errcode::Kind;
+    use ockam_core::vault::{
+        SecretAttributes, SecretPersistence, SecretType, SecretVault, SymmetricVault,
+        AES256_SECRET_LENGTH,
+    };
+    use ockam_core::Error;
     fn new_vault() -> Vault {
         Vault::default()
     }
 
     #[ockam_macros::vault_test]
     fn encryption() {}
+
+    #[tokio::test]
+    async fn aead_errors_are_distinct() {
+        let vault = new_vault();
+        let nonce = [0u8; 12];
+
+        let attrs = SecretAttributes::for_type(SecretType::X25519, SecretPersistence::Ephemeral);
+        let x25519 = vault.secret_generate(attrs).await.unwrap();
+        let err = vault
+            .aead_aes_gcm_encrypt(&x25519, b"hello", &nonce, &[])
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Error::from(VaultError::WrongSecretType).code());
+        assert_eq!(err.code().kind, Kind::Misuse);
+
+        let attrs = SecretAttributes::new(SecretType::Aes, SecretPersistence::Ephemeral, 24);
+        let aes192 = vault.secret_import(&[0u8; 24], attrs).await.unwrap();
+        let err = vault
+            .aead_aes_gcm_decrypt(&aes192, b"hello", &nonce, &[])
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Error::from(VaultError::InvalidKeyLength).code());
+        assert_eq!(err.code().kind, Kind::Misuse);
+
+        let attrs = SecretAttributes::new(
+            SecretType::Aes,
+            SecretPersistence::Ephemeral,
+            AES256_SECRET_LENGTH,
+        );
+        let aes = vault.secret_generate(attrs).await.unwrap();
+        let mut ciphertext = vault
+            .aead_aes_gcm_encrypt(&aes, b"hello", &nonce, &[])
+            .await
+            .unwrap();
+        ciphertext[0] ^= 1;
+        let err = vault
+            .aead_aes_gcm_decrypt(&aes, &ciphertext, &nonce, &[])
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Error::from(VaultError::AeadAuthFailed).code());
+        assert_eq!(err.code().kind, Kind::Invalid);
+    }
 }
@@ -1,8 +1,8 @@
 #[cfg(feature = "storage")]
 use crate::storage::*;
 use core::sync::atomic::AtomicUsize;
-use ockam_core::compat::{collections::BTreeMap, string::String, sync::Arc};
-use ockam_core::vault::{SecretAttributes, SecretKey};
+use ockam_core::compat::{collections::BTreeMap, string::String, sync::Arc, vec::Vec};
+use ockam_core::vault::{Secret, SecretAttributes, SecretKey};
 use ockam_node::compat::asynchronous::RwLock;
 
 /// Vault implementation that stores secrets in memory and uses software crypto.
@@ -75,6 +75,28 @@ impl Vault {
     pub fn create() -> Self {
         Self::new()
     }
+
+    /// List handles and attributes of all secrets currently held.
+    ///
+    /// Intended for diagnostics, e.g. detecting keys left over after a
+    /// channel was torn down.  Key material is never returned.
+    pub async fn list_secrets(&self) -> Vec<(Secret, SecretAttributes)> {
+        self.data
+            .entries
+            .read()
+            .await
+            .iter()
+            .map(|(index, entry)| (Secret::new(*index), entry.key_attributes()))
+            .collect()
+    }
+
+    /// Return the number of secrets currently held.
+    ///
+    /// Tests can compare the count before opening and after closing a
+    /// channel to make sure no keys were leaked.
+    pub async fn secret_count(&self) -> usize {
+        self.data.entries.read().await.len()
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -110,6 +132,7 @@ impl VaultEntry {
 #[cfg(test)]
 mod tests {
     use crate::Vault;
+    use ockam_core::vault::{SecretAttributes, SecretPersistence, SecretType, SecretVault};
     use std::sync::atomic::Ordering;
 
     #[tokio::test]
@@ -118,4 +141,25 @@ mod tests {
         assert_eq!(vault.data.next_id.load(Ordering::Relaxed), 0);
         assert_eq!(vault.data.entries.read().await.len(), 0);
     }
+
+    #[tokio::test]
+    async fn list_secrets() {
+        let vault = Vault::new();
+        let x25519 = SecretAttributes::for_type(SecretType::X25519, SecretPersistence::Ephemeral);
+        let aes = SecretAttributes::for_type(SecretType::Aes, SecretPersistence::Ephemeral);
+        let first = vault.secret_generate(x25519).await.unwrap();
+        let second = vault.secret_generate(aes).await.unwrap();
+
+        let secrets = vault.list_secrets().await;
+        assert_eq!(
+            secrets,
+            vec![(first.clone(), x25519), (second.clone(), aes)]
+        );
+
+        assert_eq!(vault.secret_count().await, 2);
+
+        vault.secret_destroy(first).await.unwrap();
+        assert_eq!(vault.list_secrets().await, vec![(second, aes)]);
+        assert_eq!(vault.secret_count().await, 1);
+    }
 }